@@ -41,11 +41,26 @@ fn main() {
         Err(_) => quit(&format!("{} could not be opened", path)),
     };
 
-    let spec: Result<Spec, _> = serde_json::from_reader(file);
-
-    if let Err(err) = spec {
-        quit(&format!("{} {}", path, err.to_string()));
-    }
+    let spec: Spec = match serde_json::from_reader(file) {
+        Ok(spec) => spec,
+        Err(err) => quit(&format!("{} {}", path, err.to_string())),
+    };
 
     println!("{} is valid", path);
+
+    for (block, gas_schedule) in spec.params.gas_schedule_overrides.unwrap_or_default() {
+        let block: u64 = block.into();
+        let overrides = match serde_json::to_value(&gas_schedule) {
+            Ok(serde_json::Value::Object(fields)) => fields
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .collect(),
+            _ => serde_json::Map::new(),
+        };
+        println!(
+            "Gas schedule overrides from block {}: {}",
+            block,
+            serde_json::Value::Object(overrides)
+        );
+    }
 }
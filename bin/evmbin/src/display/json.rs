@@ -22,7 +22,7 @@ use super::config::Config;
 use bytes::ToPretty;
 use display;
 use ethcore::trace;
-use ethereum_types::{BigEndianHash, H256, U256};
+use ethereum_types::{Address, BigEndianHash, H256, U256};
 use info as vm;
 
 /// JSON formatting informant.
@@ -222,7 +222,7 @@ impl trace::VMTracer for Informant {
         });
     }
 
-    fn prepare_subtrace(&mut self, code: &[u8]) {
+    fn prepare_subtrace(&mut self, _address: &Address, code: &[u8]) {
         let subdepth = self.subdepth;
         Self::with_informant_in_depth(self, subdepth, |informant: &mut Informant| {
             let mut vm = Informant::default();
@@ -20,6 +20,7 @@ use std::time::Duration;
 
 pub mod config;
 pub mod json;
+pub mod profile;
 pub mod simple;
 pub mod std_json;
 
@@ -0,0 +1,168 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Opcode profile output.
+
+use std::collections::BTreeMap;
+
+use bytes::ToPretty;
+use ethcore::trace::{self, OpcodeStats, Profile, ProfilingVMTracer};
+use ethereum_types::{Address, U256};
+use evm::Instruction;
+
+use display;
+use info as vm;
+
+/// Informant printing the gas used, time spent and number of executions of each opcode.
+#[derive(Default)]
+pub struct Informant {
+    tracer: ProfilingVMTracer,
+}
+
+impl Informant {
+    pub fn new() -> Informant {
+        Default::default()
+    }
+
+    fn print_opcodes(opcodes: &BTreeMap<u8, OpcodeStats>) {
+        let mut opcodes: Vec<_> = opcodes.iter().collect();
+        opcodes.sort_by(|a, b| b.1.gas_used.cmp(&a.1.gas_used));
+        println!(
+            "  {:<16} {:>10} {:>12} {:>14}",
+            "Opcode", "Count", "Gas used", "Time (ns)"
+        );
+        for (opcode, stats) in opcodes {
+            let name = Instruction::from_u8(*opcode)
+                .map(|i| i.info().name.to_owned())
+                .unwrap_or_else(|| format!("0x{:02x}", opcode));
+            println!(
+                "  {:<16} {:>10} {:>12} {:>14}",
+                name,
+                stats.count,
+                stats.gas_used,
+                stats.time.as_nanos()
+            );
+        }
+    }
+
+    fn print_profile(profile: &Profile) {
+        println!("Opcodes:");
+        Self::print_opcodes(&profile.opcodes());
+        for (address, opcodes) in &profile.contracts {
+            let total = profile.contract_total(address);
+            println!(
+                "Contract {:?}: {} instructions, {} gas, {} ns",
+                address,
+                total.count,
+                total.gas_used,
+                total.time.as_nanos()
+            );
+            Self::print_opcodes(opcodes);
+        }
+    }
+}
+
+impl vm::Informant for Informant {
+    type Sink = ();
+
+    fn before_test(&mut self, name: &str, action: &str) {
+        println!("Test: {} ({})", name, action);
+    }
+
+    fn clone_sink(&self) -> Self::Sink {}
+
+    fn finish(result: vm::RunResult<Self::Output>, _sink: &mut Self::Sink) {
+        match result {
+            Ok(success) => {
+                println!("Output: 0x{}", success.output.to_hex());
+                println!("Gas used: {:x}", success.gas_used);
+                println!("Time: {}", display::format_time(&success.time));
+                if let Some(profile) = success.traces {
+                    Self::print_profile(&profile);
+                }
+            }
+            Err(failure) => {
+                println!("Error: {}", failure.error);
+                println!("Time: {}", display::format_time(&failure.time));
+                if let Some(profile) = failure.traces {
+                    Self::print_profile(&profile);
+                }
+            }
+        }
+    }
+}
+
+impl trace::VMTracer for Informant {
+    type Output = Profile;
+
+    fn trace_next_instruction(&mut self, pc: usize, instruction: u8, current_gas: U256) -> bool {
+        self.tracer
+            .trace_next_instruction(pc, instruction, current_gas)
+    }
+
+    fn trace_prepare_execute(
+        &mut self,
+        pc: usize,
+        instruction: u8,
+        gas_cost: U256,
+        mem_written: Option<(usize, usize)>,
+        store_written: Option<(U256, U256)>,
+    ) {
+        self.tracer
+            .trace_prepare_execute(pc, instruction, gas_cost, mem_written, store_written)
+    }
+
+    fn trace_failed(&mut self) {
+        self.tracer.trace_failed()
+    }
+
+    fn trace_executed(&mut self, gas_used: U256, stack_push: &[U256], mem: &[u8]) {
+        self.tracer.trace_executed(gas_used, stack_push, mem)
+    }
+
+    fn prepare_subtrace(&mut self, address: &Address, code: &[u8]) {
+        self.tracer.prepare_subtrace(address, code)
+    }
+
+    fn done_subtrace(&mut self) {
+        self.tracer.done_subtrace()
+    }
+
+    fn drain(self) -> Option<Profile> {
+        self.tracer.drain()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use info::tests::run_test;
+
+    fn assert_profile(profile: Option<Profile>, _expected: &str) {
+        let opcodes = profile.expect("profile is always returned").opcodes();
+        // PUSH1
+        assert_eq!(opcodes[&0x60].count, 2);
+        assert_eq!(opcodes[&0x60].gas_used, 6.into());
+        // ADD
+        assert_eq!(opcodes[&0x01].count, 1);
+        assert_eq!(opcodes[&0x01].gas_used, 3.into());
+    }
+
+    #[test]
+    fn should_profile_opcodes() {
+        run_test(Informant::new(), assert_profile, "6001600201", 0xffff, "");
+    }
+}
@@ -19,6 +19,7 @@
 use super::config::Config;
 use bytes::ToPretty;
 use ethcore::trace;
+use ethereum_types::Address;
 
 use display;
 use info as vm;
@@ -64,7 +65,7 @@ impl vm::Informant for Informant {
 impl trace::VMTracer for Informant {
     type Output = ();
 
-    fn prepare_subtrace(&mut self, _code: &[u8]) {
+    fn prepare_subtrace(&mut self, _address: &Address, _code: &[u8]) {
         Default::default()
     }
     fn done_subtrace(&mut self) {}
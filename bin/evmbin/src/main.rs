@@ -31,8 +31,11 @@ extern crate docopt;
 extern crate env_logger;
 extern crate ethereum_types;
 extern crate evm;
+extern crate keccak_hash as hash;
 extern crate panic_hook;
 extern crate parity_bytes as bytes;
+extern crate rlp;
+extern crate triehash_ethereum as triehash;
 extern crate vm;
 
 #[cfg(test)]
@@ -54,6 +57,7 @@ use vm::{ActionParams, CallType};
 
 mod display;
 mod info;
+mod transition;
 
 use info::Informant;
 
@@ -63,16 +67,25 @@ EVM implementation for Parity.
 
 Usage:
     openethereum-evm state-test <file> [--json --std-json --std-dump-json --only NAME --chain CHAIN --std-out-only --std-err-only --omit-storage-output --omit-memory-output]
+    openethereum-evm blockchain-test <file> [--json --only NAME --chain CHAIN]
     openethereum-evm stats [options]
     openethereum-evm stats-jsontests-vm <file>
+    openethereum-evm t8n [--input-alloc FILE --input-env FILE --input-txs FILE --output-basedir DIR --output-result FILE --output-alloc FILE --output-body FILE --state-fork FORK --state-reward REWARD --state-chainid ID]
+    openethereum-evm b11r [--input-header FILE --input-ommers FILE --input-txs FILE --input-withdrawals FILE --output-basedir DIR --output-block FILE]
     openethereum-evm [options]
     openethereum-evm [-h | --help]
 
 Commands:
     state-test         Run a state test from a json file.
+    blockchain-test    Import the blocks of a blockchain test from a json file
+                       and check the best block and the post state. Forks with
+                       withdrawals (Shanghai and later) are skipped as unsupported.
     stats              Execute EVM runtime code and return the statistics.
     stats-jsontests-vm Execute standard json-tests format VMTests and return
                        timing statistics in tsv format.
+    t8n                Apply transactions to a pre-state and output the post-state,
+                       the receipts and the rejected transactions.
+    b11r               Assemble a block from a header, ommers and transactions.
 
 Transaction options:
     --code CODE        Contract code as hex (without 0x).
@@ -82,13 +95,37 @@ Transaction options:
     --gas GAS          Supplied gas as hex (without 0x).
     --gas-price WEI    Supplied gas price as hex (without 0x).
 
-State test options:
+State and blockchain test options:
     --chain CHAIN      Run only from specific chain name (i.e. one of EIP150, EIP158,
                        Frontier, Homestead, Byzantium, Constantinople,
                        ConstantinopleFix, Istanbul, EIP158ToByzantiumAt5, FrontierToHomesteadAt5,
-                       HomesteadToDaoAt5, HomesteadToEIP150At5, Berlin, Yolo3).
+                       HomesteadToDaoAt5, HomesteadToEIP150At5, Berlin, London,
+                       BerlinToLondonAt5, Shanghai, Cancun).
     --only NAME        Runs only a single test matching the name.
 
+Transition tool options:
+    --input-alloc FILE      Pre-state [default: alloc.json].
+    --input-env FILE        Block environment [default: env.json].
+    --input-txs FILE        Transactions as a JSON list or an RLP encoded hex string
+                            (t8n default: txs.json, b11r default: txs.rlp).
+    --input-header FILE     Header of the block to build [default: header.json].
+    --input-ommers FILE     RLP encoded ommer headers [default: ommers.json].
+    --input-withdrawals FILE
+                            Withdrawals of a Shanghai block.
+    --output-basedir DIR    Directory the output files are written to [default: .].
+    --output-result FILE    Execution result [default: result.json].
+    --output-alloc FILE     Post-state [default: alloc.json].
+    --output-body FILE      RLP encoded list of the applied transactions.
+    --output-block FILE     Assembled block [default: block.json].
+    --state-fork FORK       Fork rules, a fork name as in --chain or a chain spec
+                            file path [default: London].
+    --state-reward REWARD   Block reward in wei, -1 to skip rewards [default: 0].
+    --state-chainid ID      Chain id to sign transactions with, defaults to the
+                            chain id of the fork spec.
+    The flags are also accepted with a dot, i.e. --input.alloc. Use stdin as an
+    input to read it from a JSON object on the standard input and stdout or
+    stderr as an output to print it.
+
 General options:
     --json                    Display verbose results in JSON.
     --std-json                Display results in standardized JSON format.
@@ -98,6 +135,8 @@ General options:
     --omit-memory-output      With --std-json omit memory output.
     --std-dump-json           Display results in standardized JSON format
                               with additional state dump.
+    --profile                 Display the number of executions, gas used and
+                              time spent of each opcode per contract.
 
 Display result state dump in standardized JSON format.
     --chain CHAIN      Chain spec file path.
@@ -108,16 +147,25 @@ fn main() {
     panic_hook::set_abort();
     env_logger::init();
 
+    let argv = ::std::env::args().map(dashed_flag);
     let args: Args = Docopt::new(USAGE)
-        .and_then(|d| d.deserialize())
+        .and_then(|d| d.argv(argv).deserialize())
         .unwrap_or_else(|e| e.exit());
 
     let config = args.config();
 
-    if args.cmd_state_test {
+    if args.cmd_t8n {
+        run_t8n(args)
+    } else if args.cmd_b11r {
+        run_b11r(args)
+    } else if args.cmd_state_test {
         run_state_test(args)
+    } else if args.cmd_blockchain_test {
+        run_blockchain_test(args)
     } else if args.cmd_stats_jsontests_vm {
         run_stats_jsontests_vm(args)
+    } else if args.flag_profile {
+        run_call(args, display::profile::Informant::new())
     } else if args.flag_json {
         run_call(args, display::json::Informant::new(config))
     } else if args.flag_std_dump_json || args.flag_std_json {
@@ -289,6 +337,123 @@ fn run_state_test(args: Args) {
     }
 }
 
+fn run_blockchain_test(args: Args) {
+    use ethjson::blockchain::Test;
+    use json_tests::ChainTestResult;
+
+    let file = args.arg_file.expect("FILE is required");
+    let mut file = match fs::File::open(&file) {
+        Err(err) => die(format!("Unable to open: {:?}: {}", file, err)),
+        Ok(file) => file,
+    };
+    let blockchain_test = match Test::load(&mut file) {
+        Err(err) => die(format!("Unable to load the test file: {}", err)),
+        Ok(test) => test,
+    };
+    let only_test = args.flag_only.map(|s| s.to_lowercase());
+    let only_chain = args.flag_chain.map(|s| s.to_lowercase());
+
+    let mut failed = 0;
+    for (name, blockchain) in blockchain_test {
+        if let Some(false) = only_test
+            .as_ref()
+            .map(|only_test| &name.to_lowercase() == only_test)
+        {
+            continue;
+        }
+        let fork = format!("{:?}", blockchain.network);
+        if let Some(false) = only_chain
+            .as_ref()
+            .map(|only_chain| &fork.to_lowercase() == only_chain)
+        {
+            continue;
+        }
+
+        let result = json_tests::run_chain_test(&name, blockchain);
+        if let ChainTestResult::Failed(_) = result {
+            failed += 1;
+        }
+
+        if args.flag_json {
+            let (pass, failure) = match result {
+                ChainTestResult::Passed => (Some(true), None),
+                ChainTestResult::Unsupported => (None, None),
+                ChainTestResult::Failed(failure) => (Some(false), Some(failure)),
+            };
+            let (block, error) = match failure {
+                Some(failure) => (failure.block, Some(failure.reason)),
+                None => (None, None),
+            };
+            println!(
+                "{}",
+                json!({
+                    "name": name,
+                    "fork": fork,
+                    "pass": pass,
+                    "blockIndex": block.as_ref().map(|block| block.index),
+                    "blockNumber": block.as_ref().and_then(|block| block.number),
+                    "blockHash": block.as_ref().and_then(|block| block.hash),
+                    "error": error,
+                })
+            );
+            continue;
+        }
+
+        match result {
+            ChainTestResult::Passed => println!("OK   {} ({})", name, fork),
+            ChainTestResult::Unsupported => println!("SKIP {} ({}): unsupported fork", name, fork),
+            ChainTestResult::Failed(failure) => match failure.block {
+                Some(block) => println!(
+                    "FAIL {} ({}): block {} (#{}, {}): {}",
+                    name,
+                    fork,
+                    block.index,
+                    block
+                        .number
+                        .map_or_else(|| "?".to_owned(), |number| number.to_string()),
+                    block
+                        .hash
+                        .map_or_else(|| "undecodable".to_owned(), |hash| format!("{:?}", hash)),
+                    failure.reason
+                ),
+                None => println!("FAIL {} ({}): {}", name, fork, failure.reason),
+            },
+        }
+    }
+
+    if failed > 0 {
+        die(format!("{} blockchain test(s) failed", failed));
+    }
+}
+
+fn run_t8n(args: Args) {
+    let options = transition::T8nOptions {
+        spec: arg(spec_from_name(&args.flag_state_fork), "--state-fork"),
+        reward: arg(args.state_reward(), "--state-reward"),
+        chain_id: arg(args.state_chain_id(), "--state-chainid"),
+        input_alloc: args.flag_input_alloc,
+        input_env: args.flag_input_env,
+        input_txs: args.flag_input_txs.unwrap_or_else(|| "txs.json".into()),
+        output_basedir: args.flag_output_basedir,
+        output_result: args.flag_output_result,
+        output_alloc: args.flag_output_alloc,
+        output_body: args.flag_output_body,
+    };
+    transition::run_t8n(options).unwrap_or_else(|e| die(e))
+}
+
+fn run_b11r(args: Args) {
+    let options = transition::B11rOptions {
+        input_header: args.flag_input_header,
+        input_ommers: args.flag_input_ommers,
+        input_txs: args.flag_input_txs.unwrap_or_else(|| "txs.rlp".into()),
+        input_withdrawals: args.flag_input_withdrawals,
+        output_basedir: args.flag_output_basedir,
+        output_block: args.flag_output_block,
+    };
+    transition::run_b11r(options).unwrap_or_else(|e| die(e))
+}
+
 fn run_call<T: Informant>(args: Args, informant: T) {
     let from = arg(args.from(), "--from");
     let to = arg(args.to(), "--to");
@@ -338,7 +503,10 @@ fn run_call<T: Informant>(args: Args, informant: T) {
 struct Args {
     cmd_stats: bool,
     cmd_state_test: bool,
+    cmd_blockchain_test: bool,
     cmd_stats_jsontests_vm: bool,
+    cmd_t8n: bool,
+    cmd_b11r: bool,
     arg_file: Option<PathBuf>,
     flag_only: Option<String>,
     flag_from: Option<String>,
@@ -355,6 +523,21 @@ struct Args {
     flag_std_out_only: bool,
     flag_omit_storage_output: bool,
     flag_omit_memory_output: bool,
+    flag_profile: bool,
+    flag_input_alloc: String,
+    flag_input_env: String,
+    flag_input_txs: Option<String>,
+    flag_input_header: String,
+    flag_input_ommers: String,
+    flag_input_withdrawals: Option<String>,
+    flag_output_basedir: String,
+    flag_output_result: String,
+    flag_output_alloc: String,
+    flag_output_body: Option<String>,
+    flag_output_block: String,
+    flag_state_fork: String,
+    flag_state_reward: String,
+    flag_state_chainid: Option<String>,
 }
 
 impl Args {
@@ -401,20 +584,26 @@ impl Args {
     }
 
     pub fn spec(&self) -> Result<spec::Spec, String> {
-        Ok(match self.flag_chain {
-            Some(ref spec_name) => {
-                let fork_spec: Result<ethjson::spec::ForkSpec, _> =
-                    serde_json::from_str(&format!("{:?}", spec_name));
-                if let Ok(fork_spec) = fork_spec {
-                    ethcore::client::EvmTestClient::spec_from_json(&fork_spec)
-                        .expect("this forkspec is not defined")
-                } else {
-                    let file = fs::File::open(spec_name).map_err(|e| format!("{}", e))?;
-                    spec::Spec::load(&::std::env::temp_dir(), file)?
-                }
-            }
-            None => ethcore::ethereum::new_foundation(&::std::env::temp_dir()),
-        })
+        match self.flag_chain {
+            Some(ref spec_name) => spec_from_name(spec_name),
+            None => Ok(ethcore::ethereum::new_foundation(&::std::env::temp_dir())),
+        }
+    }
+
+    pub fn state_reward(&self) -> Result<Option<U256>, String> {
+        match self.flag_state_reward.as_str() {
+            "-1" => Ok(None),
+            reward => U256::from_dec_str(reward)
+                .map(Some)
+                .map_err(|e| format!("{:?}", e)),
+        }
+    }
+
+    pub fn state_chain_id(&self) -> Result<Option<u64>, String> {
+        match self.flag_state_chainid {
+            Some(ref chain_id) => chain_id.parse().map(Some).map_err(to_string),
+            None => Ok(None),
+        }
     }
 
     pub fn config(&self) -> display::config::Config {
@@ -422,6 +611,31 @@ impl Args {
     }
 }
 
+/// Loads a fork spec by its name, or a chain spec from a file.
+fn spec_from_name(spec_name: &str) -> Result<spec::Spec, String> {
+    let fork_spec: Result<ethjson::spec::ForkSpec, _> =
+        serde_json::from_str(&format!("{:?}", spec_name));
+    if let Ok(fork_spec) = fork_spec {
+        ethcore::client::EvmTestClient::spec_from_json(&fork_spec)
+            .ok_or_else(|| format!("{:?} is not supported", fork_spec))
+    } else {
+        let file = fs::File::open(spec_name).map_err(|e| format!("{}", e))?;
+        spec::Spec::load(&::std::env::temp_dir(), file)
+    }
+}
+
+/// geth spells the transition tool flags with a dot, i.e. `--input.alloc`.
+fn dashed_flag(arg: String) -> String {
+    if ["--input.", "--output.", "--state."]
+        .iter()
+        .any(|prefix| arg.starts_with(prefix))
+    {
+        arg.replacen('.', "-", 1)
+    } else {
+        arg
+    }
+}
+
 fn arg<T>(v: Result<T, String>, param: &str) -> T {
     v.unwrap_or_else(|e| die(format!("Invalid {}: {}", param, e)))
 }
@@ -437,13 +651,16 @@ fn die<T: fmt::Display>(msg: T) -> ! {
 
 #[cfg(test)]
 mod tests {
-    use super::{Args, USAGE};
+    use super::{dashed_flag, spec_from_name, Args, USAGE};
     use docopt::Docopt;
     use ethereum_types::Address;
 
     fn run<T: AsRef<str>>(args: &[T]) -> Args {
         Docopt::new(USAGE)
-            .and_then(|d| d.argv(args.into_iter()).deserialize())
+            .and_then(|d| {
+                d.argv(args.iter().map(|arg| dashed_flag(arg.as_ref().to_owned())))
+                    .deserialize()
+            })
             .unwrap()
     }
 
@@ -486,6 +703,15 @@ mod tests {
         assert_eq!(args.flag_chain, Some("./testfile".to_owned()));
     }
 
+    #[test]
+    fn should_parse_stats_profile_command() {
+        let args = run(&["openethereum-evm", "stats", "--profile", "--code", "05"]);
+
+        assert_eq!(args.cmd_stats, true);
+        assert_eq!(args.flag_profile, true);
+        assert_eq!(args.code(), Ok(Some(vec![05])));
+    }
+
     #[test]
     fn should_parse_state_test_command() {
         let args = run(&[
@@ -508,4 +734,81 @@ mod tests {
         assert_eq!(args.flag_chain, Some("homestead".to_owned()));
         assert_eq!(args.flag_only, Some("add11".to_owned()));
     }
+
+    #[test]
+    fn should_parse_blockchain_test_command() {
+        let args = run(&[
+            "openethereum-evm",
+            "blockchain-test",
+            "./file.json",
+            "--chain",
+            "Cancun",
+            "--only=transient_storage",
+            "--json",
+        ]);
+
+        assert_eq!(args.cmd_blockchain_test, true);
+        assert!(args.arg_file.is_some());
+        assert_eq!(args.flag_json, true);
+        assert_eq!(args.flag_chain, Some("Cancun".to_owned()));
+        assert_eq!(args.flag_only, Some("transient_storage".to_owned()));
+    }
+
+    #[test]
+    fn should_parse_t8n_command() {
+        let args = run(&[
+            "openethereum-evm",
+            "t8n",
+            "--input.alloc",
+            "stdin",
+            "--input.env=stdin",
+            "--output-result",
+            "stdout",
+            "--state.fork",
+            "Shanghai",
+            "--state.reward",
+            "-1",
+            "--state.chainid",
+            "5",
+        ]);
+
+        assert_eq!(args.cmd_t8n, true);
+        assert_eq!(args.flag_input_alloc, "stdin");
+        assert_eq!(args.flag_input_env, "stdin");
+        assert_eq!(args.flag_input_txs, None);
+        assert_eq!(args.flag_output_result, "stdout");
+        assert_eq!(args.flag_output_alloc, "alloc.json");
+        assert_eq!(args.flag_output_basedir, ".");
+        assert_eq!(args.flag_state_fork, "Shanghai");
+        assert_eq!(args.state_reward(), Ok(None));
+        assert_eq!(args.state_chain_id(), Ok(Some(5)));
+    }
+
+    #[test]
+    fn should_reject_unsupported_forks() {
+        assert!(spec_from_name("London").is_ok());
+        assert!(spec_from_name("FrontierToHomesteadAt5").is_err());
+    }
+
+    #[test]
+    fn should_parse_b11r_command() {
+        let args = run(&[
+            "openethereum-evm",
+            "b11r",
+            "--input.header",
+            "h.json",
+            "--input.txs",
+            "t.rlp",
+            "--output.block",
+            "stdout",
+        ]);
+
+        assert_eq!(args.cmd_b11r, true);
+        assert_eq!(args.flag_input_header, "h.json");
+        assert_eq!(args.flag_input_ommers, "ommers.json");
+        assert_eq!(args.flag_input_txs, Some("t.rlp".to_owned()));
+        assert_eq!(args.flag_input_withdrawals, None);
+        assert_eq!(args.flag_output_block, "stdout");
+        assert_eq!(args.state_reward(), Ok(Some(0.into())));
+    }
 }
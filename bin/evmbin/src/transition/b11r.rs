@@ -0,0 +1,110 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Block builder tool.
+
+use super::types::{to_hex, withdrawals_root, Block, Header as JsonHeader, Withdrawal};
+use ethereum_types::{Bloom, H256, H64, U256};
+use ethjson::bytes::Bytes;
+use hash::keccak;
+use rlp::{self, Rlp, RlpStream};
+use triehash::ordered_trie_root;
+use types::{header::Header, transaction::TypedTransaction};
+
+/// Assembles a block out of a `header`, RLP encoded `ommers` headers, an
+/// RLP encoded list of transactions and the `withdrawals` of Shanghai blocks.
+///
+/// The ommers, transactions and withdrawals roots are computed when missing from the header.
+/// The block is not sealed, the mix hash and nonce are taken from the header.
+pub fn build_block(
+    header: JsonHeader,
+    ommers: Vec<Bytes>,
+    txs: Bytes,
+    withdrawals: Option<Vec<Withdrawal>>,
+) -> Result<Block, String> {
+    let txs: Vec<u8> = txs.into();
+    let txs = TypedTransaction::decode_rlp_list(&Rlp::new(&txs))
+        .map_err(|e| format!("Invalid transactions RLP: {}", e))?;
+    let ommers: Vec<Vec<u8>> = ommers.into_iter().map(Into::into).collect();
+    for ommer in &ommers {
+        Rlp::new(ommer)
+            .item_count()
+            .map_err(|e| format!("Invalid ommer RLP: {}", e))?;
+    }
+
+    let mut ommers_rlp = RlpStream::new_list(ommers.len());
+    for ommer in &ommers {
+        ommers_rlp.append_raw(ommer, 1);
+    }
+    let ommers_rlp = ommers_rlp.out();
+
+    let mut block_header = Header::new();
+    block_header.set_parent_hash(header.parent_hash.into());
+    block_header.set_uncles_hash(
+        header
+            .ommers_hash
+            .map_or_else(|| keccak(&ommers_rlp), Into::into),
+    );
+    block_header.set_author(header.miner.into());
+    block_header.set_state_root(header.state_root.into());
+    block_header.set_transactions_root(header.transactions_root.map_or_else(
+        || ordered_trie_root(txs.iter().map(|tx| tx.encode())),
+        Into::into,
+    ));
+    block_header.set_receipts_root(header.receipts_root.into());
+    block_header.set_log_bloom(header.logs_bloom.map_or_else(Bloom::zero, Into::into));
+    block_header.set_difficulty(header.difficulty.map_or_else(U256::zero, Into::into));
+    block_header.set_number(header.number.into());
+    block_header.set_gas_limit(header.gas_limit.into());
+    block_header.set_gas_used(header.gas_used.into());
+    block_header.set_timestamp(header.timestamp.into());
+    block_header.set_extra_data(header.extra_data.map_or_else(Vec::new, Into::into));
+    block_header.set_seal(vec![
+        rlp::encode(&header.mix_hash.map_or_else(H256::zero, Into::into)),
+        rlp::encode(&header.nonce.map_or_else(H64::zero, Into::into)),
+    ]);
+    block_header.set_base_fee(header.base_fee_per_gas.map(Into::into));
+
+    // The withdrawals root is not part of `Header`, it is appended to the encoded fields.
+    let header_rlp = match (header.withdrawals_root, &withdrawals) {
+        (None, None) => rlp::encode(&block_header),
+        (root, Some(withdrawals)) => {
+            let encoded = rlp::encode(&block_header);
+            let fields = Rlp::new(&encoded);
+            let count = fields.item_count().map_err(|e| e.to_string())?;
+            let mut stream = RlpStream::new_list(count + 1);
+            for field in fields.iter() {
+                stream.append_raw(field.as_raw(), 1);
+            }
+            stream.append(&root.map_or_else(|| withdrawals_root(withdrawals), Into::into));
+            stream.out()
+        }
+        (Some(_), None) => return Err("withdrawalsRoot is given without withdrawals".into()),
+    };
+
+    let mut block = RlpStream::new_list(if withdrawals.is_some() { 4 } else { 3 });
+    block.append_raw(&header_rlp, 1);
+    TypedTransaction::rlp_append_list(&mut block, &txs);
+    block.append_raw(&ommers_rlp, 1);
+    if let Some(ref withdrawals) = withdrawals {
+        block.append_list(withdrawals);
+    }
+
+    Ok(Block {
+        rlp: to_hex(&block.out()),
+        hash: keccak(&header_rlp),
+    })
+}
@@ -0,0 +1,474 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State transition (`t8n`) and block builder (`b11r`) tools.
+//!
+//! Both use the JSON formats of the geth `evm t8n` and `evm b11r` commands, so that
+//! execution-spec-tests fixtures and differential fuzzers can be run against this EVM.
+//! An input named `stdin` is read from a single JSON object on the standard input,
+//! outputs named `stdout` or `stderr` are collected into a single JSON object.
+
+mod b11r;
+mod t8n;
+pub mod types;
+
+pub use self::{b11r::build_block, t8n::transition};
+
+use self::types::{to_hex, Env, Header, Transactions, Withdrawal};
+use ethcore::spec::Spec;
+use ethereum_types::U256;
+use ethjson::{self, bytes::Bytes};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{self, Map, Value};
+use std::{fs, io, path::PathBuf};
+
+/// Options of the `t8n` tool.
+pub struct T8nOptions {
+    /// Rules to apply.
+    pub spec: Spec,
+    /// Pre state.
+    pub input_alloc: String,
+    /// Block environment.
+    pub input_env: String,
+    /// Transactions to apply.
+    pub input_txs: String,
+    /// Directory the output files are written to.
+    pub output_basedir: String,
+    /// Execution result.
+    pub output_result: String,
+    /// Post state.
+    pub output_alloc: String,
+    /// RLP encoded applied transactions.
+    pub output_body: Option<String>,
+    /// Block reward, `None` to skip rewards.
+    pub reward: Option<U256>,
+    /// Chain id to sign transactions with.
+    pub chain_id: Option<u64>,
+}
+
+/// Options of the `b11r` tool.
+pub struct B11rOptions {
+    /// Block header.
+    pub input_header: String,
+    /// RLP encoded ommer headers.
+    pub input_ommers: String,
+    /// RLP encoded transactions.
+    pub input_txs: String,
+    /// Withdrawals of a Shanghai block.
+    pub input_withdrawals: Option<String>,
+    /// Directory the output files are written to.
+    pub output_basedir: String,
+    /// Assembled block.
+    pub output_block: String,
+}
+
+/// Runs the `t8n` tool.
+pub fn run_t8n(options: T8nOptions) -> Result<(), String> {
+    let mut inputs = Inputs::default();
+    let alloc: ethjson::spec::State = inputs.read(&options.input_alloc, "alloc")?;
+    let env: Env = inputs.read(&options.input_env, "env")?;
+    let txs: Transactions = inputs.read(&options.input_txs, "txs")?;
+    let chain_id = options.chain_id.unwrap_or_else(|| options.spec.chain_id());
+
+    let transition = transition(
+        &options.spec,
+        alloc.into(),
+        env,
+        txs,
+        options.reward,
+        chain_id,
+    )?;
+
+    let mut outputs = Outputs::new(&options.output_basedir);
+    outputs.write(&options.output_result, "result", &transition.result)?;
+    outputs.write(&options.output_alloc, "alloc", &transition.alloc)?;
+    if let Some(ref output_body) = options.output_body {
+        outputs.write(output_body, "body", &to_hex(&transition.body))?;
+    }
+    outputs.flush();
+    Ok(())
+}
+
+/// Runs the `b11r` tool.
+pub fn run_b11r(options: B11rOptions) -> Result<(), String> {
+    let mut inputs = Inputs::default();
+    let header: Header = inputs.read(&options.input_header, "header")?;
+    let ommers: Vec<Bytes> = inputs.read(&options.input_ommers, "ommers")?;
+    let txs: Bytes = inputs.read(&options.input_txs, "txs")?;
+    let withdrawals: Option<Vec<Withdrawal>> = match options.input_withdrawals {
+        Some(ref input_withdrawals) => Some(inputs.read(input_withdrawals, "withdrawals")?),
+        None => None,
+    };
+
+    let block = build_block(header, ommers, txs, withdrawals)?;
+
+    let mut outputs = Outputs::new(&options.output_basedir);
+    outputs.write(&options.output_block, "block", &block)?;
+    outputs.flush();
+    Ok(())
+}
+
+#[derive(Default)]
+struct Inputs {
+    stdin: Option<Map<String, Value>>,
+}
+
+impl Inputs {
+    fn read<T: DeserializeOwned>(&mut self, path: &str, key: &str) -> Result<T, String> {
+        if path != "stdin" {
+            let file =
+                fs::File::open(path).map_err(|e| format!("Unable to open {}: {}", path, e))?;
+            return serde_json::from_reader(file).map_err(|e| format!("Invalid {}: {}", path, e));
+        }
+
+        if self.stdin.is_none() {
+            let stdin = serde_json::from_reader(io::stdin())
+                .map_err(|e| format!("Invalid standard input: {}", e))?;
+            self.stdin = Some(stdin);
+        }
+        let value = self
+            .stdin
+            .as_mut()
+            .expect("standard input is read above; qed")
+            .remove(key)
+            .ok_or_else(|| format!("Missing {} in the standard input", key))?;
+        serde_json::from_value(value).map_err(|e| format!("Invalid {}: {}", key, e))
+    }
+}
+
+struct Outputs {
+    basedir: PathBuf,
+    stdout: Map<String, Value>,
+    stderr: Map<String, Value>,
+}
+
+impl Outputs {
+    fn new(basedir: &str) -> Self {
+        Outputs {
+            basedir: basedir.into(),
+            stdout: Map::new(),
+            stderr: Map::new(),
+        }
+    }
+
+    fn write<T: Serialize>(&mut self, path: &str, key: &str, value: &T) -> Result<(), String> {
+        let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+        match path {
+            "stdout" => {
+                self.stdout.insert(key.into(), value);
+            }
+            "stderr" => {
+                self.stderr.insert(key.into(), value);
+            }
+            _ => {
+                fs::create_dir_all(&self.basedir).map_err(|e| e.to_string())?;
+                let path = self.basedir.join(path);
+                let file = fs::File::create(&path)
+                    .map_err(|e| format!("Unable to create {}: {}", path.display(), e))?;
+                serde_json::to_writer_pretty(file, &value).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(self) {
+        if !self.stdout.is_empty() {
+            println!("{}", Value::Object(self.stdout));
+        }
+        if !self.stderr.is_empty() {
+            eprintln!("{}", Value::Object(self.stderr));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_block, transition,
+        types::{withdrawals_root, Env, Header, Transactions, Withdrawal},
+    };
+    use ethcore::client::EvmTestClient;
+    use ethereum_types::{Address, H256, U256};
+    use ethjson::{self, bytes::Bytes, spec::ForkSpec};
+    use serde_json;
+    use std::str::FromStr;
+
+    const SENDER: &str = "a94f5374fce5edbc8e2a8697c15331677e6ebf0b";
+
+    fn alloc() -> ethjson::spec::State {
+        alloc_with_code("0x600160005500")
+    }
+
+    fn alloc_with_code(code: &str) -> ethjson::spec::State {
+        serde_json::from_str(&format!(
+            r#"{{
+			"0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b": {{ "balance": "0x3635c9adc5dea00000" }},
+			"0x1000000000000000000000000000000000000000": {{ "code": "{}" }}
+		}}"#,
+            code
+        ))
+        .unwrap()
+    }
+
+    fn env() -> Env {
+        serde_json::from_str(
+            r#"{
+			"currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+			"currentDifficulty": "0x20000",
+			"currentGasLimit": "0x1000000",
+			"currentNumber": "0x1",
+			"currentTimestamp": "0x3e8",
+			"currentBaseFee": "0xa",
+			"blockHashes": { "0": "0x5a39ed1020c04d4d84539975b893a4e7c53eab6c2965db8bc3468093a31bc5ae" }
+		}"#,
+        )
+        .unwrap()
+    }
+
+    fn txs() -> Transactions {
+        serde_json::from_str(
+            r#"[
+			{
+				"type": "0x2",
+				"nonce": "0x0",
+				"maxFeePerGas": "0xa",
+				"maxPriorityFeePerGas": "0x0",
+				"gas": "0x186a0",
+				"to": "0x1000000000000000000000000000000000000000",
+				"value": "0x0",
+				"input": "0x",
+				"secretKey": "0x45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8"
+			},
+			{
+				"nonce": "0x0",
+				"gasPrice": "0xa",
+				"gas": "0x5208",
+				"to": "0x2000000000000000000000000000000000000000",
+				"value": "0x1",
+				"input": "0x",
+				"secretKey": "0x45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8"
+			},
+			{
+				"nonce": "0x1",
+				"gasPrice": "0xa",
+				"gas": "0x5208",
+				"value": "0x0",
+				"input": "0x",
+				"v": "0x1b",
+				"r": "0x0",
+				"s": "0x0"
+			}
+		]"#,
+        )
+        .unwrap()
+    }
+
+    fn withdrawals() -> Vec<Withdrawal> {
+        serde_json::from_str(
+            r#"[
+			{ "index": "0x0", "validatorIndex": "0x1", "address": "0x3000000000000000000000000000000000000000", "amount": "0x2" },
+			{ "index": "0x1", "validatorIndex": "0x2", "address": "0x3000000000000000000000000000000000000000", "amount": "0x3" }
+		]"#,
+        )
+        .unwrap()
+    }
+
+    fn random() -> H256 {
+        H256::from_str("b1ad8a8a1a1f3e5a1e2f04ba6a0f41ddf3c8f2c0ddb0b8ad9fa2e7a6a7d5b0c1").unwrap()
+    }
+
+    fn shanghai_env() -> Env {
+        let mut env = env();
+        env.current_random = Some(ethjson::hash::H256(random()));
+        env.withdrawals = Some(withdrawals());
+        env
+    }
+
+    #[test]
+    fn should_apply_transactions_and_reject_invalid() {
+        let spec = EvmTestClient::spec_from_json(&ForkSpec::London).unwrap();
+        let transition =
+            transition(&spec, alloc().into(), env(), txs(), Some(U256::zero()), 1).unwrap();
+        let result = transition.result;
+
+        assert_eq!(result.receipts.len(), 1);
+        assert_eq!(result.receipts[0].status, U256::one());
+        assert_eq!(
+            result.receipts[0].gas_used,
+            U256::from(21_000 + 2 * 3 + 22_100)
+        );
+        assert_eq!(result.gas_used, result.receipts[0].cumulative_gas_used);
+        assert_eq!(
+            result
+                .rejected
+                .iter()
+                .map(|rejected| rejected.index)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(result.current_base_fee, Some(U256::from(10)));
+
+        let sender = transition
+            .alloc
+            .get()
+            .get(&Address::from_str(SENDER).unwrap())
+            .unwrap()
+            .clone();
+        assert_eq!(sender.nonce, U256::one());
+        let contract = Address::from_str("1000000000000000000000000000000000000000").unwrap();
+        let storage = &transition.alloc.get()[&contract].storage;
+        assert_eq!(storage[&H256::zero()], H256::from_low_u64_be(1));
+        assert_eq!(result.state_root, transition.alloc.root());
+    }
+
+    #[test]
+    fn should_assemble_applied_transactions_into_block() {
+        let spec = EvmTestClient::spec_from_json(&ForkSpec::London).unwrap();
+        let transition =
+            transition(&spec, alloc().into(), env(), txs(), Some(U256::zero()), 1).unwrap();
+        let result = &transition.result;
+
+        let header: Header = serde_json::from_value(json!({
+            "parentHash": "0x5a39ed1020c04d4d84539975b893a4e7c53eab6c2965db8bc3468093a31bc5ae",
+            "miner": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+            "stateRoot": result.state_root,
+            "receiptsRoot": result.receipts_root,
+            "logsBloom": result.logs_bloom,
+            "difficulty": "0x20000",
+            "number": "0x1",
+            "gasLimit": "0x1000000",
+            "gasUsed": result.gas_used,
+            "timestamp": "0x3e8",
+            "baseFeePerGas": "0xa",
+        }))
+        .unwrap();
+        let block = build_block(header, vec![], Bytes::new(transition.body.clone()), None).unwrap();
+
+        let rlp = Bytes::from_str(&block.rlp).unwrap().0;
+        let block_view = ::types::block::Block::decode_rlp(&::rlp::Rlp::new(&rlp), 0).unwrap();
+        assert_eq!(block_view.header.hash(), block.hash);
+        assert_eq!(*block_view.header.transactions_root(), result.tx_root);
+        assert_eq!(block_view.transactions.len(), 1);
+        assert_eq!(
+            block_view.transactions[0].hash,
+            result.receipts[0].transaction_hash
+        );
+    }
+
+    #[test]
+    fn should_apply_withdrawals_since_shanghai() {
+        let spec = EvmTestClient::spec_from_json(&ForkSpec::Shanghai).unwrap();
+        let transition = transition(
+            &spec,
+            alloc().into(),
+            shanghai_env(),
+            txs(),
+            Some(U256::zero()),
+            1,
+        )
+        .unwrap();
+
+        let recipient = Address::from_str("3000000000000000000000000000000000000000").unwrap();
+        assert_eq!(
+            transition.alloc.get()[&recipient].balance,
+            U256::from(5_000_000_000u64)
+        );
+        assert_eq!(
+            transition.result.withdrawals_root,
+            Some(withdrawals_root(&withdrawals()))
+        );
+        assert_eq!(transition.result.state_root, transition.alloc.root());
+    }
+
+    #[test]
+    fn should_require_withdrawals_exactly_since_shanghai() {
+        let shanghai = EvmTestClient::spec_from_json(&ForkSpec::Shanghai).unwrap();
+        let london = EvmTestClient::spec_from_json(&ForkSpec::London).unwrap();
+
+        let mut env = shanghai_env();
+        env.withdrawals = None;
+        assert!(transition(&shanghai, alloc().into(), env, txs(), None, 1).is_err());
+        assert!(transition(&london, alloc().into(), shanghai_env(), txs(), None, 1).is_err());
+    }
+
+    #[test]
+    fn should_read_prevrandao_since_the_merge() {
+        let contract = Address::from_str("1000000000000000000000000000000000000000").unwrap();
+        // DIFFICULTY PUSH1 0 SSTORE STOP
+        let stored = |fork, env| {
+            let spec = EvmTestClient::spec_from_json(&fork).unwrap();
+            transition(
+                &spec,
+                alloc_with_code("0x4460005500").into(),
+                env,
+                txs(),
+                None,
+                1,
+            )
+            .map(|transition| transition.alloc.get()[&contract].storage[&H256::zero()])
+        };
+
+        assert_eq!(
+            stored(ForkSpec::London, env()),
+            Ok(H256::from_low_u64_be(0x20000))
+        );
+        assert_eq!(stored(ForkSpec::Shanghai, shanghai_env()), Ok(random()));
+
+        let mut env = shanghai_env();
+        env.current_random = None;
+        assert!(stored(ForkSpec::Shanghai, env).is_err());
+    }
+
+    #[test]
+    fn should_assemble_block_with_withdrawals() {
+        let spec = EvmTestClient::spec_from_json(&ForkSpec::Shanghai).unwrap();
+        let transition = transition(&spec, alloc().into(), shanghai_env(), txs(), None, 1).unwrap();
+        let result = &transition.result;
+
+        let header: Header = serde_json::from_value(json!({
+            "parentHash": "0x5a39ed1020c04d4d84539975b893a4e7c53eab6c2965db8bc3468093a31bc5ae",
+            "miner": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+            "stateRoot": result.state_root,
+            "receiptsRoot": result.receipts_root,
+            "logsBloom": result.logs_bloom,
+            "number": "0x1",
+            "gasLimit": "0x1000000",
+            "gasUsed": result.gas_used,
+            "timestamp": "0x3e8",
+            "baseFeePerGas": "0xa",
+        }))
+        .unwrap();
+        let block = build_block(
+            header,
+            vec![],
+            Bytes::new(transition.body.clone()),
+            Some(withdrawals()),
+        )
+        .unwrap();
+
+        let rlp = Bytes::from_str(&block.rlp).unwrap().0;
+        let block_rlp = ::rlp::Rlp::new(&rlp);
+        assert_eq!(block_rlp.item_count().unwrap(), 4);
+        assert_eq!(block_rlp.at(3).unwrap().item_count().unwrap(), 2);
+        let header_rlp = block_rlp.at(0).unwrap();
+        assert_eq!(::hash::keccak(header_rlp.as_raw()), block.hash);
+        assert_eq!(header_rlp.item_count().unwrap(), 17);
+        assert_eq!(
+            header_rlp.val_at::<H256>(16).unwrap(),
+            result.withdrawals_root.unwrap()
+        );
+    }
+}
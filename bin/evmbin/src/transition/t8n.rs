@@ -0,0 +1,283 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State transition tool.
+
+use super::types::{
+    to_hex, withdrawals_root, Env, ExecutionResult, Log, Receipt, RejectedTransaction, Transactions,
+};
+use bytes::Bytes;
+use ethcore::{
+    client::{EnvInfo, EvmTestClient},
+    executive,
+    pod_state::PodState,
+    spec::Spec,
+    trace, TrieSpec,
+};
+use ethereum_types::{Address, Bloom, H256, U256};
+use hash::keccak;
+use rlp::{Rlp, RlpStream};
+use std::{cmp, collections::HashMap, sync::Arc};
+use triehash::ordered_trie_root;
+use types::{
+    header::Header,
+    receipt::TransactionOutcome,
+    transaction::{Action, SignedTransaction, TypedTransaction, UnverifiedTransaction},
+};
+
+/// Outcome of a state transition.
+pub struct Transition {
+    /// Execution result.
+    pub result: ExecutionResult,
+    /// Post state.
+    pub alloc: PodState,
+    /// RLP encoded list of the applied transactions.
+    pub body: Bytes,
+}
+
+/// Applies `txs` on top of `alloc` in the block described by `env`.
+///
+/// Transactions that are invalid or cannot be applied are reported as rejected.
+/// The block `reward` (if any) is credited to the block author and ommers at the end,
+/// followed by the withdrawals of the block.
+pub fn transition(
+    spec: &Spec,
+    alloc: PodState,
+    env: Env,
+    txs: Transactions,
+    reward: Option<U256>,
+    chain_id: u64,
+) -> Result<Transition, String> {
+    let machine = spec.engine.machine();
+    let number: u64 = env.current_number.into();
+    let schedule = machine.schedule(number);
+
+    let mut header = Header::new();
+    header.set_number(number);
+
+    let withdrawals = match (env.withdrawals, schedule.eip4895) {
+        (Some(withdrawals), true) => Some(withdrawals),
+        (None, false) => None,
+        (None, true) => return Err("withdrawals are required since Shanghai".into()),
+        (Some(_), false) => return Err("withdrawals are not supported before Shanghai".into()),
+    };
+
+    let base_fee = match (
+        env.current_base_fee,
+        env.parent_base_fee,
+        env.parent_gas_used,
+        env.parent_gas_limit,
+    ) {
+        (Some(base_fee), _, _, _) => Some(base_fee.into()),
+        (None, Some(parent_base_fee), Some(parent_gas_used), Some(parent_gas_limit))
+            if number > 0 =>
+        {
+            let mut parent = Header::new();
+            parent.set_number(number - 1);
+            parent.set_gas_used(parent_gas_used.into());
+            parent.set_gas_limit(parent_gas_limit.into());
+            parent.set_base_fee(Some(parent_base_fee.into()));
+            machine.calc_base_fee(&parent)
+        }
+        _ if schedule.eip1559 => {
+            return Err("currentBaseFee or the parent block fields are required".into())
+        }
+        _ => None,
+    };
+
+    let block_hashes: HashMap<u64, H256> = env
+        .block_hashes
+        .into_iter()
+        .map(|(number, hash)| (number.into(), hash.into()))
+        .collect();
+    let last_hashes = (1..cmp::min(number + 1, 257))
+        .map(|i| block_hashes.get(&(number - i)).cloned().unwrap_or_default())
+        .collect();
+
+    let author: Address = env.current_coinbase.into();
+    let difficulty = env.current_difficulty.map_or_else(U256::zero, Into::into);
+    // After the merge the opcode formerly known as DIFFICULTY reads the randomness
+    // of the beacon chain instead (EIP-4399).
+    let env_difficulty = if schedule.eip4399 {
+        let random: H256 = env
+            .current_random
+            .ok_or("currentRandom is required since the merge")?
+            .into();
+        U256::from_big_endian(random.as_bytes())
+    } else {
+        difficulty
+    };
+    let mut env_info = EnvInfo {
+        number,
+        author,
+        timestamp: env.current_timestamp.into(),
+        difficulty: env_difficulty,
+        last_hashes: Arc::new(last_hashes),
+        gas_used: U256::zero(),
+        gas_limit: env.current_gas_limit.into(),
+        base_fee,
+    };
+
+    let txs: Vec<Result<UnverifiedTransaction, String>> = match txs {
+        Transactions::Rlp(rlp) => {
+            let rlp: Vec<u8> = rlp.into();
+            TypedTransaction::decode_rlp_list(&Rlp::new(&rlp))
+                .map_err(|e| format!("Invalid transactions RLP: {}", e))?
+                .into_iter()
+                .map(Ok)
+                .collect()
+        }
+        Transactions::Json(txs) => txs
+            .into_iter()
+            .map(|tx| tx.into_unverified(chain_id))
+            .collect(),
+    };
+
+    let mut client = EvmTestClient::from_pod_state_with_trie(spec, alloc, TrieSpec::Fat)
+        .map_err(|e| e.to_string())?;
+    let mut included = Vec::new();
+    let mut receipts = Vec::new();
+    let mut rejected = Vec::new();
+
+    for (index, tx) in txs.into_iter().enumerate() {
+        let signed = tx.and_then(|tx| {
+            // Re-decode the transaction to apply the fork specific checks.
+            let tx = machine
+                .decode_transaction(&tx.encode(), &schedule)
+                .map_err(|e| e.to_string())?;
+            machine
+                .verify_transaction_basic(&tx, &header)
+                .map_err(|e| e.to_string())?;
+            SignedTransaction::new(tx).map_err(|e| e.to_string())
+        });
+        let signed = match signed {
+            Ok(signed) => signed,
+            Err(error) => {
+                rejected.push(RejectedTransaction { index, error });
+                continue;
+            }
+        };
+
+        let outcome = match client.apply(&env_info, &signed, trace::NoopTracer, trace::NoopVMTracer)
+        {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                rejected.push(RejectedTransaction {
+                    index,
+                    error: error.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let receipt = outcome.receipt.receipt();
+        let gas_used = receipt.gas_used - env_info.gas_used;
+        env_info.gas_used = receipt.gas_used;
+        let contract_address = match signed.tx().action {
+            Action::Create => {
+                executive::contract_address(
+                    machine.create_address_scheme(number),
+                    &signed.sender(),
+                    &signed.tx().nonce,
+                    &signed.tx().data,
+                )
+                .0
+            }
+            Action::Call(_) => Address::zero(),
+        };
+        let (root, status) = match receipt.outcome {
+            TransactionOutcome::StateRoot(root) => (to_hex(root.as_bytes()), U256::zero()),
+            TransactionOutcome::StatusCode(status) => ("0x".into(), status.into()),
+            TransactionOutcome::Unknown => ("0x".into(), U256::zero()),
+        };
+        receipts.push(Receipt {
+            transaction_type: (signed.tx_type() as u8).into(),
+            root,
+            status,
+            cumulative_gas_used: receipt.gas_used,
+            logs_bloom: receipt.log_bloom,
+            logs: receipt.logs.iter().cloned().map(Log::from).collect(),
+            transaction_hash: signed.hash(),
+            contract_address,
+            gas_used,
+            block_hash: H256::zero(),
+            transaction_index: included.len().into(),
+        });
+        included.push((outcome.receipt, signed));
+    }
+
+    let mut rewards = match reward {
+        Some(reward) => {
+            let ommers = env.ommers.len() as u64;
+            let mut rewards = vec![(author, reward + reward / 32 * ommers)];
+            for ommer in env.ommers {
+                let ommer_reward = reward * (8 - cmp::min(ommer.delta, 8)) / 8;
+                rewards.push((ommer.address.into(), ommer_reward));
+            }
+            rewards
+        }
+        None => Vec::new(),
+    };
+    if let Some(ref withdrawals) = withdrawals {
+        rewards.extend(
+            withdrawals
+                .iter()
+                .map(|withdrawal| (withdrawal.address.0, withdrawal.amount_in_wei())),
+        );
+    }
+    let state_root = client
+        .finalize(&env_info, &rewards)
+        .map_err(|e| e.to_string())?;
+    let alloc = client.state().to_pod_full().map_err(|e| e.to_string())?;
+
+    let mut logs = RlpStream::new_list(included.iter().map(|(r, _)| r.receipt().logs.len()).sum());
+    for (receipt, _) in &included {
+        for log in &receipt.receipt().logs {
+            logs.append(log);
+        }
+    }
+    let mut body = RlpStream::new();
+    UnverifiedTransaction::rlp_append_list(
+        &mut body,
+        &included
+            .iter()
+            .map(|(_, tx)| UnverifiedTransaction::from(tx.clone()))
+            .collect::<Vec<_>>(),
+    );
+
+    let result = ExecutionResult {
+        state_root,
+        tx_root: ordered_trie_root(included.iter().map(|(_, tx)| tx.encode())),
+        receipts_root: ordered_trie_root(included.iter().map(|(r, _)| r.encode())),
+        logs_hash: keccak(logs.out()),
+        logs_bloom: included.iter().fold(Bloom::zero(), |mut bloom, (r, _)| {
+            bloom.accrue_bloom(&r.receipt().log_bloom);
+            bloom
+        }),
+        receipts,
+        rejected,
+        current_difficulty: difficulty,
+        gas_used: env_info.gas_used,
+        current_base_fee: base_fee,
+        withdrawals_root: withdrawals.as_deref().map(withdrawals_root),
+    };
+
+    Ok(Transition {
+        result,
+        alloc,
+        body: body.out(),
+    })
+}
@@ -0,0 +1,385 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Input and output formats of the `t8n` and `b11r` tools.
+
+use ethereum_types::{Address, Bloom, H256, U256};
+use ethjson::{
+    blockchain::transaction::AccessListItem, bytes::Bytes, crypto::publickey::Secret, hash,
+    maybe::MaybeEmpty, uint::Uint,
+};
+use rlp::{self, Encodable, RlpStream};
+use rustc_hex::ToHex;
+use std::collections::BTreeMap;
+use triehash::ordered_trie_root;
+use types::{
+    log_entry::LogEntry,
+    transaction::{
+        signature, AccessListTx, Action, EIP1559TransactionTx, SignatureComponents,
+        Transaction as RawTransaction, TypedTransaction, TypedTxId, UnverifiedTransaction,
+    },
+};
+
+/// Environment of the block the transactions are applied in.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Env {
+    /// Block author.
+    pub current_coinbase: hash::Address,
+    /// Block difficulty.
+    pub current_difficulty: Option<Uint>,
+    /// Randomness of the beacon chain, read by PREVRANDAO since the merge.
+    pub current_random: Option<hash::H256>,
+    /// Block gas limit.
+    pub current_gas_limit: Uint,
+    /// Block number.
+    pub current_number: Uint,
+    /// Block timestamp.
+    pub current_timestamp: Uint,
+    /// Block base fee, derived from the parent fields if missing.
+    pub current_base_fee: Option<Uint>,
+    /// Parent base fee.
+    pub parent_base_fee: Option<Uint>,
+    /// Parent gas used.
+    pub parent_gas_used: Option<Uint>,
+    /// Parent gas limit.
+    pub parent_gas_limit: Option<Uint>,
+    /// Hashes of the previous blocks, available to `BLOCKHASH`.
+    #[serde(default)]
+    pub block_hashes: BTreeMap<Uint, hash::H256>,
+    /// Ommers of the block, used for the reward calculation.
+    #[serde(default)]
+    pub ommers: Vec<Ommer>,
+    /// Withdrawals of the block, required since Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+/// Block ommer.
+#[derive(Debug, Deserialize)]
+pub struct Ommer {
+    /// Distance to the including block.
+    pub delta: u64,
+    /// Ommer author.
+    pub address: hash::Address,
+}
+
+/// Withdrawal from the consensus layer (EIP-4895).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    /// Withdrawal index.
+    pub index: Uint,
+    /// Validator index.
+    pub validator_index: Uint,
+    /// Recipient.
+    pub address: hash::Address,
+    /// Amount in Gwei.
+    pub amount: Uint,
+}
+
+impl Withdrawal {
+    /// Amount credited to the recipient in Wei.
+    pub fn amount_in_wei(&self) -> U256 {
+        self.amount.0.saturating_mul(U256::exp10(9))
+    }
+}
+
+impl Encodable for Withdrawal {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(4);
+        s.append(&self.index.0);
+        s.append(&self.validator_index.0);
+        s.append(&self.address.0);
+        s.append(&self.amount.0);
+    }
+}
+
+/// Root of the withdrawals trie.
+pub fn withdrawals_root(withdrawals: &[Withdrawal]) -> H256 {
+    ordered_trie_root(withdrawals.iter().map(|withdrawal| rlp::encode(withdrawal)))
+}
+
+/// Transactions, either RLP encoded or as a list of JSON objects.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Transactions {
+    /// RLP encoded list of signed transactions.
+    Rlp(Bytes),
+    /// Transactions in JSON format.
+    Json(Vec<Transaction>),
+}
+
+/// Transaction in JSON format. Signed with `secretKey` if present,
+/// otherwise `v`, `r` and `s` are used as the signature.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    /// Transaction type.
+    #[serde(rename = "type")]
+    pub transaction_type: Option<Uint>,
+    /// Chain id of typed transactions.
+    pub chain_id: Option<Uint>,
+    /// Nonce.
+    pub nonce: Uint,
+    /// Gas price of legacy and access list transactions.
+    pub gas_price: Option<Uint>,
+    /// Max fee per gas of EIP-1559 transactions.
+    pub max_fee_per_gas: Option<Uint>,
+    /// Max priority fee per gas of EIP-1559 transactions.
+    pub max_priority_fee_per_gas: Option<Uint>,
+    /// Gas limit.
+    #[serde(alias = "gasLimit")]
+    pub gas: Uint,
+    /// Recipient, contract creation if missing or empty.
+    pub to: Option<MaybeEmpty<hash::Address>>,
+    /// Value.
+    pub value: Uint,
+    /// Input data.
+    #[serde(alias = "data")]
+    pub input: Bytes,
+    /// Access list.
+    pub access_list: Option<Vec<AccessListItem>>,
+    /// Signature v.
+    pub v: Option<Uint>,
+    /// Signature r.
+    pub r: Option<Uint>,
+    /// Signature s.
+    pub s: Option<Uint>,
+    /// Key to sign the transaction with.
+    pub secret_key: Option<hash::H256>,
+    /// Whether a legacy transaction signed with `secretKey` is replay protected.
+    pub protected: Option<bool>,
+}
+
+impl Transaction {
+    /// Converts into a signed transaction, `chain_id` is used where the
+    /// transaction does not specify it.
+    pub fn into_unverified(self, chain_id: u64) -> Result<UnverifiedTransaction, String> {
+        let chain_id = self.chain_id.map_or(chain_id, |id| id.0.low_u64());
+        let to: Option<hash::Address> = self.to.and_then(Into::into);
+        let transaction = RawTransaction {
+            nonce: self.nonce.into(),
+            gas_price: self
+                .gas_price
+                .or(self.max_fee_per_gas)
+                .map_or_else(U256::zero, Into::into),
+            gas: self.gas.into(),
+            action: match to {
+                Some(to) => Action::Call(to.into()),
+                None => Action::Create,
+            },
+            value: self.value.into(),
+            data: self.input.into(),
+        };
+        let access_list = self
+            .access_list
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| (item.address, item.storage_keys))
+            .collect();
+
+        let unsigned = match self.transaction_type.map(|t| t.0.low_u64()) {
+            None | Some(0) => TypedTransaction::Legacy(transaction),
+            Some(1) => TypedTransaction::AccessList(AccessListTx::new(transaction, access_list)),
+            Some(2) => TypedTransaction::EIP1559Transaction(EIP1559TransactionTx {
+                transaction: AccessListTx::new(transaction, access_list),
+                max_priority_fee_per_gas: self
+                    .max_priority_fee_per_gas
+                    .map_or_else(U256::zero, Into::into),
+            }),
+            Some(other) => return Err(format!("unsupported transaction type {}", other)),
+        };
+
+        if let Some(secret) = self.secret_key {
+            let secret = Secret::import_key(secret.0.as_bytes()).map_err(|e| e.to_string())?;
+            let chain_id = match unsigned.tx_type() {
+                TypedTxId::Legacy if self.protected == Some(false) => None,
+                _ => Some(chain_id),
+            };
+            return Ok(unsigned.sign(&secret, chain_id).into());
+        }
+
+        let (v, r, s) = match (self.v, self.r, self.s) {
+            (Some(v), Some(r), Some(s)) => (v.0.low_u64(), r.into(), s.into()),
+            _ => return Err("transaction is neither signed nor has a secretKey".into()),
+        };
+        let (standard_v, chain_id) = match unsigned.tx_type() {
+            TypedTxId::Legacy => (
+                signature::extract_standard_v(v),
+                signature::extract_chain_id_from_legacy_v(v),
+            ),
+            _ if v > 1 => (4, Some(chain_id)),
+            _ => (v as u8, Some(chain_id)),
+        };
+
+        Ok(UnverifiedTransaction {
+            unsigned,
+            signature: SignatureComponents { standard_v, r, s },
+            chain_id,
+            hash: H256::zero(),
+        }
+        .compute_hash())
+    }
+}
+
+/// Header of the block to assemble.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Header {
+    /// Parent hash.
+    pub parent_hash: hash::H256,
+    /// Ommers hash, computed from the ommers if missing.
+    #[serde(alias = "sha3Uncles")]
+    pub ommers_hash: Option<hash::H256>,
+    /// Block author.
+    #[serde(alias = "coinbase")]
+    pub miner: hash::Address,
+    /// State root.
+    pub state_root: hash::H256,
+    /// Transactions root, computed from the transactions if missing.
+    #[serde(alias = "transactionsTrie")]
+    pub transactions_root: Option<hash::H256>,
+    /// Receipts root.
+    #[serde(alias = "receiptTrie")]
+    pub receipts_root: hash::H256,
+    /// Logs bloom.
+    #[serde(alias = "bloom")]
+    pub logs_bloom: Option<hash::Bloom>,
+    /// Difficulty.
+    pub difficulty: Option<Uint>,
+    /// Block number.
+    pub number: Uint,
+    /// Gas limit.
+    pub gas_limit: Uint,
+    /// Gas used.
+    pub gas_used: Uint,
+    /// Timestamp.
+    pub timestamp: Uint,
+    /// Extra data.
+    pub extra_data: Option<Bytes>,
+    /// Mix hash.
+    pub mix_hash: Option<hash::H256>,
+    /// Seal nonce.
+    pub nonce: Option<hash::H64>,
+    /// Base fee.
+    pub base_fee_per_gas: Option<Uint>,
+    /// Withdrawals root, computed from the withdrawals if missing.
+    pub withdrawals_root: Option<hash::H256>,
+}
+
+/// Result of a state transition.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionResult {
+    /// Post state root.
+    pub state_root: H256,
+    /// Root of the applied transactions.
+    pub tx_root: H256,
+    /// Root of the receipts.
+    pub receipts_root: H256,
+    /// Hash of the RLP encoded logs of all receipts.
+    pub logs_hash: H256,
+    /// Combined bloom of all receipts.
+    pub logs_bloom: Bloom,
+    /// Receipts of the applied transactions.
+    pub receipts: Vec<Receipt>,
+    /// Transactions that could not be applied.
+    pub rejected: Vec<RejectedTransaction>,
+    /// Block difficulty.
+    pub current_difficulty: U256,
+    /// Gas used by the applied transactions.
+    pub gas_used: U256,
+    /// Block base fee.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_base_fee: Option<U256>,
+    /// Root of the applied withdrawals, since Shanghai.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<H256>,
+}
+
+/// Receipt of an applied transaction.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    /// Transaction type.
+    #[serde(rename = "type")]
+    pub transaction_type: U256,
+    /// Intermediate state root, empty after Byzantium.
+    pub root: String,
+    /// Status code, `0x1` on success.
+    pub status: U256,
+    /// Gas used in the block up to and including this transaction.
+    pub cumulative_gas_used: U256,
+    /// Bloom of the logs.
+    pub logs_bloom: Bloom,
+    /// Logs.
+    pub logs: Vec<Log>,
+    /// Transaction hash.
+    pub transaction_hash: H256,
+    /// Address of the created contract, zero if none.
+    pub contract_address: Address,
+    /// Gas used by this transaction.
+    pub gas_used: U256,
+    /// Block hash, always zero as the block is not assembled.
+    pub block_hash: H256,
+    /// Index of the transaction in the block.
+    pub transaction_index: U256,
+}
+
+/// Log entry of a receipt.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+    /// Logging contract.
+    pub address: Address,
+    /// Topics.
+    pub topics: Vec<H256>,
+    /// Data.
+    pub data: String,
+}
+
+impl From<LogEntry> for Log {
+    fn from(log: LogEntry) -> Self {
+        Log {
+            address: log.address,
+            topics: log.topics,
+            data: to_hex(&log.data),
+        }
+    }
+}
+
+/// Transaction that could not be applied.
+#[derive(Debug, Serialize)]
+pub struct RejectedTransaction {
+    /// Index of the transaction in the input.
+    pub index: usize,
+    /// Reason of the rejection.
+    pub error: String,
+}
+
+/// Assembled block.
+#[derive(Debug, Serialize)]
+pub struct Block {
+    /// RLP encoded block.
+    pub rlp: String,
+    /// Block hash.
+    pub hash: H256,
+}
+
+/// Formats bytes as `0x` prefixed hex.
+pub fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", bytes.to_hex())
+}
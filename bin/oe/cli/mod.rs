@@ -433,6 +433,42 @@ usage! {
             "--jsonrpc-max-payload=[MB]",
             "Specify maximum size for HTTP JSON-RPC requests in megabytes.",
 
+            ARG arg_jsonrpc_rate_limit: (u64) = 0u64, or |c: &Config| c.rpc.as_ref()?.rate_limit.clone(),
+            "--jsonrpc-rate-limit=[COST]",
+            "Budget of request cost units refilled to every HTTP and WebSockets JSON-RPC client each second. Clients authorized with a JWT that names its holder in the id claim are identified by the holder, so that all tokens and connections of a holder share one budget. Other HTTP clients are identified by their IP address and other WebSockets clients by their connection, as the WebSockets server does not expose the peer address. 0 disables the limit.",
+
+            ARG arg_jsonrpc_rate_limit_burst: (Option<u64>) = None, or |c: &Config| c.rpc.as_ref()?.rate_limit_burst.clone(),
+            "--jsonrpc-rate-limit-burst=[COST]",
+            "Maximal number of request cost units a JSON-RPC client may accumulate while idle. Defaults to the value of --jsonrpc-rate-limit.",
+
+            ARG arg_jsonrpc_method_costs: (Option<String>) = None, or |c: &Config| c.rpc.as_ref()?.method_costs.as_ref().map(|vec| vec.join(",")),
+            "--jsonrpc-method-costs=[COSTS]",
+            "Override costs of JSON-RPC methods using a comma-delimited list of METHOD=COST pairs, example: eth_getLogs=100,trace_filter=200. Methods without an explicit cost cost 1 unit.",
+
+            ARG arg_jsonrpc_max_concurrent_requests: (usize) = 0usize, or |c: &Config| c.rpc.as_ref()?.max_concurrent_requests.clone(),
+            "--jsonrpc-max-concurrent-requests=[NUM]",
+            "Maximal number of requests a single HTTP or WebSockets JSON-RPC client may have in flight. 0 disables the limit.",
+
+            ARG arg_jsonrpc_max_batch_size: (usize) = 0usize, or |c: &Config| c.rpc.as_ref()?.max_batch_size.clone(),
+            "--jsonrpc-max-batch-size=[NUM]",
+            "Maximal number of calls in a single JSON-RPC batch request. 0 disables the limit.",
+
+            ARG arg_rpc_trusted_proxies: (Option<String>) = None, or |c: &Config| c.rpc.as_ref()?.trusted_proxies.as_ref().map(|vec| vec.join(",")),
+            "--rpc-trusted-proxies=[IPS]",
+            "Comma-delimited list of IP addresses of reverse proxies in front of the HTTP JSON-RPC server. Requests received from these addresses are attributed to the client in the X-Forwarded-For header for rate limiting.",
+
+            ARG arg_jsonrpc_jwt_secret: (Option<String>) = None, or |c: &Config| c.rpc.as_ref()?.jwt_secret.clone(),
+            "--jsonrpc-jwt-secret=[FILE]",
+            "Require every HTTP JSON-RPC call to carry an HS256 JWT signed with the hex-encoded 32 byte secret stored in FILE. The token is passed as a bearer token in the Authorization header and its iat claim has to be within 60 seconds of the current time. An optional apis claim restricts the token to the listed APIs (names as in --jsonrpc-apis). An optional id claim names the holder of the token for rate limiting. A random secret is written to FILE if it does not exist.",
+
+            FLAG flag_jsonrpc_graphql: (bool) = false, or |c: &Config| c.rpc.as_ref()?.graphql.clone(),
+            "--jsonrpc-graphql",
+            "Enable the EIP-1767 GraphQL server. It listens on the HTTP JSON-RPC interface and shares its hosts, CORS, JWT, payload size, server threads and rate limit settings. Every query is charged as a call of the graphql method. Queries nesting fields deeper than 10 levels are rejected.",
+
+            ARG arg_jsonrpc_graphql_port: (u16) = 8547u16, or |c: &Config| c.rpc.as_ref()?.graphql_port.clone(),
+            "--jsonrpc-graphql-port=[PORT]",
+            "Specify the port portion of the GraphQL server.",
+
             ARG arg_poll_lifetime: (u32) = 60u32, or |c: &Config| c.rpc.as_ref()?.poll_lifetime.clone(),
             "--poll-lifetime=[S]",
             "Set the RPC filter lifetime to S seconds. The filter has to be polled at least every S seconds , otherwise it is removed.",
@@ -470,6 +506,10 @@ usage! {
             "--ws-max-payload=[MB]",
             "Specify maximum size for WS JSON-RPC requests in megabytes.",
 
+            ARG arg_ws_jwt_secret: (Option<String>) = None, or |c: &Config| c.websockets.as_ref()?.jwt_secret.clone(),
+            "--ws-jwt-secret=[FILE]",
+            "Require WebSockets JSON-RPC connections to present an HS256 JWT signed with the hex-encoded 32 byte secret stored in FILE in the Authorization header of the handshake. An optional apis claim restricts the connection to the listed APIs (names as in --ws-apis). An optional id claim names the holder of the token for rate limiting. A random secret is written to FILE if it does not exist.",
+
         ["Metrics"]
             FLAG flag_metrics: (bool) = false, or |c: &Config| c.metrics.as_ref()?.enable.clone(),
             "--metrics",
@@ -606,6 +646,10 @@ usage! {
             "--no-persistent-txqueue",
             "Don't save pending local transactions to disk to be restored whenever the node restarts.",
 
+            FLAG flag_tx_queue_journal: (bool) = false, or |c: &Config| c.mining.as_ref()?.tx_queue_journal.clone(),
+            "--tx-queue-journal",
+            "Also save the transactions in the queue which are not of local origin to disk, to be verified and imported again whenever the node restarts.",
+
             FLAG flag_stratum: (bool) = false, or |c: &Config| Some(c.stratum.is_some()),
             "--stratum",
             "Run Stratum server for miner push notification.",
@@ -662,6 +706,34 @@ usage! {
             "--tx-queue-per-sender=[LIMIT]",
             "Maximum number of transactions per sender in the queue. By default it's 1% of the entire queue, but not less than 16.",
 
+            ARG arg_tx_queue_pending_per_sender: (Option<usize>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_pending_per_sender.clone(),
+            "--tx-queue-pending-per-sender=[LIMIT]",
+            "Maximum number of pending transactions (executable in the next block) per sender in the queue. Pending transactions with the highest nonces are evicted first. Unlimited by default.",
+
+            ARG arg_tx_queue_future_per_sender: (Option<usize>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_future_per_sender.clone(),
+            "--tx-queue-future-per-sender=[LIMIT]",
+            "Maximum number of future transactions (not executable because of a nonce gap) per sender in the queue. Future transactions with the highest nonces are evicted first. Unlimited by default.",
+
+            ARG arg_tx_queue_future_lifetime: (u64) = 0u64, or |c: &Config| c.mining.as_ref()?.tx_queue_future_lifetime.clone(),
+            "--tx-queue-future-lifetime=[SECONDS]",
+            "Maximum time a transaction can stay future (not executable because of a nonce gap) in the queue. Setting this parameter to 0 disables the limit.",
+
+            ARG arg_tx_private_fallback_blocks: (u64) = 5u64, or |c: &Config| c.mining.as_ref()?.tx_private_fallback_blocks.clone(),
+            "--tx-private-fallback-blocks=[BLOCKS]",
+            "Number of blocks transactions submitted with eth_sendPrivateRawTransaction are only forwarded to the validators, before falling back to public propagation. Setting this parameter to 0 propagates them publicly right away.",
+
+            ARG arg_tx_queue_admission_policy: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_admission_policy.clone(),
+            "--tx-queue-admission-policy=[FILE]",
+            "Specify a TOML file with local admission rules for the transaction queue (denied or allowed senders and recipients, denied method selectors, maximal data size and minimal priority fees). The file can be reloaded with the parity_reloadAdmissionPolicy RPC.",
+
+            ARG arg_tx_queue_capture: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_capture.clone(),
+            "--tx-queue-capture=[FILE]",
+            "Append every public transaction imported to the transaction queue and every new best block with a timestamp to FILE. The capture can be replayed with the txpool-replay tool.",
+
+            ARG arg_tx_queue_capture_size_limit: (u64) = 100u64, or |c: &Config| c.mining.as_ref()?.tx_queue_capture_size_limit.clone(),
+            "--tx-queue-capture-size-limit=[MB]",
+            "Maximum size of the transaction queue capture file. A file exceeding it is moved to FILE.1, replacing the previous one, and a new capture file is started.",
+
             ARG arg_tx_queue_locals: (Option<String>) = None, or |c: &Config| helpers::join_set(c.mining.as_ref()?.tx_queue_locals.as_ref()),
             "--tx-queue-locals=[ACCOUNTS]",
             "Specify local accounts for which transactions are prioritized in the queue. ACCOUNTS is a comma-delimited list of addresses.",
@@ -670,6 +742,14 @@ usage! {
             "--tx-queue-strategy=[S]",
             "Prioritization strategy used to order transactions in the queue. S may be: gas_price - Prioritize txs with high gas price",
 
+            ARG arg_tx_queue_journal_size: (usize) = 8_192usize, or |c: &Config| c.mining.as_ref()?.tx_queue_journal_size.clone(),
+            "--tx-queue-journal-size=[LIMIT]",
+            "Maximum number of transactions saved to disk with --tx-queue-journal, the ones with the highest gas price being kept.",
+
+            ARG arg_tx_queue_journal_max_age: (u64) = 10_800u64, or |c: &Config| c.mining.as_ref()?.tx_queue_journal_max_age.clone(),
+            "--tx-queue-journal-max-age=[SECS]",
+            "Transactions saved to disk with --tx-queue-journal for longer than SECS seconds are dropped.",
+
             ARG arg_stratum_interface: (String) = "local", or |c: &Config| c.stratum.as_ref()?.interface.clone(),
             "--stratum-interface=[IP]",
             "Interface address for Stratum server.",
@@ -684,7 +764,11 @@ usage! {
 
             ARG arg_gas_price_percentile: (usize) = 50usize, or |c: &Config| c.mining.as_ref()?.gas_price_percentile,
             "--gas-price-percentile=[PCT]",
-            "Set PCT percentile gas price value from last 100 blocks as default gas price when sending transactions.",
+            "Set PCT percentile gas price value from the recent blocks as default gas price when sending transactions.",
+
+            ARG arg_gas_price_history_blocks: (usize) = 100usize, or |c: &Config| c.mining.as_ref()?.gas_price_history_blocks,
+            "--gas-price-history-blocks=[BLOCKS]",
+            "Number of recent blocks, at most 1024, the default gas price and max priority fee are computed from. Transactions with zero gas price are not taken into account and the suggestions are never below the minimum gas price of the engine.",
 
             ARG arg_author: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.author.clone(),
             "--author=[ADDRESS]",
@@ -805,6 +889,14 @@ usage! {
             "--num-verifiers=[INT]",
             "Amount of verifier threads to use or to begin with, if verifier auto-scaling is enabled.",
 
+            FLAG flag_parallel_transactions: (bool) = false, or |c: &Config| c.footprint.as_ref()?.parallel_transactions.clone(),
+            "--parallel-transactions",
+            "Execute the transactions of imported blocks optimistically in parallel instead of sequentially.",
+
+            FLAG flag_state_prefetch: (bool) = false, or |c: &Config| c.footprint.as_ref()?.state_prefetch.clone(),
+            "--state-prefetch",
+            "Warm the state cache for imported blocks on background threads before they are executed.",
+
         ["Import/export Options"]
             FLAG flag_no_seal_check: (bool) = false, or |_| None,
             "--no-seal-check",
@@ -907,6 +999,15 @@ struct Rpc {
     experimental_rpcs: Option<bool>,
     poll_lifetime: Option<u32>,
     allow_missing_blocks: Option<bool>,
+    rate_limit: Option<u64>,
+    rate_limit_burst: Option<u64>,
+    method_costs: Option<Vec<String>>,
+    max_concurrent_requests: Option<usize>,
+    max_batch_size: Option<usize>,
+    trusted_proxies: Option<Vec<String>>,
+    jwt_secret: Option<String>,
+    graphql: Option<bool>,
+    graphql_port: Option<u16>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -920,6 +1021,7 @@ struct Ws {
     hosts: Option<Vec<String>>,
     max_connections: Option<usize>,
     max_payload: Option<usize>,
+    jwt_secret: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -978,6 +1080,7 @@ struct Mining {
     relay_set: Option<String>,
     min_gas_price: Option<u64>,
     gas_price_percentile: Option<usize>,
+    gas_price_history_blocks: Option<usize>,
     usd_per_tx: Option<String>,
     usd_per_eth: Option<String>,
     price_update_period: Option<String>,
@@ -986,6 +1089,13 @@ struct Mining {
     extra_data: Option<String>,
     tx_queue_size: Option<usize>,
     tx_queue_per_sender: Option<usize>,
+    tx_queue_pending_per_sender: Option<usize>,
+    tx_queue_future_per_sender: Option<usize>,
+    tx_queue_future_lifetime: Option<u64>,
+    tx_private_fallback_blocks: Option<u64>,
+    tx_queue_admission_policy: Option<String>,
+    tx_queue_capture: Option<String>,
+    tx_queue_capture_size_limit: Option<u64>,
     tx_queue_mem_limit: Option<u32>,
     tx_queue_locals: Option<HashSet<String>>,
     tx_queue_strategy: Option<String>,
@@ -993,6 +1103,9 @@ struct Mining {
     tx_queue_ban_time: Option<u16>,
     tx_queue_no_unfamiliar_locals: Option<bool>,
     tx_queue_no_early_reject: Option<bool>,
+    tx_queue_journal: Option<bool>,
+    tx_queue_journal_size: Option<usize>,
+    tx_queue_journal_max_age: Option<u64>,
     remove_solved: Option<bool>,
     notify_work: Option<Vec<String>>,
     refuse_service_transactions: Option<bool>,
@@ -1026,6 +1139,8 @@ struct Footprint {
     fat_db: Option<String>,
     scale_verifiers: Option<bool>,
     num_verifiers: Option<usize>,
+    parallel_transactions: Option<bool>,
+    state_prefetch: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1333,6 +1448,15 @@ mod tests {
                 arg_jsonrpc_server_threads: None,
                 arg_jsonrpc_threads: 4,
                 arg_jsonrpc_max_payload: None,
+                arg_jsonrpc_rate_limit: 0u64,
+                arg_jsonrpc_rate_limit_burst: None,
+                arg_jsonrpc_method_costs: None,
+                arg_jsonrpc_max_concurrent_requests: 0usize,
+                arg_jsonrpc_max_batch_size: 0usize,
+                arg_rpc_trusted_proxies: None,
+                arg_jsonrpc_jwt_secret: None,
+                flag_jsonrpc_graphql: false,
+                arg_jsonrpc_graphql_port: 8547u16,
                 arg_poll_lifetime: 60u32,
                 flag_jsonrpc_allow_missing_blocks: false,
 
@@ -1345,6 +1469,7 @@ mod tests {
                 arg_ws_hosts: "none".into(),
                 arg_ws_max_connections: 100,
                 arg_ws_max_payload: 5,
+                arg_ws_jwt_secret: None,
 
                 // IPC
                 flag_no_ipc: false,
@@ -1393,6 +1518,7 @@ mod tests {
                 arg_min_gas_price: Some(0u64),
                 arg_usd_per_tx: "0.0001".into(),
                 arg_gas_price_percentile: 50usize,
+                arg_gas_price_history_blocks: 100usize,
                 arg_usd_per_eth: "auto".into(),
                 arg_price_update_period: "hourly".into(),
                 arg_gas_floor_target: "8000000".into(),
@@ -1402,9 +1528,19 @@ mod tests {
                 flag_tx_queue_no_early_reject: false,
                 arg_tx_queue_size: 8192usize,
                 arg_tx_queue_per_sender: None,
+                arg_tx_queue_pending_per_sender: None,
+                arg_tx_queue_future_per_sender: None,
+                arg_tx_queue_future_lifetime: 0u64,
+                arg_tx_private_fallback_blocks: 5u64,
+                arg_tx_queue_admission_policy: None,
+                arg_tx_queue_capture: None,
+                arg_tx_queue_capture_size_limit: 100u64,
                 arg_tx_queue_mem_limit: 4u32,
                 arg_tx_queue_locals: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
                 arg_tx_queue_strategy: "gas_factor".into(),
+                flag_tx_queue_journal: false,
+                arg_tx_queue_journal_size: 8192usize,
+                arg_tx_queue_journal_max_age: 10800u64,
                 flag_remove_solved: false,
                 arg_notify_work: Some("http://localhost:3001".into()),
                 flag_refuse_service_transactions: false,
@@ -1431,6 +1567,8 @@ mod tests {
                 arg_fat_db: "auto".into(),
                 flag_scale_verifiers: true,
                 arg_num_verifiers: Some(6),
+                flag_parallel_transactions: false,
+                flag_state_prefetch: false,
 
                 // -- Import/Export Options
                 arg_export_blocks_from: "1".into(),
@@ -1538,6 +1676,7 @@ mod tests {
                     hosts: None,
                     max_connections: None,
                     max_payload: None,
+                    jwt_secret: None,
                 }),
                 rpc: Some(Rpc {
                     disable: Some(true),
@@ -1552,7 +1691,16 @@ mod tests {
                     keep_alive: None,
                     experimental_rpcs: None,
                     poll_lifetime: None,
-                    allow_missing_blocks: None
+                    allow_missing_blocks: None,
+                    rate_limit: None,
+                    rate_limit_burst: None,
+                    method_costs: None,
+                    max_concurrent_requests: None,
+                    max_batch_size: None,
+                    trusted_proxies: None,
+                    jwt_secret: None,
+                    graphql: None,
+                    graphql_port: None,
                 }),
                 ipc: Some(Ipc {
                     disable: None,
@@ -1597,6 +1745,7 @@ mod tests {
                     relay_set: None,
                     min_gas_price: None,
                     gas_price_percentile: None,
+                    gas_price_history_blocks: None,
                     usd_per_tx: None,
                     usd_per_eth: None,
                     price_update_period: Some("hourly".into()),
@@ -1604,6 +1753,13 @@ mod tests {
                     gas_cap: None,
                     tx_queue_size: Some(8192),
                     tx_queue_per_sender: None,
+                    tx_queue_pending_per_sender: None,
+                    tx_queue_future_per_sender: None,
+                    tx_queue_future_lifetime: None,
+                    tx_private_fallback_blocks: None,
+                    tx_queue_admission_policy: None,
+                    tx_queue_capture: None,
+                    tx_queue_capture_size_limit: None,
                     tx_queue_mem_limit: None,
                     tx_queue_locals: None,
                     tx_queue_strategy: None,
@@ -1611,6 +1767,9 @@ mod tests {
                     tx_queue_ban_time: None,
                     tx_queue_no_unfamiliar_locals: None,
                     tx_queue_no_early_reject: None,
+                    tx_queue_journal: None,
+                    tx_queue_journal_size: None,
+                    tx_queue_journal_max_age: None,
                     tx_gas_limit: None,
                     tx_time_limit: None,
                     extra_data: None,
@@ -1636,6 +1795,8 @@ mod tests {
                     fat_db: Some("off".into()),
                     scale_verifiers: Some(false),
                     num_verifiers: None,
+                    parallel_transactions: None,
+                    state_prefetch: None,
                 }),
                 snapshots: Some(Snapshots {
                     enable: Some(false),
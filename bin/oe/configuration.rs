@@ -18,6 +18,7 @@ use crate::{
     bytes::Bytes,
     cli::{Args, ArgsError},
     hash::keccak,
+    local_store::JournalOptions,
     metrics::MetricsConfiguration,
     miner::pool,
     sync::{self, validate_node_url, NetworkConfiguration},
@@ -40,7 +41,7 @@ use std::{
     collections::{BTreeMap, HashSet},
     io::Read,
     iter::FromIterator,
-    net::{SocketAddr, ToSocketAddrs},
+    net::{IpAddr, SocketAddr, ToSocketAddrs},
     num::NonZeroU32,
     path::PathBuf,
     time::Duration,
@@ -60,7 +61,9 @@ use crate::{
     network::IpFilter,
     params::{AccountsConfig, GasPricerConfig, MinerExtras, ResealPolicy, SpecType},
     presale::ImportWallet,
-    rpc::{HttpConfiguration, IpcConfiguration, WsConfiguration},
+    rpc::{
+        GraphQLConfiguration, HttpConfiguration, IpcConfiguration, RateLimitConfig, WsConfiguration,
+    },
     run::RunCmd,
     secretstore::{
         Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress,
@@ -75,7 +78,7 @@ use dir::{
     Directories,
 };
 use ethcore_logger::Config as LogConfig;
-use parity_rpc::NetworkSettings;
+use parity_rpc::{dispatch::MAX_GAS_PRICE_HISTORY_BLOCKS, NetworkSettings};
 
 const DEFAULT_MAX_PEERS: u16 = 50;
 const DEFAULT_MIN_PEERS: u16 = 25;
@@ -402,10 +405,13 @@ impl Configuration {
                 logger_config: logger_config.clone(),
                 miner_options: self.miner_options()?,
                 gas_price_percentile: self.args.arg_gas_price_percentile,
+                gas_price_history_blocks: self.gas_price_history_blocks()?,
                 poll_lifetime: self.args.arg_poll_lifetime,
                 ws_conf: ws_conf,
                 snapshot_conf: snapshot_conf,
                 http_conf: http_conf,
+                graphql_conf: self.graphql_config()?,
+                rate_limit_conf: self.rate_limit_config()?,
                 ipc_conf: ipc_conf,
                 net_conf: net_conf,
                 network_id: network_id,
@@ -431,7 +437,10 @@ impl Configuration {
                 new_transactions_stats_period: self.args.arg_new_transactions_stats_period,
                 verifier_settings: verifier_settings,
                 no_persistent_txqueue: self.args.flag_no_persistent_txqueue,
+                tx_queue_journal: self.tx_queue_journal(),
                 max_round_blocks_to_import: self.args.arg_max_round_blocks_to_import,
+                parallel_transactions: self.args.flag_parallel_transactions,
+                state_prefetch: self.args.flag_state_prefetch,
                 metrics_conf,
                 shutdown_on_missing_block_import: self.args.arg_shutdown_on_missing_block_import,
             };
@@ -526,6 +535,17 @@ impl Configuration {
             .unwrap_or(DEFAULT_MAX_PEERS) as u32
     }
 
+    fn gas_price_history_blocks(&self) -> Result<usize, String> {
+        let blocks = self.args.arg_gas_price_history_blocks;
+        if blocks > MAX_GAS_PRICE_HISTORY_BLOCKS {
+            return Err(format!(
+                "Invalid --gas-price-history-blocks: {}, at most {} blocks are supported",
+                blocks, MAX_GAS_PRICE_HISTORY_BLOCKS
+            ));
+        }
+        Ok(blocks)
+    }
+
     fn ip_filter(&self) -> Result<IpFilter, String> {
         IpFilter::parse(self.args.arg_allow_ips.as_str())
             .map_err(|_| "Invalid IP filter value".to_owned())
@@ -617,6 +637,18 @@ impl Configuration {
             refuse_service_transactions: self.args.flag_refuse_service_transactions,
 
             pool_limits: self.pool_limits()?,
+            pool_sender_limits: self.pool_sender_limits(),
+            private_tx_fallback_blocks: self.args.arg_tx_private_fallback_blocks,
+            admission_policy_path: self.admission_policy_path()?,
+            tx_queue_capture_path: self
+                .args
+                .arg_tx_queue_capture
+                .as_ref()
+                .map(|path| PathBuf::from(replace_home(&self.directories().base, path))),
+            tx_queue_capture_size_limit: self
+                .args
+                .arg_tx_queue_capture_size_limit
+                .saturating_mul(1024 * 1024),
             pool_verification_options: self.pool_verification_options()?,
         };
 
@@ -640,6 +672,35 @@ impl Configuration {
         })
     }
 
+    fn pool_sender_limits(&self) -> pool::SenderLimits {
+        pool::SenderLimits {
+            max_pending_per_sender: self
+                .args
+                .arg_tx_queue_pending_per_sender
+                .unwrap_or_else(usize::max_value),
+            max_future_per_sender: self
+                .args
+                .arg_tx_queue_future_per_sender
+                .unwrap_or_else(usize::max_value),
+            future_lifetime: match self.args.arg_tx_queue_future_lifetime {
+                0 => None,
+                seconds => Some(Duration::from_secs(seconds)),
+            },
+        }
+    }
+
+    fn admission_policy_path(&self) -> Result<Option<PathBuf>, String> {
+        match self.args.arg_tx_queue_admission_policy {
+            Some(ref path) => {
+                let path = PathBuf::from(replace_home(&self.directories().base, path));
+                // make sure the policy is valid at startup, it is loaded again by the miner.
+                pool::admission::AdmissionPolicy::load(&path)?;
+                Ok(Some(path))
+            }
+            None => Ok(None),
+        }
+    }
+
     fn pool_verification_options(&self) -> Result<pool::verifier::Options, String> {
         Ok(pool::verifier::Options {
             // NOTE min_gas_price,block_gas_limit block_base_fee, and allow_non_eoa_sender will be overwritten right after start.
@@ -940,8 +1001,75 @@ impl Configuration {
                 _ => 5usize,
             },
             keep_alive: !self.args.flag_jsonrpc_no_keep_alive,
+            jwt_secret: self.jwt_secret_path(&self.args.arg_jsonrpc_jwt_secret),
+            trusted_proxies: self.rpc_trusted_proxies()?,
+        };
+
+        Ok(conf)
+    }
+
+    fn rpc_trusted_proxies(&self) -> Result<Vec<IpAddr>, String> {
+        self.args
+            .arg_rpc_trusted_proxies
+            .as_ref()
+            .map_or(Ok(Vec::new()), |proxies| {
+                proxies
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|proxy| !proxy.is_empty())
+                    .map(|proxy| {
+                        proxy
+                            .parse()
+                            .map_err(|_| format!("Invalid trusted proxy address: {}", proxy))
+                    })
+                    .collect()
+            })
+    }
+
+    fn graphql_config(&self) -> Result<GraphQLConfiguration, String> {
+        let http = self.http_config()?;
+        Ok(GraphQLConfiguration {
+            enabled: http.enabled && self.args.flag_jsonrpc_graphql,
+            interface: http.interface,
+            port: self.args.arg_ports_shift + self.args.arg_jsonrpc_graphql_port,
+            hosts: http.hosts,
+            cors: http.cors,
+            jwt_secret: http.jwt_secret,
+            server_threads: http.server_threads,
+            max_payload: http.max_payload,
+            trusted_proxies: http.trusted_proxies,
+        })
+    }
+
+    fn jwt_secret_path(&self, path: &Option<String>) -> Option<PathBuf> {
+        path.as_ref()
+            .map(|path| replace_home(&self.directories().base, path).into())
+    }
+
+    fn rate_limit_config(&self) -> Result<RateLimitConfig, String> {
+        let mut conf = RateLimitConfig {
+            budget_per_second: self.args.arg_jsonrpc_rate_limit,
+            burst: self
+                .args
+                .arg_jsonrpc_rate_limit_burst
+                .unwrap_or(self.args.arg_jsonrpc_rate_limit),
+            max_concurrent: self.args.arg_jsonrpc_max_concurrent_requests,
+            max_batch_size: self.args.arg_jsonrpc_max_batch_size,
+            ..Default::default()
         };
 
+        if let Some(ref costs) = self.args.arg_jsonrpc_method_costs {
+            for entry in costs.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                let mut parts = entry.splitn(2, '=');
+                let method = parts.next().unwrap_or_default().trim();
+                let cost = parts
+                    .next()
+                    .and_then(|cost| cost.trim().parse().ok())
+                    .ok_or_else(|| format!("Invalid JSON-RPC method cost: {}", entry))?;
+                conf.method_costs.insert(method.to_owned(), cost);
+            }
+        }
+
         Ok(conf)
     }
 
@@ -961,6 +1089,7 @@ impl Configuration {
             support_token_api,
             max_connections: self.args.arg_ws_max_connections,
             max_payload: self.args.arg_ws_max_payload,
+            jwt_secret: self.jwt_secret_path(&self.args.arg_ws_jwt_secret),
         };
 
         Ok(conf)
@@ -1227,6 +1356,16 @@ impl Configuration {
         )
     }
 
+    fn tx_queue_journal(&self) -> Option<JournalOptions> {
+        match self.args.flag_tx_queue_journal {
+            true => Some(JournalOptions {
+                max_transactions: self.args.arg_tx_queue_journal_size,
+                max_age: Duration::from_secs(self.args.arg_tx_queue_journal_max_age),
+            }),
+            false => None,
+        }
+    }
+
     fn verifier_settings(&self) -> VerifierSettings {
         let mut settings = VerifierSettings::default();
         settings.scale_verifiers = self.args.flag_scale_verifiers;
@@ -1501,6 +1640,7 @@ mod tests {
                     support_token_api: true,
                     max_connections: 100,
                     max_payload: 5,
+                    jwt_secret: None,
                 },
                 LogConfig {
                     color: !cfg!(windows),
@@ -1541,9 +1681,12 @@ mod tests {
             logger_config: Default::default(),
             miner_options: Default::default(),
             gas_price_percentile: 50,
+            gas_price_history_blocks: 100,
             poll_lifetime: 60,
             ws_conf: Default::default(),
             http_conf: Default::default(),
+            graphql_conf: Default::default(),
+            rate_limit_conf: Default::default(),
             ipc_conf: Default::default(),
             net_conf: default_network_config(),
             network_id: None,
@@ -1569,7 +1712,10 @@ mod tests {
             new_transactions_stats_period: 0,
             verifier_settings: Default::default(),
             no_persistent_txqueue: false,
+            tx_queue_journal: None,
             max_round_blocks_to_import: 1,
+            parallel_transactions: false,
+            state_prefetch: false,
             metrics_conf: MetricsConfiguration::default(),
             shutdown_on_missing_block_import: None,
         };
@@ -1593,6 +1739,30 @@ mod tests {
         assert_eq!(conf2.miner_options().unwrap(), mining_options);
     }
 
+    #[test]
+    fn should_parse_future_transactions_limits() {
+        // given
+        let conf = parse(&[
+            "openethereum",
+            "--tx-queue-pending-per-sender",
+            "8",
+            "--tx-queue-future-per-sender",
+            "4",
+            "--tx-queue-future-lifetime",
+            "600",
+        ]);
+
+        // then
+        assert_eq!(
+            conf.miner_options().unwrap().pool_sender_limits,
+            pool::SenderLimits {
+                max_pending_per_sender: 8,
+                max_future_per_sender: 4,
+                future_lifetime: Some(Duration::from_secs(600)),
+            }
+        );
+    }
+
     #[test]
     fn should_fail_on_force_reseal_and_reseal_min_period() {
         let conf = parse(&[
@@ -1607,6 +1777,15 @@ mod tests {
         assert!(conf.miner_options().is_err());
     }
 
+    #[test]
+    fn should_limit_gas_price_history_blocks() {
+        let conf = parse(&["openethereum", "--gas-price-history-blocks", "1024"]);
+        assert_eq!(conf.gas_price_history_blocks(), Ok(1024));
+
+        let conf = parse(&["openethereum", "--gas-price-history-blocks", "1025"]);
+        assert!(conf.gas_price_history_blocks().is_err());
+    }
+
     #[test]
     fn should_parse_network_settings() {
         // given
@@ -1655,6 +1834,107 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_parse_rpc_rate_limits() {
+        // given
+
+        // when
+        let conf0 = parse(&["openethereum"]);
+        let conf1 = parse(&[
+            "openethereum",
+            "--jsonrpc-rate-limit",
+            "100",
+            "--jsonrpc-max-batch-size",
+            "10",
+            "--jsonrpc-method-costs",
+            "eth_getLogs=200, web3_sha3=5",
+        ]);
+        let conf2 = parse(&["openethereum", "--jsonrpc-method-costs", "eth_getLogs"]);
+
+        // then
+        assert_eq!(conf0.rate_limit_config(), Ok(RateLimitConfig::default()));
+        let rate_limit = conf1.rate_limit_config().unwrap();
+        assert_eq!(rate_limit.budget_per_second, 100);
+        assert_eq!(rate_limit.burst, 100);
+        assert_eq!(rate_limit.max_batch_size, 10);
+        assert_eq!(rate_limit.method_costs["eth_getLogs"], 200);
+        assert_eq!(rate_limit.method_costs["web3_sha3"], 5);
+        assert_eq!(rate_limit.method_costs["trace_filter"], 100);
+        assert!(conf2.rate_limit_config().is_err());
+    }
+
+    #[test]
+    fn should_parse_rpc_trusted_proxies() {
+        // given
+
+        // when
+        let conf0 = parse(&["openethereum"]);
+        let conf1 = parse(&["openethereum", "--rpc-trusted-proxies", "10.0.0.1, ::1"]);
+        let conf2 = parse(&["openethereum", "--rpc-trusted-proxies", "proxy.local"]);
+
+        // then
+        assert!(conf0.http_config().unwrap().trusted_proxies.is_empty());
+        assert_eq!(
+            conf1.http_config().unwrap().trusted_proxies,
+            vec![
+                "10.0.0.1".parse::<IpAddr>().unwrap(),
+                "::1".parse::<IpAddr>().unwrap()
+            ]
+        );
+        assert!(conf2.http_config().is_err());
+    }
+
+    #[test]
+    fn should_parse_jwt_secret() {
+        // given
+
+        // when
+        let conf0 = parse(&["openethereum"]);
+        let conf1 = parse(&[
+            "openethereum",
+            "--base-path",
+            "/base",
+            "--jsonrpc-jwt-secret",
+            "$BASE/jwt.hex",
+        ]);
+
+        // then
+        assert_eq!(conf0.http_config().unwrap().jwt_secret, None);
+        assert_eq!(conf0.ws_config().unwrap().jwt_secret, None);
+        assert_eq!(
+            conf1.http_config().unwrap().jwt_secret,
+            Some(PathBuf::from("/base/jwt.hex"))
+        );
+        assert_eq!(conf1.ws_config().unwrap().jwt_secret, None);
+    }
+
+    #[test]
+    fn should_parse_graphql_configuration() {
+        // given
+
+        // when
+        let conf0 = parse(&["openethereum"]);
+        let conf1 = parse(&[
+            "openethereum",
+            "--jsonrpc-graphql",
+            "--jsonrpc-graphql-port",
+            "9000",
+            "--jsonrpc-cors",
+            "all",
+        ]);
+        let conf2 = parse(&["openethereum", "--jsonrpc-graphql", "--no-jsonrpc"]);
+
+        // then
+        assert_eq!(
+            conf0.graphql_config().unwrap(),
+            GraphQLConfiguration::default()
+        );
+        assert!(conf1.graphql_config().unwrap().enabled);
+        assert_eq!(conf1.graphql_config().unwrap().port, 9000);
+        assert_eq!(conf1.graphql_config().unwrap().cors, None);
+        assert!(!conf2.graphql_config().unwrap().enabled);
+    }
+
     #[test]
     fn should_parse_ui_configuration() {
         // given
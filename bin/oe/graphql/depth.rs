@@ -0,0 +1,204 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Nesting depth of GraphQL queries.
+
+use std::collections::{HashMap, HashSet};
+
+use juniper::parser::{Lexer, Token};
+
+/// Operation or fragment definition of a query document.
+#[derive(Default)]
+struct Definition<'a> {
+    /// Deepest nesting of selection sets in the definition itself.
+    depth: usize,
+    /// Fragments spread in the definition with the nesting of the selection set
+    /// they are spread in.
+    spreads: Vec<(&'a str, usize)>,
+}
+
+/// Returns the deepest nesting of fields in the operations of `query`, with fragment spreads
+/// expanded and root fields at depth 1.
+///
+/// Returns `None` if the query can't be tokenized, spreads unknown fragments or its fragments
+/// are cyclic.
+pub fn query_depth(query: &str) -> Option<usize> {
+    let mut tokens = Vec::new();
+    for token in Lexer::new(query) {
+        match token.ok()?.item {
+            Token::EndOfFile => break,
+            token => tokens.push(token),
+        }
+    }
+
+    let mut operations = Vec::new();
+    let mut fragments = HashMap::new();
+    let mut definition = Definition::default();
+    let mut fragment = None;
+    // open selection sets, `true` for inline fragments which do not nest fields.
+    let mut selections: Vec<bool> = Vec::new();
+    let mut inline_fragment = false;
+    let mut parens = 0usize;
+    for (index, token) in tokens.iter().enumerate() {
+        let next = tokens.get(index + 1);
+        let depth = selections.iter().filter(|inline| !**inline).count();
+        match *token {
+            Token::ParenOpen => parens += 1,
+            Token::ParenClose => parens = parens.saturating_sub(1),
+            // arguments and variable definitions may contain object values.
+            _ if parens > 0 => {}
+            Token::Name("fragment") if selections.is_empty() => {
+                if let Some(&Token::Name(name)) = next {
+                    fragment = Some(name);
+                }
+            }
+            Token::Ellipsis => match next {
+                Some(&Token::Name(name)) if name != "on" => definition.spreads.push((name, depth)),
+                _ => inline_fragment = true,
+            },
+            Token::CurlyOpen => {
+                selections.push(inline_fragment);
+                if !inline_fragment {
+                    definition.depth = definition.depth.max(depth + 1);
+                }
+                inline_fragment = false;
+            }
+            Token::CurlyClose => {
+                selections.pop();
+                if selections.is_empty() {
+                    let done = ::std::mem::take(&mut definition);
+                    match fragment.take() {
+                        Some(name) => {
+                            fragments.insert(name, done);
+                        }
+                        None => operations.push(done),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut depths = HashMap::new();
+    for name in fragments.keys() {
+        resolve_fragment(name, &fragments, &mut depths)?;
+    }
+    operations
+        .iter()
+        .map(|operation| expanded_depth(operation, &depths))
+        .try_fold(0, |max, depth| Some(max.max(depth?)))
+}
+
+/// Depth of `definition` given the depths of all fragments it spreads.
+fn expanded_depth(definition: &Definition, depths: &HashMap<&str, usize>) -> Option<usize> {
+    definition
+        .spreads
+        .iter()
+        .map(|&(name, level)| {
+            depths
+                .get(name)
+                .map(|depth| (level + depth).saturating_sub(1))
+        })
+        .try_fold(definition.depth, |max, depth| Some(max.max(depth?)))
+}
+
+/// Resolves the depth of fragment `root` and of all fragments it spreads.
+///
+/// Iterative, as long chains of fragments must not exhaust the stack.
+fn resolve_fragment<'a>(
+    root: &'a str,
+    fragments: &HashMap<&'a str, Definition<'a>>,
+    depths: &mut HashMap<&'a str, usize>,
+) -> Option<()> {
+    // fragments that are expanded but not resolved yet, i.e. the current spread path.
+    let mut expanding = HashSet::new();
+    let mut stack = vec![(root, false)];
+    while let Some((name, expanded)) = stack.pop() {
+        if depths.contains_key(name) {
+            continue;
+        }
+        let fragment = fragments.get(name)?;
+        if expanded {
+            depths.insert(name, expanded_depth(fragment, depths)?);
+            expanding.remove(name);
+            continue;
+        }
+        if !expanding.insert(name) {
+            return None;
+        }
+        stack.push((name, true));
+        stack.extend(
+            fragment
+                .spreads
+                .iter()
+                .filter(|&&(spread, _)| !depths.contains_key(spread))
+                .map(|&(spread, _)| (spread, false)),
+        );
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::query_depth;
+
+    #[test]
+    fn should_measure_nesting_of_fields() {
+        assert_eq!(query_depth("{ block { number } }"), Some(2));
+        assert_eq!(
+            query_depth(
+                r#"query Q($n: Long) {
+                    block(number: $n) { parent { parent { hash } } }
+                    logs(filter: { topics: [] }) { index }
+                }"#
+            ),
+            Some(4)
+        );
+        // inline fragments do not nest fields
+        assert_eq!(
+            query_depth("{ block { ... on Block { number } ... @skip(if: true) { hash } } }"),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn should_expand_fragments() {
+        let query = r#"
+            { block { ...Parent transactions { ...Block } } }
+            fragment Parent on Block { parent { ...Block } }
+            fragment Block on Block { parent { parent { number } } }
+        "#;
+        // block > transactions > (Block) parent > parent > number
+        assert_eq!(query_depth(query), Some(5));
+        // block > (Parent) parent > (Block) parent > parent > number
+        let query = query.replace("transactions { ...Block }", "number");
+        assert_eq!(query_depth(&query), Some(5));
+    }
+
+    #[test]
+    fn should_not_measure_invalid_queries() {
+        assert_eq!(query_depth("{ block { ...Unknown } }"), None);
+        assert_eq!(
+            query_depth(
+                "{ block { ...A } } \
+                 fragment A on Block { parent { ...B } } \
+                 fragment B on Block { parent { ...A } }"
+            ),
+            None
+        );
+        assert_eq!(query_depth("{ block(hash: \"0x) }"), None);
+    }
+}
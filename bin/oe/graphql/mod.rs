@@ -0,0 +1,359 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-1767 GraphQL server.
+
+mod depth;
+mod schema;
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use crate::futures::{future, Future, Stream};
+use hyper::{
+    header::{self, HeaderValue},
+    server::conn::AddrStream,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use juniper::http::{graphiql::graphiql_source, GraphQLRequest};
+use parity_rpc::{
+    cors_allow_origin, is_host_allowed, limiter::RateLimiter, AccessControlAllowOrigin, AllowCors,
+    AllowedApis, Host, JwtAuth, RpcExtractor, Token,
+};
+use parity_runtime::{TokioRuntime, TokioRuntimeBuilder};
+
+pub use self::schema::Context;
+use self::schema::Schema;
+
+/// Method name GraphQL queries are charged as by the rate limiter.
+const RATE_LIMIT_METHOD: &str = "graphql";
+/// Maximal nesting of fields in a query, fragments included.
+const MAX_QUERY_DEPTH: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphQLConfiguration {
+    /// Is the GraphQL server enabled (default is false)?
+    pub enabled: bool,
+    /// The IP of the network interface used (default is 127.0.0.1).
+    pub interface: String,
+    /// The network port (default is 8547).
+    pub port: u16,
+    /// Allowed values of the Host header, `None` allows any.
+    pub hosts: Option<Vec<String>>,
+    /// Allowed CORS origins, `None` allows any.
+    pub cors: Option<Vec<String>>,
+    /// Shared JWT secret required from callers.
+    pub jwt_secret: Option<PathBuf>,
+    /// Number of threads queries are executed on.
+    pub server_threads: usize,
+    /// Maximal size of a request body in megabytes.
+    pub max_payload: usize,
+    /// Reverse proxies whose `X-Forwarded-For` header is honored by the rate limiter.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for GraphQLConfiguration {
+    fn default() -> Self {
+        GraphQLConfiguration {
+            enabled: false,
+            interface: "127.0.0.1".into(),
+            port: 8547,
+            hosts: Some(vec![]),
+            cors: Some(vec![]),
+            jwt_secret: None,
+            server_threads: 1,
+            max_payload: 5,
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+/// Running GraphQL server, stopped when dropped.
+pub struct GraphQLServer {
+    runtime: Option<TokioRuntime>,
+}
+
+impl Drop for GraphQLServer {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            let _ = runtime.shutdown_now().wait();
+        }
+    }
+}
+
+type ResponseFuture = Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send>;
+
+/// Query document of a request.
+#[derive(Deserialize)]
+struct QuerySource {
+    query: String,
+}
+
+enum BodyError {
+    TooLarge,
+    Hyper(hyper::Error),
+}
+
+struct Handler {
+    schema: Schema,
+    context: Context,
+    allowed_hosts: Option<Vec<Host>>,
+    cors_domains: Option<Vec<AccessControlAllowOrigin>>,
+    jwt: Option<JwtAuth>,
+    limiter: Arc<RateLimiter>,
+    extractor: RpcExtractor,
+    max_payload: usize,
+}
+
+impl Handler {
+    /// Returns the error to reply with if the request is not authorized.
+    ///
+    /// The holder of the token is inserted into the request extensions.
+    fn authorize(&self, req: &mut Request<Body>) -> Option<String> {
+        let jwt = self.jwt.as_ref()?;
+        let token = {
+            let auth = req
+                .headers()
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok());
+            jwt.validate_header(auth)
+        };
+        match token {
+            // GraphQL exposes the data of the eth API.
+            Ok(Token {
+                apis: Some(AllowedApis(ref apis)),
+                ..
+            }) if !apis.iter().any(|api| api == "eth") => {
+                Some("Token does not grant the eth API".to_owned())
+            }
+            Ok(token) => {
+                if let Some(id) = token.id {
+                    req.extensions_mut().insert(id);
+                }
+                None
+            }
+            Err(err) => Some(err.to_string()),
+        }
+    }
+
+    fn execute(&self, body: &[u8]) -> Response<Body> {
+        let request: GraphQLRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(err) => {
+                return response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid GraphQL request: {}", err),
+                )
+            }
+        };
+        if let Ok(QuerySource { query }) = serde_json::from_slice(body) {
+            // queries that can't be analysed are rejected by the schema validation.
+            if depth::query_depth(&query).map_or(false, |depth| depth > MAX_QUERY_DEPTH) {
+                return response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Query exceeds the maximal depth of {}\n", MAX_QUERY_DEPTH),
+                );
+            }
+        }
+
+        let result = request.execute(&self.schema, &self.context);
+        let status = match result.is_ok() {
+            true => StatusCode::OK,
+            false => StatusCode::BAD_REQUEST,
+        };
+        let mut res = response(
+            status,
+            serde_json::to_string(&result).expect("GraphQL responses are serializable; qed"),
+        );
+        res.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        res
+    }
+}
+
+fn response(status: StatusCode, body: String) -> Response<Body> {
+    let mut res = Response::new(Body::from(body));
+    *res.status_mut() = status;
+    res
+}
+
+/// Reads the request body, failing if it exceeds `max_size` bytes.
+fn read_body(body: Body, max_size: usize) -> impl Future<Item = Vec<u8>, Error = BodyError> {
+    body.map_err(BodyError::Hyper)
+        .fold(Vec::new(), move |mut body, chunk| {
+            if body.len() + chunk.len() > max_size {
+                return Err(BodyError::TooLarge);
+            }
+            body.extend_from_slice(&chunk);
+            Ok(body)
+        })
+}
+
+fn handle_request(
+    mut req: Request<Body>,
+    handler: Arc<Handler>,
+    peer: SocketAddr,
+) -> ResponseFuture {
+    if !is_host_allowed(&req, &handler.allowed_hosts) {
+        return Box::new(future::ok(response(
+            StatusCode::FORBIDDEN,
+            "Provided Host header is not whitelisted.\n".into(),
+        )));
+    }
+    let allowed_origin = match cors_allow_origin(&req, &handler.cors_domains) {
+        AllowCors::Ok(origin) => Some(origin),
+        AllowCors::NotRequired => None,
+        AllowCors::Invalid => {
+            return Box::new(future::ok(response(
+                StatusCode::FORBIDDEN,
+                "Origin of the request is not whitelisted.\n".into(),
+            )))
+        }
+    };
+    let with_cors = move |mut res: Response<Body>| {
+        if let Some(origin) = allowed_origin {
+            res.headers_mut()
+                .insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        }
+        res
+    };
+
+    let path = req.uri().path().to_owned();
+    match (req.method().clone(), path.as_str()) {
+        (Method::POST, "/graphql") => {
+            if let Some(err) = handler.authorize(&mut req) {
+                debug!(target: "graphql", "Rejected unauthorized request: {}", err);
+                return Box::new(future::ok(with_cors(response(
+                    StatusCode::UNAUTHORIZED,
+                    format!("{}\n", err),
+                ))));
+            }
+
+            req.extensions_mut().insert(peer);
+            let client = handler
+                .extractor
+                .client(&req)
+                .unwrap_or_else(|| "unknown".into());
+            // the call is released when the response future completes or is dropped.
+            let in_flight = match handler.limiter.is_enabled() {
+                true => match handler
+                    .limiter
+                    .acquire_guard(client.clone(), RATE_LIMIT_METHOD)
+                {
+                    Ok(guard) => Some(guard),
+                    Err(reason) => {
+                        trace!(target: "graphql", "Rejected request from {}: {:?}", client, reason);
+                        return Box::new(future::ok(with_cors(response(
+                            StatusCode::TOO_MANY_REQUESTS,
+                            format!("{}\n", reason.details()),
+                        ))));
+                    }
+                },
+                false => None,
+            };
+
+            let max_payload = handler.max_payload;
+            Box::new(read_body(req.into_body(), max_payload).then(move |body| {
+                let _in_flight = in_flight;
+                match body {
+                    Ok(body) => Ok(with_cors(handler.execute(&body))),
+                    Err(BodyError::TooLarge) => Ok(with_cors(response(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        format!("Request body exceeds {} bytes\n", max_payload),
+                    ))),
+                    Err(BodyError::Hyper(err)) => Err(err),
+                }
+            }))
+        }
+        (Method::OPTIONS, "/graphql") => {
+            let mut res = with_cors(Response::new(Body::empty()));
+            res.headers_mut().insert(
+                header::ACCESS_CONTROL_ALLOW_METHODS,
+                HeaderValue::from_static("POST, OPTIONS"),
+            );
+            res.headers_mut().insert(
+                header::ACCESS_CONTROL_ALLOW_HEADERS,
+                HeaderValue::from_static("Content-Type, Authorization"),
+            );
+            Box::new(future::ok(res))
+        }
+        (Method::GET, "/") | (Method::GET, "/graphql") => {
+            let mut res = response(StatusCode::OK, graphiql_source("/graphql"));
+            res.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+            Box::new(future::ok(res))
+        }
+        _ => Box::new(future::ok(response(
+            StatusCode::NOT_FOUND,
+            "not found".into(),
+        ))),
+    }
+}
+
+/// Start the GraphQL server accessible via POST <host>:<port>/graphql
+///
+/// Queries are executed on `conf.server_threads` dedicated threads.
+pub fn start(
+    conf: &GraphQLConfiguration,
+    allowed_hosts: Option<Vec<Host>>,
+    cors_domains: Option<Vec<AccessControlAllowOrigin>>,
+    context: Context,
+    jwt: Option<JwtAuth>,
+    limiter: Arc<RateLimiter>,
+) -> Result<GraphQLServer, String> {
+    let addr = format!("{}:{}", conf.interface, conf.port);
+    let addr = addr
+        .parse()
+        .map_err(|err| format!("Failed to parse address '{}': {}", addr, err))?;
+
+    let handler = Arc::new(Handler {
+        schema: schema::schema(),
+        context,
+        allowed_hosts,
+        cors_domains,
+        jwt,
+        limiter,
+        extractor: RpcExtractor::new(conf.trusted_proxies.clone()),
+        max_payload: conf.max_payload * 1024 * 1024,
+    });
+    let runtime = TokioRuntimeBuilder::new()
+        .core_threads(::std::cmp::max(conf.server_threads, 1))
+        .name_prefix("graphql.worker")
+        .build()
+        .map_err(|err| format!("GraphQL server error: {}", err))?;
+    let server = Server::try_bind(&addr)
+        .map_err(|err| format!("GraphQL server error: {}", err))?
+        .serve(make_service_fn(move |socket: &AddrStream| {
+            let handler = handler.clone();
+            let peer = socket.remote_addr();
+            service_fn(move |req: Request<Body>| handle_request(req, handler.clone(), peer))
+        }))
+        .map_err(|e| warn!(target: "graphql", "GraphQL server error: {}", e));
+    runtime.executor().spawn(server);
+    info!("Started GraphQL server at http://{}/graphql", addr);
+
+    Ok(GraphQLServer {
+        runtime: Some(runtime),
+    })
+}
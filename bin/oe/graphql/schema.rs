@@ -0,0 +1,1093 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-1767 schema resolved against the blockchain client.
+
+use std::{cmp, sync::Arc};
+
+use crate::types::{
+    filter::Filter,
+    header::Header,
+    ids::{BlockId, TransactionId, UncleId},
+    log_entry::LocalizedLogEntry,
+    receipt::{LocalizedReceipt, TransactionOutcome},
+    transaction::{
+        Action, PendingTransaction, SignedTransaction, Transaction as RawTransaction,
+        TypedTransaction, UnverifiedTransaction,
+    },
+    BlockNumber,
+};
+use ethcore::{
+    client::{
+        Balance, BlockChainClient, BlockInfo, Call, ChainInfo, Client, EngineInfo, Nonce,
+        StateClient, StateOrBlock,
+    },
+    miner::Miner,
+};
+use ethereum_types::{H160, H256, U256};
+use juniper::{
+    DefaultScalarValue, FieldResult, InputValue, ParseScalarResult, ParseScalarValue, RootNode,
+    ScalarToken, Value,
+};
+use parity_rpc::dispatch::{default_gas_price, FullDispatcher, GasPriceOracle};
+use rlp::Rlp;
+use rustc_hex::{FromHex, ToHex};
+
+/// Maximal number of blocks returned by a single `blocks` query or searched by a `logs` query.
+const MAX_BLOCKS_RANGE: u64 = 1000;
+
+/// Shared state of all resolvers.
+pub struct Context {
+    pub client: Arc<Client>,
+    pub miner: Arc<Miner>,
+    pub gas_price_oracle: GasPriceOracle,
+}
+
+impl juniper::Context for Context {}
+
+impl Context {
+    fn header(&self, id: BlockId) -> FieldResult<Option<Header>> {
+        let eip1559_transition = self.client.engine().params().eip1559_transition;
+        match self.client.block_header(id) {
+            Some(header) => Ok(Some(header.decode(eip1559_transition)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn block(&self, id: BlockId) -> FieldResult<Option<Block>> {
+        Ok(self.header(id)?.map(|header| Block { header }))
+    }
+
+    fn transaction(&self, id: TransactionId) -> FieldResult<Option<Transaction>> {
+        match self.client.block_transaction(id) {
+            Some(tx) => Ok(Some(Transaction::localized(
+                tx.signed,
+                tx.block_hash,
+                tx.block_number,
+                tx.transaction_index,
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    fn logs(&self, filter: Filter) -> FieldResult<Vec<Log>> {
+        let from = self.client.block_number(filter.from_block);
+        let to = self.client.block_number(filter.to_block);
+        if let (Some(from), Some(to)) = (from, to) {
+            if to >= from && to - from >= MAX_BLOCKS_RANGE {
+                return Err(format!("Log range is limited to {} blocks", MAX_BLOCKS_RANGE).into());
+            }
+        }
+
+        self.client
+            .logs(filter)
+            .map(|logs| logs.into_iter().map(|log| Log { log }).collect())
+            .map_err(|id| format!("Block {:?} not found", id).into())
+    }
+
+    fn state_at(&self, id: BlockId) -> FieldResult<(<Client as StateClient>::State, Header)> {
+        let state = self
+            .client
+            .state_at(id)
+            .ok_or("State is not available for this block")?;
+        let header = self.header(id)?.ok_or("Block not found")?;
+        Ok((state, header))
+    }
+}
+
+fn block_id(block: Option<Long>, default: BlockId) -> BlockId {
+    block.map_or(default, |block| BlockId::Number(block.0))
+}
+
+fn strip_hex(value: &str) -> Option<Vec<u8>> {
+    value.strip_prefix("0x")?.from_hex().ok()
+}
+
+fn parse_number<'a>(value: ScalarToken<'a>) -> ParseScalarResult<'a, DefaultScalarValue> {
+    match value {
+        ScalarToken::Int(_) => <i32 as ParseScalarValue>::from_str(value),
+        _ => <String as ParseScalarValue>::from_str(value),
+    }
+}
+
+/// 32 byte hex-encoded value.
+pub struct Bytes32(pub H256);
+
+juniper::graphql_scalar!(Bytes32 {
+    description: "32 byte binary string, represented as 0x-prefixed hexadecimal."
+
+    resolve(&self) -> Value {
+        Value::scalar(format!("{:#x}", self.0))
+    }
+
+    from_input_value(v: &InputValue) -> Option<Bytes32> {
+        v.as_scalar_value::<String>()
+            .and_then(|s| strip_hex(s))
+            .filter(|bytes| bytes.len() == 32)
+            .map(|bytes| Bytes32(H256::from_slice(&bytes)))
+    }
+
+    from_str<'a>(value: ScalarToken<'a>) -> ParseScalarResult<'a, DefaultScalarValue> {
+        <String as ParseScalarValue>::from_str(value)
+    }
+});
+
+/// 20 byte hex-encoded account address.
+pub struct Address(pub H160);
+
+juniper::graphql_scalar!(Address {
+    description: "20 byte Ethereum address, represented as 0x-prefixed hexadecimal."
+
+    resolve(&self) -> Value {
+        Value::scalar(format!("{:#x}", self.0))
+    }
+
+    from_input_value(v: &InputValue) -> Option<Address> {
+        v.as_scalar_value::<String>()
+            .and_then(|s| strip_hex(s))
+            .filter(|bytes| bytes.len() == 20)
+            .map(|bytes| Address(H160::from_slice(&bytes)))
+    }
+
+    from_str<'a>(value: ScalarToken<'a>) -> ParseScalarResult<'a, DefaultScalarValue> {
+        <String as ParseScalarValue>::from_str(value)
+    }
+});
+
+/// Arbitrary length hex-encoded binary data.
+pub struct Bytes(pub Vec<u8>);
+
+juniper::graphql_scalar!(Bytes {
+    description: "Arbitrary length binary string, represented as 0x-prefixed hexadecimal."
+
+    resolve(&self) -> Value {
+        Value::scalar(format!("0x{}", self.0.to_hex()))
+    }
+
+    from_input_value(v: &InputValue) -> Option<Bytes> {
+        v.as_scalar_value::<String>().and_then(|s| strip_hex(s)).map(Bytes)
+    }
+
+    from_str<'a>(value: ScalarToken<'a>) -> ParseScalarResult<'a, DefaultScalarValue> {
+        <String as ParseScalarValue>::from_str(value)
+    }
+});
+
+/// 256 bit unsigned integer.
+pub struct BigInt(pub U256);
+
+juniper::graphql_scalar!(BigInt {
+    description: "Large integer. Input is accepted as either a JSON number or as a string, \
+                  in decimal or 0x-prefixed hexadecimal. Output is 0x-prefixed hexadecimal."
+
+    resolve(&self) -> Value {
+        Value::scalar(format!("{:#x}", self.0))
+    }
+
+    from_input_value(v: &InputValue) -> Option<BigInt> {
+        match v.as_scalar_value::<String>() {
+            Some(s) => match s.strip_prefix("0x") {
+                Some(hex) => U256::from_str_radix(hex, 16).ok(),
+                None => U256::from_dec_str(s).ok(),
+            },
+            None => v
+                .as_scalar_value::<i32>()
+                .filter(|value| **value >= 0)
+                .map(|value| U256::from(*value as u64)),
+        }
+        .map(BigInt)
+    }
+
+    from_str<'a>(value: ScalarToken<'a>) -> ParseScalarResult<'a, DefaultScalarValue> {
+        parse_number(value)
+    }
+});
+
+/// 64 bit unsigned integer.
+pub struct Long(pub u64);
+
+juniper::graphql_scalar!(Long {
+    description: "64 bit unsigned integer. Input is accepted as either a JSON number or as a \
+                  string, in decimal or 0x-prefixed hexadecimal. Output is 0x-prefixed hexadecimal."
+
+    resolve(&self) -> Value {
+        Value::scalar(format!("{:#x}", self.0))
+    }
+
+    from_input_value(v: &InputValue) -> Option<Long> {
+        match v.as_scalar_value::<String>() {
+            Some(s) => match s.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                None => s.parse().ok(),
+            },
+            None => v
+                .as_scalar_value::<i32>()
+                .filter(|value| **value >= 0)
+                .map(|value| *value as u64),
+        }
+        .map(Long)
+    }
+
+    from_str<'a>(value: ScalarToken<'a>) -> ParseScalarResult<'a, DefaultScalarValue> {
+        parse_number(value)
+    }
+});
+
+/// Arguments of a message call.
+#[derive(juniper::GraphQLInputObject)]
+pub struct CallData {
+    /// Address the call is sent from.
+    pub from: Option<Address>,
+    /// Address the call is sent to, contract creation if missing.
+    pub to: Option<Address>,
+    /// Gas provided for the call.
+    pub gas: Option<Long>,
+    /// Price of each unit of gas.
+    pub gas_price: Option<BigInt>,
+    /// Value transferred with the call.
+    pub value: Option<BigInt>,
+    /// Input data of the call.
+    pub data: Option<Bytes>,
+}
+
+impl CallData {
+    /// Builds the transaction, its gas capped at the gas limit of the block executed in.
+    fn into_transaction(self, header: &Header) -> SignedTransaction {
+        let max_gas = *header.gas_limit();
+        TypedTransaction::Legacy(RawTransaction {
+            nonce: U256::zero(),
+            action: self.to.map_or(Action::Create, |to| Action::Call(to.0)),
+            gas: self
+                .gas
+                .map_or(max_gas, |gas| cmp::min(U256::from(gas.0), max_gas)),
+            gas_price: self.gas_price.map(|price| price.0).unwrap_or_default(),
+            value: self.value.map(|value| value.0).unwrap_or_default(),
+            data: self.data.map(|data| data.0).unwrap_or_default(),
+        })
+        .fake_sign(self.from.map(|from| from.0).unwrap_or_default())
+    }
+}
+
+/// Log filter over a range of blocks.
+#[derive(juniper::GraphQLInputObject)]
+pub struct FilterCriteria {
+    /// First block of the range, the latest block if missing.
+    pub from_block: Option<Long>,
+    /// Last block of the range, the latest block if missing.
+    pub to_block: Option<Long>,
+    /// Emitting contracts, any if missing.
+    pub addresses: Option<Vec<Address>>,
+    /// Topics by position, an empty list matches any topic.
+    pub topics: Option<Vec<Vec<Bytes32>>>,
+}
+
+impl FilterCriteria {
+    fn into_filter(self) -> Filter {
+        into_filter(
+            block_id(self.from_block, BlockId::Latest),
+            block_id(self.to_block, BlockId::Latest),
+            self.addresses,
+            self.topics,
+        )
+    }
+}
+
+/// Log filter within a single block.
+#[derive(juniper::GraphQLInputObject)]
+pub struct BlockFilterCriteria {
+    /// Emitting contracts, any if missing.
+    pub addresses: Option<Vec<Address>>,
+    /// Topics by position, an empty list matches any topic.
+    pub topics: Option<Vec<Vec<Bytes32>>>,
+}
+
+fn into_filter(
+    from_block: BlockId,
+    to_block: BlockId,
+    addresses: Option<Vec<Address>>,
+    topics: Option<Vec<Vec<Bytes32>>>,
+) -> Filter {
+    Filter {
+        from_block,
+        to_block,
+        address: addresses.map(|addresses| addresses.into_iter().map(|a| a.0).collect()),
+        topics: topics
+            .unwrap_or_default()
+            .into_iter()
+            .map(|topics| match topics.is_empty() {
+                true => None,
+                false => Some(topics.into_iter().map(|t| t.0).collect()),
+            })
+            .collect(),
+        limit: None,
+    }
+}
+
+/// Result of a message call.
+#[derive(juniper::GraphQLObject)]
+pub struct CallResult {
+    /// Return data of the call.
+    pub data: Bytes,
+    /// Gas used by the call.
+    pub gas_used: Long,
+    /// 1 if the call succeeded, 0 otherwise.
+    pub status: Long,
+}
+
+/// Account state at a given block.
+pub struct Account {
+    address: H160,
+    block: BlockId,
+}
+
+#[juniper::object(Context = Context)]
+impl Account {
+    /// Address of the account.
+    fn address(&self) -> Address {
+        Address(self.address)
+    }
+
+    /// Balance of the account in wei.
+    fn balance(&self, context: &Context) -> FieldResult<BigInt> {
+        let balance = context
+            .client
+            .balance(&self.address, StateOrBlock::Block(self.block))
+            .ok_or("State is not available for this block")?;
+        Ok(BigInt(balance))
+    }
+
+    /// Number of transactions sent from the account.
+    fn transaction_count(&self, context: &Context) -> FieldResult<Long> {
+        let nonce = context
+            .client
+            .nonce(&self.address, self.block)
+            .ok_or("State is not available for this block")?;
+        Ok(Long(nonce.low_u64()))
+    }
+
+    /// Code of the contract, empty for external accounts.
+    fn code(&self, context: &Context) -> FieldResult<Bytes> {
+        let code = context
+            .client
+            .code(&self.address, StateOrBlock::Block(self.block))
+            .ok_or("State is not available for this block")?;
+        Ok(Bytes(code.unwrap_or_default()))
+    }
+
+    /// Value of the storage slot.
+    fn storage(&self, context: &Context, slot: Bytes32) -> FieldResult<Bytes32> {
+        let value = context
+            .client
+            .storage_at(&self.address, &slot.0, StateOrBlock::Block(self.block))
+            .ok_or("State is not available for this block")?;
+        Ok(Bytes32(value))
+    }
+}
+
+/// Log emitted by a transaction.
+pub struct Log {
+    log: LocalizedLogEntry,
+}
+
+#[juniper::object(Context = Context)]
+impl Log {
+    /// Index of the log within the block.
+    fn index(&self) -> i32 {
+        self.log.log_index as i32
+    }
+
+    /// Account that emitted the log, at the given block or the block of the log.
+    fn account(&self, block: Option<Long>) -> Account {
+        Account {
+            address: self.log.entry.address,
+            block: block_id(block, BlockId::Number(self.log.block_number)),
+        }
+    }
+
+    /// Indexed topics of the log.
+    fn topics(&self) -> Vec<Bytes32> {
+        self.log.entry.topics.iter().cloned().map(Bytes32).collect()
+    }
+
+    /// Unindexed data of the log.
+    fn data(&self) -> Bytes {
+        Bytes(self.log.entry.data.clone())
+    }
+
+    /// Transaction that emitted the log.
+    fn transaction(&self, context: &Context) -> FieldResult<Transaction> {
+        context
+            .transaction(TransactionId::Hash(self.log.transaction_hash))?
+            .ok_or_else(|| "Transaction not found".into())
+    }
+}
+
+/// Mined or pending transaction.
+pub struct Transaction {
+    tx: UnverifiedTransaction,
+    sender: H160,
+    location: Option<(H256, BlockNumber, usize)>,
+}
+
+impl Transaction {
+    fn localized(
+        tx: UnverifiedTransaction,
+        block_hash: H256,
+        block_number: BlockNumber,
+        index: usize,
+    ) -> FieldResult<Self> {
+        let sender = SignedTransaction::new(tx.clone())?.sender();
+        Ok(Transaction {
+            tx,
+            sender,
+            location: Some((block_hash, block_number, index)),
+        })
+    }
+
+    fn pending(tx: &SignedTransaction) -> Self {
+        Transaction {
+            sender: tx.sender(),
+            tx: tx.clone().into(),
+            location: None,
+        }
+    }
+
+    fn state_block(&self, block: Option<Long>) -> BlockId {
+        block_id(
+            block,
+            self.location
+                .map_or(BlockId::Latest, |(_, number, _)| BlockId::Number(number)),
+        )
+    }
+
+    fn receipt(&self, context: &Context) -> Option<LocalizedReceipt> {
+        self.location?;
+        context
+            .client
+            .transaction_receipt(TransactionId::Hash(self.tx.hash()))
+    }
+}
+
+#[juniper::object(Context = Context)]
+impl Transaction {
+    /// Hash of the transaction.
+    fn hash(&self) -> Bytes32 {
+        Bytes32(self.tx.hash())
+    }
+
+    /// Nonce of the sender account.
+    fn nonce(&self) -> Long {
+        Long(self.tx.tx().nonce.low_u64())
+    }
+
+    /// Index of the transaction within the block, null if pending.
+    fn index(&self) -> Option<i32> {
+        self.location.map(|(_, _, index)| index as i32)
+    }
+
+    /// Sender account, at the given block or the block of the transaction.
+    fn from(&self, block: Option<Long>) -> Account {
+        Account {
+            address: self.sender,
+            block: self.state_block(block),
+        }
+    }
+
+    /// Recipient account, null for contract creation.
+    fn to(&self, block: Option<Long>) -> Option<Account> {
+        match self.tx.tx().action {
+            Action::Call(address) => Some(Account {
+                address,
+                block: self.state_block(block),
+            }),
+            Action::Create => None,
+        }
+    }
+
+    /// Value transferred in wei.
+    fn value(&self) -> BigInt {
+        BigInt(self.tx.tx().value)
+    }
+
+    /// Gas price, or the fee cap for EIP-1559 transactions.
+    fn gas_price(&self) -> BigInt {
+        BigInt(self.tx.tx().gas_price)
+    }
+
+    /// Gas limit of the transaction.
+    fn gas(&self) -> Long {
+        Long(self.tx.tx().gas.low_u64())
+    }
+
+    /// Input data of the transaction.
+    fn input_data(&self) -> Bytes {
+        Bytes(self.tx.tx().data.clone())
+    }
+
+    /// Block the transaction was mined in, null if pending.
+    fn block(&self, context: &Context) -> FieldResult<Option<Block>> {
+        match self.location {
+            Some((hash, _, _)) => context.block(BlockId::Hash(hash)),
+            None => Ok(None),
+        }
+    }
+
+    /// Status code of the execution, null if pending or before EIP-658.
+    fn status(&self, context: &Context) -> Option<Long> {
+        match self.receipt(context)?.outcome {
+            TransactionOutcome::StatusCode(status) => Some(Long(status as u64)),
+            _ => None,
+        }
+    }
+
+    /// Gas used by the transaction, null if pending.
+    fn gas_used(&self, context: &Context) -> Option<Long> {
+        self.receipt(context)
+            .map(|receipt| Long(receipt.gas_used.low_u64()))
+    }
+
+    /// Gas used by the block up to and including this transaction, null if pending.
+    fn cumulative_gas_used(&self, context: &Context) -> Option<Long> {
+        self.receipt(context)
+            .map(|receipt| Long(receipt.cumulative_gas_used.low_u64()))
+    }
+
+    /// Contract created by the transaction, if any.
+    fn created_contract(&self, context: &Context, block: Option<Long>) -> Option<Account> {
+        let address = self.receipt(context)?.contract_address?;
+        Some(Account {
+            address,
+            block: self.state_block(block),
+        })
+    }
+
+    /// Logs emitted by the transaction, null if pending.
+    fn logs(&self, context: &Context) -> Option<Vec<Log>> {
+        self.receipt(context)
+            .map(|receipt| receipt.logs.into_iter().map(|log| Log { log }).collect())
+    }
+
+    /// R component of the signature.
+    fn r(&self) -> BigInt {
+        BigInt(U256::from(self.tx.signature().r()))
+    }
+
+    /// S component of the signature.
+    fn s(&self) -> BigInt {
+        BigInt(U256::from(self.tx.signature().s()))
+    }
+
+    /// V component of the signature.
+    fn v(&self) -> BigInt {
+        BigInt(self.tx.v().into())
+    }
+}
+
+/// Block of the canonical chain or an ommer.
+pub struct Block {
+    header: Header,
+}
+
+impl Block {
+    fn id(&self) -> BlockId {
+        BlockId::Hash(self.header.hash())
+    }
+
+    fn load_transactions(&self, context: &Context) -> FieldResult<Option<Vec<Transaction>>> {
+        let body = match context.client.block_body(self.id()) {
+            Some(body) => body,
+            None => return Ok(None),
+        };
+        body.transactions()
+            .into_iter()
+            .enumerate()
+            .map(|(index, tx)| {
+                Transaction::localized(tx, self.header.hash(), self.header.number(), index)
+            })
+            .collect::<FieldResult<_>>()
+            .map(Some)
+    }
+
+    fn seal_field(&self, index: usize) -> Option<Rlp> {
+        self.header.seal().get(index).map(|field| Rlp::new(field))
+    }
+}
+
+#[juniper::object(Context = Context)]
+impl Block {
+    /// Number of the block.
+    fn number(&self) -> Long {
+        Long(self.header.number())
+    }
+
+    /// Hash of the block.
+    fn hash(&self) -> Bytes32 {
+        Bytes32(self.header.hash())
+    }
+
+    /// Parent block, null for the genesis block.
+    fn parent(&self, context: &Context) -> FieldResult<Option<Block>> {
+        if self.header.number() == 0 {
+            return Ok(None);
+        }
+        context.block(BlockId::Hash(*self.header.parent_hash()))
+    }
+
+    /// Proof-of-work nonce, empty for other engines.
+    fn nonce(&self) -> Bytes {
+        Bytes(
+            self.seal_field(1)
+                .and_then(|field| field.data().ok().map(|data| data.to_vec()))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Proof-of-work mix hash, zero for other engines.
+    fn mix_hash(&self) -> Bytes32 {
+        Bytes32(
+            self.seal_field(0)
+                .and_then(|field| field.as_val().ok())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Root of the transactions trie.
+    fn transactions_root(&self) -> Bytes32 {
+        Bytes32(*self.header.transactions_root())
+    }
+
+    /// Number of transactions, null if the body is not available.
+    fn transaction_count(&self, context: &Context) -> Option<i32> {
+        context
+            .client
+            .block_body(self.id())
+            .map(|body| body.transactions_count() as i32)
+    }
+
+    /// Root of the state trie after the block.
+    fn state_root(&self) -> Bytes32 {
+        Bytes32(*self.header.state_root())
+    }
+
+    /// Root of the receipts trie.
+    fn receipts_root(&self) -> Bytes32 {
+        Bytes32(*self.header.receipts_root())
+    }
+
+    /// Block author, at the given block or this block.
+    fn miner(&self, block: Option<Long>) -> Account {
+        Account {
+            address: *self.header.author(),
+            block: block_id(block, self.id()),
+        }
+    }
+
+    /// Extra data of the block.
+    fn extra_data(&self) -> Bytes {
+        Bytes(self.header.extra_data().clone())
+    }
+
+    /// Gas limit of the block.
+    fn gas_limit(&self) -> Long {
+        Long(self.header.gas_limit().low_u64())
+    }
+
+    /// Gas used by the block.
+    fn gas_used(&self) -> Long {
+        Long(self.header.gas_used().low_u64())
+    }
+
+    /// Base fee per gas, null before EIP-1559.
+    fn base_fee_per_gas(&self) -> Option<BigInt> {
+        self.header.base_fee().map(BigInt)
+    }
+
+    /// Unix timestamp of the block.
+    fn timestamp(&self) -> Long {
+        Long(self.header.timestamp())
+    }
+
+    /// Bloom filter of the logs of the block.
+    fn logs_bloom(&self) -> Bytes {
+        Bytes(self.header.log_bloom().as_bytes().to_vec())
+    }
+
+    /// Difficulty of the block.
+    fn difficulty(&self) -> BigInt {
+        BigInt(*self.header.difficulty())
+    }
+
+    /// Total difficulty of the chain up to and including this block.
+    fn total_difficulty(&self, context: &Context) -> FieldResult<BigInt> {
+        let difficulty = context
+            .client
+            .block_total_difficulty(self.id())
+            .ok_or("Total difficulty is not available")?;
+        Ok(BigInt(difficulty))
+    }
+
+    /// Hash of the ommers list.
+    fn ommer_hash(&self) -> Bytes32 {
+        Bytes32(*self.header.uncles_hash())
+    }
+
+    /// Number of ommers, null if the body is not available.
+    fn ommer_count(&self, context: &Context) -> Option<i32> {
+        context
+            .client
+            .block_body(self.id())
+            .map(|body| body.uncles_count() as i32)
+    }
+
+    /// Ommers of the block, null if the body is not available.
+    fn ommers(&self, context: &Context) -> FieldResult<Option<Vec<Block>>> {
+        let body = match context.client.block_body(self.id()) {
+            Some(body) => body,
+            None => return Ok(None),
+        };
+        let eip1559_transition = context.client.engine().params().eip1559_transition;
+        Ok(Some(
+            body.uncles(eip1559_transition)
+                .into_iter()
+                .map(|header| Block { header })
+                .collect(),
+        ))
+    }
+
+    /// Ommer at the given index.
+    fn ommer_at(&self, context: &Context, index: i32) -> FieldResult<Option<Block>> {
+        if index < 0 {
+            return Ok(None);
+        }
+        let eip1559_transition = context.client.engine().params().eip1559_transition;
+        let id = UncleId {
+            block: self.id(),
+            position: index as usize,
+        };
+        match context.client.uncle(id) {
+            Some(header) => Ok(Some(Block {
+                header: header.decode(eip1559_transition)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Transactions of the block, null if the body is not available.
+    fn transactions(&self, context: &Context) -> FieldResult<Option<Vec<Transaction>>> {
+        self.load_transactions(context)
+    }
+
+    /// Transaction at the given index.
+    fn transaction_at(&self, context: &Context, index: i32) -> FieldResult<Option<Transaction>> {
+        if index < 0 {
+            return Ok(None);
+        }
+        context.transaction(TransactionId::Location(self.id(), index as usize))
+    }
+
+    /// Logs emitted in the block and matching the filter.
+    fn logs(&self, context: &Context, filter: BlockFilterCriteria) -> FieldResult<Vec<Log>> {
+        context.logs(into_filter(
+            self.id(),
+            self.id(),
+            filter.addresses,
+            filter.topics,
+        ))
+    }
+
+    /// Account at the state of the block.
+    fn account(&self, address: Address) -> Account {
+        Account {
+            address: address.0,
+            block: self.id(),
+        }
+    }
+
+    /// Executes a message call against the state of the block.
+    fn call(&self, context: &Context, data: CallData) -> FieldResult<CallResult> {
+        let (mut state, header) = context.state_at(self.id())?;
+        let executed = context.client.call(
+            &data.into_transaction(&header),
+            Default::default(),
+            &mut state,
+            &header,
+        )?;
+        Ok(CallResult {
+            data: Bytes(executed.output),
+            gas_used: Long(executed.gas_used.low_u64()),
+            status: Long(executed.exception.is_none() as u64),
+        })
+    }
+
+    /// Estimates the gas needed by a message call against the state of the block.
+    fn estimate_gas(&self, context: &Context, data: CallData) -> FieldResult<Long> {
+        let (state, header) = context.state_at(self.id())?;
+        let gas = context
+            .client
+            .estimate_gas(&data.into_transaction(&header), &state, &header)?;
+        Ok(Long(gas.low_u64()))
+    }
+}
+
+/// Read-only queries.
+pub struct Query;
+
+#[juniper::object(Context = Context)]
+impl Query {
+    /// Block by number or hash, the latest block if neither is given.
+    fn block(
+        context: &Context,
+        number: Option<Long>,
+        hash: Option<Bytes32>,
+    ) -> FieldResult<Option<Block>> {
+        match (number, hash) {
+            (Some(_), Some(_)) => Err("Only one of number or hash may be given".into()),
+            (Some(number), None) => context.block(BlockId::Number(number.0)),
+            (None, Some(hash)) => context.block(BlockId::Hash(hash.0)),
+            (None, None) => context.block(BlockId::Latest),
+        }
+    }
+
+    /// Blocks in the inclusive range, up to the latest block if `to` is missing.
+    fn blocks(context: &Context, from: Long, to: Option<Long>) -> FieldResult<Vec<Block>> {
+        let best = context.client.chain_info().best_block_number;
+        let to = to.map_or(best, |to| cmp::min(to.0, best));
+        if to >= from.0 && to - from.0 >= MAX_BLOCKS_RANGE {
+            return Err(format!("Block range is limited to {} blocks", MAX_BLOCKS_RANGE).into());
+        }
+
+        let mut blocks = Vec::new();
+        for number in from.0..=to {
+            match context.block(BlockId::Number(number))? {
+                Some(block) => blocks.push(block),
+                None => break,
+            }
+        }
+        Ok(blocks)
+    }
+
+    /// Mined or pending transaction by hash.
+    fn transaction(context: &Context, hash: Bytes32) -> FieldResult<Option<Transaction>> {
+        if let Some(tx) = context.transaction(TransactionId::Hash(hash.0))? {
+            return Ok(Some(tx));
+        }
+        Ok(context
+            .client
+            .queued_transaction(hash.0)
+            .map(|tx| Transaction::pending(tx.signed())))
+    }
+
+    /// Logs matching the filter.
+    fn logs(context: &Context, filter: FilterCriteria) -> FieldResult<Vec<Log>> {
+        context.logs(filter.into_filter())
+    }
+
+    /// Suggested gas price for new transactions.
+    fn gas_price(context: &Context) -> BigInt {
+        BigInt(default_gas_price(
+            &*context.client,
+            &*context.miner,
+            &context.gas_price_oracle,
+        ))
+    }
+
+    /// Chain id used for replay protected signatures.
+    #[graphql(name = "chainID")]
+    fn chain_id(context: &Context) -> Option<BigInt> {
+        context
+            .client
+            .signing_chain_id()
+            .map(|id| BigInt(id.into()))
+    }
+}
+
+/// State changing operations.
+pub struct Mutation;
+
+#[juniper::object(Context = Context)]
+impl Mutation {
+    /// Submits a signed raw transaction and returns its hash.
+    fn send_raw_transaction(context: &Context, data: Bytes) -> FieldResult<Bytes32> {
+        let tx = TypedTransaction::decode(&data.0)?;
+        let signed = SignedTransaction::new(tx)?;
+        let hash = FullDispatcher::dispatch_transaction(
+            &*context.client,
+            &*context.miner,
+            PendingTransaction::new(signed, None),
+            false,
+        )
+        .map_err(|e| e.message)?;
+        Ok(Bytes32(hash))
+    }
+}
+
+pub type Schema = RootNode<'static, Query, Mutation>;
+
+/// Creates the EIP-1767 schema.
+pub fn schema() -> Schema {
+    Schema::new(Query, Mutation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethcore::test_helpers::generate_dummy_client_with_data;
+    use juniper::{FromInputValue, Variables};
+    use serde_json::{self, json};
+
+    fn context(blocks: u32) -> Context {
+        let client = generate_dummy_client_with_data(blocks, 1, &[U256::from(5)]);
+        Context {
+            miner: client.miner(),
+            client,
+            gas_price_oracle: GasPriceOracle::new(50, 10),
+        }
+    }
+
+    fn execute(context: &Context, query: &str) -> serde_json::Value {
+        let (value, errors) =
+            juniper::execute(query, None, &schema(), &Variables::new(), context).unwrap();
+        assert!(errors.is_empty(), "{:?}", errors);
+        serde_json::to_value(&value).unwrap()
+    }
+
+    fn string(value: &str) -> InputValue {
+        InputValue::scalar(value.to_owned())
+    }
+
+    #[test]
+    fn should_parse_scalars() {
+        assert_eq!(
+            Long::from_input_value(&string("0x10")).map(|l| l.0),
+            Some(16)
+        );
+        assert_eq!(Long::from_input_value(&string("10")).map(|l| l.0), Some(10));
+        assert_eq!(
+            Long::from_input_value(&InputValue::scalar(7)).map(|l| l.0),
+            Some(7)
+        );
+        assert!(Long::from_input_value(&InputValue::scalar(-1)).is_none());
+        assert_eq!(
+            BigInt::from_input_value(&string("1000000000000000000000")).map(|b| b.0),
+            Some(U256::from_dec_str("1000000000000000000000").unwrap())
+        );
+        assert_eq!(
+            Bytes::from_input_value(&string("0x0102")).map(|b| b.0),
+            Some(vec![1, 2])
+        );
+        assert!(Bytes::from_input_value(&string("0102")).is_none());
+        assert!(Address::from_input_value(&string("0x0102")).is_none());
+        assert!(Bytes32::from_input_value(&string(&format!("{:#x}", H256::zero()))).is_some());
+    }
+
+    #[test]
+    fn should_convert_filter_criteria() {
+        let topic = H256::from_low_u64_be(1);
+        let filter = FilterCriteria {
+            from_block: Some(Long(5)),
+            to_block: None,
+            addresses: Some(vec![Address(H160::from_low_u64_be(2))]),
+            topics: Some(vec![vec![], vec![Bytes32(topic)]]),
+        }
+        .into_filter();
+
+        assert_eq!(filter.from_block, BlockId::Number(5));
+        assert_eq!(filter.to_block, BlockId::Latest);
+        assert_eq!(filter.address, Some(vec![H160::from_low_u64_be(2)]));
+        assert_eq!(filter.topics, vec![None, Some(vec![topic])]);
+    }
+
+    #[test]
+    fn should_resolve_blocks_and_transactions() {
+        let context = context(3);
+        let hash = context.client.block_hash(BlockId::Number(2)).unwrap();
+
+        let result = execute(
+            &context,
+            "{ block(number: 2) { number hash parent { number } transactionCount \
+               transactions { nonce index gasPrice block { number } } } }",
+        );
+
+        assert_eq!(
+            result,
+            json!({
+                "block": {
+                    "number": "0x2",
+                    "hash": format!("{:#x}", hash),
+                    "parent": { "number": "0x1" },
+                    "transactionCount": 1,
+                    "transactions": [{
+                        "nonce": "0x1",
+                        "index": 0,
+                        "gasPrice": "0x5",
+                        "block": { "number": "0x2" },
+                    }],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn should_resolve_block_ranges_and_transactions_by_hash() {
+        let context = context(3);
+        let tx = context
+            .client
+            .block_transaction(TransactionId::Location(BlockId::Number(1), 0))
+            .unwrap();
+
+        let result = execute(
+            &context,
+            &format!(
+                "{{ blocks(from: 1) {{ number }} block {{ number }} \
+                   transaction(hash: \"{:#x}\") {{ nonce status block {{ number }} }} }}",
+                tx.signed.hash()
+            ),
+        );
+
+        assert_eq!(
+            result,
+            json!({
+                "blocks": [{ "number": "0x1" }, { "number": "0x2" }, { "number": "0x3" }],
+                "block": { "number": "0x3" },
+                "transaction": {
+                    "nonce": "0x0",
+                    "status": "0x1",
+                    "block": { "number": "0x1" },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn should_reject_large_block_ranges() {
+        let context = context(1);
+
+        let (_, errors) = juniper::execute(
+            "{ blocks(from: 0, to: 5000) { number } }",
+            None,
+            &schema(),
+            &Variables::new(),
+            &context,
+        )
+        .unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].error().message(),
+            "Block range is limited to 1000 blocks"
+        );
+    }
+}
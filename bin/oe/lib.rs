@@ -57,6 +57,7 @@ extern crate ethstore;
 extern crate fetch;
 extern crate hyper;
 extern crate journaldb;
+extern crate juniper;
 extern crate keccak_hash as hash;
 extern crate kvdb;
 extern crate node_filter;
@@ -99,6 +100,7 @@ mod cache;
 mod cli;
 mod configuration;
 mod db;
+mod graphql;
 mod helpers;
 mod informant;
 mod metrics;
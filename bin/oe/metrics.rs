@@ -2,6 +2,8 @@ use std::{sync::Arc, time::Instant};
 
 use crate::{futures::Future, rpc, rpc_apis};
 
+use parity_rpc::limiter::RateLimiter;
+
 use parking_lot::Mutex;
 
 use hyper::{service::service_fn_ok, Body, Method, Request, Response, Server, StatusCode};
@@ -36,6 +38,7 @@ impl Default for MetricsConfiguration {
 
 struct State {
     rpc_apis: Arc<rpc_apis::FullDependencies>,
+    rate_limiter: Arc<RateLimiter>,
 }
 
 fn handle_request(
@@ -52,6 +55,7 @@ fn handle_request(
             let state = state.lock();
             state.rpc_apis.client.prometheus_metrics(&mut reg);
             state.rpc_apis.sync.prometheus_metrics(&mut reg);
+            state.rate_limiter.prometheus_metrics(&mut reg);
             let elapsed = start.elapsed();
             reg.register_gauge(
                 "metrics_time",
@@ -96,6 +100,7 @@ pub fn start_prometheus_metrics(
 
     let state = State {
         rpc_apis: deps.apis.clone(),
+        rate_limiter: deps.rate_limiter.clone(),
     };
     let state = Arc::new(Mutex::new(state));
     let conf = Arc::new(conf.to_owned());
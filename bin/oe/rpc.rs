@@ -14,9 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{collections::HashSet, io, path::PathBuf, sync::Arc};
+use std::{collections::HashSet, io, net::IpAddr, path::PathBuf, sync::Arc};
 
 use crate::{
+    graphql,
     helpers::parity_ipc_path,
     rpc_apis::{self, ApiSet},
 };
@@ -24,12 +25,16 @@ use dir::{default_data_path, helpers::replace_home};
 use jsonrpc_core::MetaIoHandler;
 use parity_rpc::{
     self as rpc,
+    access::ApiAccessMiddleware,
+    dispatch::GasPriceOracle,
     informant::{Middleware, RpcStats},
+    limiter::{RateLimitMiddleware, RateLimiter},
     DomainsValidation, Metadata,
 };
 use parity_runtime::Executor;
 
-pub use parity_rpc::{HttpServer, IpcServer};
+pub use crate::graphql::{GraphQLConfiguration, GraphQLServer};
+pub use parity_rpc::{limiter::RateLimitConfig, HttpServer, IpcServer};
 //pub use parity_rpc::ws::Server as WsServer;
 pub use parity_rpc::ws::{ws, Server as WsServer};
 
@@ -47,6 +52,8 @@ pub struct HttpConfiguration {
     pub processing_threads: usize,
     pub max_payload: usize,
     pub keep_alive: bool,
+    pub jwt_secret: Option<PathBuf>,
+    pub trusted_proxies: Vec<IpAddr>,
 }
 
 impl Default for HttpConfiguration {
@@ -62,6 +69,8 @@ impl Default for HttpConfiguration {
             processing_threads: 4,
             max_payload: 5,
             keep_alive: true,
+            jwt_secret: None,
+            trusted_proxies: Vec::new(),
         }
     }
 }
@@ -100,6 +109,7 @@ pub struct WsConfiguration {
     pub signer_path: PathBuf,
     pub support_token_api: bool,
     pub max_payload: usize,
+    pub jwt_secret: Option<PathBuf>,
 }
 
 impl Default for WsConfiguration {
@@ -120,6 +130,7 @@ impl Default for WsConfiguration {
             signer_path: replace_home(&data_dir, "$BASE/signer").into(),
             support_token_api: true,
             max_payload: 5,
+            jwt_secret: None,
         }
     }
 }
@@ -150,6 +161,7 @@ pub struct Dependencies<D: rpc_apis::Dependencies> {
     pub apis: Arc<D>,
     pub executor: Executor,
     pub stats: Arc<RpcStats>,
+    pub rate_limiter: Arc<RateLimiter>,
 }
 
 pub fn new_ws<D: rpc_apis::Dependencies>(
@@ -168,12 +180,21 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
 
     let full_handler = setup_apis(rpc_apis::ApiSet::All, deps);
     let handler = {
+        let mut methods = MetaIoHandler::default();
+        let method_apis = deps
+            .apis
+            .extend_with_set(&mut methods, &conf.apis.list_apis())
+            .into_iter()
+            .map(|(method, api)| (method, api.name().to_owned()))
+            .collect();
+
         let mut handler = MetaIoHandler::with_middleware((
             rpc::WsDispatcher::new(full_handler),
             Middleware::new(deps.stats.clone(), deps.apis.activity_notifier()),
+            RateLimitMiddleware::new(deps.rate_limiter.clone()),
+            ApiAccessMiddleware::new(method_apis),
         ));
-        let apis = conf.apis.list_apis();
-        deps.apis.extend_with_set(&mut handler, &apis);
+        handler.extend_with(methods);
 
         handler
     };
@@ -181,6 +202,7 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
     let allowed_origins = into_domains(with_domain(conf.origins, domain, &None));
     let allowed_hosts = into_domains(with_domain(conf.hosts, domain, &Some(url.clone().into())));
 
+    let jwt = jwt_auth(&conf.jwt_secret)?.map(Arc::new);
     let signer_path;
     let path = match conf.support_token_api {
         true => {
@@ -196,7 +218,7 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
         allowed_hosts,
         conf.max_connections,
         rpc::WsExtractor::new(path.clone()),
-        rpc::WsExtractor::new(path.clone()),
+        rpc::WsExtractor::new(path.clone()).with_jwt(jwt),
         rpc::WsStats::new(deps.stats.clone()),
         conf.max_payload,
     );
@@ -240,17 +262,31 @@ pub fn new_http<D: rpc_apis::Dependencies>(
     let allowed_hosts = into_domains(with_domain(conf.hosts, domain, &Some(url.clone().into())));
     let health_api = Some(("/api/health", "parity_nodeStatus"));
 
-    let start_result = rpc_servers::start_http(
-        &addr,
-        cors_domains,
-        allowed_hosts,
-        health_api,
-        handler,
-        rpc::RpcExtractor,
-        conf.server_threads,
-        conf.max_payload,
-        conf.keep_alive,
-    );
+    let start_result = match jwt_auth(&conf.jwt_secret)? {
+        Some(jwt) => rpc_servers::start_http_with_middleware(
+            &addr,
+            cors_domains,
+            allowed_hosts,
+            health_api,
+            handler,
+            rpc::RpcExtractor::new(conf.trusted_proxies.clone()),
+            jwt,
+            conf.server_threads,
+            conf.max_payload,
+            conf.keep_alive,
+        ),
+        None => rpc_servers::start_http(
+            &addr,
+            cors_domains,
+            allowed_hosts,
+            health_api,
+            handler,
+            rpc::RpcExtractor::new(conf.trusted_proxies.clone()),
+            conf.server_threads,
+            conf.max_payload,
+            conf.keep_alive,
+        ),
+    };
 
     match start_result {
 		Ok(server) => Ok(Some(server)),
@@ -285,12 +321,57 @@ pub fn new_ipc<D: rpc_apis::Dependencies>(
         }
     }
 
-    match rpc_servers::start_ipc(&conf.socket_addr, handler, rpc::RpcExtractor) {
+    match rpc_servers::start_ipc(&conf.socket_addr, handler, rpc::RpcExtractor::default()) {
         Ok(server) => Ok(Some(server)),
         Err(io_error) => Err(format!("IPC error: {}", io_error)),
     }
 }
 
+pub fn new_graphql(
+    conf: GraphQLConfiguration,
+    deps: &Dependencies<rpc_apis::FullDependencies>,
+) -> Result<Option<GraphQLServer>, String> {
+    if !conf.enabled {
+        return Ok(None);
+    }
+
+    let url = format!("{}:{}", conf.interface, conf.port);
+    let allowed_hosts = into_domains(with_domain(
+        conf.hosts.clone(),
+        DAPPS_DOMAIN,
+        &Some(url.clone().into()),
+    ));
+    let cors_domains = into_domains(conf.cors.clone());
+
+    let context = graphql::Context {
+        client: deps.apis.client.clone(),
+        miner: deps.apis.miner.clone(),
+        gas_price_oracle: GasPriceOracle::new(
+            deps.apis.gas_price_percentile,
+            deps.apis.gas_price_history_blocks,
+        ),
+    };
+    let jwt = jwt_auth(&conf.jwt_secret)?;
+    graphql::start(
+        &conf,
+        allowed_hosts.into(),
+        cors_domains.into(),
+        context,
+        jwt,
+        deps.rate_limiter.clone(),
+    )
+    .map(Some)
+}
+
+fn jwt_auth(secret: &Option<PathBuf>) -> Result<Option<rpc::JwtAuth>, String> {
+    match *secret {
+        Some(ref path) => rpc::JwtSecret::from_file(path)
+            .map(|secret| Some(rpc::JwtAuth::new(secret)))
+            .map_err(|e| format!("Unable to read JWT secret from {}: {}", path.display(), e)),
+        None => Ok(None),
+    }
+}
+
 fn into_domains<T: From<String>>(items: Option<Vec<String>>) -> DomainsValidation<T> {
     items
         .map(|vals| vals.into_iter().map(T::from).collect())
@@ -329,16 +410,31 @@ fn with_domain(
 pub fn setup_apis<D>(
     apis: ApiSet,
     deps: &Dependencies<D>,
-) -> MetaIoHandler<Metadata, Middleware<D::Notifier>>
+) -> MetaIoHandler<
+    Metadata,
+    (
+        Middleware<D::Notifier>,
+        RateLimitMiddleware,
+        ApiAccessMiddleware,
+    ),
+>
 where
     D: rpc_apis::Dependencies,
 {
-    let mut handler = MetaIoHandler::with_middleware(Middleware::new(
-        deps.stats.clone(),
-        deps.apis.activity_notifier(),
+    let mut methods = MetaIoHandler::default();
+    let method_apis = deps
+        .apis
+        .extend_with_set(&mut methods, &apis.list_apis())
+        .into_iter()
+        .map(|(method, api)| (method, api.name().to_owned()))
+        .collect();
+
+    let mut handler = MetaIoHandler::with_middleware((
+        Middleware::new(deps.stats.clone(), deps.apis.activity_notifier()),
+        RateLimitMiddleware::new(deps.rate_limiter.clone()),
+        ApiAccessMiddleware::new(method_apis),
     ));
-    let apis = apis.list_apis();
-    deps.apis.extend_with_set(&mut handler, &apis);
+    handler.extend_with(methods);
 
     handler
 }
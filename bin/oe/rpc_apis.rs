@@ -16,7 +16,7 @@
 
 use std::{
     cmp::PartialEq,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     str::FromStr,
     sync::Arc,
 };
@@ -156,28 +156,32 @@ impl FromStr for ApiSet {
     }
 }
 
-fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
-    let mut modules = BTreeMap::new();
-    for api in apis {
-        let (name, version) = match *api {
-            Api::Debug => ("debug", "1.0"),
-            Api::Eth => ("eth", "1.0"),
-            Api::EthPubSub => ("pubsub", "1.0"),
-            Api::Net => ("net", "1.0"),
-            Api::Parity => ("parity", "1.0"),
-            Api::ParityAccounts => ("parity_accounts", "1.0"),
-            Api::ParityPubSub => ("parity_pubsub", "1.0"),
-            Api::ParitySet => ("parity_set", "1.0"),
-            Api::Personal => ("personal", "1.0"),
-            Api::Rpc => ("rpc", "1.0"),
-            Api::SecretStore => ("secretstore", "1.0"),
-            Api::Signer => ("signer", "1.0"),
-            Api::Traces => ("traces", "1.0"),
-            Api::Web3 => ("web3", "1.0"),
-        };
-        modules.insert(name.into(), version.into());
+impl Api {
+    /// Name of the API as used in `--jsonrpc-apis`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Api::Debug => "debug",
+            Api::Eth => "eth",
+            Api::EthPubSub => "pubsub",
+            Api::Net => "net",
+            Api::Parity => "parity",
+            Api::ParityAccounts => "parity_accounts",
+            Api::ParityPubSub => "parity_pubsub",
+            Api::ParitySet => "parity_set",
+            Api::Personal => "personal",
+            Api::Rpc => "rpc",
+            Api::SecretStore => "secretstore",
+            Api::Signer => "signer",
+            Api::Traces => "traces",
+            Api::Web3 => "web3",
+        }
     }
-    modules
+}
+
+fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
+    apis.iter()
+        .map(|api| (api.name().into(), "1.0".into()))
+        .collect()
 }
 
 macro_rules! add_signing_methods {
@@ -208,7 +212,12 @@ pub trait Dependencies {
     fn activity_notifier(&self) -> Self::Notifier;
 
     /// Extend the given I/O handler with endpoints for each API.
-    fn extend_with_set<S>(&self, handler: &mut MetaIoHandler<Metadata, S>, apis: &HashSet<Api>)
+    /// Returns the API of every added method.
+    fn extend_with_set<S>(
+        &self,
+        handler: &mut MetaIoHandler<Metadata, S>,
+        apis: &HashSet<Api>,
+    ) -> HashMap<String, Api>
     where
         S: core::Middleware<Metadata>;
 }
@@ -231,6 +240,7 @@ pub struct FullDependencies {
     pub fetch: FetchClient,
     pub executor: Executor,
     pub gas_price_percentile: usize,
+    pub gas_price_history_blocks: usize,
     pub poll_lifetime: u32,
     pub allow_missing_blocks: bool,
     pub no_ancient_blocks: bool,
@@ -242,7 +252,8 @@ impl FullDependencies {
         handler: &mut MetaIoHandler<Metadata, S>,
         apis: &HashSet<Api>,
         for_generic_pubsub: bool,
-    ) where
+    ) -> HashMap<String, Api>
+    where
         S: core::Middleware<Metadata>,
     {
         use parity_rpc::v1::*;
@@ -255,11 +266,14 @@ impl FullDependencies {
             self.miner.clone(),
             nonces.clone(),
             self.gas_price_percentile,
+            self.gas_price_history_blocks,
         );
         let account_signer = Arc::new(dispatch::Signer::new(self.accounts.clone())) as _;
         let accounts = account_utils::accounts_list(self.accounts.clone());
 
+        let mut methods = HashMap::new();
         for api in apis {
+            let known: HashSet<String> = handler.iter().map(|(name, _)| name.clone()).collect();
             match *api {
                 Api::Debug => {
                     handler.extend_with(DebugClient::new(self.client.clone()).to_delegate());
@@ -280,6 +294,7 @@ impl FullDependencies {
                         &self.external_miner,
                         EthClientOptions {
                             gas_price_percentile: self.gas_price_percentile,
+                            gas_price_history_blocks: self.gas_price_history_blocks,
                             allow_missing_blocks: self.allow_missing_blocks,
                             allow_experimental_rpcs: self.experimental_rpcs,
                             no_ancient_blocks: self.no_ancient_blocks,
@@ -314,6 +329,13 @@ impl FullDependencies {
                                     h.notify_new_transactions(hashes);
                                 }
                             }));
+                        let h = client.handler();
+                        self.miner
+                            .add_transaction_events_listener(Box::new(move |events| {
+                                if let Some(h) = h.upgrade() {
+                                    h.notify_transaction_events(events);
+                                }
+                            }));
 
                         if let Some(h) = client.handler().upgrade() {
                             self.client.add_notify(h);
@@ -419,7 +441,13 @@ impl FullDependencies {
                     handler.extend_with(SecretStoreClient::new(&self.accounts).to_delegate());
                 }
             }
+            for (name, _) in handler.iter() {
+                if !known.contains(name) {
+                    methods.insert(name.clone(), api.clone());
+                }
+            }
         }
+        methods
     }
 }
 
@@ -432,7 +460,11 @@ impl Dependencies for FullDependencies {
         }
     }
 
-    fn extend_with_set<S>(&self, handler: &mut MetaIoHandler<Metadata, S>, apis: &HashSet<Api>)
+    fn extend_with_set<S>(
+        &self,
+        handler: &mut MetaIoHandler<Metadata, S>,
+        apis: &HashSet<Api>,
+    ) -> HashMap<String, Api>
     where
         S: core::Middleware<Metadata>,
     {
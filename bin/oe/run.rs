@@ -16,6 +16,7 @@
 
 use std::{
     any::Any,
+    collections::HashMap,
     str::FromStr,
     sync::{atomic, Arc, Weak},
     thread,
@@ -56,7 +57,7 @@ use ethcore_service::ClientService;
 use ethereum_types::{H256, U64};
 use journaldb::Algorithm;
 use node_filter::NodeFilter;
-use parity_rpc::{informant, is_major_importing, NetworkSettings};
+use parity_rpc::{informant, is_major_importing, limiter::RateLimiter, NetworkSettings};
 use parity_runtime::Runtime;
 use parity_version::version;
 use sync::SyncState;
@@ -83,9 +84,12 @@ pub struct RunCmd {
     pub logger_config: LogConfig,
     pub miner_options: MinerOptions,
     pub gas_price_percentile: usize,
+    pub gas_price_history_blocks: usize,
     pub poll_lifetime: u32,
     pub ws_conf: rpc::WsConfiguration,
     pub http_conf: rpc::HttpConfiguration,
+    pub graphql_conf: rpc::GraphQLConfiguration,
+    pub rate_limit_conf: rpc::RateLimitConfig,
     pub ipc_conf: rpc::IpcConfiguration,
     pub net_conf: sync::NetworkConfiguration,
     pub network_id: Option<u64>,
@@ -112,7 +116,10 @@ pub struct RunCmd {
     pub new_transactions_stats_period: u64,
     pub verifier_settings: VerifierSettings,
     pub no_persistent_txqueue: bool,
+    pub tx_queue_journal: Option<crate::local_store::JournalOptions>,
     pub max_round_blocks_to_import: usize,
+    pub parallel_transactions: bool,
+    pub state_prefetch: bool,
     pub metrics_conf: MetricsConfiguration,
     pub shutdown_on_missing_block_import: Option<u64>,
 }
@@ -140,6 +147,36 @@ impl crate::local_store::NodeInfo for FullNodeInfo {
             })
             .collect()
     }
+
+    fn queued_transactions(&self) -> Vec<crate::types::transaction::SignedTransaction> {
+        let miner = match self.miner.as_ref() {
+            Some(m) => m,
+            None => return Vec::new(),
+        };
+
+        let local_transactions = miner.local_transactions();
+        let mut transactions: Vec<_> = miner
+            .all_transactions()
+            .into_iter()
+            .filter(|tx| !local_transactions.contains_key(&tx.signed().hash()))
+            .map(|tx| tx.signed().clone())
+            .collect();
+        // the senders whose lowest nonce pays the most are kept first, each with its whole chain.
+        transactions.sort_by_key(|tx| (tx.sender(), tx.tx().nonce));
+        let mut first_gas_price = HashMap::new();
+        for tx in &transactions {
+            first_gas_price
+                .entry(tx.sender())
+                .or_insert(tx.tx().gas_price);
+        }
+        transactions.sort_by(|a, b| {
+            first_gas_price[&b.sender()]
+                .cmp(&first_gas_price[&a.sender()])
+                .then_with(|| a.sender().cmp(&b.sender()))
+                .then_with(|| a.tx().nonce.cmp(&b.tx().nonce))
+        });
+        transactions
+    }
 }
 
 struct SyncProviderWrapper {
@@ -373,6 +410,8 @@ pub fn execute(
     client_config.queue.verifier_settings.bad_hashes = verification_bad_blocks(&cmd.spec);
     client_config.transaction_verification_queue_size = ::std::cmp::max(2048, txpool_size / 4);
     client_config.snapshot = cmd.snapshot_conf.clone();
+    client_config.parallel_transactions = cmd.parallel_transactions;
+    client_config.state_prefetch = cmd.state_prefetch;
 
     // set up bootnodes
     let mut net_conf = cmd.net_conf;
@@ -439,11 +478,14 @@ pub fn execute(
             },
         };
 
-        let store = crate::local_store::create(
+        let mut store = crate::local_store::create(
             db.key_value().clone(),
             ::ethcore_db::COL_NODE_INFO,
             node_info,
         );
+        if let Some(journal) = cmd.tx_queue_journal {
+            store = store.with_journal(journal);
+        }
 
         if cmd.no_persistent_txqueue {
             info!("Running without a persistent transaction queue.");
@@ -465,6 +507,27 @@ pub fn execute(
             Err(e) => warn!("Error loading cached pending transactions from disk: {}", e),
         }
 
+        // re-queue journaled transactions, verified as if they were received from the network.
+        match store.journaled_transactions() {
+            Ok(ref journaled) if journaled.is_empty() => {}
+            Ok(journaled) => {
+                let count = journaled.len();
+                let imported = miner
+                    .import_external_transactions(
+                        &*client,
+                        journaled.into_iter().map(Into::into).collect(),
+                    )
+                    .into_iter()
+                    .filter(Result::is_ok)
+                    .count();
+                info!(
+                    "Restored {} of {} journaled queued transactions.",
+                    imported, count
+                );
+            }
+            Err(e) => warn!("Error loading journaled transactions from disk: {}", e),
+        }
+
         Arc::new(store)
     };
 
@@ -556,6 +619,7 @@ pub fn execute(
         fetch: fetch.clone(),
         executor: runtime.executor(),
         gas_price_percentile: cmd.gas_price_percentile,
+        gas_price_history_blocks: cmd.gas_price_history_blocks,
         poll_lifetime: cmd.poll_lifetime,
         allow_missing_blocks: cmd.allow_missing_blocks,
         no_ancient_blocks: !cmd.download_old_blocks,
@@ -565,6 +629,7 @@ pub fn execute(
         apis: deps_for_rpc_apis.clone(),
         executor: runtime.executor(),
         stats: rpc_stats.clone(),
+        rate_limiter: Arc::new(RateLimiter::new(cmd.rate_limit_conf.clone())),
     };
 
     // start rpc servers
@@ -580,6 +645,7 @@ pub fn execute(
         cmd.http_conf.clone(),
         &dependencies,
     )?;
+    let graphql_server = rpc::new_graphql(cmd.graphql_conf.clone(), &dependencies)?;
 
     // secret store key server
     let secretstore_deps = secretstore::Dependencies {
@@ -664,6 +730,7 @@ pub fn execute(
                 watcher,
                 ws_server,
                 http_server,
+                graphql_server,
                 ipc_server,
                 secretstore_key_server,
                 runtime,
@@ -0,0 +1,517 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction pool capture replay tool.
+
+#![warn(missing_docs)]
+
+extern crate common_types as types;
+extern crate docopt;
+extern crate env_logger;
+extern crate ethcore;
+extern crate ethcore_miner;
+extern crate ethereum_types;
+extern crate panic_hook;
+#[cfg(test)]
+extern crate parity_crypto as crypto;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    process,
+    sync::{Arc, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+use docopt::Docopt;
+use ethcore::{
+    client::{BlockInfo, ChainInfo, EachBlockWith, TestBlockChainClient, TransactionInfo},
+    miner::{Miner, MinerOptions, MinerService, PendingOrdering},
+    spec::Spec,
+};
+use ethcore_miner::{
+    gas_pricer::GasPricer,
+    pool::{
+        self,
+        admission::AdmissionPolicy,
+        capture::{self, CaptureRecord},
+        verifier,
+    },
+};
+use ethereum_types::{Address, H256, U256};
+use types::{
+    ids::TransactionId,
+    transaction::{self, SignedTransaction},
+};
+
+const USAGE: &'static str = r#"
+Transaction pool capture replay tool.
+  Copyright 2015-2020 Parity Technologies (UK) Ltd.
+
+Usage:
+    txpool-replay <capture> [options]
+    txpool-replay [-h | --help]
+
+Replays a transaction capture recorded with `diamond-node --tx-queue-capture`
+against a transaction pool backed by a test client and reports acceptance,
+replacement and eviction statistics. Every sender starts with a large balance
+and the lowest nonce seen in the capture. Transactions from retracted blocks
+are replayed as received from the network. For every new best block of the
+capture the ready transactions are included in a block, up to its gas limit,
+and the pool is culled.
+
+Options:
+    --chain FILE                 Chain spec file the transactions are verified
+                                 against, defaults to a test chain.
+    --speed FACTOR               Replay speed relative to the capture, 0 replays
+                                 as fast as possible [default: 1].
+    --tx-queue-size LIMIT        Maximum amount of transactions in the
+                                 queue [default: 8192].
+    --tx-queue-per-sender LIMIT  Maximum number of transactions per sender in
+                                 the queue [default: 81].
+    --tx-queue-mem-limit MB      Maximum amount of memory used by the
+                                 transactions in the queue [default: 4].
+    --tx-queue-pending-per-sender LIMIT
+                                 Maximum number of pending transactions per
+                                 sender in the queue, unlimited by default.
+    --tx-queue-future-per-sender LIMIT
+                                 Maximum number of future transactions per
+                                 sender in the queue, unlimited by default.
+    --tx-queue-future-lifetime SECONDS
+                                 Maximum time a transaction can stay future in
+                                 the queue, 0 disables the limit [default: 0].
+    --tx-queue-admission-policy FILE
+                                 TOML file with local admission rules for the
+                                 transaction queue.
+    --min-gas-price WEI          Minimum gas price of transactions accepted
+                                 to the queue [default: 0].
+    -h, --help                   Display this message and exit.
+"#;
+
+/// Balance of every sender of the capture in ether.
+const SENDER_BALANCE_ETHER: usize = 1_000_000_000_000;
+
+#[derive(Debug, Deserialize)]
+struct Args {
+    arg_capture: PathBuf,
+    flag_chain: Option<PathBuf>,
+    flag_speed: f64,
+    flag_tx_queue_size: usize,
+    flag_tx_queue_per_sender: usize,
+    flag_tx_queue_mem_limit: usize,
+    flag_tx_queue_pending_per_sender: Option<usize>,
+    flag_tx_queue_future_per_sender: Option<usize>,
+    flag_tx_queue_future_lifetime: u64,
+    flag_tx_queue_admission_policy: Option<PathBuf>,
+    flag_min_gas_price: u64,
+}
+
+/// Part of the capture replayed at once.
+enum Entry {
+    /// Transactions passed to a single `import` call.
+    Batch {
+        timestamp: u64,
+        transactions: Vec<verifier::Transaction>,
+    },
+    /// New best block.
+    Block { timestamp: u64 },
+}
+
+impl Entry {
+    fn timestamp(&self) -> u64 {
+        match *self {
+            Entry::Batch { timestamp, .. } | Entry::Block { timestamp } => timestamp,
+        }
+    }
+}
+
+/// Transactions included in the replayed blocks.
+#[derive(Default)]
+struct Included(RwLock<HashSet<H256>>);
+
+impl TransactionInfo for Included {
+    fn transaction_block(&self, id: TransactionId) -> Option<H256> {
+        match id {
+            TransactionId::Hash(ref hash) if self.0.read().unwrap().contains(hash) => {
+                Some(H256::zero())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Report {
+    imported: usize,
+    accepted: usize,
+    rejected: BTreeMap<String, usize>,
+    blocks: usize,
+    included: usize,
+    elapsed: Duration,
+}
+
+impl Report {
+    fn note(&mut self, result: Result<(), transaction::Error>) {
+        self.imported += 1;
+        match result {
+            Ok(()) => self.accepted += 1,
+            Err(err) => *self.rejected.entry(error_kind(&err)).or_insert(0) += 1,
+        }
+    }
+}
+
+/// Name of the error variant, without the details of the particular transaction.
+fn error_kind(err: &transaction::Error) -> String {
+    format!("{:?}", err)
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_owned()
+}
+
+fn main() {
+    panic_hook::set_abort();
+    env_logger::init();
+
+    let args: Args = Docopt::new(USAGE)
+        .and_then(|d| d.deserialize())
+        .unwrap_or_else(|e| e.exit());
+
+    if let Err(e) = run(args) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let entries = read_entries(&args)?;
+    let (report, miner) = replay(&args, entries)?;
+
+    let evictions = miner.eviction_stats();
+    println!(
+        "Replayed {} transactions in {:.3}s",
+        report.imported,
+        report.elapsed.as_secs_f64()
+    );
+    println!("  accepted:                {}", report.accepted);
+    println!(
+        "  rejected:                {}",
+        report.rejected.values().sum::<usize>()
+    );
+    for (kind, count) in &report.rejected {
+        println!("    {:<23}{}", format!("{}:", kind), count);
+    }
+    println!("  blocks:                  {}", report.blocks);
+    println!("  included:                {}", report.included);
+    println!("  replaced:                {}", evictions.replaced);
+    println!("  evicted (pool limits):   {}", evictions.limit);
+    println!("  evicted (pending limit): {}", evictions.pending_limit);
+    println!("  evicted (future limit):  {}", evictions.future_limit);
+    println!("  expired:                 {}", evictions.expired);
+    println!("  stale:                   {}", evictions.stale);
+    print!("{}", miner.queue_status());
+
+    Ok(())
+}
+
+/// Replays the capture entries against a new pool, returns the report and the miner of the pool.
+fn replay(args: &Args, entries: Vec<Entry>) -> Result<(Report, Miner), String> {
+    let spec = match args.flag_chain {
+        Some(ref path) => {
+            let file = fs::File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            Spec::load(&::std::env::temp_dir(), file)?
+        }
+        None => Spec::new_test(),
+    };
+    if let Some(ref path) = args.flag_tx_queue_admission_policy {
+        // the miner only warns about an invalid policy.
+        AdmissionPolicy::load(path)?;
+    }
+
+    let minimal_gas_price = U256::from(args.flag_min_gas_price);
+    let miner = Miner::new(
+        MinerOptions {
+            reseal_on_own_tx: false,
+            pool_limits: pool::Options {
+                max_count: args.flag_tx_queue_size,
+                max_per_sender: args.flag_tx_queue_per_sender,
+                max_mem_usage: args.flag_tx_queue_mem_limit * 1024 * 1024,
+            },
+            pool_verification_options: pool::verifier::Options {
+                minimal_gas_price,
+                block_gas_limit: U256::max_value(),
+                block_base_fee: None,
+                tx_gas_limit: U256::max_value(),
+                no_early_reject: false,
+                allow_non_eoa_sender: false,
+            },
+            pool_sender_limits: pool::SenderLimits {
+                max_pending_per_sender: args
+                    .flag_tx_queue_pending_per_sender
+                    .unwrap_or_else(usize::max_value),
+                max_future_per_sender: args
+                    .flag_tx_queue_future_per_sender
+                    .unwrap_or_else(usize::max_value),
+                future_lifetime: match args.flag_tx_queue_future_lifetime {
+                    0 => None,
+                    seconds => Some(Duration::from_secs(seconds)),
+                },
+            },
+            admission_policy_path: args.flag_tx_queue_admission_policy.clone(),
+            ..Default::default()
+        },
+        GasPricer::new_fixed(minimal_gas_price),
+        &spec,
+        HashSet::<Address>::new(),
+    );
+    let client = TestBlockChainClient::new_with_spec(spec);
+    fund_senders(&client, &entries);
+    let included = Arc::new(Included::default());
+    miner.set_in_chain_checker(&included);
+
+    let first_timestamp = entries.first().map_or(0, Entry::timestamp);
+    let started = Instant::now();
+    let mut report = Report::default();
+    for entry in entries {
+        if args.flag_speed > 0.0 {
+            let offset = (entry.timestamp().saturating_sub(first_timestamp)) as f64;
+            let due = Duration::from_millis((offset / args.flag_speed) as u64);
+            let elapsed = started.elapsed();
+            if due > elapsed {
+                thread::sleep(due - elapsed);
+            }
+        }
+        match entry {
+            Entry::Batch { transactions, .. } => {
+                import_batch(&miner, &client, transactions, &mut report)
+            }
+            Entry::Block { .. } => produce_block(&miner, &client, &included, &mut report),
+        }
+    }
+    report.elapsed = started.elapsed();
+
+    Ok((report, miner))
+}
+
+/// Reads the capture, grouping transactions passed to the same `import` call.
+fn read_entries(args: &Args) -> Result<Vec<Entry>, String> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut last_batch = None;
+    for record in capture::read_capture(&args.arg_capture)? {
+        let record = match record {
+            CaptureRecord::Transaction(record) => record,
+            CaptureRecord::Block(record) => {
+                last_batch = None;
+                entries.push(Entry::Block {
+                    timestamp: record.timestamp,
+                });
+                continue;
+            }
+        };
+        let transaction = record.transaction()?;
+        if last_batch == Some(record.batch) {
+            if let Some(&mut Entry::Batch {
+                ref mut transactions,
+                ..
+            }) = entries.last_mut()
+            {
+                transactions.push(transaction);
+                continue;
+            }
+        }
+        last_batch = Some(record.batch);
+        entries.push(Entry::Batch {
+            timestamp: record.timestamp,
+            transactions: vec![transaction],
+        });
+    }
+    Ok(entries)
+}
+
+/// Gives every sender a large balance and the lowest nonce of its captured transactions.
+fn fund_senders(client: &TestBlockChainClient, entries: &[Entry]) {
+    let mut nonces: HashMap<Address, U256> = HashMap::new();
+    let transactions = entries.iter().flat_map(|entry| match *entry {
+        Entry::Batch {
+            ref transactions, ..
+        } => transactions.as_slice(),
+        Entry::Block { .. } => &[],
+    });
+    for transaction in transactions {
+        let (sender, nonce) = match *transaction {
+            verifier::Transaction::Unverified(ref tx)
+            | verifier::Transaction::Retracted(ref tx) => {
+                match SignedTransaction::new(tx.clone()) {
+                    Ok(tx) => (tx.sender(), tx.tx().nonce),
+                    // rejected by the pool during the replay.
+                    Err(_) => continue,
+                }
+            }
+            verifier::Transaction::Local(ref tx) => (tx.sender(), tx.tx().nonce),
+        };
+        let lowest = nonces.entry(sender).or_insert(nonce);
+        if nonce < *lowest {
+            *lowest = nonce;
+        }
+    }
+
+    for (sender, nonce) in nonces {
+        client.set_nonce(sender, nonce);
+        client.set_balance(sender, U256::from(SENDER_BALANCE_ETHER) * U256::exp10(18));
+    }
+}
+
+fn import_batch(
+    miner: &Miner,
+    client: &TestBlockChainClient,
+    transactions: Vec<verifier::Transaction>,
+    report: &mut Report,
+) {
+    let mut external = Vec::new();
+    for transaction in transactions {
+        match transaction {
+            verifier::Transaction::Unverified(tx) | verifier::Transaction::Retracted(tx) => {
+                external.push(tx)
+            }
+            verifier::Transaction::Local(tx) => {
+                // keep the order of the capture.
+                import_external(miner, client, &mut external, report);
+                report.note(miner.import_own_transaction(client, tx, true));
+            }
+        }
+    }
+    import_external(miner, client, &mut external, report);
+}
+
+fn import_external(
+    miner: &Miner,
+    client: &TestBlockChainClient,
+    transactions: &mut Vec<transaction::UnverifiedTransaction>,
+    report: &mut Report,
+) {
+    if transactions.is_empty() {
+        return;
+    }
+    for result in miner.import_external_transactions(client, transactions.split_off(0)) {
+        report.note(result);
+    }
+}
+
+/// Includes the ready transactions in a new block, up to the block gas limit, and culls the pool.
+fn produce_block(
+    miner: &Miner,
+    client: &TestBlockChainClient,
+    included: &Included,
+    report: &mut Report,
+) {
+    let gas_limit = client.best_block_header().gas_limit().clone();
+    let mut gas_used = U256::zero();
+    for tx in miner.ready_transactions(client, usize::max_value(), PendingOrdering::Priority) {
+        let tx = tx.signed();
+        if gas_used + tx.tx().gas > gas_limit {
+            break;
+        }
+        gas_used = gas_used + tx.tx().gas;
+        client.set_nonce(tx.sender(), tx.tx().nonce + 1);
+        included.0.write().unwrap().insert(tx.hash());
+        report.included += 1;
+    }
+
+    client.add_block(EachBlockWith::Nothing, |mut header| {
+        header.set_gas_limit(gas_limit);
+        header
+    });
+    let hash = client.chain_info().best_block_hash;
+    miner.chain_new_blocks(client, &[hash], &[], &[hash], &[], false);
+    report.blocks += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    use crypto::publickey::{Generator, KeyPair, Random};
+    use ethcore_miner::pool::capture::TransactionRecorder;
+    use types::transaction::{Action, Transaction, TypedTransaction};
+
+    fn unverified(keypair: &KeyPair, nonce: u64, gas_price: u64) -> verifier::Transaction {
+        let tx = TypedTransaction::Legacy(Transaction {
+            action: Action::Create,
+            value: U256::from(100),
+            data: vec![1, 2, 3],
+            gas: U256::from(100_000),
+            gas_price: U256::from(gas_price),
+            nonce: nonce.into(),
+        })
+        .sign(keypair.secret(), None);
+        verifier::Transaction::Unverified(tx.into())
+    }
+
+    fn args(capture: PathBuf) -> Args {
+        Args {
+            arg_capture: capture,
+            flag_chain: None,
+            flag_speed: 0.0,
+            flag_tx_queue_size: 8192,
+            flag_tx_queue_per_sender: 81,
+            flag_tx_queue_mem_limit: 4,
+            flag_tx_queue_pending_per_sender: Some(2),
+            flag_tx_queue_future_per_sender: None,
+            flag_tx_queue_future_lifetime: 0,
+            flag_tx_queue_admission_policy: None,
+            flag_min_gas_price: 0,
+        }
+    }
+
+    #[test]
+    fn should_replay_captured_trace() {
+        let path = env::temp_dir().join(format!("txpool-replay-{}.jsonl", process::id()));
+        let _ = fs::remove_file(&path);
+        let (first, second) = (Random.generate(), Random.generate());
+        let recorder = TransactionRecorder::create(&path, u64::max_value(), |_| false).unwrap();
+        // the nonce gap is filled, which exceeds the pending limit of the first sender.
+        recorder.record(&[unverified(&first, 0, 10), unverified(&first, 2, 10)]);
+        recorder.record(&[unverified(&first, 1, 10)]);
+        // the second sender replaces its transaction.
+        recorder.record(&[unverified(&second, 0, 10)]);
+        recorder.record(&[unverified(&second, 0, 20)]);
+        recorder.record_block(1);
+        drop(recorder);
+
+        let args = args(path.clone());
+        let entries = read_entries(&args).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(entries.len(), 5);
+
+        let (report, miner) = replay(&args, entries).unwrap();
+        let evictions = miner.eviction_stats();
+
+        assert_eq!(report.imported, 5);
+        assert_eq!(report.accepted, 5);
+        assert!(report.rejected.is_empty());
+        assert_eq!(report.blocks, 1);
+        assert_eq!(report.included, 3);
+        assert_eq!(evictions.replaced, 1);
+        assert_eq!(evictions.pending_limit, 1);
+        assert_eq!(miner.queue_status().status.transaction_count, 0);
+    }
+}
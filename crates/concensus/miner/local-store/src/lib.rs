@@ -14,9 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Manages local node data: pending local transactions, sync security level
-
-use std::{fmt, sync::Arc, time::Duration};
+//! Manages local node data: pending local transactions, journal of the transaction queue,
+//! sync security level
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use ethcore_db::KeyValueDB;
 use io::IoHandler;
@@ -46,6 +52,7 @@ extern crate ethkey;
 extern crate kvdb_memorydb;
 
 const LOCAL_TRANSACTIONS_KEY: &'static [u8] = &*b"LOCAL_TXS";
+const QUEUED_TRANSACTIONS_KEY: &'static [u8] = &*b"QUEUED_TXS";
 
 const UPDATE_TIMER: ::io::TimerToken = 0;
 const UPDATE_TIMEOUT: Duration = Duration::from_secs(15 * 60); // once every 15 minutes.
@@ -128,10 +135,53 @@ impl From<PendingTransaction> for TransactionEntry {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    rlp_bytes: Vec<u8>,
+    /// Seconds since the epoch at which the transaction was journaled first.
+    journaled_at: u64,
+}
+
+impl JournalEntry {
+    fn into_signed(self) -> Option<SignedTransaction> {
+        let tx: UnverifiedTransaction = match TypedTransaction::decode(&self.rlp_bytes) {
+            Err(e) => {
+                warn!(target: "local_store", "Invalid journaled transaction stored: {}", e);
+                return None;
+            }
+            Ok(tx) => tx,
+        };
+
+        let hash = tx.hash();
+        match SignedTransaction::new(tx) {
+            Ok(tx) => Some(tx),
+            Err(_) => {
+                warn!(target: "local_store", "Bad signature on journaled transaction: {}", hash);
+                None
+            }
+        }
+    }
+}
+
+/// Options of the journal of the transactions in the queue which are not of local origin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JournalOptions {
+    /// Maximum number of transactions written to the journal.
+    pub max_transactions: usize,
+    /// Transactions journaled for the first time longer ago than that are dropped.
+    pub max_age: Duration,
+}
+
 /// Something which can provide information about the local node.
 pub trait NodeInfo: Send + Sync {
     /// Get all pending transactions of local origin.
     fn pending_transactions(&self) -> Vec<PendingTransaction>;
+
+    /// Get the transactions in the queue which are not of local origin, the ones of the senders
+    /// to keep first coming first.
+    fn queued_transactions(&self) -> Vec<SignedTransaction> {
+        Vec::new()
+    }
 }
 
 /// Create a new local data store, given a database, a column to write to, and a node.
@@ -145,6 +195,7 @@ pub fn create<T: NodeInfo>(
         db: db,
         col: col,
         node: node,
+        journal: None,
     }
 }
 
@@ -156,9 +207,16 @@ pub struct LocalDataStore<T: NodeInfo> {
     db: Arc<dyn KeyValueDB>,
     col: Option<u32>,
     node: T,
+    journal: Option<JournalOptions>,
 }
 
 impl<T: NodeInfo> LocalDataStore<T> {
+    /// Also journal the transactions in the queue which are not of local origin.
+    pub fn with_journal(mut self, options: JournalOptions) -> Self {
+        self.journal = Some(options);
+        self
+    }
+
     /// Attempt to read pending transactions out of the local store.
     pub fn pending_transactions(&self) -> Result<Vec<PendingTransaction>, Error> {
         if let Some(val) = self
@@ -178,6 +236,33 @@ impl<T: NodeInfo> LocalDataStore<T> {
         }
     }
 
+    /// Attempt to read the journaled transactions which did not expire out of the local store.
+    /// Returns nothing if the journal is disabled.
+    pub fn journaled_transactions(&self) -> Result<Vec<SignedTransaction>, Error> {
+        let options = match self.journal {
+            Some(options) => options,
+            None => return Ok(Vec::new()),
+        };
+        let now = unix_time();
+
+        let journaled = self.read_journal()?.into_iter().filter_map(|entry| {
+            let journaled_at = entry.journaled_at;
+            entry.into_signed().map(|tx| (tx, journaled_at))
+        });
+
+        Ok(nonce_chains(journaled)
+            .into_iter()
+            .flat_map(|chain| {
+                chain
+                    .into_iter()
+                    .take_while(|&(_, journaled_at)| {
+                        now.saturating_sub(journaled_at) <= options.max_age.as_secs()
+                    })
+                    .map(|(tx, _)| tx)
+            })
+            .collect())
+    }
+
     /// Update the entries in the database.
     pub fn update(&self) -> Result<(), Error> {
         trace!(target: "local_store", "Updating local store entries.");
@@ -189,14 +274,82 @@ impl<T: NodeInfo> LocalDataStore<T> {
             .map(Into::into)
             .collect();
 
-        self.write_txs(&local_entries)
+        self.write_txs(&local_entries)?;
+        self.update_journal()
     }
 
     /// Clear data in this column.
     pub fn clear(&self) -> Result<(), Error> {
         trace!(target: "local_store", "Clearing local store entries.");
 
-        self.write_txs(&[])
+        self.write_txs(&[])?;
+        self.write_journal(&[])
+    }
+
+    fn update_journal(&self) -> Result<(), Error> {
+        let options = match self.journal {
+            Some(options) => options,
+            None => return Ok(()),
+        };
+        let now = unix_time();
+
+        // keep the time transactions which are still queued were journaled first at.
+        let journaled_at: HashMap<Vec<u8>, u64> = self
+            .read_journal()?
+            .into_iter()
+            .map(|entry| (entry.rlp_bytes, entry.journaled_at))
+            .collect();
+
+        let queued = self.node.queued_transactions().into_iter().map(|tx| {
+            let rlp_bytes = tx.encode();
+            let journaled_at = journaled_at.get(&rlp_bytes).cloned().unwrap_or(now);
+            (
+                tx,
+                JournalEntry {
+                    rlp_bytes,
+                    journaled_at,
+                },
+            )
+        });
+
+        // a transaction can't be included without the ones of lower nonces of its sender,
+        // so the chains are cut before their first expired transaction and journaled whole.
+        let mut entries: Vec<JournalEntry> = Vec::new();
+        for chain in nonce_chains(queued) {
+            let chain: Vec<JournalEntry> = chain
+                .into_iter()
+                .map(|(_, entry)| entry)
+                .take_while(|entry| {
+                    now.saturating_sub(entry.journaled_at) <= options.max_age.as_secs()
+                })
+                .collect();
+            if entries.len() + chain.len() <= options.max_transactions {
+                entries.extend(chain);
+            }
+        }
+
+        trace!(target: "local_store", "Journaling {} queued transactions.", entries.len());
+        self.write_journal(&entries)
+    }
+
+    fn read_journal(&self) -> Result<Vec<JournalEntry>, Error> {
+        match self
+            .db
+            .get(self.col, QUEUED_TRANSACTIONS_KEY)
+            .map_err(Error::Io)?
+        {
+            Some(val) => ::serde_json::from_slice(&val).map_err(Error::Json),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn write_journal(&self, entries: &[JournalEntry]) -> Result<(), Error> {
+        let mut batch = self.db.transaction();
+
+        let json = ::serde_json::to_vec(entries).map_err(Error::Json)?;
+
+        batch.put_vec(self.col, QUEUED_TRANSACTIONS_KEY, json);
+        self.db.write(batch).map_err(Error::Io)
     }
 
     // helper for writing a vector of transaction entries to disk.
@@ -211,6 +364,34 @@ impl<T: NodeInfo> LocalDataStore<T> {
     }
 }
 
+/// Groups transactions into the chains of their senders ordered by nonce, keeping the order in
+/// which the senders appear first.
+fn nonce_chains<T, I>(txs: I) -> Vec<Vec<(SignedTransaction, T)>>
+where
+    I: IntoIterator<Item = (SignedTransaction, T)>,
+{
+    let mut chains: Vec<Vec<(SignedTransaction, T)>> = Vec::new();
+    let mut senders = HashMap::new();
+    for (tx, value) in txs {
+        let index = *senders.entry(tx.sender()).or_insert_with(|| {
+            chains.push(Vec::new());
+            chains.len() - 1
+        });
+        chains[index].push((tx, value));
+    }
+    for chain in &mut chains {
+        chain.sort_by(|a, b| a.0.tx().nonce.cmp(&b.0.tx().nonce));
+    }
+    chains
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 impl<T: NodeInfo, M: Send + Sync + 'static> IoHandler<M> for LocalDataStore<T> {
     fn initialize(&self, io: &::io::IoContext<M>) {
         if let Err(e) = io.register_timer(UPDATE_TIMER, UPDATE_TIMEOUT) {
@@ -237,11 +418,13 @@ impl<T: NodeInfo> Drop for LocalDataStore<T> {
 
 #[cfg(test)]
 mod tests {
-    use super::NodeInfo;
+    use super::{JournalEntry, JournalOptions, NodeInfo};
 
     use ethkey::Brain;
-    use std::sync::Arc;
-    use types::transaction::{Condition, PendingTransaction, Transaction, TypedTransaction};
+    use std::{sync::Arc, time::Duration};
+    use types::transaction::{
+        Condition, PendingTransaction, SignedTransaction, Transaction, TypedTransaction,
+    };
 
     // we want to test: round-trip of good transactions.
     // failure to roundtrip bad transactions (but that it doesn't panic)
@@ -253,6 +436,33 @@ mod tests {
         }
     }
 
+    struct Queue(Vec<SignedTransaction>);
+    impl NodeInfo for Queue {
+        fn pending_transactions(&self) -> Vec<PendingTransaction> {
+            Vec::new()
+        }
+
+        fn queued_transactions(&self) -> Vec<SignedTransaction> {
+            self.0.clone()
+        }
+    }
+
+    fn signed_transactions(phrase: &str, count: u64) -> Vec<SignedTransaction> {
+        let keypair = Brain::new(phrase.into()).generate();
+        (0..count)
+            .map(|nonce| {
+                let mut tx = TypedTransaction::Legacy(Transaction::default());
+                tx.tx_mut().nonce = nonce.into();
+                tx.sign(keypair.secret(), None)
+            })
+            .collect()
+    }
+
+    const JOURNAL: JournalOptions = JournalOptions {
+        max_transactions: 3,
+        max_age: Duration::from_secs(3600),
+    };
+
     #[test]
     fn twice_empty() {
         let db = Arc::new(ethcore_db::InMemoryWithMetrics::create(0));
@@ -341,4 +551,91 @@ mod tests {
             assert_eq!(loaded, transactions);
         }
     }
+
+    #[test]
+    fn journals_queued_transactions() {
+        let transactions = signed_transactions("abcd", 3);
+        let db = Arc::new(ethcore_db::InMemoryWithMetrics::create(0));
+
+        {
+            // nothing journaled yet, will journal the transactions in nonce order.
+            let mut queued = transactions.clone();
+            queued.reverse();
+            let store = super::create(db.clone(), None, Queue(queued)).with_journal(JOURNAL);
+            assert_eq!(store.journaled_transactions().unwrap(), vec![])
+        }
+        {
+            // journal disabled, nothing is read.
+            let store = super::create(db.clone(), None, Dummy(vec![]));
+            assert_eq!(store.journaled_transactions().unwrap(), vec![])
+        }
+        {
+            let store = super::create(db.clone(), None, Queue(vec![])).with_journal(JOURNAL);
+            assert_eq!(store.journaled_transactions().unwrap(), transactions)
+        }
+        {
+            // queue emptied, nothing left.
+            let store = super::create(db.clone(), None, Queue(vec![])).with_journal(JOURNAL);
+            assert_eq!(store.journaled_transactions().unwrap(), vec![])
+        }
+    }
+
+    #[test]
+    fn journals_whole_nonce_chains() {
+        let first = signed_transactions("abcd", 2);
+        let second = signed_transactions("efgh", 2);
+        let third = signed_transactions("ijkl", 1);
+        let db = Arc::new(ethcore_db::InMemoryWithMetrics::create(0));
+
+        let queued = vec![
+            first[0].clone(),
+            second[0].clone(),
+            first[1].clone(),
+            second[1].clone(),
+            third[0].clone(),
+        ];
+        let store = super::create(db.clone(), None, Queue(queued)).with_journal(JOURNAL);
+        store.update().unwrap();
+
+        // the chain of the second sender does not fit anymore and is dropped whole.
+        assert_eq!(
+            store.journaled_transactions().unwrap(),
+            vec![first[0].clone(), first[1].clone(), third[0].clone()]
+        );
+    }
+
+    #[test]
+    fn skips_expired_journaled_transactions() {
+        let transactions = signed_transactions("abcd", 2);
+        let other = signed_transactions("efgh", 1);
+        let db = Arc::new(ethcore_db::InMemoryWithMetrics::create(0));
+
+        let mut queued = transactions.clone();
+        queued.extend(other.clone());
+        let store = super::create(db.clone(), None, Queue(queued)).with_journal(JOURNAL);
+        store
+            .write_journal(&[
+                JournalEntry {
+                    rlp_bytes: transactions[0].encode(),
+                    journaled_at: 0,
+                },
+                JournalEntry {
+                    rlp_bytes: transactions[1].encode(),
+                    journaled_at: super::unix_time(),
+                },
+                JournalEntry {
+                    rlp_bytes: other[0].encode(),
+                    journaled_at: super::unix_time(),
+                },
+            ])
+            .unwrap();
+        // the transaction following the expired one of its sender is of no use either.
+        assert_eq!(store.journaled_transactions().unwrap(), other);
+
+        // the expired transactions are not journaled again although still queued.
+        store.update().unwrap();
+        let entries = store.read_journal().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].rlp_bytes, other[0].encode());
+    }
 }
@@ -36,6 +36,8 @@ extern crate parking_lot;
 extern crate price_info;
 extern crate rlp;
 extern crate rustc_hex;
+extern crate serde_json;
+extern crate toml;
 extern crate txpool;
 
 #[macro_use]
@@ -0,0 +1,347 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction Admission Policy
+//!
+//! Local rules deciding which transactions are admitted to the pool,
+//! enforced by the `Verifier` on top of the consensus rules.
+//! Transactions from retracted blocks, service transactions and own transactions
+//! of the engine are not subject to the policy.
+//! The policy is loaded from a TOML file:
+//!
+//! ```toml
+//! [senders]
+//! deny = ["0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e"]
+//!
+//! [recipients]
+//! allow = ["0x00000000000000000000000000000000000000ff"]
+//!
+//! [calls]
+//! deny_selectors = ["0xa9059cbb"]
+//! max_data_size = 24576
+//!
+//! [min_priority_fee]
+//! regular = 2000000000
+//! ```
+
+use std::{collections::HashSet, fs, path::Path};
+
+use ethereum_types::{Address, U256};
+use rustc_hex::FromHex;
+use toml;
+use types::transaction::{self, Action, TypedTransaction};
+
+use super::Priority;
+
+/// Minimal priority fees required from the classes of transactions subject to the policy.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MinPriorityFees {
+    /// Minimal priority fee of regular transactions.
+    pub regular: Option<U256>,
+    /// Minimal priority fee of local transactions.
+    pub local: Option<U256>,
+}
+
+impl MinPriorityFees {
+    fn get(&self, priority: Priority) -> Option<U256> {
+        match priority {
+            Priority::Regular => self.regular,
+            Priority::Local => self.local,
+            Priority::Retracted | Priority::Service => None,
+        }
+    }
+}
+
+/// Local admission rules of the transaction pool.
+///
+/// The default policy admits every transaction.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AdmissionPolicy {
+    /// Senders whose transactions are never admitted.
+    pub denied_senders: HashSet<Address>,
+    /// If not empty, only transactions of these senders are admitted.
+    pub allowed_senders: HashSet<Address>,
+    /// Recipients calls to which are never admitted.
+    pub denied_recipients: HashSet<Address>,
+    /// If not empty, only calls to these recipients are admitted.
+    /// Contract creations are not affected.
+    pub allowed_recipients: HashSet<Address>,
+    /// Method selectors (first four bytes of the call data) of calls which are never admitted.
+    pub denied_selectors: HashSet<[u8; 4]>,
+    /// Maximal size of the transaction data in bytes.
+    pub max_data_size: Option<usize>,
+    /// Minimal priority fees per class of transactions.
+    pub min_priority_fee: MinPriorityFees,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct AccessListFile {
+    deny: Vec<Address>,
+    allow: Vec<Address>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CallsFile {
+    deny_selectors: Vec<String>,
+    max_data_size: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MinPriorityFeeFile {
+    regular: Option<u64>,
+    local: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PolicyFile {
+    senders: AccessListFile,
+    recipients: AccessListFile,
+    calls: CallsFile,
+    min_priority_fee: MinPriorityFeeFile,
+}
+
+fn parse_selector(selector: &str) -> Result<[u8; 4], String> {
+    let bytes: Vec<u8> = selector
+        .trim_start_matches("0x")
+        .from_hex()
+        .map_err(|e| format!("Invalid method selector {}: {}", selector, e))?;
+    if bytes.len() != 4 {
+        return Err(format!(
+            "Invalid method selector {}: expected 4 bytes, got {}",
+            selector,
+            bytes.len()
+        ));
+    }
+    let mut result = [0u8; 4];
+    result.copy_from_slice(&bytes);
+    Ok(result)
+}
+
+impl AdmissionPolicy {
+    /// Loads the policy from the TOML file at given path.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            format!(
+                "Unable to read admission policy file {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        Self::from_toml(&content)
+    }
+
+    /// Parses the policy from TOML.
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let file: PolicyFile =
+            toml::from_str(content).map_err(|e| format!("Invalid admission policy: {}", e))?;
+        let denied_selectors: HashSet<[u8; 4]> = file
+            .calls
+            .deny_selectors
+            .iter()
+            .map(|selector| parse_selector(selector))
+            .collect::<Result<_, _>>()?;
+
+        Ok(AdmissionPolicy {
+            denied_senders: file.senders.deny.into_iter().collect(),
+            allowed_senders: file.senders.allow.into_iter().collect(),
+            denied_recipients: file.recipients.deny.into_iter().collect(),
+            allowed_recipients: file.recipients.allow.into_iter().collect(),
+            denied_selectors,
+            max_data_size: file.calls.max_data_size,
+            min_priority_fee: MinPriorityFees {
+                regular: file.min_priority_fee.regular.map(Into::into),
+                local: file.min_priority_fee.local.map(Into::into),
+            },
+        })
+    }
+
+    /// Checks the rules which do not depend on the sender of the transaction.
+    pub fn check_transaction(&self, tx: &TypedTransaction) -> Result<(), transaction::Error> {
+        let data = &tx.tx().data;
+        if let Some(max_data_size) = self.max_data_size {
+            if data.len() > max_data_size {
+                return Err(transaction::Error::TooBig);
+            }
+        }
+
+        if let Action::Call(ref recipient) = tx.tx().action {
+            if self.denied_recipients.contains(recipient)
+                || (!self.allowed_recipients.is_empty()
+                    && !self.allowed_recipients.contains(recipient))
+            {
+                return Err(transaction::Error::RecipientBanned);
+            }
+
+            if data.len() >= 4 && !self.denied_selectors.is_empty() {
+                let mut selector = [0u8; 4];
+                selector.copy_from_slice(&data[..4]);
+                if self.denied_selectors.contains(&selector) {
+                    return Err(transaction::Error::NotAllowed);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the rules depending on the sender and the priority of the transaction.
+    pub fn check_sender(
+        &self,
+        sender: &Address,
+        priority: Priority,
+        max_priority_fee: U256,
+    ) -> Result<(), transaction::Error> {
+        if self.denied_senders.contains(sender)
+            || (!self.allowed_senders.is_empty() && !self.allowed_senders.contains(sender))
+        {
+            return Err(transaction::Error::SenderBanned);
+        }
+
+        if let Some(minimal) = self.min_priority_fee.get(priority) {
+            if max_priority_fee < minimal {
+                return Err(transaction::Error::InsufficientGasPrice {
+                    minimal,
+                    got: max_priority_fee,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::transaction::Transaction;
+
+    fn call(to: Address, data: Vec<u8>) -> TypedTransaction {
+        TypedTransaction::Legacy(Transaction {
+            action: Action::Call(to),
+            value: 0.into(),
+            data,
+            gas: 100_000.into(),
+            gas_price: 1.into(),
+            nonce: 0.into(),
+        })
+    }
+
+    #[test]
+    fn should_parse_policy_file() {
+        let policy = AdmissionPolicy::from_toml(
+            r#"
+            [senders]
+            deny = ["0x0000000000000000000000000000000000000001"]
+
+            [recipients]
+            allow = ["0x0000000000000000000000000000000000000002"]
+
+            [calls]
+            deny_selectors = ["0xa9059cbb"]
+            max_data_size = 1024
+
+            [min_priority_fee]
+            regular = 1000
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            policy,
+            AdmissionPolicy {
+                denied_senders: vec![Address::from_low_u64_be(1)].into_iter().collect(),
+                allowed_recipients: vec![Address::from_low_u64_be(2)].into_iter().collect(),
+                denied_selectors: vec![[0xa9, 0x05, 0x9c, 0xbb]].into_iter().collect(),
+                max_data_size: Some(1024),
+                min_priority_fee: MinPriorityFees {
+                    regular: Some(1000.into()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            AdmissionPolicy::from_toml("").unwrap(),
+            AdmissionPolicy::default()
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_policy_file() {
+        assert!(AdmissionPolicy::from_toml("[calls]\ndeny_selectors = [\"0xa9059c\"]").is_err());
+        assert!(AdmissionPolicy::from_toml("[senders]\nban = []").is_err());
+        assert!(AdmissionPolicy::from_toml("[min_priority_fee]\nservice = 1").is_err());
+    }
+
+    #[test]
+    fn should_check_recipients_and_selectors() {
+        let recipient = Address::from_low_u64_be(2);
+        let policy = AdmissionPolicy {
+            denied_recipients: vec![recipient].into_iter().collect(),
+            denied_selectors: vec![[0xa9, 0x05, 0x9c, 0xbb]].into_iter().collect(),
+            max_data_size: Some(8),
+            ..Default::default()
+        };
+        let other = Address::from_low_u64_be(3);
+
+        assert_eq!(
+            policy.check_transaction(&call(recipient, vec![])),
+            Err(transaction::Error::RecipientBanned)
+        );
+        assert_eq!(
+            policy.check_transaction(&call(other, vec![0xa9, 0x05, 0x9c, 0xbb, 0x01])),
+            Err(transaction::Error::NotAllowed)
+        );
+        assert_eq!(
+            policy.check_transaction(&call(other, vec![0; 9])),
+            Err(transaction::Error::TooBig)
+        );
+        assert_eq!(policy.check_transaction(&call(other, vec![0; 8])), Ok(()));
+    }
+
+    #[test]
+    fn should_check_senders_and_priority_fees() {
+        let sender = Address::from_low_u64_be(1);
+        let policy = AdmissionPolicy {
+            allowed_senders: vec![sender].into_iter().collect(),
+            min_priority_fee: MinPriorityFees {
+                regular: Some(10.into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            policy.check_sender(&Address::from_low_u64_be(2), Priority::Local, 0.into()),
+            Err(transaction::Error::SenderBanned)
+        );
+        assert_eq!(
+            policy.check_sender(&sender, Priority::Regular, 9.into()),
+            Err(transaction::Error::InsufficientGasPrice {
+                minimal: 10.into(),
+                got: 9.into(),
+            })
+        );
+        assert_eq!(
+            policy.check_sender(&sender, Priority::Local, 0.into()),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,450 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction Pool Traffic Capture
+//!
+//! Records the transactions passed to `TransactionQueue::import` and the new best blocks
+//! the queue is culled on, so the traffic can be replayed later against a test pool.
+//! The capture is a file with one JSON record per line:
+//!
+//! ```json
+//! {"timestamp":1634567890123,"batch":7,"origin":"external","rlp":"0xf86b..."}
+//! {"timestamp":1634567890456,"block":1234}
+//! ```
+//!
+//! Records are written by a background thread. Once the file exceeds the size limit
+//! it is moved to `<file>.1`, replacing the previous one, and a new file is started.
+
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+    },
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ethereum_types::H256;
+use parking_lot::Mutex;
+use rustc_hex::{FromHex, ToHex};
+use serde_json;
+use types::{
+    transaction::{PendingTransaction, SignedTransaction, TypedTransaction},
+    BlockNumber,
+};
+
+use super::verifier;
+
+/// Number of batches waiting to be written, further batches are not captured.
+const MAX_PENDING_BATCHES: usize = 1024;
+
+/// Where the captured transaction came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    /// Transaction received from the network.
+    External,
+    /// Transaction from a retracted block.
+    Retracted,
+    /// Transaction submitted locally.
+    Local,
+}
+
+/// Single captured transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionRecord {
+    /// Time of the import in milliseconds since the unix epoch.
+    pub timestamp: u64,
+    /// Sequence number of the `import` call the transaction was passed to.
+    pub batch: u64,
+    /// Origin of the transaction.
+    pub origin: Origin,
+    /// Hex encoded RLP of the signed transaction.
+    pub rlp: String,
+}
+
+impl TransactionRecord {
+    /// Creates a record of the transaction imported at `timestamp` in the given batch.
+    ///
+    /// Conditions of local transactions are not captured.
+    pub fn new(timestamp: u64, batch: u64, transaction: &verifier::Transaction) -> Self {
+        let (origin, rlp) = match *transaction {
+            verifier::Transaction::Unverified(ref tx) => (Origin::External, tx.encode()),
+            verifier::Transaction::Retracted(ref tx) => (Origin::Retracted, tx.encode()),
+            verifier::Transaction::Local(ref tx) => (Origin::Local, tx.encode()),
+        };
+
+        TransactionRecord {
+            timestamp,
+            batch,
+            origin,
+            rlp: format!("0x{}", rlp.to_hex()),
+        }
+    }
+
+    /// Decodes the captured transaction.
+    pub fn transaction(&self) -> Result<verifier::Transaction, String> {
+        let rlp: Vec<u8> = self
+            .rlp
+            .trim_start_matches("0x")
+            .from_hex()
+            .map_err(|e| format!("Invalid transaction hex: {}", e))?;
+        let tx = TypedTransaction::decode(&rlp)
+            .map_err(|e| format!("Invalid transaction rlp: {}", e))?;
+
+        Ok(match self.origin {
+            Origin::External => verifier::Transaction::Unverified(tx),
+            Origin::Retracted => verifier::Transaction::Retracted(tx),
+            Origin::Local => {
+                let signed = SignedTransaction::new(tx)
+                    .map_err(|e| format!("Invalid transaction signature: {}", e))?;
+                verifier::Transaction::Local(PendingTransaction::new(signed, None))
+            }
+        })
+    }
+}
+
+/// New best block the transaction queue was culled on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockRecord {
+    /// Time of the new best block in milliseconds since the unix epoch.
+    pub timestamp: u64,
+    /// Number of the new best block.
+    pub block: BlockNumber,
+}
+
+/// Single record of the capture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CaptureRecord {
+    /// Transaction passed to `import`.
+    Transaction(TransactionRecord),
+    /// New best block.
+    Block(BlockRecord),
+}
+
+/// Captures the imported transactions to a file.
+///
+/// Private transactions are never captured.
+pub struct TransactionRecorder {
+    sender: Mutex<Option<SyncSender<Vec<CaptureRecord>>>>,
+    writer: Option<thread::JoinHandle<()>>,
+    next_batch: AtomicU64,
+    is_private: Box<dyn Fn(&H256) -> bool + Send + Sync>,
+}
+
+impl ::std::fmt::Debug for TransactionRecorder {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        fmt.debug_struct("TransactionRecorder")
+            .field("next_batch", &self.next_batch)
+            .finish()
+    }
+}
+
+impl TransactionRecorder {
+    /// Opens the capture file at given path, new records are appended.
+    ///
+    /// Once the file exceeds `max_size` bytes it is rotated.
+    /// Transactions for which `is_private` returns true are not captured.
+    pub fn create<F>(path: &Path, max_size: u64, is_private: F) -> io::Result<Self>
+    where
+        F: Fn(&H256) -> bool + Send + Sync + 'static,
+    {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        let (sender, receiver) = mpsc::sync_channel(MAX_PENDING_BATCHES);
+        let path = path.to_owned();
+        let writer = thread::Builder::new()
+            .name("TxCapture".into())
+            .spawn(move || write_records(receiver, path, file, size, max_size))?;
+
+        Ok(TransactionRecorder {
+            sender: Mutex::new(Some(sender)),
+            writer: Some(writer),
+            next_batch: AtomicU64::new(0),
+            is_private: Box::new(is_private),
+        })
+    }
+
+    /// Records a batch of transactions passed to `import` with the current time.
+    pub fn record(&self, transactions: &[verifier::Transaction]) {
+        let timestamp = now();
+        let batch = self.next_batch.fetch_add(1, Ordering::Relaxed);
+        let records = transactions
+            .iter()
+            .filter(|tx| !(self.is_private)(&tx.hash()))
+            .map(|tx| CaptureRecord::Transaction(TransactionRecord::new(timestamp, batch, tx)))
+            .collect::<Vec<_>>();
+        if !records.is_empty() {
+            self.send(records);
+        }
+    }
+
+    /// Records a new best block with the current time.
+    pub fn record_block(&self, block: BlockNumber) {
+        self.send(vec![CaptureRecord::Block(BlockRecord {
+            timestamp: now(),
+            block,
+        })]);
+    }
+
+    fn send(&self, records: Vec<CaptureRecord>) {
+        let sender = self.sender.lock();
+        let sender = match *sender {
+            Some(ref sender) => sender,
+            None => return,
+        };
+        match sender.try_send(records) {
+            Ok(()) => {}
+            Err(TrySendError::Full(records)) => warn!(
+                target: "txqueue",
+                "Transaction capture is lagging behind, {} records were dropped",
+                records.len()
+            ),
+            Err(TrySendError::Disconnected(_)) => {
+                warn!(target: "txqueue", "Transaction capture has stopped")
+            }
+        }
+    }
+}
+
+impl Drop for TransactionRecorder {
+    fn drop(&mut self) {
+        // closing the channel stops the writer once all pending records are written.
+        self.sender.lock().take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Time in milliseconds since the unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Path the capture file at `path` is moved to when it is rotated.
+pub fn rotated_path(path: &Path) -> PathBuf {
+    let mut rotated = OsString::from(path.as_os_str());
+    rotated.push(".1");
+    rotated.into()
+}
+
+/// Writes the received records until the recorder is dropped.
+fn write_records(
+    receiver: Receiver<Vec<CaptureRecord>>,
+    path: PathBuf,
+    file: File,
+    mut size: u64,
+    max_size: u64,
+) {
+    let mut writer = BufWriter::new(file);
+    for records in receiver {
+        let result = records
+            .iter()
+            .try_for_each(|record| {
+                let line = serde_json::to_vec(record)?;
+                writer.write_all(&line)?;
+                writer.write_all(b"\n")?;
+                size += line.len() as u64 + 1;
+                Ok::<_, io::Error>(())
+            })
+            .and_then(|_| writer.flush());
+
+        let result = result.and_then(|_| {
+            if size < max_size {
+                return Ok(());
+            }
+            let rotated = rotated_path(&path);
+            let _ = fs::remove_file(&rotated);
+            fs::rename(&path, rotated)?;
+            writer = BufWriter::new(File::create(&path)?);
+            size = 0;
+            Ok(())
+        });
+
+        if let Err(e) = result {
+            warn!(target: "txqueue", "Unable to capture transactions: {}", e);
+        }
+    }
+}
+
+/// Reads all records of the capture file at given path.
+pub fn read_capture(path: &Path) -> Result<Vec<CaptureRecord>, String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Unable to open capture file {}: {}", path.display(), e))?;
+
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+        .map(|(number, line)| {
+            let line = line.map_err(|e| format!("Unable to read capture file: {}", e))?;
+            serde_json::from_str(&line)
+                .map_err(|e| format!("Invalid capture record at line {}: {}", number + 1, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    use crypto::publickey::{Generator, Random};
+    use ethereum_types::U256;
+    use types::transaction::{Action, Transaction};
+
+    fn signed(nonce: u64) -> SignedTransaction {
+        let keypair = Random.generate();
+        TypedTransaction::Legacy(Transaction {
+            action: Action::Create,
+            value: U256::from(100),
+            data: vec![1, 2, 3],
+            gas: U256::from(100_000),
+            gas_price: U256::from(10),
+            nonce: nonce.into(),
+        })
+        .sign(keypair.secret(), None)
+    }
+
+    fn capture_path(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("txpool-{}-{}.jsonl", name, process::id()));
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(rotated_path(&path));
+        path
+    }
+
+    fn transaction_record(record: &CaptureRecord) -> &TransactionRecord {
+        match *record {
+            CaptureRecord::Transaction(ref record) => record,
+            CaptureRecord::Block(_) => panic!("Expected a transaction record."),
+        }
+    }
+
+    #[test]
+    fn should_record_and_read_capture() {
+        let path = capture_path("capture");
+
+        let external = signed(0);
+        let local = signed(1);
+        let recorder = TransactionRecorder::create(&path, u64::max_value(), |_| false).unwrap();
+        recorder.record(&[
+            verifier::Transaction::Unverified(external.clone().into()),
+            verifier::Transaction::Local(local.clone().into()),
+        ]);
+        recorder.record_block(5);
+        recorder.record(&[verifier::Transaction::Unverified(external.clone().into())]);
+        drop(recorder);
+
+        let records = read_capture(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(records.len(), 4);
+        let (first, second) = (
+            transaction_record(&records[0]),
+            transaction_record(&records[1]),
+        );
+        assert_eq!(first.origin, Origin::External);
+        assert_eq!(second.origin, Origin::Local);
+        assert_eq!(first.batch, second.batch);
+        assert_eq!(first.timestamp, second.timestamp);
+        match records[2] {
+            CaptureRecord::Block(ref record) => assert_eq!(record.block, 5),
+            _ => panic!("Expected a block record."),
+        }
+        assert_eq!(transaction_record(&records[3]).batch, first.batch + 1);
+        match first.transaction().unwrap() {
+            verifier::Transaction::Unverified(tx) => assert_eq!(tx.hash(), external.hash()),
+            _ => panic!("Expected an unverified transaction."),
+        }
+        match second.transaction().unwrap() {
+            verifier::Transaction::Local(tx) => {
+                assert_eq!(tx.hash(), local.hash());
+                assert_eq!(tx.sender(), local.sender());
+            }
+            _ => panic!("Expected a local transaction."),
+        }
+    }
+
+    #[test]
+    fn should_not_record_private_transactions() {
+        let path = capture_path("private");
+
+        let public = signed(0);
+        let private = signed(1);
+        let private_hash = private.hash();
+        let recorder =
+            TransactionRecorder::create(&path, u64::max_value(), move |hash| *hash == private_hash)
+                .unwrap();
+        recorder.record(&[
+            verifier::Transaction::Unverified(private.into()),
+            verifier::Transaction::Unverified(public.clone().into()),
+        ]);
+        drop(recorder);
+
+        let records = read_capture(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(records.len(), 1);
+        match transaction_record(&records[0]).transaction().unwrap() {
+            verifier::Transaction::Unverified(tx) => assert_eq!(tx.hash(), public.hash()),
+            _ => panic!("Expected an unverified transaction."),
+        }
+    }
+
+    #[test]
+    fn should_rotate_capture_exceeding_size_limit() {
+        let path = capture_path("rotate");
+
+        let recorder = TransactionRecorder::create(&path, 1, |_| false).unwrap();
+        recorder.record(&[verifier::Transaction::Unverified(signed(0).into())]);
+        recorder.record(&[verifier::Transaction::Unverified(signed(1).into())]);
+        drop(recorder);
+
+        let rotated = read_capture(&rotated_path(&path)).unwrap();
+        let current = read_capture(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(rotated_path(&path)).unwrap();
+
+        // every batch exceeds the limit, only the last rotated one is kept.
+        assert_eq!(rotated.len(), 1);
+        assert_eq!(transaction_record(&rotated[0]).batch, 1);
+        assert!(current.is_empty());
+    }
+
+    #[test]
+    fn should_reject_invalid_records() {
+        let record = TransactionRecord {
+            timestamp: 0,
+            batch: 0,
+            origin: Origin::External,
+            rlp: "0x1234".into(),
+        };
+        assert!(record.transaction().is_err());
+        assert!(serde_json::from_str::<CaptureRecord>(
+            r#"{"timestamp":0,"batch":0,"origin":"unknown","rlp":"0x"}"#
+        )
+        .is_err());
+    }
+}
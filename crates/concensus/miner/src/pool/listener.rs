@@ -14,9 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Notifier for new transaction hashes.
+//! Notifier for new transaction hashes and transaction lifecycle events.
 
-use std::{fmt, sync::Arc};
+use std::{collections::HashSet, fmt, sync::Arc};
 
 use ethereum_types::H256;
 use txpool::{self, VerifiedTransaction};
@@ -121,6 +121,282 @@ impl txpool::Listener<Transaction> for Logger {
     }
 }
 
+/// Lifecycle event of a single transaction in the pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionEvent {
+    /// Transaction has been added to the pool.
+    Added(H256),
+    /// Transaction has been promoted to the pending set.
+    Pending(H256),
+    /// Transaction has been replaced by a transaction with the same sender and nonce.
+    Replaced {
+        /// Hash of the replaced transaction.
+        hash: H256,
+        /// Hash of the replacement.
+        by: H256,
+    },
+    /// Transaction has been dropped from the pool.
+    Dropped {
+        /// Hash of the dropped transaction.
+        hash: H256,
+        /// Hash of the transaction that took its place.
+        by: Option<H256>,
+        /// Why the transaction has been dropped.
+        reason: DropReason,
+    },
+    /// Transaction has been rejected on import.
+    Rejected {
+        /// Hash of the rejected transaction.
+        hash: H256,
+        /// Why the transaction was rejected.
+        reason: String,
+    },
+    /// Transaction has been marked as invalid by the executor.
+    Invalid(H256),
+    /// Transaction has been canceled by the user.
+    Canceled(H256),
+    /// Transaction has been culled from the pool as stale.
+    Culled(H256),
+    /// Transaction has been culled from the pool, because it was included in a block.
+    Mined(H256),
+    /// Sender of the transaction has been penalized, lowering the transaction's priority.
+    Penalized(H256),
+}
+
+impl TransactionEvent {
+    /// Hash of the transaction the event is about.
+    pub fn hash(&self) -> &H256 {
+        match *self {
+            TransactionEvent::Added(ref hash)
+            | TransactionEvent::Pending(ref hash)
+            | TransactionEvent::Replaced { ref hash, .. }
+            | TransactionEvent::Dropped { ref hash, .. }
+            | TransactionEvent::Rejected { ref hash, .. }
+            | TransactionEvent::Invalid(ref hash)
+            | TransactionEvent::Canceled(ref hash)
+            | TransactionEvent::Culled(ref hash)
+            | TransactionEvent::Mined(ref hash)
+            | TransactionEvent::Penalized(ref hash) => hash,
+        }
+    }
+}
+
+/// Why a transaction has been dropped from the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+    /// Pushed out by a better transaction, because the pool limits were reached.
+    Limit,
+    /// Evicted, because its sender reached the pending transactions limit.
+    PendingLimit,
+    /// Evicted, because its sender reached the future transactions limit.
+    FutureLimit,
+    /// Evicted, because it stayed in the future set for longer than allowed.
+    Expired,
+    /// The whole pool has been cleared.
+    Cleared,
+}
+
+impl DropReason {
+    /// Short description of the reason.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            DropReason::Limit => "limit",
+            DropReason::PendingLimit => "pendingLimit",
+            DropReason::FutureLimit => "futureLimit",
+            DropReason::Expired => "expired",
+            DropReason::Cleared => "cleared",
+        }
+    }
+}
+
+type EventsListener = Box<dyn Fn(&[TransactionEvent]) + Send + Sync>;
+
+/// Number of transactions evicted from the pool by reason.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EvictionStats {
+    /// Pushed out by a better transaction, because the pool limits were reached.
+    pub limit: u64,
+    /// Replaced by a transaction with the same sender and nonce.
+    pub replaced: u64,
+    /// Culled as stale (transactions included in a block are not counted).
+    pub stale: u64,
+    /// Future transactions that exceeded their lifetime.
+    pub expired: u64,
+    /// Pending transactions evicted, because their sender reached the pending transactions limit.
+    pub pending_limit: u64,
+    /// Future transactions evicted, because their sender reached the future transactions limit.
+    pub future_limit: u64,
+}
+
+/// Collects lifecycle events of pool transactions and dispatches them to listeners.
+///
+/// Events are only recorded if there is at least one listener registered,
+/// eviction statistics are always collected.
+#[derive(Default)]
+pub struct Events {
+    listeners: Vec<EventsListener>,
+    in_chain: Option<Box<dyn Fn(&H256) -> bool + Send + Sync>>,
+    promoted: HashSet<H256>,
+    pending: Vec<TransactionEvent>,
+    evictions: EvictionStats,
+    drop_reason: Option<DropReason>,
+}
+
+impl fmt::Debug for Events {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Events")
+            .field("listeners", &self.listeners.len())
+            .field("in_chain", &self.in_chain.is_some())
+            .field("promoted", &self.promoted.len())
+            .field("pending", &self.pending)
+            .field("evictions", &self.evictions)
+            .field("drop_reason", &self.drop_reason)
+            .finish()
+    }
+}
+
+impl Events {
+    /// Add new listener to receive lifecycle events.
+    pub fn add(&mut self, f: EventsListener) {
+        self.listeners.push(f)
+    }
+
+    /// Set blockchain checker used to tell mined transactions from the culled ones.
+    pub fn set_in_chain_checker<F>(&mut self, checker: F)
+    where
+        F: Fn(&H256) -> bool + Send + Sync + 'static,
+    {
+        self.in_chain = Some(Box::new(checker));
+    }
+
+    /// Returns true if any of given transactions has not been reported as pending yet.
+    pub fn has_unreported<'a, I: IntoIterator<Item = &'a H256>>(&self, hashes: I) -> bool {
+        !self.listeners.is_empty() && hashes.into_iter().any(|hash| !self.promoted.contains(hash))
+    }
+
+    /// Record that given transactions are part of the pending set.
+    ///
+    /// Only the first promotion of each transaction is reported.
+    pub fn promoted<'a, I: IntoIterator<Item = &'a H256>>(&mut self, hashes: I) {
+        if self.listeners.is_empty() {
+            return;
+        }
+
+        for hash in hashes {
+            if self.promoted.insert(*hash) {
+                self.pending.push(TransactionEvent::Pending(*hash));
+            }
+        }
+    }
+
+    /// Record that given transaction has been rejected on import.
+    pub fn rejected(&mut self, hash: H256, reason: String) {
+        self.push(TransactionEvent::Rejected { hash, reason });
+    }
+
+    /// Record that the sender of given transactions has been penalized.
+    pub fn penalized<'a, I: IntoIterator<Item = &'a H256>>(&mut self, hashes: I) {
+        for hash in hashes {
+            self.push(TransactionEvent::Penalized(*hash));
+        }
+    }
+
+    /// Set the reason reported for transactions evicted without a replacement,
+    /// `None` stands for clearing the pool.
+    pub fn set_drop_reason(&mut self, reason: Option<DropReason>) {
+        self.drop_reason = reason;
+    }
+
+    /// Returns statistics of evicted transactions.
+    pub fn evictions(&self) -> &EvictionStats {
+        &self.evictions
+    }
+
+    /// Dispatch all recorded events to listeners.
+    pub fn notify(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        for l in &self.listeners {
+            (l)(&self.pending);
+        }
+
+        self.pending.clear();
+    }
+
+    fn push(&mut self, event: TransactionEvent) {
+        if self.listeners.is_empty() {
+            return;
+        }
+
+        match event {
+            TransactionEvent::Added(_)
+            | TransactionEvent::Pending(_)
+            | TransactionEvent::Penalized(_) => {}
+            _ => {
+                self.promoted.remove(event.hash());
+            }
+        }
+        self.pending.push(event);
+    }
+}
+
+impl txpool::Listener<Transaction> for Events {
+    fn added(&mut self, tx: &Arc<Transaction>, old: Option<&Arc<Transaction>>) {
+        self.push(TransactionEvent::Added(*tx.hash()));
+
+        if let Some(old) = old {
+            self.evictions.replaced += 1;
+            self.push(TransactionEvent::Replaced {
+                hash: *old.hash(),
+                by: *tx.hash(),
+            });
+        }
+    }
+
+    fn dropped(&mut self, tx: &Arc<Transaction>, by: Option<&Transaction>) {
+        let reason = match by {
+            Some(_) => DropReason::Limit,
+            None => self.drop_reason.unwrap_or(DropReason::Cleared),
+        };
+        match reason {
+            DropReason::Limit => self.evictions.limit += 1,
+            DropReason::PendingLimit => self.evictions.pending_limit += 1,
+            DropReason::FutureLimit => self.evictions.future_limit += 1,
+            DropReason::Expired => self.evictions.expired += 1,
+            DropReason::Cleared => {}
+        }
+        self.push(TransactionEvent::Dropped {
+            hash: *tx.hash(),
+            by: by.map(|new| *new.hash()),
+            reason,
+        });
+    }
+
+    fn invalid(&mut self, tx: &Arc<Transaction>) {
+        self.push(TransactionEvent::Invalid(*tx.hash()));
+    }
+
+    fn canceled(&mut self, tx: &Arc<Transaction>) {
+        self.push(TransactionEvent::Canceled(*tx.hash()));
+    }
+
+    fn culled(&mut self, tx: &Arc<Transaction>) {
+        let is_in_chain = self
+            .in_chain
+            .as_ref()
+            .map(|checker| checker(tx.hash()))
+            .unwrap_or(false);
+        if is_in_chain {
+            self.push(TransactionEvent::Mined(*tx.hash()));
+        } else {
+            self.evictions.stale += 1;
+            self.push(TransactionEvent::Culled(*tx.hash()));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,6 +434,126 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_dispatch_lifecycle_events() {
+        // given
+        let received = Arc::new(Mutex::new(vec![]));
+        let r = received.clone();
+        let listener = Box::new(move |events: &[TransactionEvent]| {
+            r.lock().extend_from_slice(events);
+        });
+        let mut events = Events::default();
+        events.add(listener);
+        events.set_in_chain_checker(|_: &_| true);
+
+        // when
+        let tx = new_tx();
+        let hash = *tx.hash();
+        events.added(&tx, None);
+        events.promoted(&[hash]);
+        events.promoted(&[hash]);
+        events.rejected(H256::from_low_u64_be(1), "Too cheap".into());
+        assert_eq!(*received.lock(), vec![]);
+        events.culled(&tx);
+
+        // then
+        events.notify();
+        assert_eq!(
+            *received.lock(),
+            vec![
+                TransactionEvent::Added(hash),
+                TransactionEvent::Pending(hash),
+                TransactionEvent::Rejected {
+                    hash: H256::from_low_u64_be(1),
+                    reason: "Too cheap".into(),
+                },
+                TransactionEvent::Mined(hash),
+            ]
+        );
+        assert!(events.promoted.is_empty());
+    }
+
+    #[test]
+    fn should_not_record_events_without_listeners() {
+        let mut events = Events::default();
+        let tx = new_tx();
+        events.added(&tx, None);
+        events.promoted(&[*tx.hash()]);
+
+        assert!(events.pending.is_empty());
+        assert!(events.promoted.is_empty());
+    }
+
+    #[test]
+    fn should_count_evictions() {
+        let mut events = Events::default();
+        events.set_in_chain_checker(|_: &_| false);
+        let tx = new_tx();
+
+        events.added(&tx, Some(&tx));
+        events.dropped(&tx, Some(&tx));
+        events.dropped(&tx, None);
+        events.set_drop_reason(Some(DropReason::Expired));
+        events.dropped(&tx, None);
+        events.set_drop_reason(None);
+        events.culled(&tx);
+
+        assert_eq!(
+            *events.evictions(),
+            EvictionStats {
+                limit: 1,
+                replaced: 1,
+                stale: 1,
+                expired: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn should_report_drop_reasons_and_penalties() {
+        // given
+        let received = Arc::new(Mutex::new(vec![]));
+        let r = received.clone();
+        let listener = Box::new(move |events: &[TransactionEvent]| {
+            r.lock().extend_from_slice(events);
+        });
+        let mut events = Events::default();
+        events.add(listener);
+        let tx = new_tx();
+        let hash = *tx.hash();
+
+        // when
+        events.promoted(&[hash]);
+        events.penalized(&[hash]);
+        assert!(!events.has_unreported(&[hash]));
+        events.set_drop_reason(Some(DropReason::PendingLimit));
+        events.dropped(&tx, None);
+        events.set_drop_reason(None);
+        events.dropped(&tx, None);
+        events.notify();
+
+        // then
+        assert_eq!(
+            *received.lock(),
+            vec![
+                TransactionEvent::Pending(hash),
+                TransactionEvent::Penalized(hash),
+                TransactionEvent::Dropped {
+                    hash,
+                    by: None,
+                    reason: DropReason::PendingLimit,
+                },
+                TransactionEvent::Dropped {
+                    hash,
+                    by: None,
+                    reason: DropReason::Cleared,
+                },
+            ]
+        );
+        assert!(events.has_unreported(&[hash]));
+    }
+
     fn new_tx() -> Arc<Transaction> {
         let signed = transaction::TypedTransaction::Legacy(transaction::Transaction {
             action: transaction::Action::Create,
@@ -16,6 +16,8 @@
 
 //! Transaction Pool
 
+use std::time::Duration;
+
 use ethereum_types::{Address, H256, U256};
 use parity_util_mem::MallocSizeOfExt;
 use txpool;
@@ -25,6 +27,8 @@ mod listener;
 mod queue;
 mod ready;
 
+pub mod admission;
+pub mod capture;
 pub mod client;
 pub mod local_transactions;
 pub mod replace;
@@ -36,6 +40,7 @@ pub mod verifier;
 mod tests;
 
 pub use self::{
+    listener::{DropReason, EvictionStats, TransactionEvent},
     queue::{Status as QueueStatus, TransactionQueue},
     txpool::{Options, VerifiedTransaction as PoolVerifiedTransaction},
 };
@@ -47,6 +52,9 @@ pub use self::{
 pub enum PrioritizationStrategy {
     /// Simple gas-price based prioritization.
     GasPriceOnly,
+    /// Gas-price based prioritization with service transactions scored in their own band
+    /// above all regular transactions.
+    ServiceTransactionLane,
 }
 
 /// Transaction ordering when requesting pending set.
@@ -58,6 +66,40 @@ pub enum PendingOrdering {
     Unordered,
 }
 
+/// Per sender limits of pending and future transactions in the pool.
+///
+/// Future transactions are the ones that can't be included in the next block,
+/// because there is a nonce gap between them and the sender's state nonce.
+/// A sender exceeding the limits has its own transactions with the highest nonces
+/// evicted first, local transactions are never limited.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SenderLimits {
+    /// Maximal number of pending transactions from single sender.
+    pub max_pending_per_sender: usize,
+    /// Maximal number of future transactions from single sender.
+    pub max_future_per_sender: usize,
+    /// Maximal time a transaction can stay future in the pool.
+    pub future_lifetime: Option<Duration>,
+}
+
+impl SenderLimits {
+    /// Returns true if none of the per sender counts are limited.
+    pub fn is_unlimited(&self) -> bool {
+        self.max_pending_per_sender == usize::max_value()
+            && self.max_future_per_sender == usize::max_value()
+    }
+}
+
+impl Default for SenderLimits {
+    fn default() -> Self {
+        SenderLimits {
+            max_pending_per_sender: usize::max_value(),
+            max_future_per_sender: usize::max_value(),
+            future_lifetime: None,
+        }
+    }
+}
+
 /// Pending set query settings
 #[derive(Debug, Clone)]
 pub struct PendingSettings {
@@ -21,28 +21,32 @@ use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     fmt,
     sync::{
-        atomic::{self, AtomicUsize},
+        atomic::{self, AtomicBool, AtomicUsize},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
 use self::scoring::ScoringEvent;
 use ethereum_types::{Address, H256, U256};
 use parking_lot::RwLock;
 use txpool::{self, Verifier};
-use types::transaction;
+use types::{transaction, BlockNumber};
 
 use pool::{
-    self, client, listener,
+    self,
+    admission::AdmissionPolicy,
+    capture::TransactionRecorder,
+    client, listener,
     local_transactions::LocalTransactionsList,
     ready, replace, scoring,
     transaction_filter::{match_filter, TransactionFilter},
-    verifier, PendingOrdering, PendingSettings, PrioritizationStrategy,
+    verifier, PendingOrdering, PendingSettings, PrioritizationStrategy, SenderLimits,
 };
 
 type Listener = (
     LocalTransactionsList,
-    (listener::Notifier, listener::Logger),
+    (listener::Notifier, (listener::Logger, listener::Events)),
 );
 type Pool = txpool::Pool<pool::VerifiedTransaction, scoring::NonceAndGasPrice, Listener>;
 
@@ -230,11 +234,18 @@ pub struct TransactionQueue {
     insertion_id: Arc<AtomicUsize>,
     pool: RwLock<Pool>,
     options: RwLock<verifier::Options>,
+    admission_policy: RwLock<Arc<AdmissionPolicy>>,
+    engine_signer: RwLock<Option<Address>>,
+    sender_limits: RwLock<SenderLimits>,
+    /// Times the non-local transactions of each sender became future at, as seen on cull.
+    future_since: RwLock<HashMap<Address, HashMap<H256, Instant>>>,
+    recorder: RwLock<Option<Arc<TransactionRecorder>>>,
     /// Cached pending transactions got *with* priority fee enforcement.
     cached_enforced_pending: RwLock<CachedPending>,
     /// Cached pending transactions got *without* priority fee enforcement.
     cached_non_enforced_pending: RwLock<CachedPending>,
     recently_rejected: RecentlyRejected,
+    has_event_listeners: AtomicBool,
 }
 
 impl TransactionQueue {
@@ -256,26 +267,37 @@ impl TransactionQueue {
                 limits,
             )),
             options: RwLock::new(verification_options),
+            admission_policy: RwLock::new(Arc::new(AdmissionPolicy::default())),
+            engine_signer: RwLock::new(None),
+            sender_limits: RwLock::new(SenderLimits::default()),
+            future_since: RwLock::new(HashMap::new()),
+            recorder: RwLock::new(None),
             cached_enforced_pending: RwLock::new(CachedPending::none()),
             cached_non_enforced_pending: RwLock::new(CachedPending::none()),
             recently_rejected: RecentlyRejected::new(cmp::max(
                 MIN_REJECTED_CACHE_SIZE,
                 max_count / 4,
             )),
+            has_event_listeners: AtomicBool::new(false),
         }
     }
 
     /// If latest block has different base fee than it's parent, then transaction pool scoring needs to be updated.
     pub fn update_scoring(&self, block_base_fee: U256) {
-        let update_needed = match self.pool.read().scoring().block_base_fee {
-            Some(base_fee) => base_fee != block_base_fee,
-            None => true,
+        let (strategy, update_needed) = {
+            let pool = self.pool.read();
+            let scoring = pool.scoring();
+            let update_needed = match scoring.block_base_fee {
+                Some(base_fee) => base_fee != block_base_fee,
+                None => true,
+            };
+            (scoring.strategy, update_needed)
         };
 
         if update_needed {
             self.pool.write().set_scoring(
                 scoring::NonceAndGasPrice {
-                    strategy: PrioritizationStrategy::GasPriceOnly,
+                    strategy,
                     block_base_fee: Some(block_base_fee),
                 },
                 ScoringEvent::BlockBaseFeeChanged,
@@ -293,12 +315,51 @@ impl TransactionQueue {
         *self.options.write() = options;
     }
 
+    /// Replace the local admission policy.
+    ///
+    /// Applies to transactions imported afterwards, queued transactions are not affected.
+    pub fn set_admission_policy(&self, policy: AdmissionPolicy) {
+        *self.admission_policy.write() = Arc::new(policy);
+    }
+
+    /// Sets the address the engine signs its own transactions with.
+    ///
+    /// Own transactions of the engine are not subject to the sender rules of the admission policy.
+    pub fn set_engine_signer(&self, signer: Option<Address>) {
+        *self.engine_signer.write() = signer;
+    }
+
+    /// Update limits of future transactions.
+    pub fn set_sender_limits(&self, limits: SenderLimits) {
+        *self.sender_limits.write() = limits;
+    }
+
+    /// Sets the recorder capturing the transactions passed to `import`.
+    pub fn set_recorder(&self, recorder: Option<TransactionRecorder>) {
+        *self.recorder.write() = recorder.map(Arc::new);
+    }
+
+    /// Captures a new best block, if a recorder is set.
+    pub fn record_block(&self, block: BlockNumber) {
+        if let Some(ref recorder) = *self.recorder.read() {
+            recorder.record_block(block);
+        }
+    }
+
     /// Sets the in-chain transaction checker for pool listener.
     pub fn set_in_chain_checker<F>(&self, f: F)
     where
         F: Fn(&H256) -> bool + Send + Sync + 'static,
     {
-        self.pool.write().listener_mut().0.set_in_chain_checker(f)
+        let f = Arc::new(f);
+        let mut pool = self.pool.write();
+        let checker = f.clone();
+        pool.listener_mut()
+            .0
+            .set_in_chain_checker(move |hash: &H256| checker(hash));
+        ((pool.listener_mut().1).1)
+            .1
+            .set_in_chain_checker(move |hash| f(hash));
     }
 
     // t_nb 10.2
@@ -313,6 +374,10 @@ impl TransactionQueue {
     ) -> Vec<Result<(), transaction::Error>> {
         // Run verification
         trace_time!("pool::verify_and_import");
+        if let Some(ref recorder) = *self.recorder.read() {
+            recorder.record(&transactions);
+        }
+
         let options = self.options.read().clone();
 
         let transaction_to_replace = {
@@ -332,16 +397,20 @@ impl TransactionQueue {
         let verifier = verifier::Verifier::new(
             client.clone(),
             options,
+            self.admission_policy.read().clone(),
+            *self.engine_signer.read(),
             self.insertion_id.clone(),
             transaction_to_replace,
         );
 
+        let nonce_client = client.clone();
         let mut replace = replace::ReplaceByScoreReadinessAndValidity::new(
             self.pool.read().scoring().clone(),
             client,
             self.options.read().block_base_fee,
         );
 
+        let mut rejected = Vec::new();
         let results = transactions
             .into_iter()
             .map(|transaction| {
@@ -353,29 +422,48 @@ impl TransactionQueue {
 
                 if let Some(err) = self.recently_rejected.get(&hash) {
                     trace!(target: "txqueue", "[{:?}] Rejecting recently rejected: {:?}", hash, err);
+                    rejected.push((hash, err.to_string()));
                     return Err(err);
                 }
 
                 let imported = verifier
                     .verify_transaction(transaction)
                     .and_then(|verified| {
-						let status = self.pool.read().light_status();
-						debug!(target: "txqueue", "importing pool status: {:?}", status);
-                        self.pool.write().import(verified, &mut replace).map_err(convert_error)
+                        // read the state before locking the pool.
+                        let state_nonce = self.limited_state_nonce(&nonce_client, &verified);
+                        let mut pool = self.pool.write();
+                        debug!(target: "txqueue", "importing pool status: {:?}", pool.light_status());
+                        if let Some(state_nonce) = state_nonce {
+                            self.check_sender_limits(&pool, state_nonce, &verified)?;
+                        }
+                        let imported = pool.import(verified, &mut replace).map_err(convert_error)?;
+                        if let Some(state_nonce) = state_nonce {
+                            self.enforce_sender_limits(&mut pool, state_nonce, &imported);
+                        }
+                        Ok(())
                     });
 
                 match imported {
                     Ok(_) => Ok(()),
                     Err(err) => {
                         self.recently_rejected.insert(hash, &err);
+                        rejected.push((hash, err.to_string()));
                         Err(err)
                     },
                 }
             })
             .collect::<Vec<_>>();
 
-        // Notify about imported transactions.
-        (self.pool.write().listener_mut().1).0.notify();
+        // Notify about imported and rejected transactions.
+        {
+            let mut pool = self.pool.write();
+            let listener = pool.listener_mut();
+            (listener.1).0.notify();
+            for (hash, reason) in rejected {
+                ((listener.1).1).1.rejected(hash, reason);
+            }
+            ((listener.1).1).1.notify();
+        }
 
         if results.iter().any(|r| r.is_ok()) {
             self.cached_enforced_pending.write().clear();
@@ -471,13 +559,16 @@ impl TransactionQueue {
         // just return the unordered set.
         if let PendingOrdering::Unordered = ordering {
             let ready = Self::ready(client, block_number, current_timestamp, nonce_cap);
-            return self
+            let pending: Vec<_> = self
                 .pool
                 .read()
                 .unordered_pending(ready, includable_boundary)
                 .filter(effective_priority_fee_filter)
                 .take(max_len)
                 .collect();
+            drop(cached_pending);
+            self.notify_promoted(&pending);
+            return pending;
         }
 
         let pending: Vec<_> = self.collect_pending(
@@ -501,6 +592,8 @@ impl TransactionQueue {
             pending: Some(pending.clone()),
             max_len,
         };
+        drop(cached_pending);
+        self.notify_promoted(&pending);
 
         pending
     }
@@ -624,7 +717,9 @@ impl TransactionQueue {
 
         self.recently_rejected.clear();
 
+        let lifetime = self.sender_limits.read().future_lifetime;
         let mut removed = 0;
+        let mut expired = 0;
         let senders: Vec<_> = {
             let pool = self.pool.read();
             let senders = pool.senders().cloned().collect();
@@ -634,8 +729,160 @@ impl TransactionQueue {
             trace_time!("pool::cull::chunk");
             let state_readiness = ready::State::new(client.clone(), stale_id, nonce_cap);
             removed += self.pool.write().cull(Some(chunk), state_readiness);
+            if let Some(lifetime) = lifetime {
+                expired += self.evict_expired(&client, chunk, lifetime);
+            }
+        }
+        {
+            let senders: BTreeSet<_> = senders.into_iter().collect();
+            let mut future_since = self.future_since.write();
+            if lifetime.is_some() {
+                future_since.retain(|sender, _| senders.contains(sender));
+            } else {
+                future_since.clear();
+            }
+        }
+        self.notify_events();
+        debug!(target: "txqueue", "Removed {} stalled and {} expired transactions. {}", removed, expired, self.status());
+    }
+
+    /// Evicts the transactions of given senders that stayed future longer than `lifetime`.
+    ///
+    /// A transaction becomes future when it is first seen with a nonce gap on cull,
+    /// and is timed again if it was pending meanwhile.
+    fn evict_expired<C: client::NonceClient>(
+        &self,
+        client: &C,
+        senders: &[Address],
+        lifetime: Duration,
+    ) -> usize {
+        // read the state before locking the pool.
+        let state_nonces = senders
+            .iter()
+            .map(|sender| (sender, client.account_nonce(sender)))
+            .collect::<Vec<_>>();
+        let now = Instant::now();
+        let mut pool = self.pool.write();
+        let mut future_since = self.future_since.write();
+        let mut evicted = 0;
+        for (sender, state_nonce) in state_nonces {
+            let (_, future) = Self::future_from_sender(&pool, state_nonce, sender);
+            let previous = future_since.remove(sender).unwrap_or_default();
+            let mut since = HashMap::new();
+            for tx in future {
+                if tx.priority.is_local() {
+                    continue;
+                }
+                let became_future = previous.get(&tx.hash).cloned().unwrap_or(now);
+                if now.duration_since(became_future) < lifetime {
+                    since.insert(tx.hash, became_future);
+                    continue;
+                }
+
+                debug!(target: "txqueue", "[{:?}] Evicted, future for longer than {:?}.", tx.hash, lifetime);
+                if evict(&mut pool, &tx.hash, listener::DropReason::Expired) {
+                    evicted += 1;
+                }
+            }
+            if !since.is_empty() {
+                future_since.insert(*sender, since);
+            }
+        }
+        evicted
+    }
+
+    /// Returns the state nonce of the transaction's sender if its transactions are limited.
+    fn limited_state_nonce<C: client::NonceClient>(
+        &self,
+        client: &C,
+        transaction: &pool::VerifiedTransaction,
+    ) -> Option<U256> {
+        if self.sender_limits.read().is_unlimited() || transaction.priority.is_local() {
+            return None;
+        }
+
+        Some(client.account_nonce(&transaction.sender))
+    }
+
+    /// Rejects the transaction if it would be evicted right away by the limits of its sender.
+    fn check_sender_limits(
+        &self,
+        pool: &Pool,
+        state_nonce: U256,
+        transaction: &pool::VerifiedTransaction,
+    ) -> Result<(), transaction::Error> {
+        let limits = self.sender_limits.read();
+        let nonce = transaction.signed().tx().nonce;
+        let mut nonces = pool
+            .transactions_from_sender(&transaction.sender)
+            .iter()
+            .map(|tx| tx.signed().tx().nonce)
+            .collect::<Vec<_>>();
+        if !nonces.contains(&nonce) {
+            nonces.push(nonce);
+            nonces.sort();
         }
-        debug!(target: "txqueue", "Removed {} stalled transactions. {}", removed, self.status());
+
+        let exceeding = exceeding_sender_limits(state_nonce, &nonces, &limits);
+        if exceeding.iter().any(|&(exceeding, _)| exceeding == nonce) {
+            return Err(transaction::Error::LimitReached);
+        }
+
+        Ok(())
+    }
+
+    /// Enforces the pending and future transactions limits of the imported transaction's sender.
+    ///
+    /// Sender's non-local transactions with the highest nonces are evicted first.
+    fn enforce_sender_limits(
+        &self,
+        pool: &mut Pool,
+        state_nonce: U256,
+        imported: &pool::VerifiedTransaction,
+    ) {
+        let limits = self.sender_limits.read();
+        let transactions = pool.transactions_from_sender(&imported.sender);
+        let nonces = transactions
+            .iter()
+            .map(|tx| tx.signed().tx().nonce)
+            .collect::<Vec<_>>();
+        for (nonce, is_future) in exceeding_sender_limits(state_nonce, &nonces, &limits) {
+            let tx = match transactions
+                .iter()
+                .find(|tx| tx.signed().tx().nonce == nonce)
+            {
+                Some(tx) if !tx.priority.is_local() => tx,
+                _ => continue,
+            };
+            debug!(target: "txqueue", "[{:?}] Evicted, sender reached the {} transactions limit.", tx.hash, if is_future { "future" } else { "pending" });
+            let reason = if is_future {
+                listener::DropReason::FutureLimit
+            } else {
+                listener::DropReason::PendingLimit
+            };
+            evict(pool, &tx.hash, reason);
+        }
+    }
+
+    /// Returns the next nonce expected from given sender
+    /// and its future transactions ordered by nonce.
+    fn future_from_sender(
+        pool: &Pool,
+        state_nonce: U256,
+        sender: &Address,
+    ) -> (U256, Vec<Arc<pool::VerifiedTransaction>>) {
+        let mut next_nonce = state_nonce;
+        let mut future = Vec::new();
+        for tx in pool.transactions_from_sender(sender) {
+            let nonce = tx.signed().tx().nonce;
+            if nonce == next_nonce {
+                next_nonce = next_nonce.saturating_add(U256::from(1));
+            } else if nonce > next_nonce {
+                future.push(tx);
+            }
+        }
+
+        (next_nonce, future)
     }
 
     /// Returns next valid nonce for given sender
@@ -687,21 +934,33 @@ impl TransactionQueue {
             self.cached_enforced_pending.write().clear();
             self.cached_non_enforced_pending.write().clear();
         }
+        self.notify_events();
 
         results
     }
 
     /// Clear the entire pool.
     pub fn clear(&self) {
-        self.pool.write().clear();
+        let mut pool = self.pool.write();
+        pool.clear();
+        self.future_since.write().clear();
+        ((pool.listener_mut().1).1).1.notify();
     }
 
     /// Penalize given senders.
     pub fn penalize<'a, T: IntoIterator<Item = &'a Address>>(&self, senders: T) {
+        let has_event_listeners = self.has_event_listeners.load(atomic::Ordering::SeqCst);
         let mut pool = self.pool.write();
         for sender in senders {
             pool.update_scores(sender, ScoringEvent::Penalize);
+            if has_event_listeners {
+                let penalized = pool.transactions_from_sender(sender);
+                ((pool.listener_mut().1).1)
+                    .1
+                    .penalized(penalized.iter().map(|tx| &tx.hash));
+            }
         }
+        ((pool.listener_mut().1).1).1.notify();
     }
 
     /// Returns gas price of currently the worst transaction in the pool.
@@ -774,6 +1033,48 @@ impl TransactionQueue {
         (pool.listener_mut().1).0.add(f);
     }
 
+    /// Add a callback to be notified about lifecycle events of the pool transactions.
+    pub fn add_event_listener(&self, f: Box<dyn Fn(&[pool::TransactionEvent]) + Send + Sync>) {
+        let mut pool = self.pool.write();
+        ((pool.listener_mut().1).1).1.add(f);
+        self.has_event_listeners
+            .store(true, atomic::Ordering::SeqCst);
+    }
+
+    /// Report transactions of the computed pending set to the event listeners.
+    fn notify_promoted(&self, pending: &[Arc<pool::VerifiedTransaction>]) {
+        if !self.has_event_listeners.load(atomic::Ordering::SeqCst) {
+            return;
+        }
+        // most of the time the pending set has been reported already,
+        // so check that first without blocking the importers.
+        let hashes = || pending.iter().map(|tx| &tx.hash);
+        if !((self.pool.read().listener().1).1)
+            .1
+            .has_unreported(hashes())
+        {
+            return;
+        }
+
+        let mut pool = self.pool.write();
+        let events = &mut ((pool.listener_mut().1).1).1;
+        events.promoted(hashes());
+        events.notify();
+    }
+
+    /// Dispatch recorded lifecycle events to the event listeners.
+    fn notify_events(&self) {
+        if !self.has_event_listeners.load(atomic::Ordering::SeqCst) {
+            return;
+        }
+        ((self.pool.write().listener_mut().1).1).1.notify();
+    }
+
+    /// Returns statistics of transactions evicted from the pool.
+    pub fn eviction_stats(&self) -> pool::EvictionStats {
+        ((self.pool.read().listener().1).1).1.evictions().clone()
+    }
+
     /// Check if pending set is cached.
     #[cfg(test)]
     pub fn is_enforced_pending_cached(&self) -> bool {
@@ -786,6 +1087,46 @@ impl TransactionQueue {
     }
 }
 
+/// Evicts given transaction from the pool, reporting it as dropped for given reason.
+fn evict(pool: &mut Pool, hash: &H256, reason: listener::DropReason) -> bool {
+    ((pool.listener_mut().1).1).1.set_drop_reason(Some(reason));
+    let evicted = pool.evict(hash).is_some();
+    ((pool.listener_mut().1).1).1.set_drop_reason(None);
+    evicted
+}
+
+/// Returns the nonces exceeding the sender limits (and whether they are future ones),
+/// given the sender's state nonce and the sorted nonces of its transactions in the pool.
+///
+/// The highest nonces of both the pending and the future transactions exceed the limits first.
+fn exceeding_sender_limits(
+    state_nonce: U256,
+    nonces: &[U256],
+    limits: &SenderLimits,
+) -> Vec<(U256, bool)> {
+    let mut next_nonce = state_nonce;
+    let mut pending = Vec::new();
+    let mut future = Vec::new();
+    for &nonce in nonces {
+        if nonce == next_nonce {
+            next_nonce = next_nonce.saturating_add(U256::from(1));
+            pending.push(nonce);
+        } else if nonce > next_nonce {
+            future.push(nonce);
+        }
+    }
+
+    let pending = pending
+        .into_iter()
+        .skip(limits.max_pending_per_sender)
+        .map(|nonce| (nonce, false));
+    let future = future
+        .into_iter()
+        .skip(limits.max_future_per_sender)
+        .map(|nonce| (nonce, true));
+    pending.chain(future).collect()
+}
+
 fn convert_error<H: fmt::Debug + fmt::LowerHex>(err: txpool::Error<H>) -> transaction::Error {
     use self::txpool::Error;
 
@@ -802,7 +1143,10 @@ fn convert_error<H: fmt::Debug + fmt::LowerHex>(err: txpool::Error<H>) -> transa
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pool::tests::client::TestClient;
+    use pool::tests::{
+        client::TestClient,
+        tx::{Tx, TxExt},
+    };
 
     #[test]
     fn should_get_pending_transactions() {
@@ -821,4 +1165,43 @@ mod tests {
             assert!(tx.signed().tx().nonce > 0.into());
         }
     }
+
+    #[test]
+    fn should_measure_future_lifetime_from_becoming_future() {
+        let txq = TransactionQueue::new(
+            txpool::Options::default(),
+            verifier::Options::default(),
+            PrioritizationStrategy::GasPriceOnly,
+        );
+        txq.set_sender_limits(SenderLimits {
+            future_lifetime: Some(Duration::from_secs(1)),
+            ..Default::default()
+        });
+        let (tx1, _, tx3) = Tx::default().signed_triple();
+        let sender = tx1.sender();
+        let res = txq.import(TestClient::new(), vec![tx1, tx3].unverified());
+        assert_eq!(res, vec![Ok(()), Ok(())]);
+
+        // the future transaction starts its lifetime on the first cull.
+        txq.cull(TestClient::new());
+        assert_eq!(txq.status().status.transaction_count, 2);
+        assert_eq!(txq.future_since.read()[&sender].len(), 1);
+
+        // when
+        for since in txq
+            .future_since
+            .write()
+            .get_mut(&sender)
+            .unwrap()
+            .values_mut()
+        {
+            *since = Instant::now() - Duration::from_secs(2);
+        }
+        txq.cull(TestClient::new());
+
+        // then
+        assert_eq!(txq.status().status.transaction_count, 1);
+        assert_eq!(txq.eviction_stats().expired, 1);
+        assert!(txq.future_since.read().is_empty());
+    }
 }
@@ -26,6 +26,10 @@
 //! Transactions between senders are prioritized using `gas price`. Higher `gas price`
 //! yields more profits for miners. Additionally we prioritize transactions that originate
 //! from our local node (own transactions).
+//!
+//! With the `ServiceTransactionLane` strategy, service transactions (usually zero gas price,
+//! from certified senders) have their own score band above regular transactions, so they can
+//! always enter a full pool and are never evicted in favour of regular transactions.
 
 use std::cmp;
 
@@ -37,9 +41,15 @@ use txpool::{self, scoring};
 /// `new_gas_price > old_gas_price + old_gas_price >> SHIFT`
 const GAS_PRICE_BUMP_SHIFT: usize = 3; // 2 = 25%, 3 = 12.5%, 4 = 6.25%
 
+/// Lowest score of a service transaction (2^64, above any realistic gas price).
+const SERVICE_TRANSACTION_SCORE_BAND: U256 = U256([0, 1, 0, 0]);
+
 /// Calculate minimal gas price requirement.
+///
+/// A transaction with the same sender and nonce replaces the old one in the pool
+/// only if its effective gas price is at least the bumped gas price of the old one.
 #[inline]
-fn bump_gas_price(old_gp: U256) -> U256 {
+pub fn bump_gas_price(old_gp: U256) -> U256 {
     old_gp.saturating_add(old_gp >> GAS_PRICE_BUMP_SHIFT)
 }
 /// List of events that trigger updating of scores
@@ -81,6 +91,30 @@ impl NonceAndGasPrice {
 
         old.effective_gas_price(self.block_base_fee) > new.effective_gas_price(self.block_base_fee)
     }
+
+    /// Computes the score of a transaction based on its gas price and priority.
+    fn score<P: ScoredTransaction>(&self, tx: &P) -> U256 {
+        let gas_price = tx.effective_gas_price(self.block_base_fee);
+        let boost = match tx.priority() {
+            super::Priority::Local => 15,
+            // service transactions are not competing on gas price.
+            super::Priority::Service
+                if self.strategy == PrioritizationStrategy::ServiceTransactionLane =>
+            {
+                return SERVICE_TRANSACTION_SCORE_BAND.saturating_add(gas_price)
+            }
+            super::Priority::Service => 14,
+            super::Priority::Retracted => 10,
+            super::Priority::Regular => 0,
+        };
+
+        //boost local and retracted only if they are currently includable (base fee criteria)
+        if self.block_base_fee.is_none() || gas_price >= self.block_base_fee.unwrap() {
+            gas_price << boost
+        } else {
+            gas_price
+        }
+    }
 }
 
 impl<P> txpool::Scoring<P> for NonceAndGasPrice
@@ -125,18 +159,7 @@ where
                 assert!(i < txs.len());
                 assert!(i < scores.len());
 
-                scores[i] = txs[i].effective_gas_price(self.block_base_fee);
-                let boost = match txs[i].priority() {
-                    super::Priority::Local => 15,
-                    super::Priority::Service => 14,
-                    super::Priority::Retracted => 10,
-                    super::Priority::Regular => 0,
-                };
-
-                //boost local and retracted only if they are currently includable (base fee criteria)
-                if self.block_base_fee.is_none() || scores[i] >= self.block_base_fee.unwrap() {
-                    scores[i] = scores[i] << boost;
-                }
+                scores[i] = self.score(&*txs[i].transaction);
             }
             // We are only sending an event in case of penalization.
             // So just lower the priority of all non-local transactions.
@@ -152,20 +175,7 @@ where
                     }
                     ScoringEvent::BlockBaseFeeChanged => {
                         for i in 0..txs.len() {
-                            scores[i] = txs[i].transaction.effective_gas_price(self.block_base_fee);
-                            let boost = match txs[i].priority() {
-                                super::Priority::Local => 15,
-                                super::Priority::Service => 14,
-                                super::Priority::Retracted => 10,
-                                super::Priority::Regular => 0,
-                            };
-
-                            //boost local and retracted only if they are currently includable (base fee criteria)
-                            if self.block_base_fee.is_none()
-                                || scores[i] >= self.block_base_fee.unwrap()
-                            {
-                                scores[i] = scores[i] << boost;
-                            }
+                            scores[i] = self.score(&*txs[i].transaction);
                         }
                     }
                 }
@@ -249,4 +259,60 @@ mod tests {
         );
         assert_eq!(scores, vec![32768.into(), 128.into(), 0.into()]);
     }
+
+    #[test]
+    fn should_score_zero_gas_price_service_transactions_above_regular_ones() {
+        // given
+        let scoring = NonceAndGasPrice {
+            strategy: PrioritizationStrategy::ServiceTransactionLane,
+            block_base_fee: None,
+        };
+        let service = {
+            let mut verified = Tx::gas_price(0).signed().verified();
+            verified.priority = ::pool::Priority::Service;
+            verified
+        };
+        let regular = Tx::gas_price(1_000_000_000_000).signed().verified();
+        let transactions = vec![service, regular]
+            .into_iter()
+            .map(|verified| txpool::Transaction {
+                insertion_id: 0,
+                transaction: Arc::new(verified),
+            })
+            .collect::<Vec<_>>();
+        let mut scores = vec![U256::zero(), U256::zero()];
+
+        // when
+        scoring.update_scores(&transactions, &mut *scores, scoring::Change::InsertedAt(0));
+        scoring.update_scores(&transactions, &mut *scores, scoring::Change::InsertedAt(1));
+
+        // then
+        assert_eq!(scores[0], SERVICE_TRANSACTION_SCORE_BAND);
+        assert!(scores[0] > scores[1]);
+    }
+
+    #[test]
+    fn should_boost_service_transactions_without_lane() {
+        // given
+        let scoring = NonceAndGasPrice {
+            strategy: PrioritizationStrategy::GasPriceOnly,
+            block_base_fee: None,
+        };
+        let service = {
+            let mut verified = Tx::gas_price(1).signed().verified();
+            verified.priority = ::pool::Priority::Service;
+            verified
+        };
+        let transactions = vec![txpool::Transaction {
+            insertion_id: 0,
+            transaction: Arc::new(service),
+        }];
+        let mut scores = vec![U256::zero()];
+
+        // when
+        scoring.update_scores(&transactions, &mut *scores, scoring::Change::InsertedAt(0));
+
+        // then
+        assert_eq!(scores[0], U256::from(1 << 14));
+    }
 }
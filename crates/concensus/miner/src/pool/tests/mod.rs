@@ -14,14 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethereum_types::U256;
+use std::time::Duration;
+
+use crypto::publickey::{Generator, Random};
+use ethereum_types::{Address, U256};
 use hash::KECCAK_EMPTY;
 use txpool;
 use types::transaction::{self, PendingTransaction};
 
 use pool::{
-    transaction_filter::TransactionFilter, verifier, PendingOrdering, PendingSettings,
-    PrioritizationStrategy, TransactionQueue,
+    admission::AdmissionPolicy, transaction_filter::TransactionFilter, verifier, PendingOrdering,
+    PendingSettings, PrioritizationStrategy, SenderLimits, TransactionQueue,
 };
 
 pub mod client;
@@ -1523,3 +1526,240 @@ fn should_return_correct_cached_pending_depending_on_fees_enforcement(enforce_pr
     // then
     assert_eq!(all, limited);
 }
+
+#[test]
+fn should_evict_furthest_future_transaction_when_sender_limit_is_reached() {
+    // given
+    let txq = new_queue();
+    txq.set_sender_limits(SenderLimits {
+        max_future_per_sender: 1,
+        ..Default::default()
+    });
+    let (tx1, tx2) = Tx {
+        nonce: 124,
+        ..Default::default()
+    }
+    .signed_pair();
+    let (hash1, hash2) = (tx1.hash(), tx2.hash());
+
+    // when
+    let res = txq.import(TestClient::new(), vec![tx2.clone(), tx1].unverified());
+    assert_eq!(res, vec![Ok(()), Ok(())]);
+    let res = txq.import(TestClient::new(), vec![tx2].unverified());
+
+    // then
+    assert_eq!(res, vec![Err(transaction::Error::LimitReached)]);
+    assert_eq!(txq.status().status.transaction_count, 1);
+    assert!(txq.find(&hash1).is_some());
+    assert!(txq.find(&hash2).is_none());
+    assert_eq!(txq.eviction_stats().future_limit, 1);
+
+    // local transactions are not limited
+    let local = Tx {
+        nonce: 124,
+        ..Default::default()
+    }
+    .signed_pair();
+    let res = txq.import(TestClient::new(), local.local().into_vec());
+    assert_eq!(res, vec![Ok(()), Ok(())]);
+    assert_eq!(txq.status().status.transaction_count, 3);
+}
+
+#[test]
+fn should_reject_transactions_over_the_pending_sender_limit() {
+    // given
+    let txq = new_queue();
+    txq.set_sender_limits(SenderLimits {
+        max_pending_per_sender: 2,
+        ..Default::default()
+    });
+    let (tx1, tx2, tx3) = Tx::default().signed_triple();
+    let (hash1, hash2, hash3) = (tx1.hash(), tx2.hash(), tx3.hash());
+
+    // when
+    let res = txq.import(TestClient::new(), vec![tx1, tx2, tx3].unverified());
+
+    // then
+    assert_eq!(
+        res,
+        vec![Ok(()), Ok(()), Err(transaction::Error::LimitReached)]
+    );
+    assert!(txq.find(&hash1).is_some());
+    assert!(txq.find(&hash2).is_some());
+    assert!(txq.find(&hash3).is_none());
+    assert_eq!(txq.eviction_stats().pending_limit, 0);
+}
+
+#[test]
+fn should_evict_highest_pending_transaction_when_gap_is_filled() {
+    // given
+    let txq = new_queue();
+    txq.set_sender_limits(SenderLimits {
+        max_pending_per_sender: 2,
+        ..Default::default()
+    });
+    let (tx1, tx2, tx3) = Tx::default().signed_triple();
+    let (hash1, hash2, hash3) = (tx1.hash(), tx2.hash(), tx3.hash());
+    let res = txq.import(TestClient::new(), vec![tx1, tx3].unverified());
+    assert_eq!(res, vec![Ok(()), Ok(())]);
+
+    // when
+    let res = txq.import(TestClient::new(), vec![tx2].unverified());
+
+    // then
+    assert_eq!(res, vec![Ok(())]);
+    assert!(txq.find(&hash1).is_some());
+    assert!(txq.find(&hash2).is_some());
+    assert!(txq.find(&hash3).is_none());
+    assert_eq!(txq.eviction_stats().pending_limit, 1);
+}
+
+#[test]
+fn should_not_evict_future_transactions_if_import_fails() {
+    // given
+    let txq = TransactionQueue::new(
+        txpool::Options {
+            max_count: 2,
+            max_per_sender: 3,
+            max_mem_usage: TEST_QUEUE_MAX_MEM,
+        },
+        verifier::Options {
+            minimal_gas_price: 1.into(),
+            block_gas_limit: 1_000_000.into(),
+            tx_gas_limit: 1_000_000.into(),
+            no_early_reject: false,
+            block_base_fee: None,
+            allow_non_eoa_sender: false,
+        },
+        PrioritizationStrategy::GasPriceOnly,
+    );
+    txq.set_sender_limits(SenderLimits {
+        max_future_per_sender: 1,
+        ..Default::default()
+    });
+    let keypair = Random.generate();
+    let future = Tx {
+        nonce: 125,
+        gas_price: 10,
+        ..Default::default()
+    }
+    .unsigned()
+    .sign(keypair.secret(), None);
+    let cheap_future = Tx {
+        nonce: 124,
+        gas_price: 1,
+        ..Default::default()
+    }
+    .unsigned()
+    .sign(keypair.secret(), None);
+    let hash = future.hash();
+    let res = txq.import(
+        TestClient::new(),
+        vec![future, Tx::gas_price(5).signed()].unverified(),
+    );
+    assert_eq!(res, vec![Ok(()), Ok(())]);
+
+    // when
+    let res = txq.import(TestClient::new(), vec![cheap_future].unverified());
+
+    // then
+    assert_eq!(res.len(), 1);
+    assert!(res[0].is_err());
+    assert!(txq.find(&hash).is_some());
+    assert_eq!(txq.status().status.transaction_count, 2);
+    assert_eq!(txq.eviction_stats().future_limit, 0);
+}
+
+#[test]
+fn should_evict_expired_future_transactions_on_cull() {
+    // given
+    let txq = new_queue();
+    txq.set_sender_limits(SenderLimits {
+        future_lifetime: Some(Duration::from_secs(0)),
+        ..Default::default()
+    });
+    let (tx1, _, tx3) = Tx::default().signed_triple();
+    let (local1, _, local3) = Tx::default().signed_triple();
+    let res = txq.import(TestClient::new(), vec![tx1, tx3].unverified());
+    assert_eq!(res, vec![Ok(()), Ok(())]);
+    let res = txq.import(TestClient::new(), vec![local1, local3].local());
+    assert_eq!(res, vec![Ok(()), Ok(())]);
+
+    // when
+    txq.cull(TestClient::new());
+
+    // then
+    assert_eq!(txq.status().status.transaction_count, 3);
+    assert_eq!(txq.eviction_stats().expired, 1);
+    assert_eq!(
+        txq.pending(TestClient::new(), PendingSettings::all_prioritized(0, 0))
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn should_reject_transactions_denied_by_admission_policy() {
+    // given
+    let txq = new_queue();
+    let (tx1, tx2) = Tx::default().signed_pair();
+    let tx3 = Tx::default().signed();
+    txq.set_admission_policy(AdmissionPolicy {
+        denied_senders: vec![tx1.sender()].into_iter().collect(),
+        ..Default::default()
+    });
+
+    // when
+    let res = txq.import(TestClient::new(), vec![tx1, tx3.clone()].unverified());
+
+    // then
+    assert_eq!(res, vec![Err(transaction::Error::SenderBanned), Ok(())]);
+
+    // when
+    txq.set_admission_policy(AdmissionPolicy {
+        max_data_size: Some(4),
+        ..Default::default()
+    });
+    let res = txq.import(TestClient::new(), vec![tx2].local());
+
+    // then
+    assert_eq!(res, vec![Err(transaction::Error::TooBig)]);
+    assert_eq!(txq.status().status.transaction_count, 1);
+}
+
+#[test]
+fn should_not_apply_admission_policy_to_exempt_transactions() {
+    // given
+    let txq = new_queue();
+    let tx1 = Tx::default().signed();
+    let tx2 = Tx::default().signed();
+    let tx3 = Tx::gas_price(0).signed();
+    let tx4 = Tx::default().signed();
+    let tx5 = Tx::default().signed();
+    txq.set_admission_policy(AdmissionPolicy {
+        allowed_senders: vec![Address::zero()].into_iter().collect(),
+        max_data_size: Some(4),
+        ..Default::default()
+    });
+    txq.set_engine_signer(Some(tx1.sender()));
+
+    // when
+    let res = txq.import(TestClient::new(), vec![tx1.local(), tx2.retracted()]);
+    assert_eq!(res, vec![Ok(()), Ok(())]);
+    let res = txq.import(
+        TestClient::new().with_service_transaction(),
+        vec![tx3.unverified()],
+    );
+    assert_eq!(res, vec![Ok(())]);
+    let res = txq.import(TestClient::new(), vec![tx4.local(), tx5.unverified()]);
+
+    // then
+    assert_eq!(
+        res,
+        vec![
+            Err(transaction::Error::TooBig),
+            Err(transaction::Error::TooBig),
+        ]
+    );
+    assert_eq!(txq.status().status.transaction_count, 3);
+}
@@ -30,12 +30,13 @@ use std::{
     },
 };
 
-use ethereum_types::{H256, U256};
+use ethereum_types::{Address, H256, U256};
 use hash::KECCAK_EMPTY;
 use txpool;
 use types::transaction;
 
 use super::{
+    admission::AdmissionPolicy,
     client::{Client, TransactionType},
     VerifiedTransaction,
 };
@@ -184,6 +185,8 @@ impl Transaction {
 pub struct Verifier<C, S, V> {
     client: C,
     options: Options,
+    admission_policy: Arc<AdmissionPolicy>,
+    engine_signer: Option<Address>,
     id: Arc<AtomicUsize>,
     transaction_to_replace: Option<(S, Arc<V>)>,
 }
@@ -193,12 +196,16 @@ impl<C, S, V> Verifier<C, S, V> {
     pub fn new(
         client: C,
         options: Options,
+        admission_policy: Arc<AdmissionPolicy>,
+        engine_signer: Option<Address>,
         id: Arc<AtomicUsize>,
         transaction_to_replace: Option<(S, Arc<V>)>,
     ) -> Self {
         Verifier {
             client,
             options,
+            admission_policy,
+            engine_signer,
             id,
             transaction_to_replace,
         }
@@ -433,6 +440,26 @@ impl<C: Client> txpool::Verifier<Transaction>
             hash,
             priority
         );
+
+        // Transactions which were already included in a block, service transactions
+        // and own transactions of the engine (e.g. key generation) bypass the admission policy.
+        let is_exempt = is_retracted
+            || priority == super::Priority::Service
+            || (is_own && self.engine_signer == Some(sender));
+        if !is_exempt {
+            let admitted = self
+                .admission_policy
+                .check_transaction(transaction.as_unsigned())
+                .and_then(|_| {
+                    self.admission_policy
+                        .check_sender(&sender, priority, max_priority_fee)
+                });
+            if let Err(err) = admitted {
+                debug!(target: "txqueue", "[{:?}] Rejected tx by admission policy: {:?}", hash, err);
+                bail!(err)
+            }
+        }
+
         Ok(VerifiedTransaction {
             transaction,
             priority,
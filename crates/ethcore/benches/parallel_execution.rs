@@ -0,0 +1,161 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Compares sequential and optimistic parallel execution of the transactions of a block.
+
+#[macro_use]
+extern crate criterion;
+
+extern crate common_types as types;
+extern crate ethcore;
+extern crate ethereum_types;
+extern crate parity_crypto as crypto;
+extern crate rustc_hex;
+extern crate vm;
+
+use criterion::Criterion;
+use crypto::publickey::{Generator, KeyPair, Random};
+use ethcore::{
+    ethereum::new_istanbul_test_machine,
+    state::{CleanupMode, State},
+    state_db::StateDB,
+    test_helpers::get_temp_state,
+};
+use ethereum_types::{Address, U256};
+use rustc_hex::FromHex;
+use types::transaction::{Action, SignedTransaction, Transaction, TypedTransaction};
+use vm::EnvInfo;
+
+const TRANSACTIONS: usize = 200;
+
+criterion_group!(
+    parallel_execution,
+    independent_transfers,
+    independent_calls,
+    shared_counter
+);
+criterion_main!(parallel_execution);
+
+struct Block {
+    state: State<StateDB>,
+    env_info: EnvInfo,
+    transactions: Vec<SignedTransaction>,
+}
+
+impl Block {
+    /// Block of transactions sent by different accounts, `action` returns the
+    /// action of the transaction with the given index.
+    fn new<F: Fn(usize) -> Action>(action: F) -> Self {
+        let senders: Vec<KeyPair> = (0..TRANSACTIONS).map(|_| Random.generate()).collect();
+        let mut state = get_temp_state();
+        for (i, sender) in senders.iter().enumerate() {
+            state
+                .add_balance(
+                    &sender.address(),
+                    &U256::from(1_000_000_000u64),
+                    CleanupMode::NoEmpty,
+                )
+                .unwrap();
+            // increments storage slot 0 and stores the caller in slot 1.
+            let contract = Address::from_low_u64_be(0x1000 + i as u64);
+            state.new_contract(&contract, 0.into(), 0.into()).unwrap();
+            state
+                .init_code(&contract, "60005460010160005533600155".from_hex().unwrap())
+                .unwrap();
+        }
+        state.commit().unwrap();
+
+        let transactions = senders
+            .iter()
+            .enumerate()
+            .map(|(i, sender)| {
+                TypedTransaction::Legacy(Transaction {
+                    nonce: 0.into(),
+                    gas_price: 1.into(),
+                    gas: 100_000.into(),
+                    action: action(i),
+                    value: 1_000.into(),
+                    data: vec![],
+                })
+                .sign(sender.secret(), None)
+            })
+            .collect();
+
+        let mut env_info = EnvInfo::default();
+        env_info.gas_limit = U256::from(100_000 * TRANSACTIONS);
+        env_info.author = Address::from_low_u64_be(0xc0);
+
+        Block {
+            state,
+            env_info,
+            transactions,
+        }
+    }
+
+    fn bench(self, name: &str, c: &mut Criterion) {
+        let machine = new_istanbul_test_machine();
+        let Block {
+            state,
+            env_info,
+            transactions,
+        } = self;
+
+        let sequential_state = state.clone();
+        let sequential_machine = new_istanbul_test_machine();
+        let sequential_env_info = env_info.clone();
+        let sequential_transactions = transactions.clone();
+        c.bench_function(&format!("{}_sequential", name), move |b| {
+            b.iter_with_setup(
+                || sequential_state.clone(),
+                |mut state| {
+                    let mut env_info = sequential_env_info.clone();
+                    for t in &sequential_transactions {
+                        let outcome = state
+                            .apply(&env_info, &sequential_machine, t, false)
+                            .unwrap();
+                        env_info.gas_used = outcome.receipt.gas_used;
+                    }
+                    state
+                },
+            )
+        });
+
+        c.bench_function(&format!("{}_parallel", name), move |b| {
+            b.iter_with_setup(
+                || state.clone(),
+                |mut state| {
+                    let results = state.apply_parallel(&env_info, &machine, &transactions, false);
+                    assert_eq!(results.len(), transactions.len());
+                    state
+                },
+            )
+        });
+    }
+}
+
+fn independent_transfers(c: &mut Criterion) {
+    Block::new(|i| Action::Call(Address::from_low_u64_be(0x10_0000 + i as u64)))
+        .bench("independent_transfers", c);
+}
+
+fn independent_calls(c: &mut Criterion) {
+    Block::new(|i| Action::Call(Address::from_low_u64_be(0x1000 + i as u64)))
+        .bench("independent_calls", c);
+}
+
+fn shared_counter(c: &mut Criterion) {
+    Block::new(|_| Action::Call(Address::from_low_u64_be(0x1000))).bench("shared_counter", c);
+}
@@ -39,13 +39,13 @@ use ethereum_types::{Address, Bloom, H256, U256};
 use engines::EthEngine;
 use error::{BlockError, Error};
 use factory::Factories;
-use state::State;
+use state::{ApplyOutcome, State};
 use state_db::StateDB;
-use trace::Tracing;
+use trace::{FlatTrace, Tracing, VMTrace};
 use triehash::ordered_trie_root;
 use unexpected::{Mismatch, OutOfBounds};
 use verification::PreverifiedBlock;
-use vm::{EnvInfo, LastHashes};
+use vm::{CleanDustMode, EnvInfo, LastHashes};
 
 use hash::keccak;
 use rlp::{encode_list, RlpStream};
@@ -281,6 +281,21 @@ impl<'x> OpenBlock<'x> {
             self.block.traces.is_enabled(),
         )?;
 
+        self.push_outcome(t, h, outcome);
+        Ok(self
+            .block
+            .receipts
+            .last()
+            .expect("receipt just pushed; qed"))
+    }
+
+    /// Archive an executed transaction together with its receipt and trace.
+    fn push_outcome(
+        &mut self,
+        t: SignedTransaction,
+        h: Option<H256>,
+        outcome: ApplyOutcome<FlatTrace, VMTrace>,
+    ) {
         self.block
             .transactions_set
             .insert(h.unwrap_or_else(|| t.hash()));
@@ -289,11 +304,44 @@ impl<'x> OpenBlock<'x> {
             traces.push(outcome.trace.into());
         }
         self.block.receipts.push(outcome.receipt);
-        Ok(self
-            .block
-            .receipts
-            .last()
-            .expect("receipt just pushed; qed"))
+    }
+
+    /// Push transactions onto the block, executing them optimistically in parallel.
+    ///
+    /// Produces the same block as `push_transactions`, which is used instead if the
+    /// receipts contain intermediate state roots or the schedule removes dust accounts.
+    fn push_transactions_parallel(
+        &mut self,
+        transactions: Vec<SignedTransaction>,
+    ) -> Result<(), Error> {
+        let env_info = self.block.env_info();
+        let machine = self.engine.machine();
+        let params = machine.params();
+        let intermediate_roots = env_info.number < params.eip658_transition
+            && (env_info.number < params.eip98_transition
+                || env_info.number < params.validate_receipts_transition);
+        let dust_removal = machine.schedule(env_info.number).kill_dust != CleanDustMode::Off;
+        let unique = transactions
+            .iter()
+            .map(|t| t.hash())
+            .chain(self.block.transactions_set.iter().cloned())
+            .collect::<HashSet<_>>()
+            .len()
+            == transactions.len() + self.block.transactions_set.len();
+        if transactions.len() < 2 || intermediate_roots || dust_removal || !unique {
+            return self.push_transactions(transactions);
+        }
+
+        let results = self.block.state.apply_parallel(
+            &env_info,
+            machine,
+            &transactions,
+            self.block.traces.is_enabled(),
+        );
+        for (t, result) in transactions.into_iter().zip(results) {
+            self.push_outcome(t, None, result?);
+        }
+        Ok(())
     }
 
     /// Push transactions onto the block.
@@ -543,6 +591,7 @@ pub(crate) fn enact(
     factories: Factories,
     is_epoch_begin: bool,
     ancestry: &mut dyn Iterator<Item = ExtendedHeader>,
+    parallel: bool,
 ) -> Result<LockedBlock, Error> {
     // For trace log
     let trace_state = if log_enabled!(target: "enact", ::log::Level::Trace) {
@@ -585,8 +634,12 @@ pub(crate) fn enact(
     // t_nb 8.2.1 give engine the chance to call system transaction that should get included in the block
     b.on_before_transactions()?;
 
-    // t_nb 8.3 execute transactions one by one
-    b.push_transactions(transactions)?;
+    // t_nb 8.3 execute transactions, optimistically in parallel if enabled
+    if parallel {
+        b.push_transactions_parallel(transactions)?;
+    } else {
+        b.push_transactions(transactions)?;
+    }
 
     // t_nb 8.4 Push uncles to OpenBlock and check if we have more then max uncles
     for u in uncles {
@@ -608,6 +661,7 @@ pub fn enact_verified(
     factories: Factories,
     is_epoch_begin: bool,
     ancestry: &mut dyn Iterator<Item = ExtendedHeader>,
+    parallel: bool,
 ) -> Result<LockedBlock, Error> {
     enact(
         block.header,
@@ -621,6 +675,7 @@ pub fn enact_verified(
         factories,
         is_epoch_begin,
         ancestry,
+        parallel,
     )
 }
 
@@ -189,6 +189,13 @@ pub trait ChainNotify: Send + Sync {
         // does nothing by default
     }
 
+    /// fires when chain forwards an RLP list of private transactions to the given nodes,
+    /// returns the number of peers it was written to
+    fn send_private_transactions(&self, _packet: &[u8], _node_ids: &[H512]) -> usize {
+        // does nothing by default
+        0
+    }
+
     /// fires when new block is about to be imported
     /// implementations should be light
     fn block_pre_import(&self, _bytes: &Bytes, _hash: &H256, _difficulty: &U256) {
@@ -40,7 +40,7 @@ use hash::keccak;
 use itertools::Itertools;
 use parking_lot::{Mutex, RwLock};
 use rand::rngs::OsRng;
-use rlp::{PayloadInfo, Rlp};
+use rlp::{PayloadInfo, Rlp, RlpStream};
 use rustc_hex::FromHex;
 use trie::{Trie, TrieFactory, TrieSpec};
 use types::{
@@ -65,6 +65,8 @@ use call_contract::RegistryInfo;
 use client::{
     ancient_import::AncientVerifier,
     bad_blocks,
+    prefetch::{PrefetchJob, StatePrefetcher},
+    simulation::{self, SimulatedBlock, SimulationBlock},
     traits::{ChainSyncing, ForceUpdateSealing, ReservedPeersManagement, TransactionRequest},
     AccountData, BadBlocks, Balance, BlockChain as BlockChainTrait, BlockChainClient,
     BlockChainReset, BlockId, BlockInfo, BlockProducer, BroadcastProposalBlock, Call,
@@ -92,7 +94,8 @@ use state::{self, State};
 use state_db::StateDB;
 use stats::{PrometheusMetrics, PrometheusRegistry};
 use trace::{
-    self, Database as TraceDatabase, ImportRequest as TraceImportRequest, LocalizedTrace, TraceDB,
+    self, Database as TraceDatabase, ImportRequest as TraceImportRequest, LocalizedTrace, Profile,
+    TraceDB,
 };
 use transaction_ext::Transaction;
 use verification::{
@@ -303,6 +306,9 @@ pub struct Client {
 
     importer: Importer,
 
+    /// Warms the state cache for blocks about to be executed.
+    prefetcher: StatePrefetcher,
+
     shutdown: ShutdownManager,
 
     statistics: ClientStatistics,
@@ -598,6 +604,7 @@ impl Importer {
             client.factories.clone(),
             is_epoch_begin,
             &mut chain.ancestry_with_metadata_iter(*header.parent_hash()),
+            client.config.parallel_transactions,
         );
 
         let mut locked_block = match enact_result {
@@ -1075,6 +1082,12 @@ impl Client {
         let mut statistics = ClientStatistics::default();
         statistics.logging_enabled = true;
 
+        let prefetcher = StatePrefetcher::new(if config.state_prefetch {
+            cmp::max(1, ::num_cpus::get() / 2)
+        } else {
+            0
+        });
+
         let client = Arc::new(Client {
             enabled: AtomicBool::new(true),
             sleep_state: Mutex::new(SleepState::new(awake)),
@@ -1103,6 +1116,7 @@ impl Client {
             sync_provider: Mutex::new(None),
             reserved_peers_management: Mutex::new(None),
             importer,
+            prefetcher,
             config,
             shutdown,
             statistics,
@@ -1454,6 +1468,39 @@ impl Client {
         panic!("Couldn't get latest state in 5 tries");
     }
 
+    /// Prefetch the state read by the transactions of `header`, a child of the best block,
+    /// in the background. `transactions` is called on the background thread.
+    fn prefetch_block_state<F>(&self, header: &Header, transactions: F)
+    where
+        F: FnOnce() -> Vec<SignedTransaction> + Send + 'static,
+    {
+        let best = self.best_block_header();
+        let best_hash = best.hash();
+        if *header.parent_hash() != best_hash {
+            return;
+        }
+
+        let env_info = EnvInfo {
+            number: header.number(),
+            author: *header.author(),
+            timestamp: header.timestamp(),
+            difficulty: *header.difficulty(),
+            last_hashes: self.build_last_hashes(&best_hash),
+            gas_used: U256::zero(),
+            gas_limit: *header.gas_limit(),
+            base_fee: header.base_fee(),
+        };
+        let job = PrefetchJob {
+            state_db: self.state_db.read().boxed_clone_canon(&best_hash),
+            root: *best.state_root(),
+            account_start_nonce: self.engine.account_start_nonce(header.number()),
+            factories: self.factories.clone(),
+            env_info,
+            engine: self.engine.clone(),
+        };
+        self.prefetcher.prefetch(job, transactions);
+    }
+
     /// Attempt to get a copy of a specific block's final state.
     ///
     /// This will not fail if given BlockId::Latest.
@@ -2026,6 +2073,14 @@ impl ImportBlock for Client {
             None
         };
 
+        let prefetch = if self.prefetcher.is_enabled()
+            && unverified.parent_hash() == self.chain.read().best_block_hash()
+        {
+            Some((unverified.header.clone(), unverified.transactions.clone()))
+        } else {
+            None
+        };
+
         // t_nb 2.3
         match self.importer.block_queue.import(unverified) {
             Ok(hash) => {
@@ -2033,6 +2088,15 @@ impl ImportBlock for Client {
                 if let Some((raw, hash, difficulty)) = raw {
                     self.notify(move |n| n.block_pre_import(&raw, &hash, &difficulty));
                 }
+                // warm the state cache while the block is verified, recovering senders in the background.
+                if let Some((header, transactions)) = prefetch {
+                    self.prefetch_block_state(&header, move || {
+                        transactions
+                            .into_iter()
+                            .filter_map(|t| SignedTransaction::new(t).ok())
+                            .collect()
+                    });
+                }
                 Ok(hash)
             }
             // t_nb 2.5 if block is not okay print error. we only care about block errors (not import errors)
@@ -2137,6 +2201,78 @@ impl Call for Client {
         Ok(results)
     }
 
+    fn simulate_blocks(
+        &self,
+        blocks: &[SimulationBlock],
+        state: &mut Self::State,
+        header: &Header,
+    ) -> Result<Vec<SimulatedBlock>, CallError> {
+        let machine = self.engine.machine();
+        let mut last_hashes = (*self.build_last_hashes(&header.hash())).clone();
+        let mut parent = header.clone();
+        let mut results = Vec::with_capacity(blocks.len());
+        let mut gas_left = U256::from(simulation::SIMULATION_GAS_CAP);
+
+        for block in blocks {
+            let base_fee = self.engine.calculate_base_fee(&parent);
+            let mut header = block.block_overrides.header_on_top_of(&parent, base_fee)?;
+            simulation::apply_state_overrides(state, &block.state_overrides)
+                .map_err(|_| CallError::StateCorrupt)?;
+
+            let mut env_info = EnvInfo {
+                number: header.number(),
+                author: header.author().clone(),
+                timestamp: header.timestamp(),
+                difficulty: header.difficulty().clone(),
+                last_hashes: Arc::new(last_hashes.clone()),
+                gas_used: U256::default(),
+                gas_limit: *header.gas_limit(),
+                base_fee: header.base_fee(),
+            };
+
+            let mut executed = Vec::with_capacity(block.transactions.len());
+            for t in &block.transactions {
+                if gas_left.is_zero() {
+                    return Err(CallError::InvalidSimulation(format!(
+                        "Calls exceed the simulation gas cap of {}",
+                        simulation::SIMULATION_GAS_CAP
+                    )));
+                }
+
+                //if gas pricing is not defined, force base_fee to zero
+                if t.effective_gas_price(header.base_fee()).is_zero() {
+                    env_info.base_fee = Some(0.into());
+                } else {
+                    env_info.base_fee = header.base_fee()
+                }
+
+                // calls may use the gas left in the block, within the simulation gas cap.
+                let block_gas_left = env_info.gas_limit.saturating_sub(env_info.gas_used);
+                let gas = cmp::min(t.tx().gas, cmp::min(block_gas_left, gas_left));
+                let t = if gas == t.tx().gas {
+                    t.clone()
+                } else {
+                    let mut tx = t.as_unsigned().clone();
+                    tx.tx_mut().gas = gas;
+                    tx.fake_sign(t.sender())
+                };
+
+                let ret = Self::do_virtual_call(machine, &env_info, state, &t, Default::default())?;
+                env_info.gas_used = ret.cumulative_gas_used;
+                gas_left = gas_left.saturating_sub(ret.gas_used);
+                executed.push(ret);
+            }
+
+            header.set_gas_used(env_info.gas_used);
+            last_hashes.insert(0, header.hash());
+            last_hashes.truncate(256);
+            parent = header.clone();
+            results.push(SimulatedBlock { header, executed });
+        }
+
+        Ok(results)
+    }
+
     fn estimate_gas(
         &self,
         t: &SignedTransaction,
@@ -2682,6 +2818,33 @@ impl BlockChainClient for Client {
         })))
     }
 
+    fn profile_block(&self, block: BlockId) -> Result<Profile, CallError> {
+        let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+        let body = self.block_body(block).ok_or(CallError::StatePruned)?;
+        let mut state = self
+            .state_at_beginning(block)
+            .ok_or(CallError::StatePruned)?;
+        let machine = self.engine.machine();
+        let schedule = machine.schedule(env_info.number);
+
+        const PROOF: &'static str =
+            "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+
+        let mut profile = Profile::default();
+        for t in body.transactions() {
+            let t = SignedTransaction::new(t).expect(PROOF);
+            let options = TransactOptions::new(trace::NoopTracer, trace::ProfilingVMTracer::new())
+                .dont_check_nonce();
+            let executed = Executive::new(&mut state, &env_info, machine, &schedule)
+                .transact_virtual(&t, options)?;
+            env_info.gas_used = env_info.gas_used + executed.gas_used;
+            if let Some(tx_profile) = executed.vm_trace {
+                profile.merge(tx_profile);
+            }
+        }
+        Ok(profile)
+    }
+
     fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>> {
         if !self.tracedb.read().tracing_enabled() {
             return None;
@@ -2773,15 +2936,49 @@ impl BlockChainClient for Client {
                 .as_u64() as usize,
             )
         };
-        self.importer
-            .miner
+        let miner = &self.importer.miner;
+        miner
             .ready_transactions(self, max_len, ::miner::PendingOrdering::Priority)
+            .into_iter()
+            .filter(|tx| !miner.is_private_transaction(&tx.hash()))
+            .collect()
     }
 
     fn transaction(&self, tx_hash: &H256) -> Option<Arc<VerifiedTransaction>> {
         self.importer.miner.transaction(tx_hash)
     }
 
+    fn is_private_transaction(&self, tx_hash: &H256) -> bool {
+        self.importer.miner.is_private_transaction(tx_hash)
+    }
+
+    fn send_private_transactions(&self, transactions: &[SignedTransaction]) -> usize {
+        let validators = self.engine.validator_node_ids();
+        if validators.is_empty() {
+            warn!(target: "client", "No validators known, private transactions are not forwarded.");
+            return 0;
+        }
+
+        let mut packet = RlpStream::new_list(transactions.len());
+        for tx in transactions {
+            tx.rlp_append(&mut packet);
+        }
+        let packet = packet.out();
+
+        let mut sent = 0;
+        for np in &*self.notify.read() {
+            if let Some(n) = np.upgrade() {
+                sent += n.send_private_transactions(&packet, &validators);
+            }
+        }
+        trace!(target: "client", "Forwarded {} private transactions to {} of {} validators", transactions.len(), sent, validators.len());
+        sent
+    }
+
+    fn is_validator_node(&self, node_id: &H512) -> bool {
+        self.engine.validator_node_ids().contains(node_id)
+    }
+
     fn signing_chain_id(&self) -> Option<u64> {
         self.engine.signing_chain_id(&self.latest_env_info())
     }
@@ -2994,6 +3191,37 @@ impl IoClient for Client {
             });
     }
 
+    fn queue_private_transactions(&self, transactions: Vec<Bytes>, peer_id: usize) {
+        trace_time!("queue_private_transactions");
+        let len = transactions.len();
+        self.queue_transactions
+            .queue(&self.io_channel.read(), len, move |client| {
+                trace_time!("import_queued_private_transactions");
+                let best_block_number = client.best_block_header().number();
+                let txs: Vec<UnverifiedTransaction> = transactions
+                    .iter()
+                    .filter_map(|bytes| {
+                        client
+                            .engine
+                            .decode_transaction(bytes, best_block_number)
+                            .ok()
+                    })
+                    .collect();
+
+                client.notify(|notify| {
+                    notify.transactions_received(&txs, peer_id);
+                });
+
+                client
+                    .importer
+                    .miner
+                    .import_external_private_transactions(client, txs);
+            })
+            .unwrap_or_else(|e| {
+                debug!(target: "client", "Ignoring {} private transactions: {}", len, e);
+            });
+    }
+
     fn queue_ancient_block(
         &self,
         unverified: Unverified,
@@ -3331,6 +3559,23 @@ impl super::traits::EngineClient for Client {
             .miner
             .create_pending_block_at(self, txns, timestamp, block_number)
     }
+
+    fn prefetch_state(&self, txns: &[SignedTransaction], timestamp: u64, block_number: u64) {
+        if !self.prefetcher.is_enabled() {
+            return;
+        }
+        let best = self.best_block_header();
+        let mut header = Header::new();
+        header.set_parent_hash(best.hash());
+        header.set_number(block_number);
+        header.set_timestamp(timestamp);
+        header.set_author(self.importer.miner.authoring_params().author);
+        header.set_difficulty(*best.difficulty());
+        header.set_gas_limit(*best.gas_limit());
+        header.set_base_fee(best.base_fee());
+        let transactions = txns.to_vec();
+        self.prefetch_block_state(&header, move || transactions);
+    }
 }
 
 impl ProvingBlockChainClient for Client {
@@ -3662,8 +3907,36 @@ impl PrometheusMetrics for Client {
                 "State DB cache size",
                 state_db_size as i64,
             );
+            let (hits, misses) = state_db.cache_hits();
+            r.register_counter(
+                "statedb_cache_hits",
+                "State DB account cache hits",
+                hits as i64,
+            );
+            r.register_counter(
+                "statedb_cache_misses",
+                "State DB account cache misses",
+                misses as i64,
+            );
         });
 
+        let prefetch = self.prefetcher.report();
+        r.register_counter(
+            "prefetch_blocks",
+            "Blocks for which the state was prefetched",
+            prefetch.jobs as i64,
+        );
+        r.register_counter(
+            "prefetch_blocks_skipped",
+            "Blocks skipped because the state prefetcher was busy",
+            prefetch.dropped as i64,
+        );
+        r.register_counter(
+            "prefetch_accounts",
+            "Accounts added to the State DB cache by the state prefetcher",
+            prefetch.accounts as i64,
+        );
+
         // blockchain cache
         let blockchain_cache_info = self.blockchain_cache_info();
         r.register_gauge(
@@ -3779,6 +4052,9 @@ impl PrometheusMetrics for Client {
         // engine specific metrics.
         self.engine.prometheus_metrics(r);
 
+        // transaction queue metrics.
+        self.importer.miner.prometheus_metrics(r);
+
         self.statistics.prometheus_metrics(r);
     }
 }
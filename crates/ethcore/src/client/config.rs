@@ -125,6 +125,10 @@ pub struct ClientConfig {
     pub transaction_verification_queue_size: usize,
     /// Maximal number of blocks to import at each round.
     pub max_round_blocks_to_import: usize,
+    /// Execute the transactions of imported blocks optimistically in parallel.
+    pub parallel_transactions: bool,
+    /// Prefetch the state read by blocks before they are executed.
+    pub state_prefetch: bool,
 
     /// Shutdown client if block has not happed for n seconds.
     pub shutdown_on_missing_block_import: Option<u64>,
@@ -156,6 +160,8 @@ impl Default for ClientConfig {
             check_seal: true,
             transaction_verification_queue_size: 8192,
             max_round_blocks_to_import: 1,
+            parallel_transactions: false,
+            state_prefetch: false,
             shutdown_on_missing_block_import: Some(1800),
             snapshot: Default::default(),
         }
@@ -109,6 +109,8 @@ impl<'a> EvmTestClient<'a> {
             ForkSpec::Berlin => Some(ethereum::new_berlin_test()),
             ForkSpec::London => Some(ethereum::new_london_test()),
             ForkSpec::BerlinToLondonAt5 => Some(ethereum::new_berlin_to_london_test()),
+            ForkSpec::Shanghai => Some(ethereum::new_shanghai_test()),
+            ForkSpec::Cancun => Some(ethereum::new_cancun_test()),
             ForkSpec::FrontierToHomesteadAt5
             | ForkSpec::HomesteadToDaoAt5
             | ForkSpec::HomesteadToEIP150At5
@@ -273,6 +275,47 @@ impl<'a> EvmTestClient<'a> {
             .map_err(EvmTestError::Evm)
     }
 
+    /// Applies a SignedTransaction on top of the current state as a part of the block
+    /// described by `env_info`. Unlike `transact` the coinbase is not touched and the
+    /// state is not committed, so all transactions of a block can be applied in a row.
+    pub fn apply<T: trace::Tracer, V: trace::VMTracer>(
+        &mut self,
+        env_info: &client::EnvInfo,
+        transaction: &transaction::SignedTransaction,
+        tracer: T,
+        vm_tracer: V,
+    ) -> state::ApplyResult<T::Output, V::Output> {
+        self.state.apply_with_tracing(
+            env_info,
+            self.spec.engine.machine(),
+            transaction,
+            tracer,
+            vm_tracer,
+        )
+    }
+
+    /// Credits block and ommer rewards and commits the state at the end of a block.
+    /// Returns the resulting state root.
+    pub fn finalize(
+        &mut self,
+        env_info: &client::EnvInfo,
+        rewards: &[(H160, U256)],
+    ) -> Result<H256, EvmTestError> {
+        let schedule = self.spec.engine.machine().schedule(env_info.number);
+        for (address, reward) in rewards {
+            let cleanup_mode = if schedule.no_empty {
+                state::CleanupMode::NoEmpty
+            } else {
+                state::CleanupMode::ForceCreate
+            };
+            self.state
+                .add_balance(address, reward, cleanup_mode)
+                .map_err(EvmTestError::Trie)?;
+        }
+        self.state.commit()?;
+        Ok(*self.state.root())
+    }
+
     /// Executes a SignedTransaction within context of the provided state and `EnvInfo`.
     /// Returns the state root, gas left and the output.
     pub fn transact<T: trace::Tracer, V: trace::VMTracer>(
@@ -23,6 +23,8 @@ mod config;
 #[cfg(any(test, feature = "test-helpers"))]
 mod evm_test_client;
 mod io_message;
+mod prefetch;
+pub mod simulation;
 #[cfg(any(test, feature = "test-helpers"))]
 pub mod test_client;
 mod trace;
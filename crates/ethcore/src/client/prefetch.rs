@@ -0,0 +1,238 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prefetching of the state read by blocks which are about to be executed.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use engines::EthEngine;
+use ethereum_types::{H256, U256};
+use factory::Factories;
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use state::State;
+use state_db::StateDB;
+use types::transaction::SignedTransaction;
+use vm::EnvInfo;
+
+/// Number of blocks being prefetched or waiting for it, further blocks are skipped.
+const MAX_JOBS: usize = 2;
+
+/// State on top of which the transactions of a block are prefetched.
+pub struct PrefetchJob {
+    /// Database of the canonical state.
+    pub state_db: StateDB,
+    /// Root of the canonical state.
+    pub root: H256,
+    /// Nonce of new accounts.
+    pub account_start_nonce: U256,
+    /// Factories for the state.
+    pub factories: Factories,
+    /// Environment of the block.
+    pub env_info: EnvInfo,
+    /// Engine executing the block.
+    pub engine: Arc<dyn EthEngine>,
+}
+
+/// Prefetcher statistics.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PrefetchReport {
+    /// Number of blocks prefetched.
+    pub jobs: u64,
+    /// Number of blocks skipped because the prefetcher was busy.
+    pub dropped: u64,
+    /// Number of accounts added to the shared state cache.
+    pub accounts: u64,
+}
+
+#[derive(Default)]
+struct Stats {
+    jobs: AtomicU64,
+    dropped: AtomicU64,
+    accounts: AtomicU64,
+}
+
+/// Warms the shared state cache on background threads by executing the transactions
+/// of a block speculatively before it is imported.
+pub struct StatePrefetcher {
+    pool: Option<ThreadPool>,
+    threads: usize,
+    jobs: Arc<AtomicUsize>,
+    stats: Arc<Stats>,
+}
+
+impl StatePrefetcher {
+    /// Create a prefetcher using `threads` threads, disabled if `threads` is zero.
+    pub fn new(threads: usize) -> Self {
+        let pool = if threads == 0 {
+            None
+        } else {
+            ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(|i| format!("Prefetch #{}", i))
+                .build()
+                .map_err(|e| warn!(target: "client", "Unable to start state prefetcher: {}", e))
+                .ok()
+        };
+
+        StatePrefetcher {
+            pool,
+            threads,
+            jobs: Arc::new(AtomicUsize::new(0)),
+            stats: Arc::new(Stats::default()),
+        }
+    }
+
+    /// Whether the prefetcher is running.
+    pub fn is_enabled(&self) -> bool {
+        self.pool.is_some()
+    }
+
+    /// Prefetch the state read by `transactions` in the background. Transactions
+    /// are obtained on the background thread, e.g. to recover their senders there.
+    pub fn prefetch<F>(&self, job: PrefetchJob, transactions: F)
+    where
+        F: FnOnce() -> Vec<SignedTransaction> + Send + 'static,
+    {
+        let pool = match self.pool {
+            Some(ref pool) => pool,
+            None => return,
+        };
+        if self.jobs.fetch_add(1, Ordering::SeqCst) >= MAX_JOBS {
+            self.jobs.fetch_sub(1, Ordering::SeqCst);
+            self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+            trace!(target: "client", "Skipping state prefetch for block #{}", job.env_info.number);
+            return;
+        }
+
+        let threads = self.threads;
+        let jobs = self.jobs.clone();
+        let stats = self.stats.clone();
+        pool.spawn(move || {
+            let started = Instant::now();
+            let chunks = split_by_sender(transactions(), threads);
+            let machine = job.engine.machine();
+            let accounts: usize = chunks
+                .into_par_iter()
+                .map(|transactions| {
+                    State::from_existing(
+                        job.state_db.boxed_clone_prefetch(),
+                        job.root,
+                        job.account_start_nonce,
+                        job.factories.clone(),
+                    )
+                    .and_then(|state| state.prefetch(&job.env_info, machine, &transactions))
+                    .unwrap_or_else(|e| {
+                        debug!(target: "client", "State prefetch failed: {:?}", e);
+                        0
+                    })
+                })
+                .sum();
+
+            stats.jobs.fetch_add(1, Ordering::Relaxed);
+            stats.accounts.fetch_add(accounts as u64, Ordering::Relaxed);
+            jobs.fetch_sub(1, Ordering::SeqCst);
+            trace!(target: "client", "Prefetched {} accounts for block #{} in {:?}",
+				accounts, job.env_info.number, started.elapsed());
+        });
+    }
+
+    /// Get the prefetcher statistics.
+    pub fn report(&self) -> PrefetchReport {
+        PrefetchReport {
+            jobs: self.stats.jobs.load(Ordering::Relaxed),
+            dropped: self.stats.dropped.load(Ordering::Relaxed),
+            accounts: self.stats.accounts.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Split `transactions` into at most `chunks` parts, keeping the transactions
+/// of a sender together and in order.
+fn split_by_sender(
+    transactions: Vec<SignedTransaction>,
+    chunks: usize,
+) -> Vec<Vec<SignedTransaction>> {
+    let chunks = chunks.max(1);
+    let mut result: Vec<Vec<SignedTransaction>> = (0..chunks).map(|_| Vec::new()).collect();
+    let mut senders = HashMap::new();
+    for t in transactions {
+        let next = senders.len() % chunks;
+        let chunk = *senders.entry(t.sender()).or_insert(next);
+        result[chunk].push(t);
+    }
+    result.retain(|chunk| !chunk.is_empty());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_by_sender;
+    use crypto::publickey::{Generator, KeyPair, Random};
+    use types::transaction::{Action, SignedTransaction, Transaction, TypedTransaction};
+
+    fn transaction(sender: &KeyPair, nonce: u64) -> SignedTransaction {
+        TypedTransaction::Legacy(Transaction {
+            nonce: nonce.into(),
+            gas_price: 1.into(),
+            gas: 21_000.into(),
+            action: Action::Create,
+            value: 0.into(),
+            data: vec![],
+        })
+        .sign(sender.secret(), None)
+    }
+
+    #[test]
+    fn should_keep_transactions_of_a_sender_together() {
+        let senders: Vec<_> = (0..3).map(|_| Random.generate()).collect();
+        let transactions = vec![
+            transaction(&senders[0], 0),
+            transaction(&senders[1], 0),
+            transaction(&senders[0], 1),
+            transaction(&senders[2], 0),
+            transaction(&senders[1], 1),
+        ];
+
+        let chunks = split_by_sender(transactions, 2);
+        assert_eq!(chunks.len(), 2);
+        let nonces = |chunk: &Vec<SignedTransaction>| {
+            chunk
+                .iter()
+                .map(|t| (t.sender(), t.tx().nonce.as_u64()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            nonces(&chunks[0]),
+            vec![
+                (senders[0].address(), 0),
+                (senders[0].address(), 1),
+                (senders[2].address(), 0)
+            ]
+        );
+        assert_eq!(
+            nonces(&chunks[1]),
+            vec![(senders[1].address(), 0), (senders[1].address(), 1)]
+        );
+        assert!(split_by_sender(vec![], 4).is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types used to simulate consecutive blocks of calls on top of an existing block.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bytes::Bytes;
+use error::CallError;
+use ethereum_types::{Address, H256, U256};
+use ethtrie::Result as TrieResult;
+use executive::Executed;
+use state::{Backend, State};
+use types::{header::Header, transaction::SignedTransaction, BlockNumber};
+
+/// Default increase of the timestamp between simulated blocks.
+pub const SIMULATED_BLOCK_TIME: u64 = 12;
+/// Maximum amount of gas all calls of a simulation may use together.
+pub const SIMULATION_GAS_CAP: u64 = 500_000_000;
+
+/// Overrides of the header fields of a simulated block.
+/// Fields that are not set are derived from the parent block.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BlockOverrides {
+    /// Block number, must be greater than the number of the parent block.
+    pub number: Option<BlockNumber>,
+    /// Block timestamp, must be greater than the timestamp of the parent block.
+    pub timestamp: Option<u64>,
+    /// Block gas limit.
+    pub gas_limit: Option<U256>,
+    /// Block author (coinbase).
+    pub author: Option<Address>,
+    /// Base fee of the block.
+    pub base_fee: Option<U256>,
+    /// Block difficulty.
+    pub difficulty: Option<U256>,
+}
+
+/// Overrides of a single account applied before a simulated block is executed.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AccountOverride {
+    /// New balance of the account.
+    pub balance: Option<U256>,
+    /// New nonce of the account.
+    pub nonce: Option<U256>,
+    /// New code of the account.
+    pub code: Option<Bytes>,
+    /// Replaces the whole storage of the account.
+    pub storage: Option<HashMap<H256, H256>>,
+    /// Patches single storage slots of the account.
+    pub storage_diff: Option<HashMap<H256, H256>>,
+}
+
+/// Account overrides keyed by address.
+pub type StateOverrides = BTreeMap<Address, AccountOverride>;
+
+/// A block of calls to simulate.
+#[derive(Debug, Default, Clone)]
+pub struct SimulationBlock {
+    /// Header overrides of the block.
+    pub block_overrides: BlockOverrides,
+    /// State overrides applied before the calls of this block are executed.
+    pub state_overrides: StateOverrides,
+    /// Calls to execute, in order.
+    pub transactions: Vec<SignedTransaction>,
+}
+
+/// Result of a simulated block.
+#[derive(Debug, Clone)]
+pub struct SimulatedBlock {
+    /// Header of the simulated block, with `gas_used` filled in.
+    pub header: Header,
+    /// Results of the calls of this block, in order.
+    pub executed: Vec<Executed>,
+}
+
+impl BlockOverrides {
+    /// Builds the header of a simulated block on top of `parent`.
+    /// `base_fee` is the base fee the engine derives from `parent`.
+    pub fn header_on_top_of(
+        &self,
+        parent: &Header,
+        base_fee: Option<U256>,
+    ) -> Result<Header, CallError> {
+        let number = self.number.unwrap_or_else(|| parent.number() + 1);
+        if number <= parent.number() {
+            return Err(CallError::InvalidSimulation(format!(
+                "Block number {} must be greater than {}",
+                number,
+                parent.number()
+            )));
+        }
+        let timestamp = self
+            .timestamp
+            .unwrap_or_else(|| parent.timestamp() + SIMULATED_BLOCK_TIME);
+        if timestamp <= parent.timestamp() {
+            return Err(CallError::InvalidSimulation(format!(
+                "Block timestamp {} must be greater than {}",
+                timestamp,
+                parent.timestamp()
+            )));
+        }
+
+        let mut header = Header::new();
+        header.set_parent_hash(parent.hash());
+        header.set_number(number);
+        header.set_timestamp(timestamp);
+        header.set_author(self.author.unwrap_or_else(|| *parent.author()));
+        header.set_gas_limit(self.gas_limit.unwrap_or_else(|| *parent.gas_limit()));
+        header.set_difficulty(self.difficulty.unwrap_or_else(|| *parent.difficulty()));
+        header.set_base_fee(self.base_fee.or(base_fee));
+        Ok(header)
+    }
+}
+
+/// Applies `overrides` to `state`.
+pub fn apply_state_overrides<B: Backend>(
+    state: &mut State<B>,
+    overrides: &StateOverrides,
+) -> TrieResult<()> {
+    for (address, account) in overrides {
+        if let Some(balance) = account.balance {
+            state.set_balance(address, balance)?;
+        }
+        if let Some(nonce) = account.nonce {
+            state.set_nonce(address, nonce)?;
+        }
+        if let Some(ref code) = account.code {
+            state.reset_code(address, code.clone())?;
+        }
+        if let Some(ref storage) = account.storage {
+            state.reset_storage(address, storage.clone())?;
+        }
+        if let Some(ref storage_diff) = account.storage_diff {
+            for (key, value) in storage_diff {
+                state.set_storage(address, *key, *value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parent() -> Header {
+        let mut header = Header::new();
+        header.set_number(10);
+        header.set_timestamp(1000);
+        header.set_gas_limit(8_000_000.into());
+        header.set_author(Address::from_low_u64_be(1));
+        header
+    }
+
+    #[test]
+    fn derives_header_from_parent() {
+        let parent = parent();
+        let header = BlockOverrides::default()
+            .header_on_top_of(&parent, Some(7.into()))
+            .unwrap();
+
+        assert_eq!(header.number(), 11);
+        assert_eq!(header.timestamp(), 1000 + SIMULATED_BLOCK_TIME);
+        assert_eq!(*header.parent_hash(), parent.hash());
+        assert_eq!(*header.gas_limit(), 8_000_000.into());
+        assert_eq!(*header.author(), Address::from_low_u64_be(1));
+        assert_eq!(header.base_fee(), Some(7.into()));
+
+        let overrides = BlockOverrides {
+            base_fee: Some(9.into()),
+            ..Default::default()
+        };
+        let header = overrides.header_on_top_of(&parent, Some(7.into())).unwrap();
+        assert_eq!(header.base_fee(), Some(9.into()));
+    }
+
+    #[test]
+    fn rejects_non_increasing_number_and_timestamp() {
+        let parent = parent();
+        let overrides = BlockOverrides {
+            number: Some(10),
+            ..Default::default()
+        };
+        assert!(overrides.header_on_top_of(&parent, None).is_err());
+
+        let overrides = BlockOverrides {
+            timestamp: Some(1000),
+            ..Default::default()
+        };
+        assert!(overrides.header_on_top_of(&parent, None).is_err());
+    }
+}
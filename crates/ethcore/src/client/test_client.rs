@@ -59,6 +59,7 @@ use vm::Schedule;
 use block::{ClosedBlock, OpenBlock, SealedBlock};
 use call_contract::{CallContract, RegistryInfo};
 use client::{
+    simulation::{SimulatedBlock, SimulationBlock},
     traits::{ForceUpdateSealing, TransactionRequest},
     AccountData, BadBlocks, Balance, BlockChain, BlockChainClient, BlockChainInfo, BlockId,
     BlockInfo, BlockProducer, BlockStatus, BroadcastProposalBlock, Call, CallAnalytics, ChainInfo,
@@ -76,7 +77,7 @@ use spec::Spec;
 use state::StateInfo;
 use state_db::StateDB;
 use stats::{PrometheusMetrics, PrometheusRegistry};
-use trace::LocalizedTrace;
+use trace::{LocalizedTrace, Profile};
 use verification::queue::{kind::blocks::Unverified, QueueInfo};
 
 use super::ReservedPeersManagement;
@@ -131,6 +132,12 @@ pub struct TestBlockChainClient {
     pub disabled: AtomicBool,
     /// Transaction hashes producer
     pub new_transaction_hashes: RwLock<Option<crossbeam_channel::Sender<H256>>>,
+    /// Transactions forwarded to the validators only.
+    pub private_transactions_sent: RwLock<Vec<SignedTransaction>>,
+    /// Node ids of the current validators.
+    pub validator_node_ids: RwLock<Vec<H512>>,
+    /// Minimum gas price defined by the engine.
+    pub minimum_gas_price: RwLock<Option<U256>>,
 
     reserved_peers_management: Mutex<Option<Box<dyn ReservedPeersManagement>>>,
 }
@@ -203,6 +210,9 @@ impl TestBlockChainClient {
             disabled: AtomicBool::new(false),
             error_on_logs: RwLock::new(None),
             new_transaction_hashes: RwLock::new(None),
+            private_transactions_sent: RwLock::new(Vec::new()),
+            validator_node_ids: RwLock::new(Vec::new()),
+            minimum_gas_price: RwLock::new(None),
             reserved_peers_management: Mutex::new(None),
         };
 
@@ -407,6 +417,28 @@ impl TestBlockChainClient {
         hash
     }
 
+    /// Inserts a privately forwarded transaction to miners transactions queue.
+    pub fn insert_private_transaction_to_queue(&self) -> H256 {
+        let keypair = Random.generate();
+        let tx = TypedTransaction::Legacy(Transaction {
+            action: Action::Create,
+            value: U256::from(100),
+            data: "3331600055".from_hex().unwrap(),
+            gas: U256::from(100_000),
+            gas_price: U256::from(20_000_000_000u64),
+            nonce: U256::zero(),
+        });
+        let signed_tx = tx.sign(keypair.secret(), None);
+        self.set_balance(signed_tx.sender(), 10_000_000_000_000_000_000u64.into());
+        let hash = signed_tx.hash();
+        let res = self
+            .miner
+            .import_external_private_transactions(self, vec![signed_tx.into()]);
+        assert!(res.into_iter().next().unwrap().is_ok());
+
+        hash
+    }
+
     /// Inserts a transaction to miners transactions queue.
     pub fn insert_transaction_to_queue(&self) -> H256 {
         self.insert_transaction_with_gas_price_to_queue(U256::from(20_000_000_000u64))
@@ -700,6 +732,28 @@ impl Call for TestBlockChainClient {
         Ok(res)
     }
 
+    fn simulate_blocks(
+        &self,
+        blocks: &[SimulationBlock],
+        state: &mut Self::State,
+        header: &Header,
+    ) -> Result<Vec<SimulatedBlock>, CallError> {
+        let mut parent = header.clone();
+        let mut res = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let header = block
+                .block_overrides
+                .header_on_top_of(&parent, parent.base_fee())?;
+            let mut executed = Vec::with_capacity(block.transactions.len());
+            for tx in &block.transactions {
+                executed.push(self.call(tx, Default::default(), state, &header)?);
+            }
+            parent = header.clone();
+            res.push(SimulatedBlock { header, executed });
+        }
+        Ok(res)
+    }
+
     fn estimate_gas(
         &self,
         _t: &SignedTransaction,
@@ -782,6 +836,10 @@ impl BlockChainClient for TestBlockChainClient {
         ))
     }
 
+    fn profile_block(&self, _block: BlockId) -> Result<Profile, CallError> {
+        Ok(Profile::default())
+    }
+
     fn block_total_difficulty(&self, _id: BlockId) -> Option<U256> {
         Some(U256::zero())
     }
@@ -851,8 +909,24 @@ impl BlockChainClient for TestBlockChainClient {
         self.receipts.read().get(&id).cloned()
     }
 
-    fn localized_block_receipts(&self, _id: BlockId) -> Option<Vec<LocalizedReceipt>> {
-        Some(self.receipts.read().values().cloned().collect())
+    fn localized_block_receipts(&self, id: BlockId) -> Option<Vec<LocalizedReceipt>> {
+        let number = match id {
+            BlockId::Hash(ref hash) if !self.blocks.read().contains_key(hash) => return None,
+            BlockId::Hash(_) => None,
+            _ => Some(self.block_number(id)?),
+        };
+        let mut receipts = self
+            .receipts
+            .read()
+            .values()
+            .filter(|receipt| match id {
+                BlockId::Hash(ref hash) => receipt.block_hash == *hash,
+                _ => Some(receipt.block_number) == number,
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        receipts.sort_by_key(|receipt| receipt.transaction_index);
+        Some(receipts)
     }
 
     fn logs(&self, filter: Filter) -> Result<Vec<LocalizedLogEntry>, BlockId> {
@@ -873,8 +947,8 @@ impl BlockChainClient for TestBlockChainClient {
         unimplemented!();
     }
 
-    fn is_canon(&self, _hash: &H256) -> bool {
-        unimplemented!()
+    fn is_canon(&self, hash: &H256) -> bool {
+        self.numbers.read().values().any(|canon| canon == hash)
     }
 
     fn block_number(&self, id: BlockId) -> Option<BlockNumber> {
@@ -1026,6 +1100,9 @@ impl BlockChainClient for TestBlockChainClient {
     fn transactions_to_propagate(&self) -> Vec<Arc<VerifiedTransaction>> {
         self.miner
             .ready_transactions(self, 4096, miner::PendingOrdering::Priority)
+            .into_iter()
+            .filter(|tx| !self.miner.is_private_transaction(&tx.hash()))
+            .collect()
     }
 
     fn signing_chain_id(&self) -> Option<u64> {
@@ -1035,7 +1112,7 @@ impl BlockChainClient for TestBlockChainClient {
     /// Some Engine might define the minimum gas price by themselve.
     /// (for example: contract)
     fn minimum_gas_price(&self) -> Option<U256> {
-        None
+        self.minimum_gas_price.read().clone()
     }
 
     fn mode(&self) -> Mode {
@@ -1153,6 +1230,21 @@ impl BlockChainClient for TestBlockChainClient {
         self.miner.transaction(tx_hash)
     }
 
+    fn is_private_transaction(&self, tx_hash: &H256) -> bool {
+        self.miner.is_private_transaction(tx_hash)
+    }
+
+    fn send_private_transactions(&self, transactions: &[SignedTransaction]) -> usize {
+        self.private_transactions_sent
+            .write()
+            .extend_from_slice(transactions);
+        self.validator_node_ids.read().len()
+    }
+
+    fn is_validator_node(&self, node_id: &H512) -> bool {
+        self.validator_node_ids.read().contains(node_id)
+    }
+
     /// Returns the devp2p network endpoint IP and Port information that is used to communicate with other peers.
 
     fn reserved_peers_management(&self) -> &Mutex<Option<Box<dyn ReservedPeersManagement>>> {
@@ -1170,6 +1262,15 @@ impl IoClient for TestBlockChainClient {
         self.miner.import_external_transactions(self, txs);
     }
 
+    fn queue_private_transactions(&self, transactions: Vec<Bytes>, _peer_id: usize) {
+        // import right here
+        let txs = transactions
+            .into_iter()
+            .filter_map(|bytes| TypedTransaction::decode(&bytes).ok())
+            .collect();
+        self.miner.import_external_private_transactions(self, txs);
+    }
+
     fn ancient_block_queue_fullness(&self) -> f32 {
         0.0
     }
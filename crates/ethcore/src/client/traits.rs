@@ -51,13 +51,16 @@ use types::{
 use vm::LastHashes;
 
 use block::{ClosedBlock, OpenBlock, SealedBlock};
-use client::Mode;
+use client::{
+    simulation::{SimulatedBlock, SimulationBlock},
+    Mode,
+};
 use engines::EthEngine;
 use error::{Error, EthcoreResult};
 use executed::CallError;
 use executive::Executed;
 use state::StateInfo;
-use trace::LocalizedTrace;
+use trace::{LocalizedTrace, Profile};
 use verification::queue::{kind::blocks::Unverified, QueueInfo as BlockQueueInfo};
 
 /// State information to be used during client query
@@ -198,6 +201,15 @@ pub trait Call {
         header: &Header,
     ) -> Result<Vec<Executed>, CallError>;
 
+    /// Simulates consecutive blocks of calls on top of the block described by `header`.
+    /// Every call sees the effects of all calls before it, including those of previous blocks.
+    fn simulate_blocks(
+        &self,
+        blocks: &[SimulationBlock],
+        state: &mut Self::State,
+        header: &Header,
+    ) -> Result<Vec<SimulatedBlock>, CallError>;
+
     /// Estimates how much gas will be necessary for a call.
     fn estimate_gas(
         &self,
@@ -245,6 +257,9 @@ pub trait IoClient: Sync + Send {
     /// Queue transactions for importing.
     fn queue_transactions(&self, transactions: Vec<Bytes>, peer_id: usize);
 
+    /// Queue privately forwarded transactions for importing.
+    fn queue_private_transactions(&self, transactions: Vec<Bytes>, peer_id: usize);
+
     /// Queue block import with transaction receipts. Does no sealing and transaction validation.
     fn queue_ancient_block(
         &self,
@@ -398,6 +413,9 @@ pub trait BlockChainClient:
         analytics: CallAnalytics,
     ) -> Result<Box<dyn Iterator<Item = (H256, Executed)>>, CallError>;
 
+    /// Replays all the transactions in a given block and profiles the executed opcodes.
+    fn profile_block(&self, block: BlockId) -> Result<Profile, CallError>;
+
     /// Returns traces matching given filter.
     fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
 
@@ -419,6 +437,16 @@ pub trait BlockChainClient:
     /// Get verified transaction with specified transaction hash.
     fn transaction(&self, tx_hash: &H256) -> Option<Arc<VerifiedTransaction>>;
 
+    /// Returns true if transaction with specified hash must not be propagated publicly (yet).
+    fn is_private_transaction(&self, tx_hash: &H256) -> bool;
+
+    /// Forward transactions to the current validators only, bypassing public propagation.
+    /// Returns the number of validators the transactions were sent to.
+    fn send_private_transactions(&self, transactions: &[SignedTransaction]) -> usize;
+
+    /// Returns true if the given node id belongs to a current validator.
+    fn is_validator_node(&self, node_id: &H512) -> bool;
+
     /// Sorted list of transaction gas prices from at least last sample_size blocks.
     fn gas_price_corpus(&self, sample_size: usize) -> ::stats::Corpus<U256> {
         let mut h = self.chain_info().best_block_hash;
@@ -482,6 +510,70 @@ pub trait BlockChainClient:
         corpus.into()
     }
 
+    /// Sorted list of the effective gas prices paid in the last `sample_size` blocks.
+    ///
+    /// Unlike `gas_price_corpus` the walk never goes further back, and zero gas price
+    /// transactions (like hbbft service transactions) are skipped.
+    fn gas_price_history(&self, sample_size: usize) -> ::stats::Corpus<U256> {
+        let mut h = self.chain_info().best_block_hash;
+        let mut corpus = Vec::new();
+        for _ in 0..sample_size {
+            let block = match self.block(BlockId::Hash(h)) {
+                Some(block) => block,
+                None => break,
+            };
+
+            if block.number() == 0 {
+                break;
+            }
+            block
+                .transaction_views()
+                .iter()
+                .filter(|t| t.gas_price() > 0.into())
+                .foreach(|t| {
+                    corpus.push(t.effective_gas_price({
+                        match t.transaction_type() {
+                            TypedTxId::Legacy => None,
+                            TypedTxId::AccessList => None,
+                            TypedTxId::EIP1559Transaction => Some(block.header().base_fee()),
+                        }
+                    }))
+                });
+            h = block.parent_hash().clone();
+        }
+        corpus.into()
+    }
+
+    /// Sorted list of the effective priority gas prices paid in the last `sample_size` blocks
+    /// since the eip1559 transition, skipping zero gas price transactions.
+    fn priority_gas_price_history(
+        &self,
+        sample_size: usize,
+        eip1559_transition: BlockNumber,
+    ) -> ::stats::Corpus<U256> {
+        let mut h = self.chain_info().best_block_hash;
+        let mut corpus = Vec::new();
+        for _ in 0..sample_size {
+            let block = match self.block(BlockId::Hash(h)) {
+                Some(block) => block,
+                None => break,
+            };
+
+            if block.number() == 0 || block.number() < eip1559_transition {
+                break;
+            }
+            block
+                .transaction_views()
+                .iter()
+                .filter(|t| t.gas_price() > 0.into())
+                .foreach(|t| {
+                    corpus.push(t.effective_priority_gas_price(Some(block.header().base_fee())))
+                });
+            h = block.parent_hash().clone();
+        }
+        corpus.into()
+    }
+
     /// Some Engines might define their gas price on their own.
     fn minimum_gas_price(&self) -> Option<U256>;
 
@@ -689,6 +781,10 @@ pub trait EngineClient: Sync + Send + ChainInfo {
         block_number: u64,
     ) -> Option<Header>;
 
+    /// Prefetch the state read by the transactions of the block `block_number` on top of
+    /// the best block in the background.
+    fn prefetch_state(&self, _txns: &[SignedTransaction], _timestamp: u64, _block_number: u64) {}
+
     /// Time in seconds until the Engine shuts down if no Block Import is performed.
     fn config_shutdown_on_missing_block_import(&self) -> Option<u64> {
         None
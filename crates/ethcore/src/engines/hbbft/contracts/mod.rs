@@ -2,5 +2,6 @@ pub mod connectivity_tracker_hbbft;
 pub mod keygen_history;
 pub mod permission;
 pub mod random_hbbft;
+pub mod service_transaction;
 pub mod staking;
 pub mod validator_set;
@@ -0,0 +1,27 @@
+use client::EngineClient;
+use engines::hbbft::utils::bound_contract::{BoundContract, CallError};
+use ethereum_types::Address;
+use std::str::FromStr;
+use types::ids::BlockId;
+
+use_contract!(
+    service_transaction_contract,
+    "res/contracts/service_transaction.json"
+);
+
+lazy_static! {
+    static ref SERVICE_TRANSACTION_CONTRACT_ADDRESS: Address =
+        Address::from_str("5000000000000000000000000000000000000001").unwrap();
+}
+
+/// Returns true if `sender` is certified to send zero gas price service transactions.
+pub fn is_certified(
+    client: &dyn EngineClient,
+    block_id: BlockId,
+    sender: &Address,
+) -> Result<bool, CallError> {
+    let c = BoundContract::bind(client, block_id, *SERVICE_TRANSACTION_CONTRACT_ADDRESS);
+    c.call_const(service_transaction_contract::functions::certified::call(
+        *sender,
+    ))
+}
@@ -13,8 +13,8 @@ use block::ExecutedBlock;
 use client::traits::{EngineClient, ForceUpdateSealing};
 use crypto::publickey::Signature;
 use engines::{
-    default_system_or_code_call, signer::EngineSigner, Engine, EngineError, ForkChoice, Seal,
-    SealingState,
+    default_system_or_code_call, signer::EngineSigner, Engine, EngineError, ForkChoice,
+    PendingBlockPreview, Seal, SealingState,
 };
 use error::{BlockError, Error};
 use ethereum_types::{Address, Public, H256, H512, U256};
@@ -47,12 +47,15 @@ use super::{
     contracts::{
         keygen_history::{all_parts_acks_available, initialize_synckeygen},
         staking::start_time_of_next_phase_transition,
-        validator_set::{get_pending_validators, is_pending_validator, ValidatorType},
+        validator_set::{
+            get_pending_validators, get_validator_pubkeys, is_pending_validator, ValidatorType,
+        },
     },
     contribution::{unix_now_millis, unix_now_secs},
     hbbft_state::{Batch, HbMessage, HbbftState, HoneyBadgerStep},
     keygen_transactions::KeygenTransactionSender,
     sealing::{self, RlpSig, Sealing},
+    service_transaction_lane::{contract_certifier, prioritize_service_transactions},
     NodeId,
 };
 use engines::hbbft::{
@@ -93,6 +96,8 @@ pub struct HoneyBadgerBFT {
     current_minimum_gas_price: Mutex<Option<U256>>,
     early_epoch_manager: Mutex<Option<HbbftEarlyEpochEndManager>>,
     hbbft_engine_cache: Mutex<HbbftEngineCache>,
+    /// Current validators with their node ids, read at the best block of the given hash.
+    validators_at_best_block: Mutex<Option<(H256, BTreeMap<Address, Public>)>>,
 }
 
 struct TransitionHandler {
@@ -439,6 +444,7 @@ impl HoneyBadgerBFT {
             current_minimum_gas_price: Mutex::new(None),
             early_epoch_manager: Mutex::new(None),
             hbbft_engine_cache: Mutex::new(HbbftEngineCache::new()),
+            validators_at_best_block: Mutex::new(None),
         });
 
         if !engine.params.is_unit_test.unwrap_or(false) {
@@ -455,6 +461,21 @@ impl HoneyBadgerBFT {
         Ok(engine)
     }
 
+    /// Returns the gas reserved for service transactions in the block `block_number`,
+    /// if the service transaction lane is active at that block.
+    fn service_lane_gas(&self, block_number: BlockNumber) -> Option<U256> {
+        self.params
+            .service_transaction_lane
+            .as_ref()
+            .filter(|lane| block_number >= lane.from_block)
+            .map(|lane| lane.gas.into())
+    }
+
+    fn next_block_service_lane_gas(&self, client: &dyn EngineClient) -> Option<U256> {
+        let next_block = client.block_number(BlockId::Latest)? + 1;
+        self.service_lane_gas(next_block)
+    }
+
     fn process_output(
         &self,
         client: Arc<dyn EngineClient>,
@@ -475,7 +496,7 @@ impl HoneyBadgerBFT {
         trace!(target: "consensus", "Batch received for epoch {}, creating new Block.", batch.epoch);
 
         // Decode and de-duplicate transactions
-        let batch_txns: Vec<_> = batch
+        let mut batch_txns: Vec<_> = batch
             .contributions
             .iter()
             .flat_map(|(_, c)| &c.transactions)
@@ -493,6 +514,14 @@ impl HoneyBadgerBFT {
         debug!(target: "consensus", "Block creation: Batch received for epoch {}, total {} contributions, with {} unique transactions.", batch.epoch, batch
             .contributions.iter().fold(0, |i, c| i + c.1.transactions.len()), batch_txns.len());
 
+        // Service transactions within the reserved lane gas are executed ahead of all others,
+        // so they can not be crowded out of the block. The reordering is deterministic.
+        if let Some(lane_gas) = self.service_lane_gas(batch.epoch) {
+            let certifier =
+                contract_certifier(&*client, BlockId::Number(batch.epoch.saturating_sub(1)));
+            batch_txns = prioritize_service_transactions(batch_txns, lane_gas, certifier);
+        }
+
         trace!(target: "consensus", "Block creation: transactions {}", batch_txns.iter().map(|x| x.hash.to_string()).join(", "));
 
         // Make sure the resulting transactions do not contain nonces out of order.
@@ -519,6 +548,9 @@ impl HoneyBadgerBFT {
             }
         };
 
+        // Warm the state cache on background threads while the block is created.
+        client.prefetch_state(&batch_txns, timestamp, batch.epoch);
+
         let random_number = batch
             .contributions
             .iter()
@@ -762,7 +794,11 @@ impl HoneyBadgerBFT {
         let step = self
             .hbbft_state
             .write()
-            .contribute_if_contribution_threshold_reached(client.clone(), &self.signer);
+            .contribute_if_contribution_threshold_reached(
+                client.clone(),
+                &self.signer,
+                self.next_block_service_lane_gas(&*client),
+            );
         if let Some((step, network_info)) = step {
             self.process_step(client, step, &network_info)
         } else {
@@ -780,7 +816,11 @@ impl HoneyBadgerBFT {
             .hbbft_state
             .try_write_for(std::time::Duration::from_millis(10))
         {
-            Some(mut state_lock) => state_lock.try_send_contribution(client.clone(), &self.signer),
+            Some(mut state_lock) => state_lock.try_send_contribution(
+                client.clone(),
+                &self.signer,
+                self.next_block_service_lane_gas(&*client),
+            ),
             None => {
                 return;
             }
@@ -1456,6 +1496,19 @@ impl Engine<EthereumMachine> for HoneyBadgerBFT {
         SealingState::NotReady
     }
 
+    fn pending_block_preview(&self) -> Option<PendingBlockPreview> {
+        let client = self.client_arc()?;
+        let next_block = client.block_number(BlockId::Latest)? + 1;
+
+        // Once a batch for the next block is agreed on, the miner's pending block is built from it.
+        if self.sealing.read().contains_key(&next_block) {
+            return Some(PendingBlockPreview::Batch);
+        }
+
+        let contribution = self.hbbft_state.read().pending_contribution()?;
+        Some(PendingBlockPreview::Contribution(contribution))
+    }
+
     fn on_transactions_imported(&self) {
         if self.params.is_unit_test.unwrap_or(false) {
             self.start_hbbft_epoch_if_ready();
@@ -1477,6 +1530,46 @@ impl Engine<EthereumMachine> for HoneyBadgerBFT {
         }
     }
 
+    fn validator_node_ids(&self) -> Vec<H512> {
+        let client = match self.client_arc() {
+            Some(client) => client,
+            None => return Vec::new(),
+        };
+        let own_address = self.signer.read().as_ref().map(|signer| signer.address());
+        let best_block_hash = client.chain_info().best_block_hash;
+
+        // the validators are asked for on every private transactions packet,
+        // so they are read only once per block.
+        let mut cached = self.validators_at_best_block.lock();
+        let is_stale = cached
+            .as_ref()
+            .map_or(true, |(block_hash, _)| *block_hash != best_block_hash);
+        if is_stale {
+            match get_validator_pubkeys(
+                &*client,
+                BlockId::Hash(best_block_hash),
+                ValidatorType::Current,
+            ) {
+                Ok(validators) => *cached = Some((best_block_hash, validators)),
+                Err(e) => {
+                    debug!(target: "engine", "could not read the current validators: {:?}", e);
+                    return Vec::new();
+                }
+            }
+        }
+
+        cached
+            .as_ref()
+            .map(|(_, validators)| {
+                validators
+                    .iter()
+                    .filter(|(address, _)| Some(**address) != own_address)
+                    .map(|(_, public)| *public)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     fn seal_fields(&self, _header: &Header) -> usize {
         1
     }
@@ -1513,6 +1606,10 @@ impl Engine<EthereumMachine> for HoneyBadgerBFT {
         false
     }
 
+    fn has_service_transaction_lane(&self) -> bool {
+        self.params.service_transaction_lane.is_some()
+    }
+
     fn on_before_transactions(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
         // trace!(target: "consensus", "on_before_transactions: {:?} extra data: {:?}", block.header.number(), block.header.extra_data());
         let random_numbers = self.random_numbers.read();
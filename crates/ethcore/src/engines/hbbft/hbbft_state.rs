@@ -1,7 +1,7 @@
 use client::traits::EngineClient;
 use engines::signer::EngineSigner;
 use ethcore_miner::pool::{PoolVerifiedTransaction, ScoredTransaction};
-use ethereum_types::U256;
+use ethereum_types::{Address, U256};
 use ethjson::spec::hbbft::HbbftNetworkFork;
 use hbbft::{
     crypto::{PublicKey, Signature},
@@ -15,7 +15,7 @@ use std::{
     sync::Arc,
     time::Duration,
 };
-use types::{header::Header, ids::BlockId};
+use types::{header::Header, ids::BlockId, transaction::SignedTransaction};
 
 use crate::engines::hbbft::contracts::permission::get_minimum_gas_from_permission_contract;
 
@@ -29,6 +29,7 @@ use super::{
     hbbft_early_epoch_end_manager::HbbftEarlyEpochEndManager,
     hbbft_network_fork_manager::HbbftNetworkForkManager,
     hbbft_peers_management::HbbftPeersManagement,
+    service_transaction_lane::{contract_certifier, select_service_senders},
     NodeId,
 };
 
@@ -49,6 +50,8 @@ pub(crate) struct HbbftState {
     last_posdao_epoch_start_block: Option<u64>,
     future_messages_cache: BTreeMap<u64, Vec<(NodeId, HbMessage)>>,
     fork_manager: HbbftNetworkForkManager,
+    /// Honey badger epoch and transactions of our last contribution.
+    own_contribution: Option<(u64, Vec<SignedTransaction>)>,
 }
 
 impl HbbftState {
@@ -64,6 +67,7 @@ impl HbbftState {
             last_fork_start_block: None,
             future_messages_cache: BTreeMap::new(),
             fork_manager: HbbftNetworkForkManager::new(),
+            own_contribution: None,
         }
     }
 
@@ -411,21 +415,26 @@ impl HbbftState {
         &mut self,
         client: Arc<dyn EngineClient>,
         signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+        service_lane_gas: Option<U256>,
     ) -> Option<(HoneyBadgerStep, NetworkInfo<NodeId>)> {
         // If honey_badger is None we are not a validator, nothing to do.
         let honey_badger = self.honey_badger.as_mut()?;
         let network_info = self.network_info.as_ref()?;
 
         if honey_badger.received_proposals() > network_info.num_faulty() {
-            return self.try_send_contribution(client, signer);
+            return self.try_send_contribution(client, signer, service_lane_gas);
         }
         None
     }
 
+    /// Proposes our contribution for the current honey badger epoch.
+    /// If `service_lane_gas` is set, service transactions up to that amount of gas
+    /// are contributed in addition to the randomly selected transactions.
     pub fn try_send_contribution(
         &mut self,
         client: Arc<dyn EngineClient>,
         signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+        service_lane_gas: Option<U256>,
     ) -> Option<(HoneyBadgerStep, NetworkInfo<NodeId>)> {
         // Make sure we are in the most current epoch.
         self.skip_to_current_epoch(client.clone(), signer)?;
@@ -457,7 +466,35 @@ impl HbbftState {
         }
 
         let network_info = self.network_info.as_ref()?.clone();
+        let epoch = honey_badger.epoch();
+        let signed_transactions =
+            Self::select_contribution(&*client, &network_info, service_lane_gas, epoch)?;
+        self.own_contribution = Some((epoch, signed_transactions.clone()));
 
+        // Now we can select the transactions to include in our contribution.
+        let input_contribution = Contribution::new(&signed_transactions);
+
+        let mut rng = rand::thread_rng();
+        let step = honey_badger.propose(&input_contribution, &mut rng);
+        match step {
+            Ok(step) => Some((step, network_info)),
+            _ => {
+                // TODO: Report detailed consensus step errors
+                error!(target: "consensus", "Error on proposing Contribution.");
+                None
+            }
+        }
+    }
+
+    /// Selects the transactions of our contribution for the given honey badger epoch.
+    /// If `service_lane_gas` is set, service transactions up to that amount of gas
+    /// are selected in addition to the randomly selected transactions.
+    fn select_contribution(
+        client: &dyn EngineClient,
+        network_info: &NetworkInfo<NodeId>,
+        service_lane_gas: Option<U256>,
+        epoch: u64,
+    ) -> Option<Vec<SignedTransaction>> {
         // Choose a random subset of the maximum transactions, but at least 1.
         // Since not all nodes may contribute we do not use the full number of nodes
         // but the minimum number of nodes required to build a block.
@@ -486,8 +523,6 @@ impl HbbftState {
                 .push(t.clone());
         }
 
-        // Randomly select a sender and add all their transactions
-        // until we at least reached the target contribution size.
         let mut transactions_subset = Vec::new();
         let mut my_rng = rand::thread_rng();
 
@@ -498,45 +533,77 @@ impl HbbftState {
             return None;
         };
 
+        // Even after block import there may still be transactions in the pending set which already
+        // have been included on the chain. We filter out transactions where the nonce is too low.
+        let add_sender_transactions =
+            |transactions_by_sender: &mut HashMap<Address, Vec<Arc<PoolVerifiedTransaction>>>,
+             sender: &Address,
+             subset: &mut Vec<Arc<PoolVerifiedTransaction>>| {
+                // add all transactions for that sender and delete the sender from the map.
+                if let Some(ts) = transactions_by_sender.remove(sender) {
+                    let min_nonce = full_client.latest_nonce(sender);
+                    for tx in ts {
+                        if tx.nonce() >= min_nonce {
+                            subset.push(tx);
+                        } else {
+                            debug!(target: "consensus", "Block creation: Pending transaction with nonce too low, got {}, expected at least {}", tx.nonce(), min_nonce);
+                        }
+                    }
+                }
+            };
+
+        // Service transactions fitting into the reserved lane are always contributed,
+        // they do not count towards the target contribution size.
+        if let Some(lane_gas) = service_lane_gas {
+            let queued = max_transactions_for_block.iter().map(|t| t.signed());
+            let certifier = contract_certifier(client, BlockId::Latest);
+            for sender in select_service_senders(queued, lane_gas, certifier) {
+                add_sender_transactions(
+                    &mut transactions_by_sender,
+                    &sender,
+                    &mut transactions_subset,
+                );
+            }
+        }
+        let transactions_subset_size = transactions_subset.len() + transactions_subset_size;
+
+        // Randomly select a sender and add all their transactions
+        // until we at least reached the target contribution size.
         while transactions_subset.len() < transactions_subset_size {
             let chosen_key = match transactions_by_sender.keys().choose(&mut my_rng) {
                 None => break,
                 Some(key) => key.clone(),
             };
-            // add all transactions for that sender and delete the sender from the map.
-            if let Some(ts) = transactions_by_sender.remove(&chosen_key) {
-                // Even after block import there may still be transactions in the pending set which already
-                // have been included on the chain. We filter out transactions where the nonce is too low.
-                let min_nonce = full_client.latest_nonce(&chosen_key);
-                for tx in ts {
-                    if tx.nonce() >= min_nonce {
-                        transactions_subset.push(tx);
-                    } else {
-                        debug!(target: "consensus", "Block creation: Pending transaction with nonce too low, got {}, expected at least {}", tx.nonce(), min_nonce);
-                    }
-                }
-            }
+            add_sender_transactions(
+                &mut transactions_by_sender,
+                &chosen_key,
+                &mut transactions_subset,
+            );
         }
 
-        trace!(target: "consensus", "Block creation: Honeybadger epoch {}, Transactions subset target size: {}, actual size: {}, from available {}.", honey_badger.epoch(), transactions_subset_size, transactions_subset.len(), max_transactions_for_block.len());
+        trace!(target: "consensus", "Block creation: Honeybadger epoch {}, Transactions subset target size: {}, actual size: {}, from available {}.", epoch, transactions_subset_size, transactions_subset.len(), max_transactions_for_block.len());
 
-        let signed_transactions = transactions_subset
-            .iter()
-            .map(|txn| txn.signed().clone())
-            .collect();
-
-        // Now we can select the transactions to include in our contribution.
-        let input_contribution = Contribution::new(&signed_transactions);
+        Some(
+            transactions_subset
+                .iter()
+                .map(|txn| txn.signed().clone())
+                .collect(),
+        )
+    }
 
-        let mut rng = rand::thread_rng();
-        let step = honey_badger.propose(&input_contribution, &mut rng);
-        match step {
-            Ok(step) => Some((step, network_info)),
-            _ => {
-                // TODO: Report detailed consensus step errors
-                error!(target: "consensus", "Error on proposing Contribution.");
-                None
+    /// Transactions of our contribution to the next block, as proposed for the current epoch.
+    /// Until we proposed, the contribution is reported as empty.
+    /// Returns `None` if we are not a validator.
+    pub fn pending_contribution(&self) -> Option<Vec<SignedTransaction>> {
+        if !self.is_validator() {
+            return None;
+        }
+        let epoch = self.honey_badger.as_ref()?.epoch();
+        match self.own_contribution {
+            Some((proposed_epoch, ref transactions)) if proposed_epoch == epoch => {
+                Some(transactions.clone())
             }
+            _ => Some(Vec::new()),
         }
     }
 
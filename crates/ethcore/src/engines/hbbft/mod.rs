@@ -10,6 +10,7 @@ mod hbbft_peers_management;
 mod hbbft_state;
 mod keygen_transactions;
 mod sealing;
+mod service_transaction_lane;
 #[cfg(test)]
 mod test;
 mod utils;
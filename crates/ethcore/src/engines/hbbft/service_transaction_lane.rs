@@ -0,0 +1,188 @@
+//! Reserved block space for service transactions.
+//!
+//! Service transactions are zero gas price transactions of senders certified by the
+//! service transaction contract, like key generation parts and acks or availability announcements.
+
+use client::EngineClient;
+use engines::hbbft::contracts::service_transaction::is_certified;
+use ethereum_types::{Address, U256};
+use std::collections::{HashMap, HashSet};
+use types::{ids::BlockId, transaction::SignedTransaction};
+
+/// Returns true if the transaction may belong into the service transaction lane.
+/// The sender needs to be certified in addition.
+pub fn is_service_transaction(transaction: &SignedTransaction) -> bool {
+    transaction.tx().gas_price.is_zero()
+}
+
+/// Returns a predicate checking senders against the service transaction contract at `block_id`.
+pub fn contract_certifier<'a>(
+    client: &'a dyn EngineClient,
+    block_id: BlockId,
+) -> impl FnMut(&Address) -> bool + 'a {
+    move |sender| match is_certified(client, block_id, sender) {
+        Ok(certified) => certified,
+        Err(e) => {
+            debug!(target: "consensus", "Could not check service transaction certification of {}: {:?}", sender, e);
+            false
+        }
+    }
+}
+
+/// Selects the certified senders of service transactions whose transactions fit into `lane_gas`.
+///
+/// Senders are considered in order of their first transaction. Senders with any
+/// non-service transaction are not selected, to avoid nonce gaps.
+pub fn select_service_senders<'a, I, F>(
+    transactions: I,
+    lane_gas: U256,
+    mut is_certified: F,
+) -> HashSet<Address>
+where
+    I: IntoIterator<Item = &'a SignedTransaction>,
+    F: FnMut(&Address) -> bool,
+{
+    let mut senders = Vec::new();
+    let mut gas_by_sender: HashMap<Address, Option<U256>> = HashMap::new();
+    for transaction in transactions {
+        let sender = transaction.sender();
+        let gas = gas_by_sender.entry(sender).or_insert_with(|| {
+            senders.push(sender);
+            Some(U256::zero())
+        });
+        *gas = match *gas {
+            Some(gas) if is_service_transaction(transaction) => {
+                Some(gas.saturating_add(transaction.tx().gas))
+            }
+            _ => None,
+        };
+    }
+
+    let mut used_gas = U256::zero();
+    let mut selected = HashSet::new();
+    for sender in senders {
+        if let Some(gas) = gas_by_sender[&sender] {
+            if used_gas.saturating_add(gas) <= lane_gas && is_certified(&sender) {
+                used_gas = used_gas + gas;
+                selected.insert(sender);
+            }
+        }
+    }
+
+    selected
+}
+
+/// Moves the service transactions of certified senders fitting into `lane_gas` to the front.
+///
+/// The relative order of all transactions is preserved otherwise,
+/// so the result is deterministic for every validator.
+pub fn prioritize_service_transactions<F>(
+    transactions: Vec<SignedTransaction>,
+    lane_gas: U256,
+    is_certified: F,
+) -> Vec<SignedTransaction>
+where
+    F: FnMut(&Address) -> bool,
+{
+    let service_senders = select_service_senders(&transactions, lane_gas, is_certified);
+    if service_senders.is_empty() {
+        return transactions;
+    }
+
+    let (mut lane, regular): (Vec<_>, Vec<_>) = transactions
+        .into_iter()
+        .partition(|transaction| service_senders.contains(&transaction.sender()));
+    lane.extend(regular);
+    lane
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::publickey::{Generator, KeyPair, Random};
+    use types::transaction::{Action, Transaction, TypedTransaction};
+
+    fn transaction(keypair: &KeyPair, nonce: u64, gas_price: u64) -> SignedTransaction {
+        TypedTransaction::Legacy(Transaction {
+            action: Action::Call(Address::from_low_u64_be(5798439875)),
+            value: U256::zero(),
+            data: vec![],
+            gas: U256::from(100_000),
+            gas_price: gas_price.into(),
+            nonce: nonce.into(),
+        })
+        .sign(keypair.secret(), None)
+    }
+
+    #[test]
+    fn should_move_service_transactions_to_the_front() {
+        let regular = Random.generate();
+        let service = Random.generate();
+        let transactions = vec![
+            transaction(&regular, 0, 10),
+            transaction(&service, 0, 0),
+            transaction(&regular, 1, 10),
+            transaction(&service, 1, 0),
+        ];
+
+        let ordered =
+            prioritize_service_transactions(transactions.clone(), 200_000.into(), |_| true);
+
+        assert_eq!(
+            ordered,
+            vec![
+                transactions[1].clone(),
+                transactions[3].clone(),
+                transactions[0].clone(),
+                transactions[2].clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_respect_lane_gas_and_mixed_senders() {
+        let mixed = Random.generate();
+        let first = Random.generate();
+        let second = Random.generate();
+        let transactions = vec![
+            transaction(&mixed, 0, 10),
+            transaction(&mixed, 1, 0),
+            transaction(&first, 0, 0),
+            transaction(&second, 0, 0),
+        ];
+
+        let ordered =
+            prioritize_service_transactions(transactions.clone(), 150_000.into(), |_| true);
+
+        // only the first service sender fits, the mixed sender keeps its place.
+        assert_eq!(
+            ordered,
+            vec![
+                transactions[2].clone(),
+                transactions[0].clone(),
+                transactions[1].clone(),
+                transactions[3].clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_not_prioritize_uncertified_senders() {
+        let certified = Random.generate();
+        let uncertified = Random.generate();
+        let transactions = vec![
+            transaction(&uncertified, 0, 0),
+            transaction(&certified, 0, 0),
+        ];
+
+        let ordered =
+            prioritize_service_transactions(transactions.clone(), 1_000_000.into(), |sender| {
+                *sender == certified.address()
+            });
+
+        assert_eq!(
+            ordered,
+            vec![transactions[1].clone(), transactions[0].clone()]
+        );
+    }
+}
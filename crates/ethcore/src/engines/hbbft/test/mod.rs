@@ -10,6 +10,7 @@ use super::{
     test::hbbft_test_client::{create_hbbft_client, create_hbbft_clients, HbbftTestClient},
 };
 use client::traits::BlockInfo;
+use engines::PendingBlockPreview;
 use crypto::publickey::{Generator, KeyPair, Random, Secret};
 use ethereum_types::{Address, U256};
 use std::str::FromStr;
@@ -58,6 +59,25 @@ fn test_miner_transaction_injection() {
     assert_eq!(block.transactions_count(), 1);
 }
 
+#[test]
+fn test_pending_block_preview() {
+    let mut test_data = create_hbbft_client(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+
+    // With instant sealing the transaction is included in block 1 right away.
+    test_data.create_some_transaction(None);
+    assert_eq!(test_data.client.chain().best_block_number(), 1);
+
+    // No batch for block 2 yet, and no transactions left to contribute to it.
+    assert_eq!(
+        test_data.client.engine().pending_block_preview(),
+        Some(PendingBlockPreview::Contribution(Vec::new()))
+    );
+
+    // Nodes which are not validators do not contribute.
+    let observer = create_hbbft_client(Random.generate());
+    assert_eq!(observer.client.engine().pending_block_preview(), None);
+}
+
 #[test]
 fn test_staking_account_creation() {
     // Create Master of Ceremonies
@@ -203,6 +203,15 @@ pub enum SealingState {
     External,
 }
 
+/// What the engine knows about the block following the best block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PendingBlockPreview {
+    /// No block has been agreed on yet, these are the transactions this node proposes for it.
+    Contribution(Vec<SignedTransaction>),
+    /// The block has been agreed on, the miner's pending block is the block being sealed.
+    Batch,
+}
+
 /// A system-calling closure. Enacts calls on a block's state from the system address.
 pub type SystemCall<'a> = dyn FnMut(Address, Vec<u8>) -> Result<Vec<u8>, String> + 'a;
 
@@ -369,6 +378,12 @@ pub trait Engine<M: Machine>: Sync + Send {
         SealingState::External
     }
 
+    /// Preview of the next block for engines which agree on the block contents
+    /// before sealing, `None` if the miner's pending block is the preview.
+    fn pending_block_preview(&self) -> Option<PendingBlockPreview> {
+        None
+    }
+
     /// Called in `miner.chain_new_blocks` if the engine wishes to `update_sealing`
     /// after a block was recently sealed.
     ///
@@ -481,6 +496,12 @@ pub trait Engine<M: Machine>: Sync + Send {
         Err(EngineError::UnexpectedMessage)
     }
 
+    /// Network node ids of the current validators, excluding this node.
+    /// Used to forward private transactions directly to the block producers.
+    fn validator_node_ids(&self) -> Vec<H512> {
+        Vec::new()
+    }
+
     /// Register a component which signs consensus messages.
     fn set_signer(&self, _signer: Option<Box<dyn EngineSigner>>) {}
 
@@ -581,6 +602,12 @@ pub trait Engine<M: Machine>: Sync + Send {
         true
     }
 
+    /// Whether the engine reserves block space for service transactions, which the
+    /// transaction pool then scores above all regular transactions.
+    fn has_service_transaction_lane(&self) -> bool {
+        false
+    }
+
     /// allows engines to define a block that should not get pruned in the DB.
     /// This is useful for engines that need to keep a certain block in the DB.
     fn pruning_protection_block_number(&self) -> Option<u64> {
@@ -292,6 +292,22 @@ pub fn new_berlin_to_london_test() -> Spec {
     )
 }
 
+/// Create a new Foundation Shanghai era spec.
+pub fn new_shanghai_test() -> Spec {
+    load(
+        None,
+        include_bytes!("../../res/chainspec/test/shanghai_test.json"),
+    )
+}
+
+/// Create a new Foundation Cancun era spec.
+pub fn new_cancun_test() -> Spec {
+    load(
+        None,
+        include_bytes!("../../res/chainspec/test/cancun_test.json"),
+    )
+}
+
 /// Create a new Musicoin-MCIP3-era spec.
 pub fn new_mcip3_test() -> Spec {
     load(
@@ -206,6 +206,8 @@ pub enum CallError {
     StateCorrupt,
     /// Error executing.
     Execution(ExecutionError),
+    /// The requested simulation is inconsistent (e.g. block numbers are not increasing).
+    InvalidSimulation(String),
 }
 
 impl From<ExecutionError> for CallError {
@@ -224,6 +226,7 @@ impl fmt::Display for CallError {
             Exceptional(ref e) => format!("An exception ({}) happened in the execution", e),
             StateCorrupt => "Stored state found to be corrupted.".into(),
             Execution(ref e) => format!("{}", e),
+            InvalidSimulation(ref e) => format!("Invalid simulation: {}", e),
         };
 
         f.write_fmt(format_args!("Transaction execution error ({}).", msg))
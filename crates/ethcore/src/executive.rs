@@ -31,7 +31,7 @@ use transaction_ext::Transaction;
 use types::transaction::{Action, SignedTransaction, TypedTransaction};
 use vm::{
     self, AccessList, ActionParams, ActionValue, CleanDustMode, CreateContractAddress, EnvInfo,
-    ResumeCall, ResumeCreate, ReturnData, Schedule, TrapError,
+    ResumeCall, ResumeCreate, ReturnData, Schedule, TransientStorage, TrapError,
 };
 
 #[cfg(any(test, feature = "test-helpers"))]
@@ -235,15 +235,17 @@ pub struct CallCreateExecutive<'a> {
 }
 
 impl<'a> CallCreateExecutive<'a> {
-    /// Create new state with access list.
+    /// Create new state with access list and transient storage.
     pub fn new_substate(params: &ActionParams, schedule: &'a Schedule) -> Substate {
-        if schedule.eip2929 {
+        let mut substate = if schedule.eip2929 {
             let mut substate = Substate::from_access_list(&params.access_list);
             substate.access_list.insert_address(params.address);
             substate
         } else {
             Substate::default()
-        }
+        };
+        substate.transient_storage = params.transient_storage.clone();
+        substate
     }
 
     /// Create a new call executive using raw data.
@@ -441,9 +443,6 @@ impl<'a> CallCreateExecutive<'a> {
             | Err(vm::Error::MutableCallInStaticContext)
             | Err(vm::Error::OutOfBounds)
             | Err(vm::Error::Reverted)
-            | Err(vm::Error::SubStackUnderflow { .. })
-            | Err(vm::Error::OutOfSubStack { .. })
-            | Err(vm::Error::InvalidSubEntry)
             | Err(vm::Error::InvalidCode)
             | Ok(FinalizationResult {
                 apply_state: false, ..
@@ -455,6 +454,7 @@ impl<'a> CallCreateExecutive<'a> {
                 }
                 state.revert_to_checkpoint();
                 un_substate.access_list.rollback();
+                un_substate.transient_storage.rollback();
             }
             Ok(_) | Err(vm::Error::Internal(_)) => {
                 state.discard_checkpoint();
@@ -673,6 +673,12 @@ impl<'a> CallCreateExecutive<'a> {
                     }
                 }
 
+                if self.schedule.eip6780 {
+                    unconfirmed_substate
+                        .transient_storage
+                        .insert_created(params.address);
+                }
+
                 let origin_info = OriginInfo::from(&params);
                 let exec = self.factory.create(params, self.schedule, self.depth);
 
@@ -976,7 +982,7 @@ impl<'a> CallCreateExecutive<'a> {
 				},
 				Some((_, _, Err(TrapError::Call(subparams, resume)))) => {
 					tracer.prepare_trace_call(&subparams, resume.depth + 1, resume.machine.builtin(&subparams.address, resume.info.number).is_some());
-					vm_tracer.prepare_subtrace(subparams.code.as_ref().map_or_else(|| &[] as &[u8], |d| &*d as &[u8]));
+					vm_tracer.prepare_subtrace(&subparams.code_address, subparams.code.as_ref().map_or_else(|| &[] as &[u8], |d| &*d as &[u8]));
 
 					let sub_exec = CallCreateExecutive::new_call_raw(
 						subparams,
@@ -995,7 +1001,7 @@ impl<'a> CallCreateExecutive<'a> {
 				},
 				Some((_, _, Err(TrapError::Create(subparams, address, resume)))) => {
 					tracer.prepare_trace_create(&subparams);
-					vm_tracer.prepare_subtrace(subparams.code.as_ref().map_or_else(|| &[] as &[u8], |d| &*d as &[u8]));
+					vm_tracer.prepare_subtrace(&subparams.code_address, subparams.code.as_ref().map_or_else(|| &[] as &[u8], |d| &*d as &[u8]));
 
 					let sub_exec = CallCreateExecutive::new_create_raw(
 						subparams,
@@ -1147,12 +1153,26 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
         let sender = t.sender();
         let nonce = self.state.nonce(&sender)?;
 
+        if schedule.eip3860
+            && t.tx().action == Action::Create
+            && t.tx().data.len() > schedule.max_initcode_size()
+        {
+            return Err(ExecutionError::TransactionMalformed(format!(
+                "Initcode size {} exceeds limit {} (EIP-3860)",
+                t.tx().data.len(),
+                schedule.max_initcode_size()
+            )));
+        }
+
         let mut base_gas_required = U256::from(t.tx().gas_required(&schedule));
 
         let mut access_list = AccessList::new(schedule.eip2929);
 
         if schedule.eip2929 {
             access_list.insert_address(sender);
+            if schedule.eip3651 {
+                access_list.insert_address(self.info.author);
+            }
             for (address, builtin) in self.machine.builtins() {
                 if builtin.is_active(self.info.number) {
                     access_list.insert_address(*address);
@@ -1162,11 +1182,10 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
             if let Some(al) = t.access_list() {
                 for item in al.iter() {
                     access_list.insert_address(item.0);
-                    base_gas_required += vm::schedule::EIP2930_ACCESS_LIST_ADDRESS_COST.into();
+                    base_gas_required += schedule.access_list_address_cost.into();
                     for key in item.1.iter() {
                         access_list.insert_storage_key(item.0, *key);
-                        base_gas_required +=
-                            vm::schedule::EIP2930_ACCESS_LIST_STORAGE_KEY_COST.into();
+                        base_gas_required += schedule.access_list_storage_key_cost.into();
                     }
                 }
             }
@@ -1274,6 +1293,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
                     call_type: CallType::None,
                     params_type: vm::ParamsType::Embedded,
                     access_list: access_list,
+                    transient_storage: TransientStorage::new(),
                 };
                 let res = self.create(params, &mut substate, &mut tracer, &mut vm_tracer);
                 let out = match &res {
@@ -1298,6 +1318,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
                     call_type: CallType::Call,
                     params_type: vm::ParamsType::Separate,
                     access_list: access_list,
+                    transient_storage: TransientStorage::new(),
                 };
                 let res = self.call(params, &mut substate, &mut tracer, &mut vm_tracer);
                 let out = match &res {
@@ -1343,6 +1364,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
                 .is_some(),
         );
         vm_tracer.prepare_subtrace(
+            &params.code_address,
             params
                 .code
                 .as_ref()
@@ -1412,6 +1434,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
     {
         tracer.prepare_trace_create(&params);
         vm_tracer.prepare_subtrace(
+            &params.code_address,
             params
                 .code
                 .as_ref()
@@ -1548,7 +1571,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
             fees_value,
             &self.info.author
         );
-        self.state.add_balance(
+        self.state.credit_balance(
             &self.info.author,
             &fees_value,
             substate.to_cleanup_mode(&schedule),
@@ -1558,7 +1581,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
             && self.machine.params().eip1559_fee_collector.is_some()
             && self.info.number >= self.machine.params().eip1559_fee_collector_transition
         {
-            self.state.add_balance(
+            self.state.credit_balance(
                 &self.machine.params().eip1559_fee_collector.unwrap(),
                 &burnt_fee,
                 substate.to_cleanup_mode(&schedule),
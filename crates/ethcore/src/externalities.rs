@@ -17,6 +17,7 @@
 //! Transaction Execution environment.
 use bytes::Bytes;
 use ethereum_types::{Address, BigEndianHash, H256, U256};
+use evm::eof;
 use executive::*;
 use machine::EthereumMachine as Machine;
 use state::{Backend as StateBackend, CleanupMode, State, Substate};
@@ -25,7 +26,8 @@ use trace::{Tracer, VMTracer};
 use types::transaction::UNSIGNED_SENDER;
 use vm::{
     self, AccessList, ActionParams, ActionValue, CallType, ContractCreateResult,
-    CreateContractAddress, EnvInfo, Ext, MessageCallResult, ReturnData, Schedule, TrapKind,
+    CreateContractAddress, EnvInfo, Ext, MessageCallResult, ReturnData, Schedule, TransientStorage,
+    TrapKind,
 };
 
 /// Policy for handling output data on `RETURN` opcode.
@@ -43,6 +45,8 @@ pub struct OriginInfo {
     origin: Address,
     gas_price: U256,
     value: U256,
+    /// Whether the executed code is an EOF container.
+    eof: bool,
 }
 
 impl OriginInfo {
@@ -55,6 +59,7 @@ impl OriginInfo {
             value: match params.value {
                 ActionValue::Transfer(val) | ActionValue::Apparent(val) => val,
             },
+            eof: params.code.as_ref().map_or(false, |code| eof::is_eof(code)),
         }
     }
 }
@@ -150,6 +155,24 @@ where
         }
     }
 
+    fn transient_storage_at(&self, key: &H256) -> vm::Result<H256> {
+        Ok(self
+            .substate
+            .transient_storage
+            .storage_at(&self.origin_info.address, key))
+    }
+
+    fn set_transient_storage(&mut self, key: H256, value: H256) -> vm::Result<()> {
+        if self.static_flag {
+            Err(vm::Error::MutableCallInStaticContext)
+        } else {
+            self.substate
+                .transient_storage
+                .set_storage(self.origin_info.address, key, value);
+            Ok(())
+        }
+    }
+
     fn is_static(&self) -> bool {
         return self.static_flag;
     }
@@ -203,6 +226,7 @@ where
                 call_type: CallType::Call,
                 params_type: vm::ParamsType::Separate,
                 access_list: AccessList::default(),
+                transient_storage: TransientStorage::default(),
             };
 
             let mut ex = Executive::new(self.state, self.env_info, self.machine, self.schedule);
@@ -290,6 +314,7 @@ where
             call_type: CallType::None,
             params_type: vm::ParamsType::Embedded,
             access_list: self.substate.access_list.clone(),
+            transient_storage: self.substate.transient_storage.clone(),
         };
 
         if !self.static_flag {
@@ -370,6 +395,7 @@ where
             call_type: call_type,
             params_type: vm::ParamsType::Separate,
             access_list: self.substate.access_list.clone(),
+            transient_storage: self.substate.transient_storage.clone(),
         };
 
         if let Some(value) = value {
@@ -429,7 +455,16 @@ where
                         false => Ok(*gas),
                     };
                 }
-                if self.schedule.eip3541 && data.get(0) == Some(&0xefu8) {
+                // EOF initcode deploys valid containers only, legacy initcode never deploys EOF code.
+                let invalid_code = if self.schedule.eip3540 && self.origin_info.eof {
+                    eof::Container::new(data)
+                        .and_then(|container| container.check_schedule(self.schedule))
+                        .is_err()
+                } else {
+                    (self.schedule.eip3541 && data.get(0) == Some(&0xefu8))
+                        || (self.schedule.eip3540 && eof::is_eof(data))
+                };
+                if invalid_code {
                     return match self.schedule.exceptional_failed_code_deposit {
                         true => Err(vm::Error::InvalidCode),
                         false => Ok(*gas),
@@ -467,6 +502,24 @@ where
 
         let address = self.origin_info.address.clone();
         let balance = self.balance(&address)?;
+
+        // EIP-6780: only contracts created in the same transaction are deleted,
+        // others just send their balance to the beneficiary.
+        if self.schedule.eip6780 && !self.substate.transient_storage.is_created(&address) {
+            if &address != refund_address {
+                trace!(target: "ext", "Sending balance {} -> {} (xfer: {})", address, refund_address, balance);
+                self.state.transfer_balance(
+                    &address,
+                    refund_address,
+                    &balance,
+                    self.substate.to_cleanup_mode(&self.schedule),
+                )?;
+            }
+            self.tracer
+                .trace_suicide(address, balance, refund_address.clone());
+            return Ok(());
+        }
+
         if &address == refund_address {
             // TODO [todr] To be consistent with CPP client we set balance to 0 in that case.
             self.state
@@ -563,6 +616,7 @@ mod tests {
     use super::*;
     use ethereum_types::{Address, U256};
     use evm::{CallType, EnvInfo, Ext};
+    use rustc_hex::FromHex;
     use state::{State, Substate};
     use std::str::FromStr;
     use test_helpers::get_temp_state;
@@ -574,6 +628,7 @@ mod tests {
             origin: Address::zero(),
             gas_price: U256::zero(),
             value: U256::zero(),
+            eof: false,
         }
     }
 
@@ -960,4 +1015,82 @@ mod tests {
         let result = call_ret(Schedule::new_london(), &data);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn eip_3540() {
+        let ret = |schedule: Schedule, eof_initcode: bool, data: &ReturnData| {
+            let mut setup = TestSetup::default();
+            setup.schedule = schedule;
+            let mut tracer = NoopTracer;
+            let mut vm_tracer = NoopVMTracer;
+            let mut origin = get_test_origin();
+            origin.eof = eof_initcode;
+            let ext = Externalities::new(
+                &mut setup.state,
+                &setup.env_info,
+                &setup.machine,
+                &setup.schedule,
+                0,
+                0,
+                &origin,
+                &mut setup.sub_state,
+                OutputPolicy::InitContract,
+                &mut tracer,
+                &mut vm_tracer,
+                false,
+            );
+            ext.ret(&U256::from(100000), &data, true)
+        };
+        let call_ret = |schedule: Schedule, data: &ReturnData| ret(schedule, false, data);
+        let eof_ret = |schedule: Schedule, data: &ReturnData| ret(schedule, true, data);
+        let eof_schedule = || {
+            let mut schedule = Schedule::new_cancun();
+            schedule.eip3540 = true;
+            schedule
+        };
+
+        // container with a single STOP instruction
+        let code = "ef00010100040200010001030000000000000000"
+            .from_hex()
+            .unwrap();
+        let data = ReturnData::new(code.clone(), 0, code.len());
+        assert!(call_ret(Schedule::new_cancun(), &data).is_err());
+        // valid containers are not deployed by legacy creates either
+        assert!(call_ret(eof_schedule(), &data).is_err());
+
+        // the 0xef00 prefix is rejected without EIP-3541
+        let mut schedule = Schedule::new_berlin();
+        schedule.eip3540 = true;
+        assert!(call_ret(schedule, &data).is_err());
+
+        // EIP-3541 still rejects other code starting with 0xef
+        let data = ReturnData::new(vec![0xefu8, 0x01u8], 0, 2);
+        assert!(call_ret(eof_schedule(), &data).is_err());
+
+        // legacy code is deployed
+        let data = ReturnData::new(vec![0x60u8, 0x00u8], 0, 2);
+        assert!(call_ret(eof_schedule(), &data).is_ok());
+
+        // EOF initcode deploys valid containers
+        let data = ReturnData::new(code.clone(), 0, code.len());
+        assert!(eof_ret(eof_schedule(), &data).is_ok());
+
+        // but not truncated containers
+        let data = ReturnData::new(code.clone(), 0, code.len() - 1);
+        assert!(eof_ret(eof_schedule(), &data).is_err());
+
+        // nor legacy code
+        let data = ReturnData::new(vec![0x60u8, 0x00u8], 0, 2);
+        assert!(eof_ret(eof_schedule(), &data).is_err());
+
+        // nor containers using instructions which are not active, PUSH0 POP STOP
+        let code = "ef0001010004020001000303000000000000015f5000"
+            .from_hex()
+            .unwrap();
+        let data = ReturnData::new(code.clone(), 0, code.len());
+        assert!(eof_ret(eof_schedule(), &data).is_ok());
+        let mut schedule = Schedule::new_berlin();
+        schedule.eip3540 = true;
+        assert!(eof_ret(schedule, &data).is_err());
+    }
 }
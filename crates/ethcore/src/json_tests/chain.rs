@@ -18,8 +18,8 @@ use crate::exit::ShutdownManager;
 
 use super::HookType;
 use client::{
-    Balance, BlockChainClient, BlockId, ChainInfo, Client, ClientConfig, EvmTestClient,
-    ImportBlock, Nonce, StateOrBlock,
+    BadBlocks, Balance, BlockChainClient, BlockId, BlockStatus, ChainInfo, Client, ClientConfig,
+    EvmTestClient, ImportBlock, Nonce, StateOrBlock,
 };
 use ethereum_types::{H256, U256};
 use ethjson;
@@ -30,14 +30,50 @@ use rustc_hex::ToHex;
 use spec::Genesis;
 use std::{path::Path, sync::Arc};
 use test_helpers;
+use types::BlockNumber;
 use verification::{queue::kind::blocks::Unverified, VerifierType};
 
+/// Block of a blockchain test that did not import as the test expects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedBlock {
+    /// Position of the block in the test.
+    pub index: usize,
+    /// Block number, unless the block could not be decoded.
+    pub number: Option<BlockNumber>,
+    /// Block hash, unless the block could not be decoded.
+    pub hash: Option<H256>,
+}
+
+/// Reason a blockchain test failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainTestFailure {
+    /// First block which did not import as expected, if any.
+    pub block: Option<FailedBlock>,
+    /// Description of the failure.
+    pub reason: String,
+}
+
+/// Outcome of a single blockchain test.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainTestResult {
+    /// Best block and post state match the expectations.
+    Passed,
+    /// The network of the test is not supported, or its blocks can't be decoded.
+    Unsupported,
+    /// The test failed.
+    Failed(ChainTestFailure),
+}
+
 fn check_poststate(
     client: &Arc<Client>,
     test_name: &str,
     post_state: ethjson::blockchain::State,
-) -> bool {
-    let mut success = true;
+) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    let mut mismatch = |message: String| {
+        warn!(target: "json-tests", "{} – {}", test_name, message);
+        mismatches.push(message);
+    };
 
     for (address, expected) in post_state {
         if let Some(expected_balance) = expected.balance {
@@ -49,9 +85,10 @@ fn check_poststate(
                 )
                 .unwrap();
             if expected_balance != current_balance {
-                warn!(target: "json-tests", "{} – Poststate {:?} balance mismatch current={} expected={}",
-					test_name, address, current_balance, expected_balance);
-                success = false;
+                mismatch(format!(
+                    "Poststate {:?} balance mismatch current={} expected={}",
+                    address, current_balance, expected_balance
+                ));
             }
         }
 
@@ -61,9 +98,10 @@ fn check_poststate(
                 .nonce(&address.clone().into(), BlockId::Latest)
                 .unwrap();
             if expected_nonce != current_nonce {
-                warn!(target: "json-tests", "{} – Poststate {:?} nonce mismatch current={} expected={}",
-					test_name, address, current_nonce, expected_nonce);
-                success = false;
+                mismatch(format!(
+                    "Poststate {:?} nonce mismatch current={} expected={}",
+                    address, current_nonce, expected_nonce
+                ));
             }
         }
 
@@ -77,9 +115,10 @@ fn check_poststate(
                 _ => "".to_string(),
             };
             if current_code != expected_code {
-                warn!(target: "json-tests", "{} – Poststate {:?} code mismatch current={} expected={}",
-					test_name, address, current_code, expected_code);
-                success = false;
+                mismatch(format!(
+                    "Poststate {:?} code mismatch current={} expected={}",
+                    address, current_code, expected_code
+                ));
             }
         }
 
@@ -103,24 +142,165 @@ fn check_poststate(
                     let position: &[u8] = position.as_ref();
                     let current_value: &[u8] = current_value.as_ref();
                     let expected_value: &[u8] = expected_value.as_ref();
-                    warn!(target: "json-tests", "{} – Poststate {:?} state {} mismatch actual={} expected={}",
-						test_name, address, position.to_hex(), current_value.to_hex(),
-						expected_value.to_hex());
-                    success = false;
+                    mismatch(format!(
+                        "Poststate {:?} state {} mismatch actual={} expected={}",
+                        address,
+                        position.to_hex(),
+                        current_value.to_hex(),
+                        expected_value.to_hex()
+                    ));
                 }
             }
         }
 
         if expected.builtin.is_some() {
-            warn!(target: "json-tests", "{} – Poststate {:?} builtin not supported", test_name, address);
-            success = false;
+            mismatch(format!("Poststate {:?} builtin not supported", address));
         }
         if expected.constructor.is_some() {
-            warn!(target: "json-tests", "{} – Poststate {:?} constructor not supported", test_name, address);
-            success = false;
+            mismatch(format!("Poststate {:?} constructor not supported", address));
         }
     }
-    success
+    mismatches
+}
+
+/// Imports the blocks of a blockchain test through a `Client` and checks the
+/// best block and the post state against the expectations of the test.
+///
+/// When the test fails, the first block which was rejected although the test
+/// expects it to be valid (or imported although it is expected to be invalid)
+/// is reported alongside the reason.
+pub fn run_chain_test(name: &str, blockchain: ethjson::blockchain::BlockChain) -> ChainTestResult {
+    let spec = {
+        let mut spec = match EvmTestClient::spec_from_json(&blockchain.network) {
+            Some(spec) => spec,
+            None => return ChainTestResult::Unsupported,
+        };
+        // Headers and bodies of blocks with withdrawals (EIP-4895) have extra fields,
+        // which are not decoded.
+        if spec.params().eip4895_transition != BlockNumber::max_value() {
+            return ChainTestResult::Unsupported;
+        }
+
+        let genesis = Genesis::from(blockchain.genesis());
+        let state = From::from(blockchain.pre_state.clone());
+        spec.set_genesis_state(state)
+            .expect("Failed to overwrite genesis state");
+        spec.overwrite_genesis_params(genesis);
+        spec
+    };
+
+    let db = test_helpers::new_db();
+    let mut config = ClientConfig::default();
+    if ethjson::blockchain::Engine::NoProof == blockchain.engine {
+        config.verifier_type = VerifierType::CanonNoSeal;
+        config.check_seal = false;
+    }
+    config.history = 8;
+    config.queue.verifier_settings.num_verifiers = 1;
+    let client = Client::new(
+        config,
+        &spec,
+        db,
+        Arc::new(Miner::new_for_tests(&spec, None)),
+        IoChannel::disconnected(),
+        ShutdownManager::null(),
+    )
+    .expect("Failed to instantiate a new Client");
+
+    let eip1559_transition = spec.params().eip1559_transition;
+    let mut unexpected: Option<ChainTestFailure> = None;
+    for (index, b) in blockchain.blocks.iter().enumerate() {
+        let bytes = b.rlp();
+        let bytes_len = bytes.len();
+        let (failed_block, error) = match Unverified::from_rlp(bytes, eip1559_transition) {
+            Ok(block) => {
+                let num = block.header.number();
+                let hash = block.header.hash();
+                debug!(target: "json-tests", "{} – Importing {} bytes. Block #{}", name, bytes_len, num);
+                let mut error = match client.import_block(block) {
+                    Ok(_) => None,
+                    Err(e) => {
+                        warn!(target: "json-tests", "{} – Error importing block #{}: {:?}", name, num, e);
+                        Some(format!("{}", e))
+                    }
+                };
+                client.flush_queue();
+                client.import_verified_blocks();
+
+                if error.is_none()
+                    && client.block_status(BlockId::Hash(hash)) != BlockStatus::InChain
+                {
+                    error = Some(
+                        client
+                            .bad_blocks()
+                            .into_iter()
+                            .find(|(bad, _)| bad.header.hash() == hash)
+                            .map_or_else(|| "Block was not imported".into(), |(_, reason)| reason),
+                    );
+                }
+                let failed_block = FailedBlock {
+                    index,
+                    number: Some(num),
+                    hash: Some(hash),
+                };
+                (failed_block, error)
+            }
+            Err(decoder_err) => {
+                warn!(target: "json-tests", "Error decoding test block: {:?} ({} bytes)", decoder_err, bytes_len);
+                let failed_block = FailedBlock {
+                    index,
+                    number: None,
+                    hash: None,
+                };
+                (
+                    failed_block,
+                    Some(format!("Invalid block RLP: {}", decoder_err)),
+                )
+            }
+        };
+
+        if unexpected.is_some() {
+            continue;
+        }
+        unexpected = match (error, b.expects_exception()) {
+            (Some(reason), false) => Some(ChainTestFailure {
+                block: Some(failed_block),
+                reason,
+            }),
+            (None, true) => Some(ChainTestFailure {
+                block: Some(failed_block),
+                reason: match b.expect_exception {
+                    Some(ref exception) => format!("Block was imported, expected {}", exception),
+                    None => "Block was imported, expected it to be rejected".into(),
+                },
+            }),
+            _ => None,
+        };
+    }
+
+    let best_block_hash = client.chain_info().best_block_hash;
+    let expected_best_block: H256 = blockchain.best_block.into();
+    let mismatches = match blockchain.post_state {
+        Some(post_state) => check_poststate(&client, name, post_state),
+        None => Vec::new(),
+    };
+    client.shutdown();
+
+    if best_block_hash == expected_best_block && mismatches.is_empty() {
+        return ChainTestResult::Passed;
+    }
+
+    ChainTestResult::Failed(unexpected.unwrap_or_else(|| ChainTestFailure {
+        block: None,
+        reason: if best_block_hash != expected_best_block {
+            format!(
+                "Best block mismatch current={:?} expected={:?}",
+                best_block_hash, expected_best_block
+            )
+        } else {
+            mismatches.join(", ")
+        },
+    }))
 }
 
 pub fn json_chain_test<H: FnMut(&str, HookType)>(
@@ -151,102 +331,86 @@ pub fn json_chain_test<H: FnMut(&str, HookType)>(
             continue;
         }
 
-        let mut fail = false;
-        {
-            let mut fail_unless = |cond: bool| {
-                if !cond && !fail {
-                    failed.push(name.clone());
-                    flushln!("FAIL");
-                    fail = true;
-                    true
-                } else {
-                    false
-                }
-            };
+        let network = format!("{:?}", blockchain.network);
+        start_stop_hook(&name, HookType::OnStart);
 
-            let spec = {
-                let mut spec = match EvmTestClient::spec_from_json(&blockchain.network) {
-                    Some(spec) => spec,
-                    None => {
-                        info!(
-                            "   SKIPPED {:?} {:?} - Unimplemented chainspec ",
-                            name, blockchain.network
-                        );
-                        continue;
-                    }
-                };
+        match run_chain_test(&name, blockchain) {
+            ChainTestResult::Passed => flushln!("   - chain: {}...OK", name),
+            ChainTestResult::Unsupported => info!(
+                "   SKIPPED {:?} {} - Unimplemented chainspec ",
+                name, network
+            ),
+            ChainTestResult::Failed(failure) => {
+                warn!(target: "json-tests", "{} – {}", name, failure.reason);
+                failed.push(name.clone());
+                flushln!("FAIL");
+                flushln!("   - chain: {}...FAILED", name);
+            }
+        }
 
-                let genesis = Genesis::from(blockchain.genesis());
-                let state = From::from(blockchain.pre_state.clone());
-                spec.set_genesis_state(state)
-                    .expect("Failed to overwrite genesis state");
-                spec.overwrite_genesis_params(genesis);
-                spec
-            };
+        start_stop_hook(&name, HookType::OnStop);
+    }
 
-            start_stop_hook(&name, HookType::OnStart);
+    failed
+}
 
-            {
-                let db = test_helpers::new_db();
-                let mut config = ClientConfig::default();
-                if ethjson::blockchain::Engine::NoProof == blockchain.engine {
-                    config.verifier_type = VerifierType::CanonNoSeal;
-                    config.check_seal = false;
-                }
-                config.history = 8;
-                config.queue.verifier_settings.num_verifiers = 1;
-                let client = Client::new(
-                    config,
-                    &spec,
-                    db,
-                    Arc::new(Miner::new_for_tests(&spec, None)),
-                    IoChannel::disconnected(),
-                    ShutdownManager::null(),
-                )
-                .expect("Failed to instantiate a new Client");
-
-                for b in blockchain.blocks_rlp() {
-                    let bytes_len = b.len();
-                    let block = Unverified::from_rlp(b, spec.params().eip1559_transition);
-                    match block {
-                        Ok(block) => {
-                            let num = block.header.number();
-                            debug!(target: "json-tests", "{} – Importing {} bytes. Block #{}", name, bytes_len, num);
-                            let res = client.import_block(block);
-                            if let Err(e) = res {
-                                warn!(target: "json-tests", "{} – Error importing block #{}: {:?}", name, num, e);
-                            }
-                            client.flush_queue();
-                            client.import_verified_blocks();
-                        }
-                        Err(decoder_err) => {
-                            warn!(target: "json-tests", "Error decoding test block: {:?} ({} bytes)", decoder_err, bytes_len);
-                        }
-                    }
-                }
+#[cfg(test)]
+mod tests {
+    use super::{run_chain_test, ChainTestFailure, ChainTestResult, FailedBlock};
+    use ethereum_types::H256;
+    use ethjson;
+    use std::str::FromStr;
 
-                let post_state_success = if let Some(post_state) = blockchain.post_state.clone() {
-                    check_poststate(&client, &name, post_state)
-                } else {
-                    true
-                };
+    /// A valid Frontier block followed by a sibling with a broken seal.
+    fn bad_seal() -> ethjson::blockchain::BlockChain {
+        let json = std::fs::read("res/local_tests/chain/bad_seal.json").unwrap();
+        let (_, blockchain) = ethjson::blockchain::Test::load(&json[..])
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap();
+        blockchain
+    }
 
-                fail_unless(
-                    client.chain_info().best_block_hash == blockchain.best_block.into()
-                        && post_state_success,
-                );
-                client.shutdown()
-            }
-        }
+    #[test]
+    fn should_pass_when_invalid_block_is_rejected() {
+        assert_eq!(
+            run_chain_test("badSeal", bad_seal()),
+            ChainTestResult::Passed
+        );
+    }
 
-        if fail {
-            flushln!("   - chain: {}...FAILED", name);
-        } else {
-            flushln!("   - chain: {}...OK", name);
-        }
+    #[test]
+    fn should_report_block_which_failed_to_import() {
+        let mut blockchain = bad_seal();
+        blockchain.blocks[1].expect_exception = None;
 
-        start_stop_hook(&name, HookType::OnStop);
+        match run_chain_test("badSeal", blockchain) {
+            ChainTestResult::Failed(ChainTestFailure { block, .. }) => assert_eq!(
+                block,
+                Some(FailedBlock {
+                    index: 1,
+                    number: Some(1),
+                    hash: Some(
+                        H256::from_str(
+                            "c14854695cc5925e9e90d72beebbfd89f8ae3a7cf2630f1c51a52f7ccfea03ed"
+                        )
+                        .unwrap()
+                    ),
+                })
+            ),
+            result => panic!("Unexpected result {:?}", result),
+        }
     }
 
-    failed
+    #[test]
+    fn should_not_run_tests_with_withdrawals() {
+        let mut blockchain = bad_seal();
+        blockchain.network = ethjson::spec::ForkSpec::Shanghai;
+
+        assert_eq!(
+            run_chain_test("badSeal", blockchain),
+            ChainTestResult::Unsupported
+        );
+    }
 }
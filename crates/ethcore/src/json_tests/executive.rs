@@ -130,6 +130,14 @@ where
         self.ext.set_storage(key, value)
     }
 
+    fn transient_storage_at(&self, key: &H256) -> vm::Result<H256> {
+        self.ext.transient_storage_at(key)
+    }
+
+    fn set_transient_storage(&mut self, key: H256, value: H256) -> vm::Result<()> {
+        self.ext.set_transient_storage(key, value)
+    }
+
     fn exists(&self, address: &Address) -> vm::Result<bool> {
         self.ext.exists(address)
     }
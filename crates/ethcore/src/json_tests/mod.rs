@@ -29,6 +29,7 @@ mod trie;
 pub mod runner;
 
 pub use self::{
+    chain::{run_chain_test, ChainTestFailure, ChainTestResult, FailedBlock},
     executive::json_executive_test,
     test_common::{debug_include_test, find_json_files_recursive, HookType},
 };
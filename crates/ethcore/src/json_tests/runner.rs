@@ -266,3 +266,14 @@ fn ethereum_json_tests() {
     );
     assert!(result.failed.len() == 0);
 }
+
+#[test]
+fn local_state_tests() {
+    let test = StateTests {
+        path: "res/local_tests/state".into(),
+        skip: Vec::new(),
+    };
+    let result = TestRunner::run_state_tests(&test);
+    assert_eq!(result.success, 3);
+    assert!(result.failed.is_empty(), "{:?}", result.failed);
+}
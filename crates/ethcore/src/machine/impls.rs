@@ -26,14 +26,14 @@ use ethereum_types::{Address, H256, U256};
 use types::{
     header::Header,
     transaction::{
-        self, SignedTransaction, TypedTransaction, UnverifiedTransaction, SYSTEM_ADDRESS,
+        self, Action, SignedTransaction, TypedTransaction, UnverifiedTransaction, SYSTEM_ADDRESS,
         UNSIGNED_SENDER,
     },
     BlockNumber,
 };
 use vm::{
     AccessList, ActionParams, ActionValue, CallType, CreateContractAddress, EnvInfo, ParamsType,
-    Schedule,
+    Schedule, TransientStorage,
 };
 
 use block::ExecutedBlock;
@@ -200,6 +200,7 @@ impl EthereumMachine {
             call_type: call_type.unwrap_or(CallType::Call),
             params_type: ParamsType::Separate,
             access_list: AccessList::default(),
+            transient_storage: TransientStorage::default(),
         };
         let schedule = self.schedule(env_info.number);
         let mut ex = Executive::new(&mut state, &env_info, self, &schedule);
@@ -315,6 +316,10 @@ impl EthereumMachine {
             (rules)(&mut schedule, block_number)
         }
 
+        for (_, overrides) in self.params.gas_schedule_overrides.range(..=block_number) {
+            schedule.apply_overrides(overrides);
+        }
+
         schedule
     }
 
@@ -459,6 +464,16 @@ impl EthereumMachine {
             _ => (),
         };
 
+        if schedule.eip3860
+            && tx.tx().action == Action::Create
+            && tx.tx().data.len() > schedule.max_initcode_size()
+        {
+            return Err(transaction::Error::InitCodeTooBig {
+                limit: schedule.max_initcode_size(),
+                got: tx.tx().data.len(),
+            });
+        }
+
         Ok(tx)
     }
 
@@ -581,6 +596,7 @@ mod tests {
     use crate::ethereum::new_london_test_machine;
     use ethereum_types::H160;
     use std::str::FromStr;
+    use vm::ScheduleOverrides;
 
     fn get_default_ethash_extensions() -> EthashExtensions {
         EthashExtensions {
@@ -679,4 +695,39 @@ mod tests {
             assert_eq!(expected_base_fee[i], base_fee.unwrap());
         }
     }
+
+    #[test]
+    fn should_apply_gas_schedule_overrides() {
+        let spec = ::ethereum::new_london_test();
+        let mut params = spec.params().clone();
+        params.gas_schedule_overrides.insert(
+            10,
+            ScheduleOverrides {
+                sstore_set_gas: Some(30000),
+                log_data_gas: Some(16),
+                ..Default::default()
+            },
+        );
+        params.gas_schedule_overrides.insert(
+            20,
+            ScheduleOverrides {
+                log_data_gas: Some(12),
+                ..Default::default()
+            },
+        );
+        let machine = EthereumMachine::regular(params, Default::default());
+
+        let schedule = machine.schedule(9);
+        assert_eq!(schedule.sstore_set_gas, 20000);
+        assert_eq!(schedule.log_data_gas, 8);
+
+        let schedule = machine.schedule(10);
+        assert_eq!(schedule.sstore_set_gas, 30000);
+        assert_eq!(schedule.log_data_gas, 16);
+
+        let schedule = machine.schedule(25);
+        assert_eq!(schedule.sstore_set_gas, 30000);
+        assert_eq!(schedule.log_data_gas, 12);
+        assert_eq!(schedule.sload_gas, 100);
+    }
 }
@@ -16,7 +16,8 @@
 
 use std::{
     cmp,
-    collections::{BTreeMap, BTreeSet, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -31,8 +32,11 @@ use ethcore_miner::{
     local_accounts::LocalAccounts,
     pool::{
         self,
+        admission::AdmissionPolicy,
+        capture::TransactionRecorder,
         transaction_filter::{match_filter, TransactionFilter},
-        PrioritizationStrategy, QueueStatus, TransactionQueue, VerifiedTransaction,
+        EvictionStats, PrioritizationStrategy, QueueStatus, TransactionEvent, TransactionQueue,
+        VerifiedTransaction,
     },
     service_transaction_checker::ServiceTransactionChecker,
 };
@@ -46,6 +50,7 @@ use miner::{
 };
 use parking_lot::{Mutex, RwLock};
 use rayon::prelude::*;
+use stats::{PrometheusMetrics, PrometheusRegistry};
 use types::{
     block::Block,
     header::Header,
@@ -123,6 +128,12 @@ const DEFAULT_MINIMAL_GAS_PRICE: u64 = 20_000_000_000;
 /// in case we have only a fraction of available block gas limit left.
 const MAX_SKIPPED_TRANSACTIONS: usize = 128;
 
+/// Maximal number of transactions excluded from public propagation at the same time.
+///
+/// Further private transactions are rejected until the current ones are included
+/// or fall back to public propagation.
+const MAX_PRIVATE_TRANSACTIONS: usize = 4096;
+
 /// Configures the behaviour of the miner.
 #[derive(Debug, PartialEq)]
 pub struct MinerOptions {
@@ -159,6 +170,16 @@ pub struct MinerOptions {
     pub refuse_service_transactions: bool,
     /// Transaction pool limits.
     pub pool_limits: pool::Options,
+    /// Per sender limits of pending and future transactions in the pool.
+    pub pool_sender_limits: pool::SenderLimits,
+    /// Number of blocks private transactions are kept out of public propagation.
+    pub private_tx_fallback_blocks: BlockNumber,
+    /// Path of the TOML file with the local transaction admission policy.
+    pub admission_policy_path: Option<PathBuf>,
+    /// Path of the file all public transactions imported to the pool are captured to.
+    pub tx_queue_capture_path: Option<PathBuf>,
+    /// Size in bytes after which the capture file is rotated.
+    pub tx_queue_capture_size_limit: u64,
     /// Initial transaction verification options.
     pub pool_verification_options: pool::verifier::Options,
 }
@@ -185,6 +206,11 @@ impl Default for MinerOptions {
                 max_per_sender: 81,
                 max_mem_usage: 4 * 1024 * 1024,
             },
+            pool_sender_limits: Default::default(),
+            private_tx_fallback_blocks: 5,
+            admission_policy_path: None,
+            tx_queue_capture_path: None,
+            tx_queue_capture_size_limit: 100 * 1024 * 1024,
             pool_verification_options: pool::verifier::Options {
                 minimal_gas_price: DEFAULT_MINIMAL_GAS_PRICE.into(),
                 block_gas_limit: U256::max_value(),
@@ -264,6 +290,8 @@ pub struct Miner {
     accounts: Arc<dyn LocalAccounts>,
     io_channel: RwLock<Option<IoChannel<ClientIoMessage>>>,
     service_transaction_checker: Option<ServiceTransactionChecker>,
+    /// Transactions excluded from public propagation, with the block number they become public at.
+    private_transactions: Arc<RwLock<HashMap<H256, BlockNumber>>>,
 }
 
 impl Miner {
@@ -275,8 +303,60 @@ impl Miner {
     }
 
     /// Set a callback to be notified about imported transactions' hashes.
+    ///
+    /// Private transactions are not reported.
     pub fn add_transactions_listener(&self, f: Box<dyn Fn(&[H256]) + Send + Sync>) {
-        self.transaction_queue.add_listener(f);
+        let private_transactions = self.private_transactions.clone();
+        self.transaction_queue.add_listener(Box::new(move |hashes| {
+            let private_transactions = private_transactions.read();
+            if private_transactions.is_empty() {
+                return f(hashes);
+            }
+            let public: Vec<H256> = hashes
+                .iter()
+                .filter(|hash| !private_transactions.contains_key(hash))
+                .cloned()
+                .collect();
+            if !public.is_empty() {
+                f(&public);
+            }
+        }));
+    }
+
+    /// Set a callback to be notified about lifecycle events of the queued transactions.
+    ///
+    /// Events concerning private transactions are not reported.
+    pub fn add_transaction_events_listener(
+        &self,
+        f: Box<dyn Fn(&[TransactionEvent]) + Send + Sync>,
+    ) {
+        let private_transactions = self.private_transactions.clone();
+        self.transaction_queue
+            .add_event_listener(Box::new(move |events| {
+                let private_transactions = private_transactions.read();
+                if private_transactions.is_empty() {
+                    return f(events);
+                }
+                let is_private = |hash: &H256| private_transactions.contains_key(hash);
+                let public: Vec<TransactionEvent> = events
+                    .iter()
+                    .filter(|event| match **event {
+                        TransactionEvent::Replaced { ref hash, ref by } => {
+                            !is_private(hash) && !is_private(by)
+                        }
+                        TransactionEvent::Dropped {
+                            ref hash,
+                            by: Some(ref by),
+                            ..
+                        } => !is_private(hash) && !is_private(by),
+                        ref event => !is_private(event.hash()),
+                    })
+                    .cloned()
+                    .collect();
+                if !public.is_empty() {
+                    f(&public);
+                }
+            }));
     }
 
     /// Creates new instance of miner Arc.
@@ -288,11 +368,44 @@ impl Miner {
     ) -> Self {
         let limits = options.pool_limits.clone();
         let verifier_options = options.pool_verification_options.clone();
-        let tx_queue_strategy = options.tx_queue_strategy;
+        let sender_limits = options.pool_sender_limits.clone();
+        let tx_queue_strategy = match options.tx_queue_strategy {
+            PrioritizationStrategy::GasPriceOnly if spec.engine.has_service_transaction_lane() => {
+                PrioritizationStrategy::ServiceTransactionLane
+            }
+            strategy => strategy,
+        };
         let nonce_cache_size = cmp::max(4096, limits.max_count / 4);
         let balance_cache_size = cmp::max(4096, limits.max_count / 4);
         let refuse_service_transactions = options.refuse_service_transactions;
         let engine = spec.engine.clone();
+        let transaction_queue = TransactionQueue::new(limits, verifier_options, tx_queue_strategy);
+        transaction_queue.set_sender_limits(sender_limits);
+        if let Some(ref path) = options.admission_policy_path {
+            match AdmissionPolicy::load(path) {
+                Ok(policy) => transaction_queue.set_admission_policy(policy),
+                Err(e) => warn!(target: "miner", "{}", e),
+            }
+        }
+        let private_transactions: Arc<RwLock<HashMap<H256, BlockNumber>>> =
+            Arc::new(RwLock::new(HashMap::new()));
+        if let Some(ref path) = options.tx_queue_capture_path {
+            let private = Arc::downgrade(&private_transactions);
+            let is_private = move |hash: &H256| match private.upgrade() {
+                Some(private) => private.read().contains_key(hash),
+                None => false,
+            };
+            match TransactionRecorder::create(path, options.tx_queue_capture_size_limit, is_private)
+            {
+                Ok(recorder) => transaction_queue.set_recorder(Some(recorder)),
+                Err(e) => warn!(
+                    target: "miner",
+                    "Unable to open transaction capture file {}: {}",
+                    path.display(),
+                    e
+                ),
+            }
+        }
 
         Miner {
             sealing: Mutex::new(SealingWork {
@@ -310,11 +423,7 @@ impl Miner {
             nonce_cache: Cache::<Address, U256>::new("Nonce", nonce_cache_size),
             balance_cache: Cache::<Address, U256>::new("Balance", balance_cache_size),
             options,
-            transaction_queue: Arc::new(TransactionQueue::new(
-                limits,
-                verifier_options,
-                tx_queue_strategy,
-            )),
+            transaction_queue: Arc::new(transaction_queue),
             accounts: Arc::new(accounts),
             engine,
             io_channel: RwLock::new(None),
@@ -323,6 +432,7 @@ impl Miner {
             } else {
                 Some(ServiceTransactionChecker::default())
             },
+            private_transactions,
         }
     }
 
@@ -412,6 +522,11 @@ impl Miner {
         }
     }
 
+    /// Returns statistics of transactions evicted from the transaction queue.
+    pub fn eviction_stats(&self) -> EvictionStats {
+        self.transaction_queue.eviction_stats()
+    }
+
     /// Returns ServiceTransactionChecker
     pub fn service_transaction_checker(&self) -> Option<ServiceTransactionChecker> {
         self.service_transaction_checker.clone()
@@ -449,6 +564,36 @@ impl Miner {
         )
     }
 
+    /// Excludes given transactions from public propagation for the configured number of blocks.
+    ///
+    /// Returns for every hash whether it was newly marked as private,
+    /// or `None` if it could not be marked, because too many transactions are private already.
+    fn mark_private<C>(&self, chain: &C, hashes: &[H256]) -> Vec<Option<bool>>
+    where
+        C: miner::BlockChainClient,
+    {
+        if self.options.private_tx_fallback_blocks == 0 {
+            return vec![Some(false); hashes.len()];
+        }
+
+        let public_at =
+            chain.best_block_header().number() + self.options.private_tx_fallback_blocks;
+        let mut private_transactions = self.private_transactions.write();
+        hashes
+            .iter()
+            .map(|hash| {
+                if private_transactions.contains_key(hash) {
+                    Some(false)
+                } else if private_transactions.len() < MAX_PRIVATE_TRANSACTIONS {
+                    private_transactions.insert(*hash, public_at);
+                    Some(true)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
     /// Returns a newly created block and transactions requested by the Engine for insertion.
     fn create_open_block<'a, C>(
         &self,
@@ -1124,6 +1269,12 @@ impl miner::MinerService for Miner {
         let author_opt = author.into();
         self.params.write().author = author_opt.as_ref().map(Author::address).unwrap_or_default();
 
+        let engine_signer = match author_opt {
+            Some(Author::Sealer(ref signer)) => Some(signer.address()),
+            _ => None,
+        };
+        self.transaction_queue.set_engine_signer(engine_signer);
+
         match author_opt {
             Some(Author::Sealer(signer)) => {
                 if self.engine.sealing_state() != SealingState::External {
@@ -1173,6 +1324,17 @@ impl miner::MinerService for Miner {
         self.params.read().gas_range_target.0 / 5
     }
 
+    fn reload_admission_policy(&self) -> Result<bool, String> {
+        let path = match self.options.admission_policy_path {
+            Some(ref path) => path,
+            None => return Ok(false),
+        };
+        let policy = AdmissionPolicy::load(path)?;
+        info!(target: "miner", "Reloaded transaction admission policy from {}", path.display());
+        self.transaction_queue.set_admission_policy(policy);
+        Ok(true)
+    }
+
     fn set_minimal_gas_price(&self, new_price: U256) -> Result<bool, &str> {
         match *self.gas_pricer.lock() {
             // Binding the gas pricer to `gp` here to prevent
@@ -1284,6 +1446,65 @@ impl miner::MinerService for Miner {
         }
     }
 
+    fn import_private_transaction<C: miner::BlockChainClient>(
+        &self,
+        chain: &C,
+        pending: PendingTransaction,
+    ) -> Result<(), transaction::Error> {
+        // mark before importing, so the transaction listeners never see a public transaction.
+        let hash = pending.hash();
+        let marked = match self.mark_private(chain, &[hash])[0] {
+            Some(marked) => marked,
+            None => return Err(transaction::Error::LimitReached),
+        };
+        let imported = self.import_own_transaction(chain, pending, false);
+        if imported.is_err() && marked {
+            self.private_transactions.write().remove(&hash);
+        }
+
+        imported
+    }
+
+    fn import_external_private_transactions<C: miner::BlockChainClient>(
+        &self,
+        chain: &C,
+        transactions: Vec<UnverifiedTransaction>,
+    ) -> Vec<Result<(), transaction::Error>> {
+        let hashes: Vec<H256> = transactions.iter().map(|tx| tx.hash()).collect();
+        let marked = self.mark_private(chain, &hashes);
+        let accepted = transactions
+            .into_iter()
+            .zip(&marked)
+            .filter(|(_, marked)| marked.is_some())
+            .map(|(tx, _)| tx)
+            .collect();
+        let mut imported = self
+            .import_external_transactions(chain, accepted)
+            .into_iter();
+
+        let mut private_transactions = self.private_transactions.write();
+        hashes
+            .iter()
+            .zip(marked)
+            .map(|(hash, marked)| match marked {
+                Some(marked) => {
+                    let result = imported
+                        .next()
+                        .expect("one result per accepted transaction; qed");
+                    if result.is_err() && marked {
+                        private_transactions.remove(hash);
+                    }
+                    result
+                }
+                None => Err(transaction::Error::LimitReached),
+            })
+            .collect()
+    }
+
+    fn is_private_transaction(&self, hash: &H256) -> bool {
+        self.private_transactions.read().contains_key(hash)
+    }
+
     fn local_transactions(&self) -> BTreeMap<H256, pool::local_transactions::Status> {
         self.transaction_queue.local_transactions()
     }
@@ -1654,6 +1875,13 @@ impl miner::MinerService for Miner {
             // Clear nonce cache
             self.nonce_cache.clear();
             self.balance_cache.clear();
+
+            // Private transactions that were not included in time fall back to public propagation.
+            let best_number = chain.best_block_header().number();
+            self.transaction_queue.record_block(best_number);
+            self.private_transactions
+                .write()
+                .retain(|_, public_at| *public_at > best_number);
         }
 
         // t_nb 10.1 First update gas limit in transaction queue and minimal gas price.
@@ -1802,6 +2030,54 @@ impl miner::MinerService for Miner {
     }
 }
 
+impl PrometheusMetrics for Miner {
+    fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+        let status = self.transaction_queue.status();
+        r.register_gauge(
+            "txqueue_transactions",
+            "Transactions in the queue",
+            status.status.transaction_count as i64,
+        );
+        r.register_gauge(
+            "txqueue_senders",
+            "Senders of the transactions in the queue",
+            status.status.senders as i64,
+        );
+
+        let evictions = self.transaction_queue.eviction_stats();
+        r.register_counter(
+            "txqueue_evicted_limit",
+            "Transactions pushed out of the queue, because the queue limits were reached",
+            evictions.limit as i64,
+        );
+        r.register_counter(
+            "txqueue_evicted_replaced",
+            "Transactions replaced by a transaction with the same sender and nonce",
+            evictions.replaced as i64,
+        );
+        r.register_counter(
+            "txqueue_evicted_stale",
+            "Transactions culled from the queue as stale",
+            evictions.stale as i64,
+        );
+        r.register_counter(
+            "txqueue_evicted_expired",
+            "Future transactions evicted after their lifetime",
+            evictions.expired as i64,
+        );
+        r.register_counter(
+            "txqueue_evicted_pending_limit",
+            "Pending transactions evicted, because the sender reached the pending transactions limit",
+            evictions.pending_limit as i64,
+        );
+        r.register_counter(
+            "txqueue_evicted_future_limit",
+            "Future transactions evicted, because the sender reached the future transactions limit",
+            evictions.future_limit as i64,
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::iter::FromIterator;
@@ -1871,6 +2147,11 @@ mod tests {
                 tx_queue_no_unfamiliar_locals: false,
                 refuse_service_transactions: false,
                 pool_limits: Default::default(),
+                pool_sender_limits: Default::default(),
+                private_tx_fallback_blocks: 5,
+                admission_policy_path: None,
+                tx_queue_capture_path: None,
+                tx_queue_capture_size_limit: 100 * 1024 * 1024,
                 pool_verification_options: pool::verifier::Options {
                     minimal_gas_price: 0.into(),
                     block_gas_limit: U256::max_value(),
@@ -2031,6 +2312,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_release_private_transactions_after_fallback_blocks() {
+        // given
+        let client = TestBlockChainClient::default();
+        let miner = miner();
+        let transaction = transaction();
+        let hash = transaction.hash();
+        let imported = [H256::zero()];
+        let empty = &[];
+
+        // when
+        let res = miner.import_private_transaction(&client, transaction.into());
+
+        // then
+        assert_eq!(res.unwrap(), ());
+        assert!(miner.is_private_transaction(&hash));
+
+        // still private before the fallback block is reached
+        client.add_blocks(4, EachBlockWith::Nothing);
+        miner.chain_new_blocks(&client, &imported, empty, &imported, empty, false);
+        assert!(miner.is_private_transaction(&hash));
+
+        client.add_blocks(1, EachBlockWith::Nothing);
+        miner.chain_new_blocks(&client, &imported, empty, &imported, empty, false);
+        assert!(!miner.is_private_transaction(&hash));
+    }
+
     #[test]
     fn should_activate_eip_3607_according_to_spec() {
         // given
@@ -187,6 +187,30 @@ pub trait MinerService: Send + Sync {
     where
         C: BlockChainClient;
 
+    /// Imports own transaction to queue, keeping it out of public propagation.
+    ///
+    /// The transaction should be forwarded to the validators only. It falls back to
+    /// public propagation if it is not included within the configured number of blocks.
+    fn import_private_transaction<C>(
+        &self,
+        chain: &C,
+        transaction: PendingTransaction,
+    ) -> Result<(), transaction::Error>
+    where
+        C: BlockChainClient;
+
+    /// Imports transactions privately forwarded by other nodes to transaction queue.
+    fn import_external_private_transactions<C>(
+        &self,
+        client: &C,
+        transactions: Vec<UnverifiedTransaction>,
+    ) -> Vec<Result<(), transaction::Error>>
+    where
+        C: BlockChainClient;
+
+    /// Returns true if transaction with given hash must not be propagated publicly (yet).
+    fn is_private_transaction(&self, hash: &H256) -> bool;
+
     /// Removes transaction from the pool.
     ///
     /// Attempts to "cancel" a transaction. If it was not propagated yet (or not accepted by other peers)
@@ -276,4 +300,8 @@ pub trait MinerService: Send + Sync {
     /// Set a new minimum gas limit.
     /// Will not work if dynamic gas calibration is set.
     fn set_minimal_gas_price(&self, gas_price: U256) -> Result<bool, &str>;
+
+    /// Reloads the local transaction admission policy from its file.
+    /// Returns `false` if no policy file is configured.
+    fn reload_admission_policy(&self) -> Result<bool, String>;
 }
@@ -32,7 +32,10 @@ use parking_lot::RwLock;
 use rlp::{Rlp, RlpStream};
 use rustc_hex::FromHex;
 use types::{header::Header, BlockNumber};
-use vm::{AccessList, ActionParams, ActionValue, CallType, EnvInfo, ParamsType};
+use vm::{
+    AccessList, ActionParams, ActionValue, CallType, EnvInfo, ParamsType, ScheduleOverrides,
+    TransientStorage,
+};
 
 use builtin::Builtin;
 use engines::{
@@ -133,8 +136,6 @@ pub struct CommonParams {
     pub eip1884_transition: BlockNumber,
     /// Number of first block where EIP-2028 rules begin.
     pub eip2028_transition: BlockNumber,
-    /// Number of first block where EIP-2315 rules begin.
-    pub eip2315_transition: BlockNumber,
     /// Number of first block where EIP-2929 rules begin.
     pub eip2929_transition: BlockNumber,
     /// Number of first block where EIP-2930 rules begin.
@@ -149,6 +150,25 @@ pub struct CommonParams {
     pub eip3541_transition: BlockNumber,
     /// Number of first block where EIP-3607 rule begins.
     pub eip3607_transition: BlockNumber,
+    /// Number of first block where EIP-3651 rules begin. Warm COINBASE.
+    pub eip3651_transition: BlockNumber,
+    /// Number of first block where EIP-3855 rules begin. PUSH0 opcode.
+    pub eip3855_transition: BlockNumber,
+    /// Number of first block where EIP-4399 rules begin. PREVRANDAO replaces DIFFICULTY.
+    pub eip4399_transition: BlockNumber,
+    /// Number of first block where EIP-4895 rules begin. Beacon chain withdrawals.
+    pub eip4895_transition: BlockNumber,
+    /// Number of first block where EIP-3860 rules begin. Initcode limit and metering.
+    pub eip3860_transition: BlockNumber,
+    /// Number of first block where EIP-1153 rules begin. TLOAD and TSTORE opcodes.
+    pub eip1153_transition: BlockNumber,
+    /// Number of first block where EIP-5656 rules begin. MCOPY opcode.
+    pub eip5656_transition: BlockNumber,
+    /// Number of first block where EIP-6780 rules begin. SELFDESTRUCT only in same transaction.
+    pub eip6780_transition: BlockNumber,
+    /// Number of first block where EOF v1 rules begin. Validated containers with
+    /// static jumps and functions (EIP-3540, EIP-3670, EIP-4200, EIP-4750 and EIP-5450).
+    pub eip3540_transition: BlockNumber,
     /// Number of first block where dust cleanup rules (EIP-168 and EIP169) begin.
     pub dust_protection_transition: BlockNumber,
     /// Nonce cap increase per block. Nonce cap is only checked if dust protection is enabled.
@@ -195,6 +215,9 @@ pub struct CommonParams {
     pub eip1559_fee_collector_transition: BlockNumber,
     /// Block at which zero gas price transactions start being checked with Certifier contract.
     pub validate_service_transactions_transition: BlockNumber,
+    /// Gas schedule values overridden from the given block on, applied on top of the ones of
+    /// the activated forks and of previous overrides.
+    pub gas_schedule_overrides: BTreeMap<BlockNumber, ScheduleOverrides>,
 }
 
 impl CommonParams {
@@ -238,12 +261,20 @@ impl CommonParams {
             && !(block_number >= self.eip1283_disable_transition))
             || block_number >= self.eip1283_reenable_transition;
         schedule.eip1706 = block_number >= self.eip1706_transition;
-        schedule.have_subs = block_number >= self.eip2315_transition;
         schedule.eip2929 = block_number >= self.eip2929_transition;
         schedule.eip2930 = block_number >= self.eip2930_transition;
         schedule.eip3541 = block_number >= self.eip3541_transition;
         schedule.eip1559 = block_number >= self.eip1559_transition;
         schedule.eip3198 = block_number >= self.eip3198_transition;
+        schedule.eip3651 = block_number >= self.eip3651_transition;
+        schedule.eip3855 = block_number >= self.eip3855_transition;
+        schedule.eip4399 = block_number >= self.eip4399_transition;
+        schedule.eip4895 = block_number >= self.eip4895_transition;
+        schedule.eip3860 = block_number >= self.eip3860_transition;
+        schedule.eip1153 = block_number >= self.eip1153_transition;
+        schedule.eip5656 = block_number >= self.eip5656_transition;
+        schedule.eip6780 = block_number >= self.eip6780_transition;
+        schedule.eip3540 = block_number >= self.eip3540_transition;
         if schedule.eip1559 {
             schedule.eip1559_elasticity_multiplier = self.eip1559_elasticity_multiplier.as_usize();
 
@@ -412,9 +443,6 @@ impl From<ethjson::spec::Params> for CommonParams {
             eip2028_transition: p
                 .eip2028_transition
                 .map_or_else(BlockNumber::max_value, Into::into),
-            eip2315_transition: p
-                .eip2315_transition
-                .map_or_else(BlockNumber::max_value, Into::into),
             eip2929_transition: p
                 .eip2929_transition
                 .map_or_else(BlockNumber::max_value, Into::into),
@@ -437,6 +465,33 @@ impl From<ethjson::spec::Params> for CommonParams {
                 .dust_protection_transition
                 .map_or_else(BlockNumber::max_value, Into::into),
             eip3607_transition: p.eip3607_transition.map_or(0, Into::into),
+            eip3651_transition: p
+                .eip3651_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip3855_transition: p
+                .eip3855_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip4399_transition: p
+                .eip4399_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip4895_transition: p
+                .eip4895_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip3860_transition: p
+                .eip3860_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip1153_transition: p
+                .eip1153_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip5656_transition: p
+                .eip5656_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip6780_transition: p
+                .eip6780_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
+            eip3540_transition: p
+                .eip3540_transition
+                .map_or_else(BlockNumber::max_value, Into::into),
             nonce_cap_increment: p.nonce_cap_increment.map_or(64, Into::into),
             remove_dust_contracts: p.remove_dust_contracts.unwrap_or(false),
             gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
@@ -483,6 +538,12 @@ impl From<ethjson::spec::Params> for CommonParams {
             validate_service_transactions_transition: p
                 .validate_service_transactions_transition
                 .map_or_else(BlockNumber::max_value, Into::into),
+            gas_schedule_overrides: p
+                .gas_schedule_overrides
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(block, overrides)| (block.into(), overrides.into()))
+                .collect(),
         }
     }
 }
@@ -744,13 +805,21 @@ impl Spec {
             params.eip1344_transition,
             params.eip1884_transition,
             params.eip2028_transition,
-            params.eip2315_transition,
             params.eip2929_transition,
             params.eip2930_transition,
             params.eip1559_transition,
             params.eip3198_transition,
             params.eip3529_transition,
             params.eip3541_transition,
+            params.eip3651_transition,
+            params.eip3855_transition,
+            params.eip4399_transition,
+            params.eip4895_transition,
+            params.eip3860_transition,
+            params.eip1153_transition,
+            params.eip5656_transition,
+            params.eip6780_transition,
+            params.eip3540_transition,
             params.dust_protection_transition,
             params.wasm_activation_transition,
             params.wasm_disable_transition,
@@ -762,6 +831,7 @@ impl Spec {
             params.eip1559_base_fee_min_value_transition,
             params.validate_service_transactions_transition,
         ];
+        hard_forks.extend(params.gas_schedule_overrides.keys().copied());
         // BUG: Rinkeby has homestead transition at block 1 but we can't reflect that in specs for non-Ethash networks
         if params.network_id == 0x4 {
             hard_forks.insert(1);
@@ -885,6 +955,7 @@ impl Spec {
                         call_type: CallType::None,
                         params_type: ParamsType::Embedded,
                         access_list: AccessList::default(),
+                        transient_storage: TransientStorage::default(),
                     };
 
                     let mut substate = Substate::new();
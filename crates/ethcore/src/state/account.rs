@@ -510,6 +510,16 @@ impl Account {
         &self.storage_changes
     }
 
+    /// Set the nonce of the account.
+    pub fn set_nonce(&mut self, nonce: U256) {
+        self.nonce = nonce;
+    }
+
+    /// Set the balance of the account.
+    pub fn set_balance(&mut self, balance: U256) {
+        self.balance = balance;
+    }
+
     /// Increment the nonce of the account by one.
     pub fn inc_nonce(&mut self) {
         self.nonce = self.nonce.saturating_add(U256::from(1u8));
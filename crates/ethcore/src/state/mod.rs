@@ -55,6 +55,8 @@ use ethtrie::{Result as TrieResult, TrieDB};
 use trie::{Recorder, Trie, TrieError};
 
 mod account;
+mod parallel;
+mod prefetch;
 mod substate;
 
 pub mod backend;
@@ -312,6 +314,8 @@ pub struct State<B> {
     cache: RefCell<HashMap<Address, AccountEntry>>,
     // The original account is preserved in
     checkpoints: RefCell<Vec<HashMap<Address, Option<AccountEntry>>>>,
+    // Accesses of the transaction being executed speculatively, if any.
+    accesses: RefCell<Option<parallel::Accesses>>,
     account_start_nonce: U256,
     factories: Factories,
 }
@@ -382,6 +386,7 @@ impl<B: Backend> State<B> {
             root: root,
             cache: RefCell::new(HashMap::new()),
             checkpoints: RefCell::new(Vec::new()),
+            accesses: RefCell::new(None),
             account_start_nonce: account_start_nonce,
             factories: factories,
         }
@@ -403,6 +408,7 @@ impl<B: Backend> State<B> {
             root: root,
             cache: RefCell::new(HashMap::new()),
             checkpoints: RefCell::new(Vec::new()),
+            accesses: RefCell::new(None),
             account_start_nonce: account_start_nonce,
             factories: factories,
         };
@@ -495,6 +501,18 @@ impl<B: Backend> State<B> {
         }
     }
 
+    fn note_read(&self, address: &Address) {
+        if let Some(ref mut accesses) = *self.accesses.borrow_mut() {
+            accesses.note_read(address);
+        }
+    }
+
+    fn note_storage_read(&self, address: &Address, key: &H256) {
+        if let Some(ref mut accesses) = *self.accesses.borrow_mut() {
+            accesses.note_storage_read(address, key);
+        }
+    }
+
     /// Destroy the current object and return root and database.
     pub fn drop(mut self) -> (H256, B) {
         self.propagate_to_global_cache();
@@ -545,6 +563,7 @@ impl<B: Backend> State<B> {
 
     /// Remove an existing account.
     pub fn kill_account(&mut self, account: &Address) {
+        self.note_read(account);
         self.insert_cache(account, AccountEntry::new_dirty(None));
     }
 
@@ -626,6 +645,7 @@ impl<B: Backend> State<B> {
             SameAsNext,
         }
 
+        self.note_storage_read(address, key);
         let kind = {
             let checkpoints = self.checkpoints.borrow();
 
@@ -706,6 +726,7 @@ impl<B: Backend> State<B> {
         // 1. If there's an entry for the account in the local cache check for the key and return it if found.
         // 2. If there's an entry for the account in the global cache check for the key or load it into that account.
         // 3. If account is missing in the global cache load it into the local cache and cache the key there.
+        self.note_storage_read(address, key);
 
         {
             // check local cache first without updating
@@ -832,6 +853,30 @@ impl<B: Backend> State<B> {
         Ok(())
     }
 
+    /// Add `incr` to the balance of account `a` like `add_balance`, for payments such as
+    /// transaction fees which do not depend on the balance of the account. When executing
+    /// speculatively the credit is not recorded as a read, so that transactions paying the
+    /// same account do not conflict.
+    pub fn credit_balance(
+        &mut self,
+        a: &Address,
+        incr: &U256,
+        cleanup_mode: CleanupMode,
+    ) -> TrieResult<()> {
+        if incr.is_zero() || self.accesses.get_mut().is_none() {
+            return self.add_balance(a, incr, cleanup_mode);
+        }
+        let mut accesses = self
+            .accesses
+            .get_mut()
+            .take()
+            .expect("accesses are recorded, checked above; qed");
+        let result = self.add_balance(a, incr, cleanup_mode);
+        accesses.note_credit(a, incr);
+        *self.accesses.get_mut() = Some(accesses);
+        result
+    }
+
     /// Subtract `decr` from the balance of account `a`.
     pub fn sub_balance(
         &mut self,
@@ -867,6 +912,25 @@ impl<B: Backend> State<B> {
         self.require(a, false).map(|mut x| x.inc_nonce())
     }
 
+    /// Set the nonce of account `a`, creating the account if necessary.
+    pub fn set_nonce(&mut self, a: &Address, nonce: U256) -> TrieResult<()> {
+        self.require(a, false).map(|mut x| x.set_nonce(nonce))
+    }
+
+    /// Set the balance of account `a`, creating the account if necessary.
+    pub fn set_balance(&mut self, a: &Address, balance: U256) -> TrieResult<()> {
+        trace!(target: "state", "set_balance({}, {}): {}", a, balance, self.balance(a)?);
+        self.require(a, false).map(|mut x| x.set_balance(balance))
+    }
+
+    /// Replace the whole storage of account `a` with `storage`, keeping its code.
+    pub fn reset_storage(&mut self, a: &Address, storage: HashMap<H256, H256>) -> TrieResult<()> {
+        let mut account = self.require(a, true)?;
+        let code = account.code().unwrap_or_else(|| Arc::new(Vec::new()));
+        account.reset_code_and_storage(code, storage);
+        Ok(())
+    }
+
     /// Mutate storage of account `a` so that it is `value` for `key`.
     pub fn set_storage(&mut self, a: &Address, key: H256, value: H256) -> TrieResult<()> {
         trace!(target: "state", "set_storage({}:{:x} to {:x})", a, key, value);
@@ -1321,6 +1385,7 @@ impl<B: Backend> State<B> {
     where
         F: Fn(Option<&Account>) -> U,
     {
+        self.note_read(a);
         // check local cache first
         if let Some(ref mut maybe_acc) = self.cache.borrow_mut().get_mut(a) {
             if let Some(ref mut account) = maybe_acc.account {
@@ -1401,6 +1466,7 @@ impl<B: Backend> State<B> {
         F: FnOnce() -> Account,
         G: FnOnce(&mut Account),
     {
+        self.note_read(a);
         let contains_key = self.cache.borrow().contains_key(a);
         if !contains_key {
             match self.db.get_cached_account(a) {
@@ -1565,6 +1631,7 @@ impl Clone for State<StateDB> {
             root: self.root.clone(),
             cache: RefCell::new(cache),
             checkpoints: RefCell::new(Vec::new()),
+            accesses: RefCell::new(None),
             account_start_nonce: self.account_start_nonce.clone(),
             factories: self.factories.clone(),
         }
@@ -2786,6 +2853,35 @@ mod tests {
         assert_eq!(state.nonce(&a).unwrap(), U256::from(3u64));
     }
 
+    #[test]
+    fn override_account_fields() {
+        let mut state = get_temp_state();
+        let a = Address::from_low_u64_be(10);
+        let key = BigEndianHash::from_uint(&U256::from(1u64));
+        let other_key = BigEndianHash::from_uint(&U256::from(2u64));
+        state.init_code(&a, vec![0x60, 0x00]).unwrap();
+        state
+            .set_storage(&a, key, BigEndianHash::from_uint(&U256::from(7u64)))
+            .unwrap();
+        state.commit().unwrap();
+
+        state.set_balance(&a, U256::from(1000u64)).unwrap();
+        state.set_nonce(&a, U256::from(42u64)).unwrap();
+        let mut storage = HashMap::new();
+        storage.insert(other_key, BigEndianHash::from_uint(&U256::from(9u64)));
+        state.reset_storage(&a, storage).unwrap();
+        state.commit().unwrap();
+
+        assert_eq!(state.balance(&a).unwrap(), U256::from(1000u64));
+        assert_eq!(state.nonce(&a).unwrap(), U256::from(42u64));
+        assert_eq!(state.code(&a).unwrap(), Some(Arc::new(vec![0x60, 0x00])));
+        assert_eq!(state.storage_at(&a, &key).unwrap(), H256::zero());
+        assert_eq!(
+            state.storage_at(&a, &other_key).unwrap(),
+            BigEndianHash::from_uint(&U256::from(9u64))
+        );
+    }
+
     #[test]
     fn balance_nonce() {
         let mut state = get_temp_state();
@@ -0,0 +1,639 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Optimistic parallel execution of the transactions of a block.
+//!
+//! Every transaction is first executed speculatively on the state at the beginning of
+//! the block, recording the accounts and storage slots it reads. Each worker thread
+//! executes its transactions on a single copy of the state, reverted after every one.
+//! The speculative results are then validated in block order: the changes of a
+//! transaction which read nothing changed by the transactions before it are merged
+//! into the state, any other transaction is executed again on top of the merged state.
+//! Fees credited to the block author are merged as increments, so that they do not
+//! make every transaction conflict with the previous one.
+
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+};
+
+use ethereum_types::{Address, H256, U256};
+use ethtrie::Result as TrieResult;
+use machine::EthereumMachine as Machine;
+use rayon::prelude::*;
+use state_db::StateDB;
+use trace::{FlatTrace, VMTrace};
+use trie::Trie;
+use types::transaction::SignedTransaction;
+use vm::EnvInfo;
+
+use super::{Account, AccountEntry, ApplyOutcome, ApplyResult, Backend, CleanupMode, State};
+
+/// Accounts and storage slots read by a transaction, and the balance credited
+/// to accounts without reading them.
+#[derive(Default)]
+pub struct Accesses {
+    accounts: HashSet<Address>,
+    storage: HashSet<(Address, H256)>,
+    credits: HashMap<Address, U256>,
+}
+
+impl Accesses {
+    pub fn note_read(&mut self, address: &Address) {
+        self.accounts.insert(*address);
+    }
+
+    pub fn note_storage_read(&mut self, address: &Address, key: &H256) {
+        self.storage.insert((*address, *key));
+    }
+
+    pub fn note_credit(&mut self, address: &Address, incr: &U256) {
+        *self.credits.entry(*address).or_insert_with(U256::zero) += *incr;
+    }
+
+    /// Accounts read or credited.
+    pub fn accounts<'a>(&'a self) -> impl Iterator<Item = &'a Address> + 'a {
+        self.accounts.iter().chain(
+            self.credits
+                .keys()
+                .filter(move |address| !self.accounts.contains(*address)),
+        )
+    }
+
+    /// Storage slots read.
+    pub fn storage<'a>(&'a self) -> impl Iterator<Item = &'a (Address, H256)> + 'a {
+        self.storage.iter()
+    }
+
+    /// Whether the account was credited, but not read otherwise.
+    fn is_credit_only(&self, address: &Address) -> bool {
+        self.credits.contains_key(address) && !self.accounts.contains(address)
+    }
+}
+
+/// Accounts and storage slots changed by transactions.
+#[derive(Default)]
+struct Changes {
+    /// Accounts created, killed or with new code and storage.
+    replaced: HashSet<Address>,
+    /// Accounts with a changed balance or nonce.
+    accounts: HashSet<Address>,
+    /// Changed storage slots.
+    storage: HashSet<(Address, H256)>,
+}
+
+impl Changes {
+    /// Whether any of the `accesses` reads a value changed here.
+    fn overlaps(&self, accesses: &Accesses) -> bool {
+        accesses
+            .accounts
+            .iter()
+            .any(|address| self.accounts.contains(address) || self.replaced.contains(address))
+            || accesses
+                .storage
+                .iter()
+                .any(|slot| self.replaced.contains(&slot.0) || self.storage.contains(slot))
+    }
+
+    fn extend(&mut self, other: Changes) {
+        self.replaced.extend(other.replaced);
+        self.accounts.extend(other.accounts);
+        self.storage.extend(other.storage);
+    }
+}
+
+/// Account fields compared to find the changes of a transaction.
+struct Fields {
+    balance: U256,
+    nonce: U256,
+    code_hash: H256,
+    storage_root: H256,
+}
+
+impl Fields {
+    fn new(account: &Account) -> Self {
+        Fields {
+            balance: *account.balance(),
+            nonce: *account.nonce(),
+            code_hash: account.code_hash(),
+            storage_root: account.base_storage_root(),
+        }
+    }
+}
+
+/// Changes of a speculative execution with their new values, captured before the
+/// speculative state is reverted.
+struct Captured {
+    changes: Changes,
+    /// Entries of the replaced accounts.
+    replaced: Vec<(Address, AccountEntry)>,
+    /// New balance and nonce of the changed accounts.
+    accounts: Vec<(Address, U256, U256)>,
+    /// New values of the changed storage slots.
+    storage: Vec<(Address, H256, H256)>,
+}
+
+/// Result of the speculative execution of a transaction.
+struct Speculation {
+    accesses: Accesses,
+    result: Option<(ApplyOutcome<FlatTrace, VMTrace>, Captured)>,
+}
+
+impl Speculation {
+    /// Executes the transaction on `state` and reverts it afterwards,
+    /// so the state can be reused for the next speculation.
+    fn execute(
+        state: &mut State<StateDB>,
+        env_info: &EnvInfo,
+        machine: &Machine,
+        t: &SignedTransaction,
+        tracing: bool,
+    ) -> Self {
+        *state.accesses.get_mut() = Some(Accesses::default());
+        state.checkpoint();
+        let outcome = state.apply(env_info, machine, t, tracing).ok();
+        let accesses = state.accesses.get_mut().take().unwrap_or_default();
+        let result = match outcome {
+            Some(outcome) => state
+                .capture_changes(&accesses)
+                .ok()
+                .map(|captured| (outcome, captured)),
+            None => None,
+        };
+        state.revert_to_checkpoint();
+        Speculation { accesses, result }
+    }
+}
+
+impl State<StateDB> {
+    /// Applies `transactions` in order, with the same result as consecutive calls to
+    /// `apply`, but executes them speculatively in parallel first.
+    ///
+    /// Every worker thread executes its share of the transactions on its own copy of the
+    /// state at the beginning of the block. The transactions are applied sequentially
+    /// if a checkpoint is open, since the speculative copies can not represent it.
+    ///
+    /// Stops at the first transaction which cannot be applied, its error is the last
+    /// of the results. `env_info.gas_used` is the gas used before the first transaction.
+    /// Must not be used for blocks whose receipts contain intermediate state roots.
+    pub fn apply_parallel(
+        &mut self,
+        env_info: &EnvInfo,
+        machine: &Machine,
+        transactions: &[SignedTransaction],
+        tracing: bool,
+    ) -> Vec<ApplyResult<FlatTrace, VMTrace>> {
+        if transactions.is_empty() || !self.checkpoints.get_mut().is_empty() {
+            return self.apply_sequential(env_info, machine, transactions, tracing);
+        }
+
+        let workers = cmp::min(rayon::current_num_threads(), transactions.len());
+        let chunk_size = (transactions.len() + workers - 1) / workers;
+        let states: Vec<_> = (0..workers).map(|_| self.clone()).collect();
+        let speculations: Vec<Vec<_>> = transactions
+            .par_chunks(chunk_size)
+            .zip(states.into_par_iter())
+            .map(|(chunk, mut state)| {
+                chunk
+                    .iter()
+                    .map(|t| Speculation::execute(&mut state, env_info, machine, t, tracing))
+                    .collect()
+            })
+            .collect();
+
+        let mut env_info = env_info.clone();
+        let start_gas_used = env_info.gas_used;
+        let mut changed = Changes::default();
+        let mut results = Vec::with_capacity(transactions.len());
+        for (t, speculation) in transactions.iter().zip(speculations.into_iter().flatten()) {
+            let fits = env_info.gas_used + t.tx().gas <= env_info.gas_limit;
+            let result: ApplyResult<FlatTrace, VMTrace> = match speculation {
+                Speculation {
+                    accesses,
+                    result: Some((mut outcome, captured)),
+                } if fits && !changed.overlaps(&accesses) => {
+                    match self.merge(captured, &accesses, &mut changed) {
+                        Ok(()) => {
+                            let gas_used = outcome.receipt.gas_used - start_gas_used;
+                            outcome.receipt.gas_used = env_info.gas_used + gas_used;
+                            Ok(outcome)
+                        }
+                        Err(e) => Err(e.into()),
+                    }
+                }
+                _ => {
+                    trace!(target: "state", "Executing transaction {:?} again", t.hash());
+                    self.checkpoint();
+                    let result = self.apply(&env_info, machine, t, tracing);
+                    let changes = self.checkpoint_changes(None);
+                    self.discard_checkpoint();
+                    match changes {
+                        Ok(changes) => {
+                            changed.extend(changes);
+                            result
+                        }
+                        Err(e) => Err(e.into()),
+                    }
+                }
+            };
+
+            match result {
+                Ok(outcome) => {
+                    env_info.gas_used = outcome.receipt.gas_used;
+                    results.push(Ok(outcome));
+                }
+                Err(e) => {
+                    results.push(Err(e));
+                    break;
+                }
+            }
+        }
+        results
+    }
+
+    /// Applies `transactions` one after another, stopping at the first error.
+    fn apply_sequential(
+        &mut self,
+        env_info: &EnvInfo,
+        machine: &Machine,
+        transactions: &[SignedTransaction],
+        tracing: bool,
+    ) -> Vec<ApplyResult<FlatTrace, VMTrace>> {
+        let mut env_info = env_info.clone();
+        let mut results = Vec::with_capacity(transactions.len());
+        for t in transactions {
+            match self.apply(&env_info, machine, t, tracing) {
+                Ok(outcome) => {
+                    env_info.gas_used = outcome.receipt.gas_used;
+                    results.push(Ok(outcome));
+                }
+                Err(e) => {
+                    results.push(Err(e));
+                    break;
+                }
+            }
+        }
+        results
+    }
+
+    /// Merges the changes of a speculative execution into this state and adds them to `changed`.
+    fn merge(
+        &mut self,
+        captured: Captured,
+        accesses: &Accesses,
+        changed: &mut Changes,
+    ) -> TrieResult<()> {
+        let Captured {
+            mut changes,
+            replaced,
+            accounts,
+            storage,
+        } = captured;
+        for (address, entry) in replaced {
+            self.insert_cache(&address, entry);
+        }
+        for (address, balance, nonce) in accounts {
+            let mut ours = self.require(&address, false)?;
+            ours.set_balance(balance);
+            ours.set_nonce(nonce);
+        }
+        for (address, key, value) in storage {
+            self.set_storage(&address, key, value)?;
+        }
+        for (address, incr) in &accesses.credits {
+            if !accesses.is_credit_only(address) {
+                continue;
+            }
+            self.add_balance(address, incr, CleanupMode::NoEmpty)?;
+            changes.accounts.insert(*address);
+        }
+        changed.extend(changes);
+        Ok(())
+    }
+
+    /// Collects the changes since the last checkpoint together with their new values.
+    fn capture_changes(&self, accesses: &Accesses) -> TrieResult<Captured> {
+        let changes = self.checkpoint_changes(Some(accesses))?;
+        let cache = self.cache.borrow();
+        let entry = |address: &Address| {
+            cache
+                .get(address)
+                .expect("changed accounts exist in the cache; qed")
+        };
+        let account = |address: &Address| {
+            entry(address)
+                .account
+                .as_ref()
+                .expect("changed accounts exist in the cache; qed")
+        };
+
+        let replaced = changes
+            .replaced
+            .iter()
+            .map(|address| (*address, entry(address).clone_dirty()))
+            .collect();
+        let accounts = changes
+            .accounts
+            .iter()
+            .map(|address| {
+                let account = account(address);
+                (*address, *account.balance(), *account.nonce())
+            })
+            .collect();
+        let storage = changes
+            .storage
+            .iter()
+            .map(|&(address, key)| (address, key, account(&address).storage_changes()[&key]))
+            .collect();
+        Ok(Captured {
+            changes,
+            replaced,
+            accounts,
+            storage,
+        })
+    }
+
+    /// Collects the changes since the last checkpoint, which was created before executing
+    /// a transaction. Accounts which were not cached at that point are unchanged since
+    /// the last commit and are looked up at the state root.
+    /// Accounts only credited according to `accesses` are skipped.
+    fn checkpoint_changes(&self, accesses: Option<&Accesses>) -> TrieResult<Changes> {
+        let mut changes = Changes::default();
+        let checkpoints = self.checkpoints.borrow();
+        let checkpoint = match checkpoints.last() {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(changes),
+        };
+        let cache = self.cache.borrow();
+
+        for (address, original) in checkpoint {
+            if accesses.map_or(false, |accesses| accesses.is_credit_only(address)) {
+                continue;
+            }
+            let current = match cache.get(address) {
+                Some(entry) => entry.account.as_ref(),
+                None => continue,
+            };
+            let (original, original_storage) = match *original {
+                Some(ref entry) => (
+                    entry.account.as_ref().map(Fields::new),
+                    entry.account.as_ref().map(Account::storage_changes),
+                ),
+                None => (self.committed_fields(address)?, None),
+            };
+
+            match (original, current) {
+                (None, None) => {}
+                // an account recreated with the same code drops the storage changes before it
+                (Some(ref original), Some(current))
+                    if original.code_hash == current.code_hash()
+                        && original.storage_root == current.base_storage_root()
+                        && original_storage.map_or(true, |storage| {
+                            storage
+                                .keys()
+                                .all(|key| current.storage_changes().contains_key(key))
+                        }) =>
+                {
+                    if original.balance != *current.balance() || original.nonce != *current.nonce()
+                    {
+                        changes.accounts.insert(*address);
+                    }
+                    for (key, value) in current.storage_changes() {
+                        if original_storage.and_then(|storage| storage.get(key)) != Some(value) {
+                            changes.storage.insert((*address, *key));
+                        }
+                    }
+                }
+                _ => {
+                    changes.replaced.insert(*address);
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Reads the fields of an account as of the last commit, bypassing the local cache.
+    fn committed_fields(&self, address: &Address) -> TrieResult<Option<Fields>> {
+        if let Some(fields) = self.db.get_cached(address, |account| {
+            account.map(|account| Fields::new(account))
+        }) {
+            return Ok(fields);
+        }
+        let db = &self.db.as_hash_db();
+        let db = self.factories.trie.readonly(db, &self.root)?;
+        let from_rlp = |b: &[u8]| Account::from_rlp(b).expect("decoding db value failed");
+        let account = db.get_with(address.as_bytes(), from_rlp)?;
+        Ok(account.as_ref().map(Fields::new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{CleanupMode, State};
+    use crypto::publickey::{Generator, KeyPair, Random};
+    use ethereum::new_istanbul_test_machine;
+    use ethereum_types::{Address, H256, U256};
+    use rustc_hex::FromHex;
+    use state_db::StateDB;
+    use test_helpers::get_temp_state;
+    use types::transaction::{Action, SignedTransaction, Transaction, TypedTransaction};
+    use vm::EnvInfo;
+
+    const COUNTER: u64 = 0x1000;
+
+    fn env_info() -> EnvInfo {
+        let mut env_info = EnvInfo::default();
+        env_info.gas_limit = 10_000_000.into();
+        env_info.author = Address::from_low_u64_be(0xc0);
+        env_info
+    }
+
+    fn transaction(
+        sender: &KeyPair,
+        nonce: u64,
+        action: Action,
+        value: U256,
+        data: Vec<u8>,
+    ) -> SignedTransaction {
+        TypedTransaction::Legacy(Transaction {
+            nonce: nonce.into(),
+            gas_price: 1.into(),
+            gas: 100_000.into(),
+            action,
+            value,
+            data,
+        })
+        .sign(sender.secret(), None)
+    }
+
+    fn call_counter(sender: &KeyPair, nonce: u64) -> SignedTransaction {
+        let counter = Address::from_low_u64_be(COUNTER);
+        transaction(sender, nonce, Action::Call(counter), 0.into(), vec![])
+    }
+
+    fn senders_with_state(count: usize) -> (Vec<KeyPair>, State<StateDB>) {
+        let senders: Vec<_> = (0..count).map(|_| Random.generate()).collect();
+        let mut state = get_temp_state();
+        for sender in &senders {
+            state
+                .add_balance(
+                    &sender.address(),
+                    &U256::from(1_000_000_000u64),
+                    CleanupMode::NoEmpty,
+                )
+                .unwrap();
+        }
+        // increments storage slot 0 and stores the caller in slot 1.
+        let counter = Address::from_low_u64_be(COUNTER);
+        state.new_contract(&counter, 0.into(), 0.into()).unwrap();
+        state
+            .init_code(&counter, "60005460010160005533600155".from_hex().unwrap())
+            .unwrap();
+        state.commit().unwrap();
+        (senders, state)
+    }
+
+    fn assert_same_as_sequential(state: State<StateDB>, transactions: &[SignedTransaction]) {
+        let machine = new_istanbul_test_machine();
+        let env_info = env_info();
+
+        let mut sequential = state.clone();
+        let mut sequential_env_info = env_info.clone();
+        let mut expected = Vec::new();
+        for t in transactions {
+            let outcome = sequential
+                .apply(&sequential_env_info, &machine, t, true)
+                .unwrap();
+            sequential_env_info.gas_used = outcome.receipt.gas_used;
+            expected.push(outcome);
+        }
+        sequential.commit().unwrap();
+
+        let mut parallel = state;
+        let results = parallel.apply_parallel(&env_info, &machine, transactions, true);
+        parallel.commit().unwrap();
+
+        assert_eq!(results.len(), expected.len());
+        for (result, expected) in results.into_iter().zip(expected) {
+            let outcome = result.unwrap();
+            assert_eq!(outcome.receipt, expected.receipt);
+            assert_eq!(outcome.output, expected.output);
+            assert_eq!(outcome.trace, expected.trace);
+        }
+        assert_eq!(parallel.root(), sequential.root());
+    }
+
+    #[test]
+    fn should_apply_independent_transactions() {
+        let (senders, state) = senders_with_state(8);
+        let transactions: Vec<_> = senders
+            .iter()
+            .enumerate()
+            .map(|(i, sender)| {
+                let to = Address::from_low_u64_be(0x2000 + i as u64);
+                transaction(sender, 0, Action::Call(to), 1_000.into(), vec![])
+            })
+            .collect();
+        assert_same_as_sequential(state, &transactions);
+    }
+
+    #[test]
+    fn should_execute_conflicting_transactions_again() {
+        let (senders, state) = senders_with_state(4);
+        let mut transactions: Vec<_> = senders
+            .iter()
+            .map(|sender| call_counter(sender, 0))
+            .collect();
+        transactions.push(call_counter(&senders[0], 1));
+        transactions.push(transaction(
+            &senders[1],
+            1,
+            Action::Call(senders[2].address()),
+            5_000.into(),
+            vec![],
+        ));
+        transactions.push(transaction(
+            &senders[2],
+            1,
+            Action::Call(Address::from_low_u64_be(0x3000)),
+            999_000_000.into(),
+            vec![],
+        ));
+        assert_same_as_sequential(state, &transactions);
+    }
+
+    #[test]
+    fn should_merge_created_contracts() {
+        let (senders, state) = senders_with_state(3);
+        // returns the code of the counter contract.
+        let init = "6c60005460010160005533600155600052600d6013f3"
+            .from_hex()
+            .unwrap();
+        let transactions: Vec<_> = senders
+            .iter()
+            .map(|sender| transaction(sender, 0, Action::Create, 0.into(), init.clone()))
+            .collect();
+        assert_same_as_sequential(state, &transactions);
+    }
+
+    #[test]
+    fn should_apply_sequentially_with_open_checkpoint() {
+        let (senders, mut state) = senders_with_state(2);
+        let transactions: Vec<_> = senders
+            .iter()
+            .map(|sender| call_counter(sender, 0))
+            .collect();
+        state.checkpoint();
+        let results = state.apply_parallel(
+            &env_info(),
+            &new_istanbul_test_machine(),
+            &transactions,
+            false,
+        );
+        state.discard_checkpoint();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(Result::is_ok));
+        let counter = Address::from_low_u64_be(COUNTER);
+        assert_eq!(
+            state.storage_at(&counter, &H256::zero()).unwrap(),
+            H256::from_low_u64_be(2)
+        );
+    }
+
+    #[test]
+    fn should_stop_at_first_invalid_transaction() {
+        let (senders, mut state) = senders_with_state(2);
+        let transactions = vec![
+            call_counter(&senders[0], 0),
+            call_counter(&senders[1], 5),
+            call_counter(&senders[1], 0),
+        ];
+        let results = state.apply_parallel(
+            &env_info(),
+            &new_istanbul_test_machine(),
+            &transactions,
+            false,
+        );
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        let counter = Address::from_low_u64_be(COUNTER);
+        assert_eq!(
+            state.storage_at(&counter, &H256::zero()).unwrap(),
+            H256::from_low_u64_be(1)
+        );
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Warming of the shared account cache for transactions which are about to be executed.
+
+use ethtrie::Result as TrieResult;
+use machine::EthereumMachine as Machine;
+use state_db::StateDB;
+use types::transaction::SignedTransaction;
+use vm::EnvInfo;
+
+use super::{parallel::Accesses, RequireCache, State};
+
+impl State<StateDB> {
+    /// Executes `transactions` speculatively on a copy of this state to find the accounts and
+    /// storage they read, loads those missing in the shared cache of the state database and
+    /// adds them to it. The state database must have been created for a canonical state.
+    ///
+    /// Returns the number of accounts added to the shared cache.
+    pub fn prefetch(
+        self,
+        env_info: &EnvInfo,
+        machine: &Machine,
+        transactions: &[SignedTransaction],
+    ) -> TrieResult<usize> {
+        let accesses = {
+            let mut speculative = State::from_existing(
+                self.db.boxed_clone_prefetch(),
+                self.root,
+                self.account_start_nonce,
+                self.factories.clone(),
+            )?;
+            *speculative.accesses.get_mut() = Some(Accesses::default());
+            for t in transactions {
+                // reads of failing transactions are recorded as well.
+                let _ = speculative.apply(env_info, machine, t, false);
+            }
+            speculative.accesses.into_inner().unwrap_or_default()
+        };
+
+        // accounts found in the shared cache are not added to the local one,
+        // storage read for them is cached in the shared account directly.
+        for address in accesses.accounts() {
+            self.ensure_cached(address, RequireCache::Code, |_| ())?;
+        }
+        for &(ref address, ref key) in accesses.storage() {
+            self.storage_at(address, key)?;
+        }
+
+        let accounts = self
+            .cache
+            .into_inner()
+            .into_iter()
+            .map(|(address, entry)| (address, entry.account))
+            .collect();
+        Ok(self.db.add_prefetched(accounts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{CleanupMode, State};
+    use crypto::publickey::{Generator, Random};
+    use ethereum::new_istanbul_test_machine;
+    use ethereum_types::{Address, H256, U256};
+    use factory::Factories;
+    use rustc_hex::FromHex;
+    use state::Backend;
+    use test_helpers::get_temp_state;
+    use types::transaction::{Action, Transaction, TypedTransaction};
+    use vm::EnvInfo;
+
+    #[test]
+    fn should_prefetch_accounts_and_storage_read_by_transactions() {
+        let sender = Random.generate();
+        let counter = Address::from_low_u64_be(0x1000);
+        let block_hash = H256::random();
+
+        // a contract incrementing storage slot 0.
+        let mut state = get_temp_state();
+        state
+            .add_balance(
+                &sender.address(),
+                &U256::from(1_000_000_000u64),
+                CleanupMode::NoEmpty,
+            )
+            .unwrap();
+        state.new_contract(&counter, 0.into(), 0.into()).unwrap();
+        state
+            .init_code(&counter, "60005460010160005500".from_hex().unwrap())
+            .unwrap();
+        state
+            .set_storage(&counter, H256::zero(), H256::from_low_u64_be(41))
+            .unwrap();
+        state.commit().unwrap();
+        let (root, state_db) = state.drop();
+
+        let t = TypedTransaction::Legacy(Transaction {
+            nonce: 0.into(),
+            gas_price: 1.into(),
+            gas: 100_000.into(),
+            action: Action::Call(counter),
+            value: 0.into(),
+            data: vec![],
+        })
+        .sign(sender.secret(), None);
+        let mut env_info = EnvInfo::default();
+        env_info.gas_limit = 1_000_000.into();
+
+        let state = State::from_existing(
+            state_db
+                .boxed_clone_canon(&block_hash)
+                .boxed_clone_prefetch(),
+            root,
+            U256::zero(),
+            Factories::default(),
+        )
+        .unwrap();
+        let prefetched = state
+            .prefetch(&env_info, &new_istanbul_test_machine(), &[t])
+            .unwrap();
+        assert!(prefetched >= 2);
+
+        let db = state_db.boxed_clone_canon(&block_hash);
+        assert!(db.get_cached_account(&sender.address()).is_some());
+        let storage = db.get_cached(&counter, |account| {
+            account.and_then(|account| account.cached_storage_at(&H256::zero()))
+        });
+        assert_eq!(storage, Some(Some(H256::from_low_u64_be(41))));
+    }
+}
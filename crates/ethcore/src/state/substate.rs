@@ -20,7 +20,7 @@ use ethereum_types::Address;
 use evm::{CleanDustMode, Schedule};
 use std::collections::HashSet;
 use types::log_entry::LogEntry;
-use vm::access_list::AccessList;
+use vm::{access_list::AccessList, transient_storage::TransientStorage};
 
 /// State changes which should be applied in finalize,
 /// after transaction is fully executed.
@@ -43,6 +43,9 @@ pub struct Substate {
 
     /// List of accesses addresses and slots
     pub access_list: AccessList,
+
+    /// Transient storage and contracts created within the transaction
+    pub transient_storage: TransientStorage,
 }
 
 impl Substate {
@@ -59,6 +62,7 @@ impl Substate {
             sstore_clears_refund: 0,
             contracts_created: Vec::default(),
             access_list: access_list.clone(),
+            transient_storage: TransientStorage::default(),
         }
     }
 
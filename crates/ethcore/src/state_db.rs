@@ -48,6 +48,10 @@ struct AccountCache {
     /// Information on the modifications in recently committed blocks; specifically which addresses
     /// changed in which block. Ordered by block number.
     modifications: VecDeque<BlockChanges>,
+    /// Number of account lookups answered by the cache.
+    hits: u64,
+    /// Number of account lookups not answered by the cache.
+    misses: u64,
 }
 
 /// Buffered account cache item.
@@ -107,6 +111,8 @@ pub struct StateDB {
     commit_hash: Option<H256>,
     /// Number of the committing block or `None` if not committed yet.
     commit_number: Option<BlockNumber>,
+    /// Whether lookups of this instance are counted in the cache hits and misses.
+    count_lookups: bool,
 }
 
 impl StateDB {
@@ -124,6 +130,8 @@ impl StateDB {
             account_cache: Arc::new(Mutex::new(AccountCache {
                 accounts: LruCache::new(cache_items),
                 modifications: VecDeque::new(),
+                hits: 0,
+                misses: 0,
             })),
             code_cache: Arc::new(Mutex::new(MemoryLruCache::new(code_cache_size))),
             local_cache: Vec::new(),
@@ -131,6 +139,7 @@ impl StateDB {
             parent_hash: None,
             commit_hash: None,
             commit_number: None,
+            count_lookups: true,
         }
     }
 
@@ -295,6 +304,7 @@ impl StateDB {
             parent_hash: None,
             commit_hash: None,
             commit_number: None,
+            count_lookups: true,
         }
     }
 
@@ -309,9 +319,64 @@ impl StateDB {
             parent_hash: Some(parent.clone()),
             commit_hash: None,
             commit_number: None,
+            count_lookups: true,
         }
     }
 
+    /// Clone the database for prefetching on top of the same canonical state.
+    /// Lookups of the clone are not counted in the cache hits and misses.
+    pub fn boxed_clone_prefetch(&self) -> StateDB {
+        StateDB {
+            db: self.db.boxed_clone(),
+            account_cache: self.account_cache.clone(),
+            code_cache: self.code_cache.clone(),
+            local_cache: Vec::new(),
+            cache_size: self.cache_size,
+            parent_hash: self.parent_hash.clone(),
+            commit_hash: None,
+            commit_number: None,
+            count_lookups: false,
+        }
+    }
+
+    /// Add accounts loaded from the canonical state on top of which this instance was created
+    /// to the shared cache. Accounts which are already cached or were modified in a later block
+    /// are skipped. Nothing is added if the state is not one of the recently committed blocks,
+    /// since the cache might have been wiped after the accounts were read.
+    /// Returns the number of accounts added.
+    pub fn add_prefetched(&self, accounts: Vec<(Address, Option<Account>)>) -> usize {
+        let parent_hash = match self.parent_hash {
+            Some(ref parent_hash) => parent_hash,
+            None => return 0,
+        };
+        let mut cache = self.account_cache.lock();
+        let cache = &mut *cache;
+        if !cache.modifications.iter().any(|m| &m.hash == parent_hash) {
+            trace!(
+                "Prefetched accounts skipped: state {:?} is not recent",
+                parent_hash
+            );
+            return 0;
+        }
+        let mut added = 0;
+        for (address, account) in accounts {
+            if cache.accounts.contains_key(&address)
+                || !Self::is_allowed(&address, parent_hash, &cache.modifications)
+            {
+                continue;
+            }
+            cache.accounts.insert(address, account);
+            added += 1;
+        }
+        added
+    }
+
+    /// Number of account lookups answered and not answered by the shared cache.
+    pub fn cache_hits(&self) -> (u64, u64) {
+        let cache = self.account_cache.lock();
+        (cache.hits, cache.misses)
+    }
+
     /// Check if pruning is enabled on the database.
     pub fn is_pruned(&self) -> bool {
         self.db.is_pruned()
@@ -341,6 +406,17 @@ impl StateDB {
         self.cache_size
     }
 
+    fn count_lookup(&self, cache: &mut AccountCache, hit: bool) {
+        if !self.count_lookups {
+            return;
+        }
+        if hit {
+            cache.hits += 1;
+        } else {
+            cache.misses += 1;
+        }
+    }
+
     /// Check if the account can be returned from cache by matching current block parent hash against canonical
     /// state and filtering out account modified in later blocks.
     fn is_allowed(
@@ -407,12 +483,15 @@ impl state::Backend for StateDB {
         self.parent_hash.as_ref().and_then(|parent_hash| {
             let mut cache = self.account_cache.lock();
             if !Self::is_allowed(addr, parent_hash, &cache.modifications) {
+                self.count_lookup(&mut cache, false);
                 return None;
             }
-            cache
+            let account = cache
                 .accounts
                 .get_mut(addr)
-                .map(|a| a.as_ref().map(|a| a.clone_basic()))
+                .map(|a| a.as_ref().map(|a| a.clone_basic()));
+            self.count_lookup(&mut cache, account.is_some());
+            account
         })
     }
 
@@ -423,9 +502,12 @@ impl state::Backend for StateDB {
         self.parent_hash.as_ref().and_then(|parent_hash| {
             let mut cache = self.account_cache.lock();
             if !Self::is_allowed(a, parent_hash, &cache.modifications) {
+                self.count_lookup(&mut cache, false);
                 return None;
             }
-            cache.accounts.get_mut(a).map(|c| f(c.as_mut()))
+            let result = cache.accounts.get_mut(a).map(|c| f(c.as_mut()));
+            self.count_lookup(&mut cache, result.is_some());
+            result
         })
     }
 
@@ -523,4 +605,53 @@ mod tests {
         let s = state_db.boxed_clone_canon(&h3a);
         assert!(s.get_cached_account(&address).is_none());
     }
+
+    #[test]
+    fn should_add_prefetched_accounts() {
+        let state_db = get_temp_state_db();
+        let h0 = H256::random();
+        let h1 = H256::random();
+        let unchanged = Address::random();
+        let changed = Address::random();
+        let mut batch = DBTransaction::new();
+
+        let mut s = state_db.boxed_clone_canon(&H256::random());
+        s.journal_under(&mut batch, 0, &h0).unwrap();
+        s.sync_cache(&[], &[], true);
+
+        let mut s = state_db.boxed_clone_canon(&h0);
+        s.add_to_account_cache(changed, Some(Account::new_basic(1.into(), 0.into())), true);
+        s.journal_under(&mut batch, 1, &h1).unwrap();
+        s.sync_cache(&[], &[], true);
+
+        // accounts read at the state of `h0`, before `h1` was committed.
+        let prefetch = state_db.boxed_clone_canon(&h0).boxed_clone_prefetch();
+        let added = prefetch.add_prefetched(vec![
+            (unchanged, Some(Account::new_basic(2.into(), 0.into()))),
+            (changed, Some(Account::new_basic(3.into(), 0.into()))),
+        ]);
+        assert_eq!(added, 1);
+
+        let s = state_db.boxed_clone_canon(&h1);
+        assert_eq!(
+            s.get_cached_account(&unchanged).unwrap().unwrap().balance(),
+            &U256::from(2)
+        );
+        assert_eq!(
+            s.get_cached_account(&changed).unwrap().unwrap().balance(),
+            &U256::from(1)
+        );
+        assert!(prefetch.get_cached_account(&Address::random()).is_none());
+        assert_eq!(state_db.cache_hits(), (2, 0));
+
+        // accounts read at a state the cache knows nothing about.
+        let stale = state_db
+            .boxed_clone_canon(&H256::random())
+            .boxed_clone_prefetch();
+        let added = stale.add_prefetched(vec![(
+            Address::random(),
+            Some(Account::new_basic(4.into(), 0.into())),
+        )]);
+        assert_eq!(added, 0);
+    }
 }
@@ -24,7 +24,7 @@ use std::sync::Arc;
 use test_helpers::get_temp_state_with_factory;
 use trace::{NoopTracer, NoopVMTracer};
 use types::transaction::SYSTEM_ADDRESS;
-use vm::{AccessList, ActionParams, ActionValue, CallType, EnvInfo, ParamsType};
+use vm::{AccessList, ActionParams, ActionValue, CallType, EnvInfo, ParamsType, TransientStorage};
 
 use rustc_hex::FromHex;
 
@@ -63,6 +63,7 @@ fn test_blockhash_eip210(factory: Factory) {
             call_type: CallType::Call,
             params_type: ParamsType::Separate,
             access_list: AccessList::default(),
+            transient_storage: TransientStorage::default(),
         };
         let schedule = machine.schedule(env_info.number);
         let mut ex = Executive::new(&mut state, &env_info, &machine, &schedule);
@@ -87,6 +88,7 @@ fn test_blockhash_eip210(factory: Factory) {
         call_type: CallType::Call,
         params_type: ParamsType::Separate,
         access_list: AccessList::default(),
+        transient_storage: TransientStorage::default(),
     };
     let schedule = machine.schedule(env_info.number);
     let mut ex = Executive::new(&mut state, &env_info, &machine, &schedule);
@@ -313,7 +313,7 @@ impl VMTracer for ExecutiveVMTracer {
         });
     }
 
-    fn prepare_subtrace(&mut self, code: &[u8]) {
+    fn prepare_subtrace(&mut self, _address: &Address, code: &[u8]) {
         Self::with_trace_in_depth(&mut self.data, self.depth, move |trace| {
             let parent_step = trace.operations.len() - 1; // won't overflow since we must already have pushed an operation in trace_prepare_execute.
             trace.subs.push(VMTrace {
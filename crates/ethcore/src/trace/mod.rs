@@ -21,6 +21,7 @@ mod db;
 mod executive_tracer;
 mod import;
 mod noop_tracer;
+mod profiler;
 mod types;
 
 pub use self::{
@@ -30,6 +31,7 @@ pub use self::{
     import::ImportRequest,
     localized::LocalizedTrace,
     noop_tracer::{NoopTracer, NoopVMTracer},
+    profiler::{OpcodeStats, Profile, ProfilingVMTracer},
 };
 
 pub use self::types::{
@@ -107,8 +109,9 @@ pub trait VMTracer: Send {
     /// Trace the finalised execution of a single valid instruction.
     fn trace_executed(&mut self, _gas_used: U256, _stack_push: &[U256], _mem: &[u8]) {}
 
-    /// Spawn subtracer which will be used to trace deeper levels of execution.
-    fn prepare_subtrace(&mut self, _code: &[u8]) {}
+    /// Spawn subtracer which will be used to trace deeper levels of execution
+    /// of the code at `address`.
+    fn prepare_subtrace(&mut self, _address: &Address, _code: &[u8]) {}
 
     /// Finalize subtracer.
     fn done_subtrace(&mut self) {}
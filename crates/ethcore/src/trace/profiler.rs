@@ -0,0 +1,287 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! VM tracer aggregating executed opcodes per contract.
+
+use std::{
+    collections::BTreeMap,
+    ops::AddAssign,
+    time::{Duration, Instant},
+};
+
+use ethereum_types::{Address, U256};
+use trace::VMTracer;
+
+/// Aggregated executions of an opcode.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct OpcodeStats {
+    /// Number of executions.
+    pub count: u64,
+    /// Gas used, without the gas used by calls and creates in nested frames.
+    pub gas_used: U256,
+    /// Execution time, without the time spent in nested frames.
+    pub time: Duration,
+}
+
+impl AddAssign for OpcodeStats {
+    fn add_assign(&mut self, other: OpcodeStats) {
+        self.count += other.count;
+        self.gas_used = self.gas_used.saturating_add(other.gas_used);
+        self.time += other.time;
+    }
+}
+
+/// Opcode statistics of the executed contracts.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Profile {
+    /// Statistics of each opcode, by address of the executed code.
+    pub contracts: BTreeMap<Address, BTreeMap<u8, OpcodeStats>>,
+}
+
+impl Profile {
+    /// Add the statistics of another profile, e.g. of the next transaction of a block.
+    pub fn merge(&mut self, other: Profile) {
+        for (address, opcodes) in other.contracts {
+            let contract = self.contracts.entry(address).or_insert_with(BTreeMap::new);
+            for (opcode, stats) in opcodes {
+                *contract.entry(opcode).or_insert_with(Default::default) += stats;
+            }
+        }
+    }
+
+    /// Statistics of each opcode over all contracts.
+    pub fn opcodes(&self) -> BTreeMap<u8, OpcodeStats> {
+        let mut opcodes = BTreeMap::new();
+        for stats in self.contracts.values() {
+            for (opcode, stats) in stats {
+                *opcodes.entry(*opcode).or_insert_with(OpcodeStats::default) += *stats;
+            }
+        }
+        opcodes
+    }
+
+    /// Statistics of all opcodes of a contract.
+    pub fn contract_total(&self, address: &Address) -> OpcodeStats {
+        let mut total = OpcodeStats::default();
+        for stats in self
+            .contracts
+            .get(address)
+            .into_iter()
+            .flat_map(|c| c.values())
+        {
+            total += *stats;
+        }
+        total
+    }
+}
+
+/// Instruction being executed.
+struct Pending {
+    opcode: u8,
+    gas_before: U256,
+    gas_cost: U256,
+    started: Instant,
+    /// Gas used and time spent by the frames the instruction created.
+    nested_gas: U256,
+    nested_time: Duration,
+}
+
+/// Execution frame of a contract.
+struct Frame {
+    address: Address,
+    started: Instant,
+    gas_start: Option<U256>,
+    gas_left: U256,
+    pending: Option<Pending>,
+}
+
+/// VM tracer collecting the count, gas used and execution time of each opcode per contract.
+/// Calls and creates are accounted without the nested execution they trigger.
+#[derive(Default)]
+pub struct ProfilingVMTracer {
+    profile: Profile,
+    frames: Vec<Frame>,
+}
+
+impl ProfilingVMTracer {
+    /// Create a new profiler.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn record(&mut self, address: Address, opcode: u8, gas_used: U256, time: Duration) {
+        *self
+            .profile
+            .contracts
+            .entry(address)
+            .or_insert_with(BTreeMap::new)
+            .entry(opcode)
+            .or_insert_with(OpcodeStats::default) += OpcodeStats {
+            count: 1,
+            gas_used,
+            time,
+        };
+    }
+
+    /// Records the pending instruction of the innermost frame.
+    fn finish(&mut self, gas_left: Option<U256>) {
+        let (address, pending) = match self.frames.last_mut() {
+            Some(frame) => match frame.pending.take() {
+                Some(pending) => (frame.address, pending),
+                None => return,
+            },
+            None => return,
+        };
+        let gas_used = match gas_left {
+            Some(gas_left) => pending
+                .gas_before
+                .saturating_sub(gas_left)
+                .saturating_sub(pending.nested_gas),
+            None => pending.gas_cost,
+        };
+        let time = pending
+            .started
+            .elapsed()
+            .checked_sub(pending.nested_time)
+            .unwrap_or_default();
+        self.record(address, pending.opcode, gas_used, time);
+    }
+}
+
+impl VMTracer for ProfilingVMTracer {
+    type Output = Profile;
+
+    fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8, current_gas: U256) -> bool {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.gas_start.get_or_insert(current_gas);
+            frame.gas_left = current_gas;
+        }
+        true
+    }
+
+    fn trace_prepare_execute(
+        &mut self,
+        _pc: usize,
+        instruction: u8,
+        gas_cost: U256,
+        _mem_written: Option<(usize, usize)>,
+        _store_written: Option<(U256, U256)>,
+    ) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.pending = Some(Pending {
+                opcode: instruction,
+                gas_before: frame.gas_left,
+                gas_cost,
+                started: Instant::now(),
+                nested_gas: U256::zero(),
+                nested_time: Duration::default(),
+            });
+        }
+    }
+
+    fn trace_failed(&mut self) {
+        self.finish(None);
+    }
+
+    fn trace_executed(&mut self, gas_used: U256, _stack_push: &[U256], _mem: &[u8]) {
+        // `gas_used` is the gas left after the instruction.
+        if let Some(frame) = self.frames.last_mut() {
+            frame.gas_left = gas_used;
+        }
+        self.finish(Some(gas_used));
+    }
+
+    fn prepare_subtrace(&mut self, address: &Address, _code: &[u8]) {
+        self.frames.push(Frame {
+            address: *address,
+            started: Instant::now(),
+            gas_start: None,
+            gas_left: U256::zero(),
+            pending: None,
+        });
+    }
+
+    fn done_subtrace(&mut self) {
+        let frame = match self.frames.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let gas_used = frame
+            .gas_start
+            .map_or_else(U256::zero, |start| start.saturating_sub(frame.gas_left));
+        if let Some(pending) = self
+            .frames
+            .last_mut()
+            .and_then(|parent| parent.pending.as_mut())
+        {
+            pending.nested_gas = pending.nested_gas.saturating_add(gas_used);
+            pending.nested_time += frame.started.elapsed();
+        }
+    }
+
+    fn drain(self) -> Option<Profile> {
+        Some(self.profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_account_calls_without_nested_frames() {
+        let caller = Address::from_low_u64_be(1);
+        let callee = Address::from_low_u64_be(2);
+        let mut tracer = ProfilingVMTracer::new();
+
+        tracer.prepare_subtrace(&caller, &[]);
+        // PUSH1
+        assert!(tracer.trace_next_instruction(0, 0x60, 1000.into()));
+        tracer.trace_prepare_execute(0, 0x60, 3.into(), None, None);
+        tracer.trace_executed(997.into(), &[], &[]);
+        // CALL, forwarding 500 gas of which the callee uses 3
+        tracer.trace_next_instruction(2, 0xf1, 997.into());
+        tracer.trace_prepare_execute(2, 0xf1, 600.into(), None, None);
+        tracer.prepare_subtrace(&callee, &[]);
+        tracer.trace_next_instruction(0, 0x60, 500.into());
+        tracer.trace_prepare_execute(0, 0x60, 3.into(), None, None);
+        tracer.trace_executed(497.into(), &[], &[]);
+        tracer.done_subtrace();
+        tracer.trace_executed(894.into(), &[], &[]);
+        // PUSH1 failing
+        tracer.trace_next_instruction(3, 0x60, 894.into());
+        tracer.trace_prepare_execute(3, 0x60, 3.into(), None, None);
+        tracer.trace_failed();
+        tracer.done_subtrace();
+
+        let profile = tracer.drain().unwrap();
+        let caller_stats = &profile.contracts[&caller];
+        assert_eq!(caller_stats[&0x60].count, 2);
+        assert_eq!(caller_stats[&0x60].gas_used, 6.into());
+        assert_eq!(caller_stats[&0xf1].count, 1);
+        assert_eq!(caller_stats[&0xf1].gas_used, 100.into());
+        assert_eq!(profile.contracts[&callee][&0x60].gas_used, 3.into());
+        assert_eq!(profile.contract_total(&caller).count, 3);
+
+        let opcodes = profile.opcodes();
+        assert_eq!(opcodes[&0x60].count, 3);
+        assert_eq!(opcodes[&0x60].gas_used, 9.into());
+
+        let mut merged = profile.clone();
+        merged.merge(profile);
+        assert_eq!(merged.opcodes()[&0xf1].count, 2);
+    }
+}
@@ -35,7 +35,8 @@ pub enum Error {
     StackUnderflow,
     /// When execution would exceed defined Stack Limit
     OutOfStack,
-    /// When there is not enough subroutine stack elements to return from
+    /// When there is not enough subroutine stack elements to return from.
+    /// EIP-2315 has been withdrawn, kept to decode existing traces.
     SubStackUnderflow,
     /// When execution would exceed defined subroutine Stack Limit
     OutOfSubStack,
@@ -66,9 +67,6 @@ impl<'a> From<&'a VmError> for Error {
             VmError::BadInstruction { .. } => Error::BadInstruction,
             VmError::StackUnderflow { .. } => Error::StackUnderflow,
             VmError::OutOfStack { .. } => Error::OutOfStack,
-            VmError::SubStackUnderflow { .. } => Error::SubStackUnderflow,
-            VmError::OutOfSubStack { .. } => Error::OutOfSubStack,
-            VmError::InvalidSubEntry { .. } => Error::InvalidSubEntry,
             VmError::BuiltIn { .. } => Error::BuiltIn,
             VmError::InvalidCode => Error::InvalidCode,
             VmError::Wasm { .. } => Error::Wasm,
@@ -40,17 +40,24 @@ impl Transaction for transaction::Transaction {
 
 /// Get the transaction cost in gas for the given params.
 fn gas_required_for(is_create: bool, data: &[u8], schedule: &Schedule) -> u64 {
-    data.iter().fold(
-        (if is_create {
-            schedule.tx_create_gas
-        } else {
-            schedule.tx_gas
-        }) as u64,
-        |g, b| {
-            g + (match *b {
-                0 => schedule.tx_data_zero_gas,
-                _ => schedule.tx_data_non_zero_gas,
-            }) as u64
-        },
-    )
+    let initcode_gas = if is_create && schedule.eip3860 {
+        ((data.len() as u64 + 31) / 32) * schedule.initcode_word_gas as u64
+    } else {
+        0
+    };
+
+    initcode_gas
+        + data.iter().fold(
+            (if is_create {
+                schedule.tx_create_gas
+            } else {
+                schedule.tx_gas
+            }) as u64,
+            |g, b| {
+                g + (match *b {
+                    0 => schedule.tx_data_zero_gas,
+                    _ => schedule.tx_data_non_zero_gas,
+                }) as u64
+            },
+        )
 }
@@ -34,6 +34,7 @@ use chain::{
     fork_filter::ForkFilterApi, ChainSyncApi, SyncState, SyncStatus as EthSyncStatus,
     ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_64, ETH_PROTOCOL_VERSION_65,
     ETH_PROTOCOL_VERSION_66, PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2,
+    PAR_PROTOCOL_VERSION_3,
 };
 use ethcore::{
     client::{BlockChainClient, ChainMessageType, ChainNotify, NewBlocks},
@@ -662,7 +663,11 @@ impl ChainNotify for EthSync {
             .register_protocol(
                 self.eth_handler.clone(),
                 PAR_PROTOCOL,
-                &[PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2],
+                &[
+                    PAR_PROTOCOL_VERSION_1,
+                    PAR_PROTOCOL_VERSION_2,
+                    PAR_PROTOCOL_VERSION_3,
+                ],
             )
             .unwrap_or_else(|e| warn!("Error registering snapshot sync protocol: {:?}", e));
     }
@@ -723,6 +728,42 @@ impl ChainNotify for EthSync {
         });
     }
 
+    fn send_private_transactions(&self, packet: &[u8], node_ids: &[H512]) -> usize {
+        // private transactions are not cached for peers connecting later, only the ones
+        // written right away are reported.
+        self.network
+            .with_context_eval(PAR_PROTOCOL, |context| {
+                let mut sync_io = NetSyncIo::new(
+                    context,
+                    &*self.eth_handler.chain,
+                    &*self.eth_handler.snapshot_service,
+                    &self.eth_handler.overlay,
+                );
+                let mut sent = 0;
+                for peer_id in self.network.connected_peers() {
+                    let is_target = context
+                        .session_info(peer_id)
+                        .and_then(|session_info| session_info.id)
+                        .map_or(false, |id| node_ids.contains(&id));
+                    if is_target
+                        && self
+                            .eth_handler
+                            .sync
+                            .write()
+                            .send_private_transactions_packet(
+                                &mut sync_io,
+                                packet.to_vec(),
+                                peer_id,
+                            )
+                    {
+                        sent += 1;
+                    }
+                }
+                sent
+            })
+            .unwrap_or(0)
+    }
+
     fn transactions_received(&self, txs: &[UnverifiedTransaction], peer_id: PeerId) {
         let mut sync = self.eth_handler.sync.write();
         sync.transactions_received(txs, peer_id);
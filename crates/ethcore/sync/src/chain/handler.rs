@@ -43,7 +43,7 @@ use super::{
 use super::{
     BlockSet, ChainSync, ForkConfirmation, PacketProcessError, PeerAsking, PeerInfo, SyncRequester,
     SyncState, ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_64, ETH_PROTOCOL_VERSION_66,
-    MAX_NEW_BLOCK_AGE, MAX_NEW_HASHES, PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2,
+    MAX_NEW_BLOCK_AGE, MAX_NEW_HASHES, PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_3,
 };
 use network::client_version::ClientCapabilities;
 
@@ -822,7 +822,7 @@ impl SyncHandler {
         if false
             || (warp_protocol
                 && (peer.protocol_version < PAR_PROTOCOL_VERSION_1.0
-                    || peer.protocol_version > PAR_PROTOCOL_VERSION_2.0))
+                    || peer.protocol_version > PAR_PROTOCOL_VERSION_3.0))
             || (!warp_protocol
                 && (peer.protocol_version < ETH_PROTOCOL_VERSION_63.0
                     || peer.protocol_version > ETH_PROTOCOL_VERSION_66.0))
@@ -939,6 +939,38 @@ impl SyncHandler {
         io.chain().queue_transactions(transactions, peer_id);
         Ok(())
     }
+
+    /// Called when a peer forwards us transactions that must not be gossiped publicly (yet).
+    pub fn on_private_transactions(
+        sync: &ChainSync,
+        io: &mut dyn SyncIo,
+        peer_id: PeerId,
+        r: &Rlp,
+        node_id: Option<H512>,
+    ) -> Result<(), PacketProcessError> {
+        if !sync.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
+            trace!(target: "sync", "{} Ignoring private transactions from unconfirmed/unknown peer", peer_id);
+            return Ok(());
+        }
+        if !node_id.map_or(false, |node_id| io.chain().is_validator_node(&node_id)) {
+            trace!(target: "sync", "{} Ignoring private transactions from a peer that is not a validator", peer_id);
+            return Ok(());
+        }
+
+        let item_count = r.item_count()?;
+        trace!(target: "sync", "{:02} -> PrivateTransactions ({} entries)", peer_id, item_count);
+        let mut transactions = Vec::with_capacity(item_count);
+        for i in r.iter() {
+            let tx = if i.is_list() {
+                i.as_raw().to_vec()
+            } else {
+                i.data()?.to_vec()
+            };
+            transactions.push(tx);
+        }
+        io.chain().queue_private_transactions(transactions, peer_id);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -166,6 +166,8 @@ pub const ETH_PROTOCOL_VERSION_63: (u8, u8) = (63, 0x11);
 pub const PAR_PROTOCOL_VERSION_1: (u8, u8) = (1, 0x15);
 /// 2 version of OpenEthereum protocol (consensus messages added).
 pub const PAR_PROTOCOL_VERSION_2: (u8, u8) = (2, 0x16);
+/// 3 version of OpenEthereum protocol (private transactions added).
+pub const PAR_PROTOCOL_VERSION_3: (u8, u8) = (3, 0x17);
 
 pub const MAX_BODIES_TO_SEND: usize = 256;
 pub const MAX_HEADERS_TO_SEND: usize = 512;
@@ -1780,6 +1782,16 @@ impl ChainSync {
     pub fn send_consensus_packet(&mut self, io: &mut dyn SyncIo, packet: Bytes, peer_id: usize) {
         SyncPropagator::send_consensus_packet(self, io, packet, peer_id);
     }
+
+    /// Forward private transactions to a specific peer, returns whether they were sent.
+    pub fn send_private_transactions_packet(
+        &mut self,
+        io: &mut dyn SyncIo,
+        packet: Bytes,
+        peer_id: usize,
+    ) -> bool {
+        SyncPropagator::send_private_transactions_packet(self, io, packet, peer_id)
+    }
 }
 
 #[cfg(test)]
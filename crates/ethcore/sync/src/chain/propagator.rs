@@ -29,7 +29,7 @@ use super::sync_packet::SyncPacket::{self, *};
 
 use super::{
     random, ChainSync, ETH_PROTOCOL_VERSION_65, MAX_PEERS_PROPAGATION, MAX_PEER_LAG_PROPAGATION,
-    MAX_TRANSACTION_PACKET_SIZE, MIN_PEERS_PROPAGATION,
+    MAX_TRANSACTION_PACKET_SIZE, MIN_PEERS_PROPAGATION, PAR_PROTOCOL_VERSION_3,
 };
 use ethcore_miner::pool::VerifiedTransaction;
 use std::sync::Arc;
@@ -109,6 +109,7 @@ impl SyncPropagator {
         let transactions = move |io: &dyn SyncIo| {
             tx_hashes
                 .iter()
+                .filter(|hash| !io.chain().is_private_transaction(hash))
                 .filter_map(|hash| io.chain().transaction(hash))
                 .collect()
         };
@@ -336,6 +337,27 @@ impl SyncPropagator {
         SyncPropagator::send_packet(io, peer_id, ConsensusDataPacket, packet.clone());
     }
 
+    /// Forward private transactions to a specific peer, if it speaks a protocol version supporting them.
+    /// Returns whether the packet was sent.
+    pub fn send_private_transactions_packet(
+        sync: &mut ChainSync,
+        io: &mut dyn SyncIo,
+        packet: Bytes,
+        peer_id: PeerId,
+    ) -> bool {
+        let supported = sync
+            .peers
+            .get(&peer_id)
+            .map_or(false, |p| p.protocol_version >= PAR_PROTOCOL_VERSION_3.0);
+        if !supported {
+            debug!(target: "sync", "{}: Peer does not support private transactions, not forwarding", peer_id);
+            return false;
+        }
+        trace!(target: "sync", "Sending private transactions to {}", peer_id);
+        SyncPropagator::send_packet(io, peer_id, PrivateTransactionsPacket, packet);
+        true
+    }
+
     fn select_peers_for_transactions<F>(sync: &ChainSync, filter: F, are_new: bool) -> Vec<PeerId>
     where
         F: Fn(&PeerId) -> bool,
@@ -846,6 +868,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_not_propagate_private_transactions() {
+        let mut client = TestBlockChainClient::new();
+        client.add_blocks(100, EachBlockWith::Uncle);
+        let tx_hash = client.insert_private_transaction_to_queue();
+        let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+        let queue = RwLock::new(VecDeque::new());
+        let ss = TestSnapshotService::new();
+        let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+        let peer_count = SyncPropagator::propagate_ready_transactions(&mut sync, &mut io, || true);
+        let peer_count2 =
+            SyncPropagator::propagate_new_transactions(&mut sync, &mut io, vec![tx_hash], || true);
+
+        assert_eq!(0, peer_count);
+        assert_eq!(0, peer_count2);
+        assert_eq!(0, io.packets.len());
+    }
+
+    #[test]
+    fn should_send_private_transactions_to_supporting_peers_only() {
+        let mut client = TestBlockChainClient::new();
+        client.add_blocks(100, EachBlockWith::Uncle);
+        let block_hash = client.block_hash_delta_minus(1);
+        let mut sync = dummy_sync(&client);
+        let queue = RwLock::new(VecDeque::new());
+        let ss = TestSnapshotService::new();
+        let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+        insert_dummy_peer(&mut sync, 1, block_hash);
+        insert_dummy_peer(&mut sync, 2, block_hash);
+        sync.peers.get_mut(&2).unwrap().protocol_version = PAR_PROTOCOL_VERSION_3.0;
+
+        let sent1 =
+            SyncPropagator::send_private_transactions_packet(&mut sync, &mut io, vec![0xc0], 1);
+        let sent2 =
+            SyncPropagator::send_private_transactions_packet(&mut sync, &mut io, vec![0xc0], 2);
+
+        assert!(!sent1);
+        assert!(sent2);
+
+        assert_eq!(1, io.packets.len());
+        assert_eq!(2, io.packets[0].recipient);
+        // PRIVATE_TRANSACTIONS_PACKET
+        assert_eq!(0x16, io.packets[0].packet_id);
+    }
+
     #[test]
     fn should_propagate_service_transaction_to_selected_peers_only() {
         let mut client = TestBlockChainClient::new();
@@ -143,6 +143,18 @@ impl SyncSupplier {
                             ConsensusDataPacket => {
                                 SyncHandler::on_consensus_packet(io, peer, &rlp, node_id)
                             }
+                            PrivateTransactionsPacket => {
+                                let res = {
+                                    let sync_ro = sync.read();
+                                    SyncHandler::on_private_transactions(
+                                        &*sync_ro, io, peer, &rlp, node_id,
+                                    )
+                                };
+                                if res.is_err() {
+                                    io.disable_peer(peer);
+                                    sync.write().deactivate_peer(io, peer);
+                                }
+                            }
                             TransactionsPacket => {
                                 let res = {
                                     let sync_ro = sync.read();
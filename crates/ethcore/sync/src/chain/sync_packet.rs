@@ -58,6 +58,7 @@ pub enum SyncPacket {
     GetSnapshotDataPacket = 0x13,
     SnapshotDataPacket = 0x14,
     ConsensusDataPacket = 0x15,
+    PrivateTransactionsPacket = 0x16,
 }
 }
 
@@ -96,7 +97,8 @@ impl PacketInfo for SyncPacket {
             | SnapshotManifestPacket
             | GetSnapshotDataPacket
             | SnapshotDataPacket
-            | ConsensusDataPacket => PAR_PROTOCOL,
+            | ConsensusDataPacket
+            | PrivateTransactionsPacket => PAR_PROTOCOL,
         }
     }
 
@@ -153,4 +155,13 @@ mod tests {
         assert_eq!(ConsensusDataPacket.id(), ConsensusDataPacket as PacketId);
         assert_eq!(ConsensusDataPacket.protocol(), PAR_PROTOCOL);
     }
+
+    #[test]
+    fn when_private_transactions_packet_then_id_and_protocol_match() {
+        assert_eq!(
+            PrivateTransactionsPacket.id(),
+            PrivateTransactionsPacket as PacketId
+        );
+        assert_eq!(PrivateTransactionsPacket.protocol(), PAR_PROTOCOL);
+    }
 }
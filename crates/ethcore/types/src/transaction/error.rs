@@ -95,6 +95,13 @@ pub enum Error {
     TransactionTypeNotEnabled,
     /// Transaction sender is not an EOA (see EIP-3607)
     SenderIsNotEOA,
+    /// Contract creation code exceeds the initcode size limit (see EIP-3860)
+    InitCodeTooBig {
+        /// Maximal initcode size
+        limit: usize,
+        /// Initcode size of the transaction
+        got: usize,
+    },
 }
 
 impl From<crypto::publickey::Error> for Error {
@@ -157,6 +164,10 @@ impl fmt::Display for Error {
                 format!("Transaction type is not enabled for current block")
             }
             SenderIsNotEOA => "Transaction sender is not an EOA (see EIP-3607)".into(),
+            InitCodeTooBig { limit, got } => format!(
+                "Initcode size exceeds limit (see EIP-3860). Limit={}, Given={}",
+                limit, got
+            ),
         };
 
         f.write_fmt(format_args!("Transaction error ({})", msg))
@@ -30,6 +30,9 @@ pub struct Block {
     pub transactions: Option<Vec<Transaction>>,
     #[serde(rename = "uncleHeaders")]
     pub uncles: Option<Vec<Header>>,
+    /// Reason the block is expected to be rejected, if any.
+    #[serde(rename = "expectException")]
+    pub expect_exception: Option<String>,
 }
 
 impl Block {
@@ -37,6 +40,11 @@ impl Block {
     pub fn rlp(&self) -> Vec<u8> {
         self.rlp.clone().into()
     }
+
+    /// Returns true if the block is expected to be rejected.
+    pub fn expects_exception(&self) -> bool {
+        self.header.is_none() || self.expect_exception.is_some()
+    }
 }
 
 #[cfg(test)]
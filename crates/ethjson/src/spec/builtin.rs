@@ -94,6 +94,14 @@ pub struct Bls12G2Multiexp {
     pub base: u64,
 }
 
+/// Pricing for secp256r1 signature verification (EIP-7212)
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct P256Verify {
+    /// Fixed price.
+    pub price: u64,
+}
+
 /// Pricing variants.
 #[derive(Debug, PartialEq, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
@@ -122,6 +130,8 @@ pub enum Pricing {
     Bls12G1Multiexp(Bls12G1Multiexp),
     /// Pricing of bls12_381 multiexp operations in G2
     Bls12G2Multiexp(Bls12G2Multiexp),
+    /// Pricing of secp256r1 signature verification
+    P256Verify(P256Verify),
 }
 
 /// Builtin compability layer
@@ -197,7 +207,7 @@ pub struct PricingAt {
 mod tests {
     use super::{
         AltBn128ConstOperations, BTreeMap, Bls12G1Multiexp, Bls12G2Multiexp, Builtin,
-        BuiltinCompat, Linear, Modexp, Modexp2565, Pricing, PricingAt,
+        BuiltinCompat, Linear, Modexp, Modexp2565, P256Verify, Pricing, PricingAt,
     };
     use macros::map;
     use serde_json;
@@ -389,4 +399,29 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn deserialization_p256_verify() {
+        let s = r#"{
+			"name": "p256_verify",
+			"pricing": {
+				"10000000": {
+					"price": { "p256_verify": { "price": 3450 } }
+				}
+			}
+		}"#;
+        let builtin: Builtin = serde_json::from_str::<BuiltinCompat>(s).unwrap().into();
+        assert_eq!(builtin.name, "p256_verify");
+        assert_eq!(
+            builtin.pricing,
+            btreemap![
+                10000000 => PricingAt {
+                    info: None,
+                    price: Pricing::P256Verify(P256Verify{
+                            price: 3450
+                    }),
+                }
+            ]
+        );
+    }
 }
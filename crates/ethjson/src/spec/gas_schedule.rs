@@ -0,0 +1,255 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas schedule overrides deserialization.
+
+use crate::uint::{self, Uint};
+use serde::{Deserialize, Deserializer};
+
+/// Gas costs overriding the ones of the EVM schedule. Fields which are not set keep the value
+/// given by the activated forks.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct GasSchedule {
+    /// VM stack limit.
+    #[serde(default, deserialize_with = "uint::validate_optional_non_zero_usize")]
+    pub stack_limit: Option<Uint>,
+    /// Max number of nested calls/creates.
+    #[serde(default, deserialize_with = "uint::validate_optional_non_zero_usize")]
+    pub max_depth: Option<Uint>,
+    /// Gas prices for instructions in all tiers.
+    #[serde(default, deserialize_with = "validate_tier_step_gas")]
+    pub tier_step_gas: Option<[Uint; 8]>,
+    /// Gas price for `EXP` opcode.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub exp_gas: Option<Uint>,
+    /// Additional gas for `EXP` opcode for each byte of exponent.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub exp_byte_gas: Option<Uint>,
+    /// Gas price for `SHA3` opcode.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub sha3_gas: Option<Uint>,
+    /// Additional gas for `SHA3` opcode for each word of hashed memory.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub sha3_word_gas: Option<Uint>,
+    /// Gas price for loading from storage.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub sload_gas: Option<Uint>,
+    /// Gas price for setting new value to storage.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub sstore_set_gas: Option<Uint>,
+    /// Gas price for altering value in storage.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub sstore_reset_gas: Option<Uint>,
+    /// Gas refund for `SSTORE` clearing.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub sstore_refund_gas: Option<Uint>,
+    /// Gas price for altering a dirty storage slot, the `SLOAD` price if not set.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub sstore_dirty_gas: Option<Uint>,
+    /// Gas price for `JUMPDEST` opcode.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub jumpdest_gas: Option<Uint>,
+    /// Gas price for `LOG*`.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub log_gas: Option<Uint>,
+    /// Additional gas for data in `LOG*`.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub log_data_gas: Option<Uint>,
+    /// Additional gas for each topic in `LOG*`.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub log_topic_gas: Option<Uint>,
+    /// Gas price for `CREATE` opcode.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub create_gas: Option<Uint>,
+    /// Gas price for `*CALL*` opcodes.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub call_gas: Option<Uint>,
+    /// EIP-2929 COLD_SLOAD_COST.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub cold_sload_cost: Option<Uint>,
+    /// EIP-2929 COLD_ACCOUNT_ACCESS_COST.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub cold_account_access_cost: Option<Uint>,
+    /// EIP-2929 WARM_STORAGE_READ_COST.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub warm_storage_read_cost: Option<Uint>,
+    /// EIP-2930 ACCESS_LIST_ADDRESS_COST.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub access_list_address_cost: Option<Uint>,
+    /// EIP-2930 ACCESS_LIST_STORAGE_KEY_COST.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub access_list_storage_key_cost: Option<Uint>,
+    /// Gas price for `TLOAD` opcode.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub tload_gas: Option<Uint>,
+    /// Gas price for `TSTORE` opcode.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub tstore_gas: Option<Uint>,
+    /// Stipend for transfer for `CALL|CALLCODE` opcode when `value>0`.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub call_stipend: Option<Uint>,
+    /// Additional gas required for value transfer (`CALL|CALLCODE`).
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub call_value_transfer_gas: Option<Uint>,
+    /// Additional gas for creating new account (`CALL|CALLCODE`).
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub call_new_account_gas: Option<Uint>,
+    /// Refund for `SUICIDE`.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub suicide_refund_gas: Option<Uint>,
+    /// Gas for used memory.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub memory_gas: Option<Uint>,
+    /// Cost for contract length when executing `CREATE`.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub create_data_gas: Option<Uint>,
+    /// Maximum code size when creating a contract, overriding `maxCodeSize`.
+    #[serde(default, deserialize_with = "uint::validate_optional_non_zero_usize")]
+    pub create_data_limit: Option<Uint>,
+    /// Additional gas for each word of initcode.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub initcode_word_gas: Option<Uint>,
+    /// Transaction cost.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub tx_gas: Option<Uint>,
+    /// `CREATE` transaction cost.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub tx_create_gas: Option<Uint>,
+    /// Additional cost for empty data transaction.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub tx_data_zero_gas: Option<Uint>,
+    /// Additional cost for non-empty data transaction.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub tx_data_non_zero_gas: Option<Uint>,
+    /// Gas price for copying memory.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub copy_gas: Option<Uint>,
+    /// Price of `EXTCODESIZE`.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub extcodesize_gas: Option<Uint>,
+    /// Base price of `EXTCODECOPY`.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub extcodecopy_base_gas: Option<Uint>,
+    /// Price of `BALANCE`.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub balance_gas: Option<Uint>,
+    /// Price of `EXTCODEHASH`.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub extcodehash_gas: Option<Uint>,
+    /// Price of `SUICIDE`.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub suicide_gas: Option<Uint>,
+    /// Amount of additional gas to pay when `SUICIDE` credits a non-existant account.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub suicide_to_new_account_cost: Option<Uint>,
+    /// `BLOCKHASH` instruction gas cost.
+    #[serde(default, deserialize_with = "uint::validate_optional_usize")]
+    pub blockhash_gas: Option<Uint>,
+    /// Gas used in transaction divided by this number is the maximum refundable amount.
+    #[serde(default, deserialize_with = "uint::validate_optional_non_zero_usize")]
+    pub max_refund_quotient: Option<Uint>,
+    /// Gas available to sub calls and creates is limited to all but this fraction of the gas left.
+    #[serde(default, deserialize_with = "uint::validate_optional_non_zero_usize")]
+    pub sub_gas_cap_divisor: Option<Uint>,
+}
+
+fn validate_tier_step_gas<'de, D>(d: D) -> Result<Option<[Uint; 8]>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Option<[Uint; 8]> = Option::deserialize(d)?;
+    if let Some(tiers) = value {
+        for tier in tiers.iter() {
+            uint::check_usize(*tier)?;
+        }
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{spec::gas_schedule::GasSchedule, uint::Uint};
+    use ethereum_types::U256;
+    use serde_json;
+
+    #[test]
+    fn gas_schedule_deserialization() {
+        let s = r#"{
+			"sstoreSetGas": "0x7530",
+			"logDataGas": 16,
+			"sstoreDirtyGas": "0xc8",
+			"initcodeWordGas": 4,
+			"tierStepGas": [0, 2, 3, 5, 8, 10, 20, 0]
+		}"#;
+
+        let deserialized: GasSchedule = serde_json::from_str(s).unwrap();
+        assert_eq!(deserialized.sstore_set_gas, Some(Uint(U256::from(30000))));
+        assert_eq!(deserialized.log_data_gas, Some(Uint(U256::from(16))));
+        assert_eq!(deserialized.sstore_dirty_gas, Some(Uint(U256::from(200))));
+        assert_eq!(deserialized.initcode_word_gas, Some(Uint(U256::from(4))));
+        assert_eq!(
+            deserialized.tier_step_gas.map(|tiers| tiers[6]),
+            Some(Uint(U256::from(20)))
+        );
+        assert_eq!(deserialized.sload_gas, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown field")]
+    fn should_error_on_unknown_fields() {
+        let _deserialized: GasSchedule = serde_json::from_str(r#"{"sstoreGas": 1}"#).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "a non-zero value")]
+    fn should_error_on_zero_max_depth() {
+        let _deserialized: GasSchedule = serde_json::from_str(r#"{"maxDepth": 0}"#).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "a value fitting into usize")]
+    fn should_error_on_values_not_fitting_usize() {
+        let _deserialized: GasSchedule =
+            serde_json::from_str(r#"{"sloadGas": "0x10000000000000000000000000000000000000000"}"#)
+                .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "a value fitting into usize")]
+    fn should_error_on_tiers_not_fitting_usize() {
+        let _deserialized: GasSchedule = serde_json::from_str(
+            r#"{"tierStepGas": [0, 2, 3, 5, 8, 10, 20, "0x10000000000000000000000000000000000000000"]}"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "a non-zero value")]
+    fn should_error_on_zero_sub_gas_cap_divisor() {
+        let _deserialized: GasSchedule =
+            serde_json::from_str(r#"{"subGasCapDivisor": 0}"#).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid length 2")]
+    fn should_error_on_wrong_number_of_tiers() {
+        let _deserialized: GasSchedule =
+            serde_json::from_str(r#"{"tierStepGas": [0, 2]}"#).unwrap();
+    }
+}
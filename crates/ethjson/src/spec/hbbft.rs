@@ -33,6 +33,19 @@ pub struct HbbftParamsSkipBlockReward {
     pub to_block: Option<u64>,
 }
 
+/// Block space reserved for service transactions.
+/// Service transactions are zero gas price transactions of senders certified by the
+/// service transaction contract, like key generation and availability announcements.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct HbbftParamsServiceTransactionLane {
+    /// Service transactions are executed ahead of all other transactions beginning with this block (inclusive).
+    pub from_block: u64,
+    /// Block gas reserved for service transactions.
+    pub gas: u64,
+}
+
 #[serde_as]
 #[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
@@ -93,6 +106,10 @@ pub struct HbbftParams {
     /// no block verifications are done.
     #[serde(default)]
     pub forks: Vec<HbbftNetworkFork>,
+    /// Block space reserved for service transactions.
+    /// Service transactions within the lane gas are contributed and executed ahead of all other transactions.
+    /// No block space is reserved if not set.
+    pub service_transaction_lane: Option<HbbftParamsServiceTransactionLane>,
 }
 
 /// Hbbft engine config.
@@ -206,6 +223,31 @@ mod tests {
             deserialized.params.block_reward_contract_address,
             Address::from_str("2000000000000000000000000000000000000002").ok()
         );
+        assert_eq!(deserialized.params.service_transaction_lane, None);
+    }
+
+    #[test]
+    fn hbbft_deserialization_service_transaction_lane() {
+        let s = r#"{
+			"params": {
+				"minimumBlockTime": 0,
+				"maximumBlockTime": 600,
+				"transactionQueueSizeTrigger": 1,
+				"serviceTransactionLane": {
+					"fromBlock": 1000,
+					"gas": 3000000
+				}
+			}
+		}"#;
+
+        let deserialized: Hbbft = serde_json::from_str(s).unwrap();
+        assert_eq!(
+            deserialized.params.service_transaction_lane,
+            Some(HbbftParamsServiceTransactionLane {
+                from_block: 1000,
+                gas: 3_000_000,
+            })
+        );
     }
 
     #[test]
@@ -23,6 +23,7 @@ pub mod builtin;
 pub mod clique;
 pub mod engine;
 pub mod ethash;
+pub mod gas_schedule;
 pub mod genesis;
 pub mod hbbft;
 pub mod instant_seal;
@@ -42,6 +43,7 @@ pub use self::{
     clique::{Clique, CliqueParams},
     engine::Engine,
     ethash::{BlockReward, Ethash, EthashParams},
+    gas_schedule::GasSchedule,
     genesis::Genesis,
     hbbft::{Hbbft, HbbftParams},
     instant_seal::{InstantSeal, InstantSealParams},
@@ -19,8 +19,11 @@
 use crate::{
     bytes::Bytes,
     hash::{Address, H256},
+    spec::GasSchedule,
     uint::{self, Uint},
 };
+use serde::de::{Deserializer, Error};
+use std::collections::BTreeMap;
 
 /// Spec params.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -105,7 +108,8 @@ pub struct Params {
     pub eip1884_transition: Option<Uint>,
     /// See `CommonParams` docs.
     pub eip2028_transition: Option<Uint>,
-    /// See `CommonParams` docs.
+    /// EIP-2315 was withdrawn, specs setting this transition are rejected.
+    #[serde(default, deserialize_with = "reject_eip2315_transition")]
     pub eip2315_transition: Option<Uint>,
     /// See `CommonParams` docs.
     pub eip2929_transition: Option<Uint>,
@@ -122,6 +126,24 @@ pub struct Params {
     /// See `CommonParams` docs.
     pub eip3607_transition: Option<Uint>,
     /// See `CommonParams` docs.
+    pub eip3651_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip3855_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip4399_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip4895_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip3860_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip1153_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip5656_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip6780_transition: Option<Uint>,
+    /// See `CommonParams` docs.
+    pub eip3540_transition: Option<Uint>,
+    /// See `CommonParams` docs.
     pub dust_protection_transition: Option<Uint>,
     /// See `CommonParams` docs.
     pub nonce_cap_increment: Option<Uint>,
@@ -170,6 +192,18 @@ pub struct Params {
     pub eip1559_fee_collector_transition: Option<Uint>,
     /// Block at which zero gas price transactions start being checked with Certifier contract.
     pub validate_service_transactions_transition: Option<Uint>,
+    /// Gas costs of the EVM schedule overridden from the given block on.
+    pub gas_schedule_overrides: Option<BTreeMap<Uint, GasSchedule>>,
+}
+
+/// Fails on any value, EIP-2315 subroutines were withdrawn and never activated on a network.
+fn reject_eip2315_transition<'de, D>(_: D) -> Result<Option<Uint>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Err(D::Error::custom(
+        "eip2315Transition is not supported: EIP-2315 was withdrawn, remove it from the spec",
+    ))
 }
 
 #[cfg(test)]
@@ -190,7 +224,11 @@ mod tests {
 			"gasLimitBoundDivisor": "0x20",
 			"maxCodeSize": "0x1000",
 			"wasmActivationTransition": "0x1010",
-            "wasmDisableTransition": "0x2010"
+            "wasmDisableTransition": "0x2010",
+			"gasScheduleOverrides": {
+				"0x10": { "sstoreSetGas": "0x7530" },
+				"0x20": { "logDataGas": 16 }
+			}
 		}"#;
 
         let deserialized: Params = serde_json::from_str(s).unwrap();
@@ -213,6 +251,15 @@ mod tests {
             deserialized.wasm_disable_transition,
             Some(Uint(U256::from(0x2010)))
         );
+        let overrides = deserialized.gas_schedule_overrides.unwrap();
+        assert_eq!(
+            overrides[&Uint(U256::from(0x10))].sstore_set_gas,
+            Some(Uint(U256::from(30000)))
+        );
+        assert_eq!(
+            overrides[&Uint(U256::from(0x20))].log_data_gas,
+            Some(Uint(U256::from(16)))
+        );
     }
 
     #[test]
@@ -231,4 +278,19 @@ mod tests {
 
         let _deserialized: Params = serde_json::from_str(s).unwrap();
     }
+
+    #[test]
+    #[should_panic(expected = "eip2315Transition is not supported")]
+    fn test_eip2315_transition_rejected() {
+        let s = r#"{
+			"maximumExtraDataSize": "0x20",
+			"networkID" : "0x1",
+			"minGasLimit": "0x1388",
+			"accountStartNonce": "0x01",
+			"gasLimitBoundDivisor": "0x20",
+			"eip2315Transition": "0x0"
+		}"#;
+
+        let _deserialized: Params = serde_json::from_str(s).unwrap();
+    }
 }
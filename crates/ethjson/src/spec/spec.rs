@@ -40,6 +40,8 @@ pub enum ForkSpec {
     Berlin,
     London,
     BerlinToLondonAt5,
+    Shanghai,
+    Cancun,
 }
 
 /// Spec deserialization.
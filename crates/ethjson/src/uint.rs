@@ -146,6 +146,35 @@ where
     Ok(value)
 }
 
+/// Validate that the value fits into `usize`
+pub(crate) fn check_usize<E: Error>(value: Uint) -> Result<Uint, E> {
+    if value.0 > U256::from(usize::max_value()) {
+        return Err(Error::invalid_value(
+            Unexpected::Str(&value.0.to_string()),
+            &"a value fitting into usize",
+        ));
+    }
+
+    Ok(value)
+}
+
+/// Deserialize and validate that the value fits into `usize`
+pub fn validate_optional_usize<'de, D>(d: D) -> Result<Option<Uint>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Option<Uint> = Option::deserialize(d)?;
+    value.map(check_usize).transpose()
+}
+
+/// Deserialize and validate that the value is non-zero and fits into `usize`
+pub fn validate_optional_non_zero_usize<'de, D>(d: D) -> Result<Option<Uint>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    validate_optional_non_zero(d)?.map(check_usize).transpose()
+}
+
 #[cfg(test)]
 mod test {
     use crate::uint::Uint;
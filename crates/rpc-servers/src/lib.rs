@@ -18,18 +18,73 @@
 
 #![warn(missing_docs)]
 
-use std::{io, net::SocketAddr};
+#[macro_use]
+extern crate log;
+
+use std::{io, net::SocketAddr, sync::Arc};
+
+use http::{
+    hyper::{self, server::conn::Http},
+    tokio::{self, runtime::Runtime},
+};
+use jsonrpc_core::futures::{sync::oneshot, Future, Stream};
 
 pub use jsonrpc_core::{MetaIoHandler, Metadata, Middleware};
 
 /// Type alias for ipc server
 pub type IpcServer = ipc::Server;
-/// Type alias for http server
-pub type HttpServer = http::Server;
 /// Type alias for ws server
 pub type WsServer = ws::Server;
 
+/// HTTP server handle, the server is closed when the handle is dropped.
+pub struct HttpServer {
+    address: SocketAddr,
+    close: Option<oneshot::Sender<()>>,
+    runtime: Option<Runtime>,
+}
+
+impl HttpServer {
+    /// Returns the address the server is listening on.
+    pub fn address(&self) -> &SocketAddr {
+        &self.address
+    }
+
+    /// Closes the server.
+    pub fn close(self) {}
+}
+
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        if let Some(close) = self.close.take() {
+            let _ = close.send(());
+        }
+        if let Some(runtime) = self.runtime.take() {
+            let _ = runtime.shutdown_now().wait();
+        }
+    }
+}
+
+/// Stores the address of the connected peer in the request extensions,
+/// where metadata extractors can read it as `SocketAddr`.
+struct PeerAddress {
+    peer: SocketAddr,
+    middleware: Arc<dyn http::RequestMiddleware>,
+}
+
+impl http::RequestMiddleware for PeerAddress {
+    fn on_request(
+        &self,
+        mut request: hyper::Request<hyper::Body>,
+    ) -> http::RequestMiddlewareAction {
+        request.extensions_mut().insert(self.peer);
+        self.middleware.on_request(request)
+    }
+}
+
 /// Start http server asynchronously and returns result with `Server` handle on success or an error.
+///
+/// The address of the connected peer is available to the metadata `extractor`
+/// as a `SocketAddr` request extension.
 pub fn start_http<M, S, H, T, A, B>(
     addr: &SocketAddr,
     cors_domains: http::DomainsValidation<http::AccessControlAllowOrigin>,
@@ -51,23 +106,30 @@ where
     A: Into<String>,
     B: Into<String>,
 {
-    Ok(http::ServerBuilder::with_meta_extractor(handler, extractor)
-        .keep_alive(keep_alive)
-        .threads(threads)
-        .cors(cors_domains)
-        .allowed_hosts(allowed_hosts)
-        .health_api(health_api)
-        .cors_allow_headers(http::cors::AccessControlAllowHeaders::Any)
-        .max_request_body_size(max_payload * 1024 * 1024)
-        .start_http(addr)?)
+    start_http_with_middleware(
+        addr,
+        cors_domains,
+        allowed_hosts,
+        health_api,
+        handler,
+        extractor,
+        |request: hyper::Request<hyper::Body>| http::RequestMiddlewareAction::Proceed {
+            should_continue_on_invalid_cors: false,
+            request,
+        },
+        threads,
+        max_payload,
+        keep_alive,
+    )
 }
 
 /// Same as `start_http`, but takes an additional `middleware` parameter that is introduced as a
 /// hyper middleware.
-pub fn start_http_with_middleware<M, S, H, T, R>(
+pub fn start_http_with_middleware<M, S, H, T, R, A, B>(
     addr: &SocketAddr,
     cors_domains: http::DomainsValidation<http::AccessControlAllowOrigin>,
     allowed_hosts: http::DomainsValidation<http::Host>,
+    health_api: Option<(A, B)>,
     handler: H,
     extractor: T,
     middleware: R,
@@ -83,18 +145,87 @@ where
     H: Into<jsonrpc_core::MetaIoHandler<M, S>>,
     T: http::MetaExtractor<M>,
     R: http::RequestMiddleware,
+    A: Into<String>,
+    B: Into<String>,
 {
-    Ok(http::ServerBuilder::with_meta_extractor(handler, extractor)
-        .keep_alive(keep_alive)
-        .threads(threads)
-        .cors(cors_domains)
-        .allowed_hosts(allowed_hosts)
-        .cors_allow_headers(http::cors::AccessControlAllowHeaders::Any)
-        .max_request_body_size(max_payload * 1024 * 1024)
-        .request_middleware(middleware)
-        .start_http(addr)?)
+    let rpc = http::Rpc {
+        handler: Arc::new(handler.into()),
+        extractor: Arc::new(extractor),
+    };
+    let middleware: Arc<dyn http::RequestMiddleware> = Arc::new(middleware);
+    let cors_domains: Option<Vec<_>> = cors_domains.into();
+    let health_api = health_api.map(|(path, method)| (path.into(), method.into()));
+    let max_request_body_size = max_payload * 1024 * 1024;
+
+    let listener = tokio::net::TcpListener::bind(addr)?;
+    let address = listener.local_addr()?;
+    let allowed_hosts = allowed_hosts_with(allowed_hosts.into(), &address);
+
+    let runtime = tokio::runtime::Builder::new()
+        .core_threads(::std::cmp::max(threads, 1))
+        .name_prefix("http.worker")
+        .build()?;
+    let (close, shutdown_signal) = oneshot::channel();
+
+    let mut http = Http::new();
+    http.keep_alive(keep_alive);
+    let server = listener
+        .incoming()
+        .for_each(move |socket| {
+            let peer = socket.peer_addr()?;
+            let service = http::ServerHandler::new(
+                rpc.downgrade(),
+                cors_domains.clone(),
+                None,
+                http::cors::AccessControlAllowHeaders::Any,
+                allowed_hosts.clone(),
+                Arc::new(PeerAddress {
+                    peer,
+                    middleware: middleware.clone(),
+                }),
+                http::RestApi::Disabled,
+                health_api.clone(),
+                max_request_body_size,
+                keep_alive,
+            );
+            tokio::spawn(
+                http.serve_connection(socket, service)
+                    .map_err(|e| error!("Error serving connection: {:?}", e)),
+            );
+            Ok(())
+        })
+        .map_err(|e| warn!("Incoming streams error, closing server: {:?}", e))
+        .select(shutdown_signal.map_err(|_| ()))
+        .map(|_| ())
+        .map_err(|_| ());
+    runtime.executor().spawn(server);
+
+    Ok(HttpServer {
+        address,
+        close: Some(close),
+        runtime: Some(runtime),
+    })
 }
 
+/// Allows the address the server is listening on as host, like the jsonrpc HTTP server does.
+fn allowed_hosts_with(
+    hosts: Option<Vec<http::Host>>,
+    address: &SocketAddr,
+) -> Option<Vec<http::Host>> {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    hosts.map(|mut hosts| {
+        let address_string = address.to_string();
+        if address.ip() == IpAddr::V4(Ipv4Addr::UNSPECIFIED) {
+            hosts.push(address_string.replace("0.0.0.0", "127.0.0.1").into());
+            hosts.push(address_string.replace("0.0.0.0", "localhost").into());
+        } else if address.ip() == IpAddr::V4(Ipv4Addr::LOCALHOST) {
+            hosts.push(address_string.replace("127.0.0.1", "localhost").into());
+        }
+        hosts.push(address_string.into());
+        hosts
+    })
+}
 /// Start IPC server listening on given path.
 pub fn start_ipc<M, S, H, T>(addr: &str, handler: H, extractor: T) -> io::Result<ipc::Server>
 where
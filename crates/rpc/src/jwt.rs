@@ -0,0 +1,385 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! HS256 JWT authentication of RPC requests.
+
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::Path,
+};
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use crypto::hmac;
+use http::{self, hyper};
+use rand::{rngs::OsRng, RngCore};
+use rustc_hex::{FromHex, ToHex};
+use serde_json;
+
+use authcodes::{DefaultTimeProvider, TimeProvider};
+
+/// Length of the shared secret in bytes.
+pub const JWT_SECRET_LENGTH: usize = 32;
+/// Maximal difference in seconds between the `iat` claim and the current time.
+const IAT_THRESHOLD: u64 = 60;
+
+/// JWT validation error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JwtError {
+    /// `Authorization` header is missing or is not a bearer token.
+    MissingToken,
+    /// Token is not a well-formed JWT.
+    Malformed,
+    /// Token is not signed with HS256.
+    UnsupportedAlgorithm(String),
+    /// Signature does not match the shared secret.
+    InvalidSignature,
+    /// `iat` claim is missing.
+    MissingIssuedAt,
+    /// `iat` claim is too far from the current time.
+    Stale(u64),
+}
+
+impl fmt::Display for JwtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JwtError::MissingToken => write!(f, "Missing bearer token"),
+            JwtError::Malformed => write!(f, "Malformed token"),
+            JwtError::UnsupportedAlgorithm(ref alg) => {
+                write!(f, "Unsupported signing algorithm: {}", alg)
+            }
+            JwtError::InvalidSignature => write!(f, "Invalid token signature"),
+            JwtError::MissingIssuedAt => write!(f, "Missing iat claim"),
+            JwtError::Stale(iat) => write!(f, "Stale token issued at {}", iat),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    iat: Option<u64>,
+    /// APIs the token grants access to, all enabled APIs if missing.
+    #[serde(default)]
+    apis: Option<Vec<String>>,
+    /// Stable identifier of the token holder.
+    #[serde(default)]
+    id: Option<String>,
+}
+
+/// APIs a request is restricted to by the `apis` claim of its token.
+///
+/// Inserted into the request extensions by `JwtAuth`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllowedApis(pub Vec<String>);
+
+/// Holder of the token a request was authorized with, given by the `id` claim.
+///
+/// Requests are rate limited per holder, so that every token issued to the same
+/// holder draws from one budget. Inserted into the request extensions by `JwtAuth`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenId(pub String);
+
+/// Claims of a valid token.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Token {
+    /// APIs the token is restricted to, `None` if it grants access to all APIs.
+    pub apis: Option<AllowedApis>,
+    /// Holder of the token, if the token names one.
+    pub id: Option<TokenId>,
+}
+
+/// Shared secret used to sign tokens.
+#[derive(Clone, PartialEq)]
+pub struct JwtSecret([u8; JWT_SECRET_LENGTH]);
+
+impl fmt::Debug for JwtSecret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "JwtSecret(..)")
+    }
+}
+
+impl JwtSecret {
+    /// Creates a new random secret.
+    pub fn random() -> Self {
+        let mut secret = [0u8; JWT_SECRET_LENGTH];
+        OsRng.fill_bytes(&mut secret);
+        JwtSecret(secret)
+    }
+
+    /// Parses a hex-encoded secret, with or without `0x` prefix.
+    pub fn from_hex(hex: &str) -> Result<Self, String> {
+        let hex = hex.trim();
+        let hex = hex.strip_prefix("0x").unwrap_or(hex);
+        let bytes: Vec<u8> = hex
+            .from_hex()
+            .map_err(|e| format!("Invalid JWT secret: {}", e))?;
+        if bytes.len() != JWT_SECRET_LENGTH {
+            return Err(format!(
+                "Invalid JWT secret: expected {} bytes, got {}",
+                JWT_SECRET_LENGTH,
+                bytes.len()
+            ));
+        }
+        let mut secret = [0u8; JWT_SECRET_LENGTH];
+        secret.copy_from_slice(&bytes);
+        Ok(JwtSecret(secret))
+    }
+
+    /// Reads the secret from given file. A random secret is generated
+    /// and written to the file if it does not exist.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            return JwtSecret::from_hex(&content)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+
+        let secret = JwtSecret::random();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        // the secret must not be readable by other users.
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(path)?;
+        file.write_all(secret.0.to_hex().as_bytes())?;
+        Ok(secret)
+    }
+}
+
+/// Validates HS256 JWTs signed with a shared secret.
+pub struct JwtAuth<T: TimeProvider = DefaultTimeProvider> {
+    secret: JwtSecret,
+    now: T,
+}
+
+impl JwtAuth<DefaultTimeProvider> {
+    /// Creates new validator using `DefaultTimeProvider`.
+    pub fn new(secret: JwtSecret) -> Self {
+        JwtAuth::with_time_provider(secret, DefaultTimeProvider)
+    }
+}
+
+impl<T: TimeProvider> JwtAuth<T> {
+    /// Creates new validator with given `TimeProvider`.
+    pub fn with_time_provider(secret: JwtSecret, now: T) -> Self {
+        JwtAuth { secret, now }
+    }
+
+    /// Validates the value of an `Authorization` header and returns the claims of the token.
+    pub fn validate_header(&self, header: Option<&str>) -> Result<Token, JwtError> {
+        let token = header
+            .and_then(|header| header.strip_prefix("Bearer "))
+            .ok_or(JwtError::MissingToken)?;
+        self.validate(token.trim())
+    }
+
+    /// Validates a token and returns its claims.
+    pub fn validate(&self, token: &str) -> Result<Token, JwtError> {
+        let mut parts = token.split('.');
+        let (header, claims, signature) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(header), Some(claims), Some(signature), None) => (header, claims, signature),
+                _ => return Err(JwtError::Malformed),
+            };
+
+        let decode = |part: &str| {
+            URL_SAFE_NO_PAD
+                .decode(part)
+                .map_err(|_| JwtError::Malformed)
+        };
+        let header: Header =
+            serde_json::from_slice(&decode(header)?).map_err(|_| JwtError::Malformed)?;
+        if header.alg != "HS256" {
+            return Err(JwtError::UnsupportedAlgorithm(header.alg));
+        }
+
+        let signed = &token[..token.len() - signature.len() - 1];
+        let key = hmac::VerifyKey::sha256(&self.secret.0);
+        if !hmac::verify(&key, signed.as_bytes(), &decode(signature)?) {
+            return Err(JwtError::InvalidSignature);
+        }
+
+        let claims: Claims =
+            serde_json::from_slice(&decode(claims)?).map_err(|_| JwtError::Malformed)?;
+        let iat = claims.iat.ok_or(JwtError::MissingIssuedAt)?;
+        let now = self.now.now();
+        if iat.max(now) - iat.min(now) > IAT_THRESHOLD {
+            return Err(JwtError::Stale(iat));
+        }
+
+        Ok(Token {
+            apis: claims.apis.map(AllowedApis),
+            id: claims.id.map(TokenId),
+        })
+    }
+
+    fn unauthorized(&self, error: JwtError) -> hyper::Response<hyper::Body> {
+        let mut response = hyper::Response::new(hyper::Body::from(format!("{}\n", error)));
+        *response.status_mut() = hyper::StatusCode::UNAUTHORIZED;
+        response
+    }
+}
+
+impl<T: TimeProvider + Send + Sync + 'static> http::RequestMiddleware for JwtAuth<T> {
+    fn on_request(&self, request: hyper::Request<hyper::Body>) -> http::RequestMiddlewareAction {
+        // Only RPC calls are authenticated, CORS preflight and health checks are not.
+        if request.method() != hyper::Method::POST {
+            return http::RequestMiddlewareAction::Proceed {
+                should_continue_on_invalid_cors: false,
+                request,
+            };
+        }
+
+        let header = request
+            .headers()
+            .get(hyper::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+        match self.validate_header(header) {
+            Ok(token) => {
+                let mut request = request;
+                if let Some(apis) = token.apis {
+                    request.extensions_mut().insert(apis);
+                }
+                if let Some(id) = token.id {
+                    request.extensions_mut().insert(id);
+                }
+                http::RequestMiddlewareAction::Proceed {
+                    should_continue_on_invalid_cors: false,
+                    request,
+                }
+            }
+            Err(error) => {
+                debug!(target: "rpc", "Rejected unauthorized request: {}", error);
+                self.unauthorized(error).into()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    const SECRET: &str = "0x7365637265747365637265747365637265747365637265747365637265747365";
+
+    pub fn token(secret: &JwtSecret, header: &str, claims: &str) -> String {
+        let signed = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header),
+            URL_SAFE_NO_PAD.encode(claims)
+        );
+        let signature = hmac::sign(&hmac::SigKey::sha256(&secret.0), signed.as_bytes());
+        format!("{}.{}", signed, URL_SAFE_NO_PAD.encode(&*signature))
+    }
+
+    fn auth(now: u64) -> JwtAuth<impl TimeProvider> {
+        JwtAuth::with_time_provider(JwtSecret::from_hex(SECRET).unwrap(), move || now)
+    }
+
+    #[test]
+    fn should_accept_fresh_token() {
+        let secret = JwtSecret::from_hex(SECRET).unwrap();
+        let token = token(&secret, r#"{"alg":"HS256","typ":"JWT"}"#, r#"{"iat":1000}"#);
+
+        assert_eq!(auth(1000).validate(&token), Ok(Token::default()));
+        assert_eq!(auth(1060).validate(&token), Ok(Token::default()));
+        assert_eq!(
+            auth(1000).validate_header(Some(&format!("Bearer {}", token))),
+            Ok(Token::default())
+        );
+    }
+
+    #[test]
+    fn should_return_claims() {
+        let secret = JwtSecret::from_hex(SECRET).unwrap();
+        let token = token(
+            &secret,
+            r#"{"alg":"HS256","typ":"JWT"}"#,
+            r#"{"iat":1000,"apis":["eth","net"],"id":"alice"}"#,
+        );
+
+        assert_eq!(
+            auth(1000).validate(&token),
+            Ok(Token {
+                apis: Some(AllowedApis(vec!["eth".into(), "net".into()])),
+                id: Some(TokenId("alice".into())),
+            })
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_tokens() {
+        let secret = JwtSecret::from_hex(SECRET).unwrap();
+        let header = r#"{"alg":"HS256","typ":"JWT"}"#;
+        let auth = auth(1000);
+
+        assert_eq!(
+            auth.validate(&token(&secret, header, r#"{"iat":900}"#)),
+            Err(JwtError::Stale(900))
+        );
+        assert_eq!(
+            auth.validate(&token(&secret, header, r#"{"exp":2000}"#)),
+            Err(JwtError::MissingIssuedAt)
+        );
+        assert_eq!(
+            auth.validate(&token(&JwtSecret::random(), header, r#"{"iat":1000}"#)),
+            Err(JwtError::InvalidSignature)
+        );
+        assert_eq!(
+            auth.validate(&token(&secret, r#"{"alg":"none"}"#, r#"{"iat":1000}"#)),
+            Err(JwtError::UnsupportedAlgorithm("none".into()))
+        );
+        assert_eq!(auth.validate("abc.def"), Err(JwtError::Malformed));
+        assert_eq!(auth.validate_header(None), Err(JwtError::MissingToken));
+    }
+
+    #[test]
+    fn should_create_secret_file() {
+        let tempdir = TempDir::new("").unwrap();
+        let path = tempdir.path().join("jwt.hex");
+
+        let secret = JwtSecret::from_file(&path).unwrap();
+
+        assert_eq!(JwtSecret::from_file(&path).unwrap(), secret);
+        assert!(JwtSecret::from_hex("0x1234").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_create_secret_file_readable_by_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tempdir = TempDir::new("").unwrap();
+        let path = tempdir.path().join("jwt.hex");
+
+        JwtSecret::from_file(&path).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
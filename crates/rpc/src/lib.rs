@@ -41,6 +41,7 @@
 extern crate futures;
 
 extern crate ansi_term;
+extern crate base64;
 extern crate itertools;
 extern crate order_stat;
 extern crate parking_lot;
@@ -68,6 +69,7 @@ extern crate ethcore_sync as sync;
 extern crate ethereum_types;
 extern crate ethkey;
 extern crate ethstore;
+extern crate evm;
 extern crate fetch;
 extern crate keccak_hash as hash;
 extern crate parity_bytes as bytes;
@@ -112,7 +114,6 @@ extern crate ethcore_call_contract as call_contract;
 // #[cfg(test)]
 extern crate tempdir;
 
-#[cfg(test)]
 extern crate rpc_servers;
 
 extern crate rpc_common;
@@ -120,13 +121,15 @@ extern crate rpc_common;
 pub extern crate jsonrpc_ws_server as ws;
 
 mod authcodes;
+mod jwt;
 pub mod v1;
 
 // #[cfg(test)]
 pub mod tests;
 
 pub use http::{
-    cors::AccessControlAllowHeaders, hyper, AccessControlAllowOrigin, DomainsValidation, Host,
+    cors::{AccessControlAllowHeaders, AllowCors},
+    cors_allow_origin, hyper, is_host_allowed, AccessControlAllowOrigin, DomainsValidation, Host,
     RequestMiddleware, RequestMiddlewareAction,
 };
 pub use ipc::{
@@ -135,12 +138,13 @@ pub use ipc::{
 pub use jsonrpc_pubsub::Session as PubSubSession;
 
 pub use authcodes::{AuthCodes, TimeProvider};
+pub use jwt::{AllowedApis, JwtAuth, JwtError, JwtSecret, Token, TokenId};
 pub use v1::{
     block_import::{is_major_importing, is_major_importing_or_waiting},
     dispatch,
     extractors::{RpcExtractor, WsDispatcher, WsExtractor, WsStats},
-    informant, signer, Metadata, NetworkSettings, Origin,
+    access, informant, limiter, signer, Metadata, NetworkSettings, Origin,
 };
 
 /// RPC HTTP Server instance
-pub type HttpServer = http::Server;
+pub type HttpServer = rpc_servers::HttpServer;
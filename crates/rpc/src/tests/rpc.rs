@@ -36,8 +36,9 @@ fn serve(handler: Option<MetaIoHandler<Metadata>>) -> Server<HttpServer> {
             &address,
             http::DomainsValidation::Disabled,
             http::DomainsValidation::Disabled,
+            None::<(String, String)>,
             handler,
-            extractors::RpcExtractor,
+            extractors::RpcExtractor::default(),
             |request: hyper::Request<hyper::Body>| http::RequestMiddlewareAction::Proceed {
                 should_continue_on_invalid_cors: false,
                 request,
@@ -0,0 +1,144 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Restricts requests to the APIs granted by the `apis` claim of their JWT.
+
+use std::collections::HashMap;
+
+use jsonrpc_core as core;
+use jsonrpc_core::futures::{
+    future::{self, Either},
+    Future,
+};
+
+use v1::{helpers::errors, Metadata};
+
+/// Middleware rejecting calls to methods of APIs the caller's token does not grant.
+#[derive(Debug, Default)]
+pub struct ApiAccessMiddleware {
+    /// API names of all registered methods.
+    methods: HashMap<String, String>,
+}
+
+impl ApiAccessMiddleware {
+    /// Creates new middleware with the API name of every registered method.
+    pub fn new(methods: HashMap<String, String>) -> Self {
+        ApiAccessMiddleware { methods }
+    }
+
+    fn is_allowed(&self, method: &str, meta: &Metadata) -> bool {
+        match meta.apis {
+            None => true,
+            Some(ref apis) => match self.methods.get(method) {
+                Some(api) => apis.contains(api),
+                // unknown methods are rejected by the handler anyway.
+                None => true,
+            },
+        }
+    }
+}
+
+impl core::Middleware<Metadata> for ApiAccessMiddleware {
+    type Future = core::FutureResponse;
+    type CallFuture = core::FutureOutput;
+
+    fn on_call<F, X>(
+        &self,
+        call: core::Call,
+        meta: Metadata,
+        next: F,
+    ) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(core::Call, Metadata) -> X + Send + Sync,
+        X: Future<Item = Option<core::Output>, Error = ()> + Send + 'static,
+    {
+        let (method, id, jsonrpc) = match call {
+            core::Call::MethodCall(ref call) => (&call.method, Some(call.id.clone()), call.jsonrpc),
+            core::Call::Notification(ref notification) => {
+                (&notification.method, None, notification.jsonrpc)
+            }
+            core::Call::Invalid { .. } => return Either::B(next(call, meta)),
+        };
+
+        if self.is_allowed(method, &meta) {
+            return Either::B(next(call, meta));
+        }
+
+        debug!(target: "rpc", "Rejected {}: API not granted by token", method);
+        let error = errors::api_not_allowed(method);
+        let output = id.map(|id| core::Output::from(Err(error), id, jsonrpc));
+        Either::A(Box::new(future::ok(output)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::{MetaIoHandler, Params, Value};
+
+    fn io() -> MetaIoHandler<Metadata, ApiAccessMiddleware> {
+        let methods = vec![
+            ("eth_blockNumber".to_owned(), "eth".to_owned()),
+            ("personal_listAccounts".to_owned(), "personal".to_owned()),
+        ];
+        let mut io =
+            MetaIoHandler::with_middleware(ApiAccessMiddleware::new(methods.into_iter().collect()));
+        io.add_method("eth_blockNumber", |_: Params| {
+            Ok(Value::String("0x1".into()))
+        });
+        io.add_method("personal_listAccounts", |_: Params| {
+            Ok(Value::Array(vec![]))
+        });
+        io
+    }
+
+    #[test]
+    fn should_only_allow_granted_apis() {
+        let io = io();
+        let meta = Metadata {
+            apis: Some(vec!["eth".into()]),
+            ..Default::default()
+        };
+
+        let request = r#"{"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 1}"#;
+        let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+        assert_eq!(
+            io.handle_request_sync(request, meta.clone()),
+            Some(response.to_owned())
+        );
+
+        let request =
+            r#"{"jsonrpc": "2.0", "method": "personal_listAccounts", "params": [], "id": 1}"#;
+        let response = r#"{"jsonrpc":"2.0","error":{"code":-32040,"message":"Request has been rejected.","data":"API of personal_listAccounts is not granted by the token."},"id":1}"#;
+        assert_eq!(
+            io.handle_request_sync(request, meta),
+            Some(response.to_owned())
+        );
+    }
+
+    #[test]
+    fn should_allow_all_apis_without_restriction() {
+        let io = io();
+
+        let request =
+            r#"{"jsonrpc": "2.0", "method": "personal_listAccounts", "params": [], "id": 1}"#;
+        let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+        assert_eq!(
+            io.handle_request_sync(request, Metadata::default()),
+            Some(response.to_owned())
+        );
+    }
+}
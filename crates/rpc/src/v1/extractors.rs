@@ -17,6 +17,8 @@
 //! OpenEthereum-specific metadata extractors.
 
 use std::{
+    cell::RefCell,
+    net::{IpAddr, SocketAddr},
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -28,12 +30,61 @@ use ipc;
 use jsonrpc_core as core;
 use jsonrpc_core::futures::future::Either;
 use jsonrpc_pubsub::Session;
+use jwt::{AllowedApis, JwtAuth, TokenId};
 use ws;
 
 use v1::{informant::RpcStats, Metadata, Origin};
 
 /// Common HTTP & IPC metadata extractor.
-pub struct RpcExtractor;
+#[derive(Debug, Clone, Default)]
+pub struct RpcExtractor {
+    trusted_proxies: Vec<IpAddr>,
+}
+
+impl RpcExtractor {
+    /// Creates new `RpcExtractor` honoring the `X-Forwarded-For` header of requests
+    /// received from given proxies.
+    pub fn new(trusted_proxies: Vec<IpAddr>) -> Self {
+        RpcExtractor { trusted_proxies }
+    }
+
+    /// Returns the identifier used to rate limit the client that sent the request.
+    ///
+    /// Requests authorized with a token that names its holder are attributed to the holder,
+    /// other requests to the client address which requires the peer `SocketAddr` in the
+    /// request extensions.
+    pub fn client(&self, req: &hyper::Request<hyper::Body>) -> Option<String> {
+        if let Some(TokenId(ref id)) = req.extensions().get::<TokenId>() {
+            return Some(token_client(id));
+        }
+        self.client_address(req).map(|addr| format!("ip:{}", addr))
+    }
+
+    /// Returns the address of the client that sent the request.
+    ///
+    /// Requests received from a trusted proxy are attributed to the last address in
+    /// `X-Forwarded-For` that was not added by a trusted proxy.
+    fn client_address(&self, req: &hyper::Request<hyper::Body>) -> Option<IpAddr> {
+        let peer = req.extensions().get::<SocketAddr>()?.ip();
+        let forwarded_for = req
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|val| val.to_str().ok())
+            .unwrap_or_default();
+
+        let mut client = peer;
+        for addr in forwarded_for.rsplit(',') {
+            if !self.trusted_proxies.contains(&client) {
+                break;
+            }
+            match addr.trim().parse() {
+                Ok(addr) => client = addr,
+                Err(_) => break,
+            }
+        }
+        Some(client)
+    }
+}
 
 impl http::MetaExtractor<Metadata> for RpcExtractor {
     fn read_metadata(&self, req: &hyper::Request<hyper::Body>) -> Metadata {
@@ -51,6 +102,11 @@ impl http::MetaExtractor<Metadata> for RpcExtractor {
                 user_agent.unwrap_or_else(|| "unknown agent".to_string())
             )),
             session: None,
+            client: self.client(req),
+            apis: req
+                .extensions()
+                .get::<AllowedApis>()
+                .map(|apis| apis.0.clone()),
         }
     }
 }
@@ -60,13 +116,43 @@ impl ipc::MetaExtractor<Metadata> for RpcExtractor {
         Metadata {
             origin: Origin::Ipc(H256::from_low_u64_be(req.session_id)),
             session: Some(Arc::new(Session::new(req.sender.clone()))),
+            client: None,
+            apis: None,
         }
     }
 }
 
+/// Returns the identifier used to rate limit the holder of a token.
+fn token_client(id: &str) -> String {
+    format!("token:{}", id)
+}
+
+/// Claims of the token presented in a WebSockets handshake.
+#[derive(Debug, Default, PartialEq)]
+struct Handshake {
+    /// Identifier used to rate limit the session.
+    client: Option<String>,
+    /// APIs the session is restricted to.
+    apis: Option<Vec<String>>,
+}
+
+thread_local! {
+    /// Claims of the handshake that is currently processed.
+    ///
+    /// The server runs the middleware and the metadata extractor of a session one after the
+    /// other on the same thread, but only the middleware gets to see the handshake headers.
+    static HANDSHAKE: RefCell<Handshake> = RefCell::new(Handshake::default());
+}
+
+/// Takes the claims stored by the middleware for the current handshake.
+fn take_handshake() -> Handshake {
+    HANDSHAKE.with(|handshake| handshake.replace(Handshake::default()))
+}
+
 /// WebSockets server metadata extractor and request middleware.
 pub struct WsExtractor {
     authcodes_path: Option<PathBuf>,
+    jwt: Option<Arc<JwtAuth>>,
 }
 
 impl WsExtractor {
@@ -74,8 +160,15 @@ impl WsExtractor {
     pub fn new(path: Option<&Path>) -> Self {
         WsExtractor {
             authcodes_path: path.map(ToOwned::to_owned),
+            jwt: None,
         }
     }
+
+    /// Requires connections to present a valid JWT in the `Authorization` header.
+    pub fn with_jwt(mut self, jwt: Option<Arc<JwtAuth>>) -> Self {
+        self.jwt = jwt;
+        self
+    }
 }
 
 impl ws::MetaExtractor<Metadata> for WsExtractor {
@@ -100,7 +193,16 @@ impl ws::MetaExtractor<Metadata> for WsExtractor {
             },
         };
         let session = Some(Arc::new(Session::new(req.sender())));
-        Metadata { origin, session }
+        // the server does not expose the peer address, sessions without a token
+        // holder are limited separately.
+        let handshake = take_handshake();
+        let client = handshake.client.unwrap_or_else(|| format!("ws:{}", id));
+        Metadata {
+            origin,
+            session,
+            client: Some(client),
+            apis: handshake.apis,
+        }
     }
 }
 
@@ -108,6 +210,8 @@ impl ws::RequestMiddleware for WsExtractor {
     fn process(&self, req: &ws::ws::Request) -> ws::MiddlewareAction {
         use self::ws::ws::Response;
 
+        take_handshake();
+
         // Reply with 200 OK to HEAD requests.
         if req.method() == "HEAD" {
             let mut response = Response::new(200, "OK", vec![]);
@@ -126,6 +230,36 @@ impl ws::RequestMiddleware for WsExtractor {
             return Some(response).into();
         }
 
+        // If JWT authentication is enabled the token needs to be valid.
+        if let Some(ref jwt) = self.jwt {
+            let authorization = req
+                .header("authorization")
+                .and_then(|e| ::std::str::from_utf8(e).ok());
+            let error = match jwt.validate_header(authorization) {
+                Ok(token) => {
+                    let handshake = Handshake {
+                        client: token.id.map(|TokenId(id)| token_client(&id)),
+                        apis: token.apis.map(|AllowedApis(apis)| apis),
+                    };
+                    HANDSHAKE.with(|current| *current.borrow_mut() = handshake);
+                    None
+                }
+                Err(error) => Some(error.to_string()),
+            };
+            if let Some(error) = error {
+                warn!(
+                    "Blocked connection from {}: {}.",
+                    req.header("origin")
+                        .and_then(|e| ::std::str::from_utf8(e).ok())
+                        .unwrap_or("Unknown Origin"),
+                    error
+                );
+                let mut response = Response::new(401, "Unauthorized", vec![]);
+                add_security_headers(&mut response);
+                return Some(response).into();
+            }
+        }
+
         // If protocol is provided it needs to be valid.
         let protocols = req.protocols().ok().unwrap_or_else(Vec::new);
         if let Some(ref path) = self.authcodes_path {
@@ -260,17 +394,26 @@ impl<M: core::Middleware<Metadata>> core::Middleware<Metadata> for WsDispatcher<
 
 #[cfg(test)]
 mod tests {
-    use super::RpcExtractor;
+    use super::{take_handshake, Handshake, RpcExtractor, WsExtractor};
+    use std::{
+        net::{IpAddr, SocketAddr},
+        sync::Arc,
+        time::UNIX_EPOCH,
+    };
+
     use http::{
         hyper::{Body, Request},
         MetaExtractor,
     };
+    use jwt::{tests::token, JwtAuth, JwtSecret, TokenId};
+    use v1::limiter::{RateLimitConfig, RateLimiter, Rejection};
+    use ws::{self, RequestMiddleware};
     use Origin;
 
     #[test]
     fn should_extract_rpc_origin() {
         // given
-        let extractor = RpcExtractor;
+        let extractor = RpcExtractor::default();
         let req1 = Request::get("127.0.0.1").body(Body::empty()).unwrap();
         let req2 = Request::get("127.0.0.1")
             .header("user-agent", "http://openethereum.github.io")
@@ -301,4 +444,129 @@ mod tests {
             Origin::Rpc("http://openethereum.github.io / http://openethereum.github.io".into())
         );
     }
+
+    #[test]
+    fn should_identify_client_by_peer_address() {
+        // given
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let extractor = RpcExtractor::new(vec![proxy]);
+        let request = |peer: &str, forwarded_for: &str| {
+            let mut req = Request::post("/")
+                .header("x-forwarded-for", forwarded_for)
+                .header("authorization", "Bearer token")
+                .body(Body::empty())
+                .unwrap();
+            req.extensions_mut()
+                .insert(peer.parse::<SocketAddr>().unwrap());
+            extractor.read_metadata(&req).client
+        };
+
+        // then
+        // forwarded addresses of untrusted peers are ignored
+        assert_eq!(
+            request("1.2.3.4:1000", "5.6.7.8"),
+            Some("ip:1.2.3.4".into())
+        );
+        assert_eq!(
+            request("1.2.3.4:2000", "9.9.9.9"),
+            Some("ip:1.2.3.4".into())
+        );
+        // trusted proxies are skipped
+        assert_eq!(
+            request("10.0.0.1:1000", "6.6.6.6, 5.6.7.8, 10.0.0.1"),
+            Some("ip:5.6.7.8".into())
+        );
+        assert_eq!(
+            request("10.0.0.1:1000", "garbage"),
+            Some("ip:10.0.0.1".into())
+        );
+        // requests without a peer address are not attributed to a client
+        let req = Request::post("/").body(Body::empty()).unwrap();
+        assert_eq!(extractor.read_metadata(&req).client, None);
+    }
+
+    /// Runs the middleware on a handshake with a token carrying given claims.
+    fn handshake(extractor: &WsExtractor, secret: &JwtSecret, claims: &str) -> Handshake {
+        let iat = UNIX_EPOCH.elapsed().unwrap().as_secs();
+        let claims = claims.replace("IAT", &iat.to_string());
+        let token = token(secret, r#"{"alg":"HS256","typ":"JWT"}"#, &claims);
+        let req = format!(
+            "GET / HTTP/1.1\r\n\
+             Host: 127.0.0.1\r\n\
+             Authorization: Bearer {}\r\n\
+             Connection: Upgrade\r\n\
+             Upgrade: websocket\r\n\
+             Sec-WebSocket-Key: x3JJHMbDL1EzLkh9GBhXDw==\r\n\
+             Sec-WebSocket-Version: 13\r\n\r\n",
+            token
+        );
+        let req = ws::ws::Request::parse(req.as_bytes()).unwrap().unwrap();
+        match extractor.process(&req) {
+            ws::MiddlewareAction::Proceed => {}
+            _ => panic!("handshake should be accepted"),
+        }
+        take_handshake()
+    }
+
+    #[test]
+    fn should_share_budget_of_ws_sessions_of_the_same_token_holder() {
+        // given
+        let secret = JwtSecret::random();
+        let extractor =
+            WsExtractor::new(None).with_jwt(Some(Arc::new(JwtAuth::new(secret.clone()))));
+        let limiter = RateLimiter::new(RateLimitConfig {
+            budget_per_second: 1,
+            burst: 1,
+            ..Default::default()
+        });
+        let handshake = |claims| handshake(&extractor, &secret, claims);
+
+        // when
+        let session1 = handshake(r#"{"iat":IAT,"id":"alice"}"#);
+        let session2 = handshake(r#"{"iat":IAT,"id":"alice"}"#);
+        let anonymous = handshake(r#"{"iat":IAT}"#);
+
+        // then
+        assert_eq!(session1.client, Some("token:alice".into()));
+        assert_eq!(session2.client, session1.client);
+        // sessions of tokens without a holder are limited separately
+        assert_eq!(anonymous.client, None);
+        assert_eq!(limiter.acquire("token:alice", "eth_blockNumber"), Ok(()));
+        assert_eq!(
+            limiter.acquire("token:alice", "eth_blockNumber"),
+            Err(Rejection::RateLimited)
+        );
+    }
+
+    #[test]
+    fn should_restrict_ws_sessions_to_apis_of_the_token() {
+        // given
+        let secret = JwtSecret::random();
+        let extractor =
+            WsExtractor::new(None).with_jwt(Some(Arc::new(JwtAuth::new(secret.clone()))));
+
+        // when
+        let restricted = handshake(&extractor, &secret, r#"{"iat":IAT,"apis":["eth"]}"#);
+        let unrestricted = handshake(&extractor, &secret, r#"{"iat":IAT}"#);
+
+        // then
+        assert_eq!(restricted.apis, Some(vec!["eth".into()]));
+        assert_eq!(unrestricted.apis, None);
+    }
+
+    #[test]
+    fn should_identify_client_by_token_holder() {
+        // given
+        let extractor = RpcExtractor::default();
+        let mut req = Request::post("/").body(Body::empty()).unwrap();
+        req.extensions_mut()
+            .insert("1.2.3.4:1000".parse::<SocketAddr>().unwrap());
+        req.extensions_mut().insert(TokenId("alice".into()));
+
+        // then
+        assert_eq!(
+            extractor.read_metadata(&req).client,
+            Some("token:alice".into())
+        );
+    }
 }
@@ -34,8 +34,8 @@ use v1::{
 };
 
 use super::{
-    default_gas_price, prospective_signer::ProspectiveSigner, Accounts, Dispatcher, PostSign,
-    SignWith,
+    default_gas_price, prospective_signer::ProspectiveSigner, replacement, Accounts, Dispatcher,
+    GasPriceOracle, PostSign, Replacement, SignWith,
 };
 
 /// A dispatcher which uses references to a client and miner in order to sign
@@ -45,7 +45,7 @@ pub struct FullDispatcher<C, M> {
     client: Arc<C>,
     miner: Arc<M>,
     nonces: Arc<Mutex<nonce::Reservations>>,
-    gas_price_percentile: usize,
+    gas_price_oracle: Arc<GasPriceOracle>,
 }
 
 impl<C, M> FullDispatcher<C, M> {
@@ -55,12 +55,16 @@ impl<C, M> FullDispatcher<C, M> {
         miner: Arc<M>,
         nonces: Arc<Mutex<nonce::Reservations>>,
         gas_price_percentile: usize,
+        gas_price_history_blocks: usize,
     ) -> Self {
         FullDispatcher {
             client,
             miner,
             nonces,
-            gas_price_percentile,
+            gas_price_oracle: Arc::new(GasPriceOracle::new(
+                gas_price_percentile,
+                gas_price_history_blocks,
+            )),
         }
     }
 }
@@ -71,7 +75,7 @@ impl<C, M> Clone for FullDispatcher<C, M> {
             client: self.client.clone(),
             miner: self.miner.clone(),
             nonces: self.nonces.clone(),
-            gas_price_percentile: self.gas_price_percentile,
+            gas_price_oracle: self.gas_price_oracle.clone(),
         }
     }
 }
@@ -102,6 +106,30 @@ impl<C: miner::BlockChainClient, M: MinerService> FullDispatcher<C, M> {
     }
 }
 
+impl<C: miner::BlockChainClient + BlockChainClient, M: MinerService> FullDispatcher<C, M> {
+    /// Post transaction to the current validators only.
+    ///
+    /// The transaction is kept out of public propagation unless it is not included in time.
+    pub fn dispatch_private_transaction(
+        client: &C,
+        miner: &M,
+        signed_transaction: PendingTransaction,
+    ) -> Result<H256> {
+        let hash = signed_transaction.transaction.hash();
+        let transaction = signed_transaction.transaction.clone();
+
+        miner
+            .import_private_transaction(client, signed_transaction)
+            .map_err(errors::transaction)?;
+        if client.send_private_transactions(&[transaction]) == 0 {
+            miner.remove_transaction(&hash);
+            return Err(errors::private_transaction_not_forwarded(hash));
+        }
+
+        Ok(hash)
+    }
+}
+
 impl<C: miner::BlockChainClient + BlockChainClient, M: MinerService> Dispatcher
     for FullDispatcher<C, M>
 {
@@ -126,7 +154,7 @@ impl<C: miner::BlockChainClient + BlockChainClient, M: MinerService> Dispatcher
             to: request.to,
             nonce,
             gas_price: Some(request.gas_price.unwrap_or_else(|| {
-                default_gas_price(&*self.client, &*self.miner, self.gas_price_percentile)
+                default_gas_price(&*self.client, &*self.miner, &self.gas_price_oracle)
             })),
             max_fee_per_gas: request.max_fee_per_gas,
             gas: request
@@ -181,4 +209,24 @@ impl<C: miner::BlockChainClient + BlockChainClient, M: MinerService> Dispatcher
     fn dispatch_transaction(&self, signed_transaction: PendingTransaction) -> Result<H256> {
         Self::dispatch_transaction(&*self.client, &*self.miner, signed_transaction, true)
     }
+
+    fn replacement_request(
+        &self,
+        hash: H256,
+        replacement: Replacement,
+    ) -> Result<FilledTransactionRequest> {
+        let original = self
+            .miner
+            .transaction(&hash)
+            .ok_or_else(|| errors::transaction_not_pending(hash))?;
+        // the pool compares the fees at the base fee of the next block.
+        let options = self.miner.queue_status().options;
+
+        Ok(replacement::replacement_request(
+            original.signed(),
+            replacement,
+            options.block_base_fee,
+            options.minimal_gas_price,
+        ))
+    }
 }
@@ -81,7 +81,10 @@ mod signing {
 }
 
 pub use self::{full::FullDispatcher, signing::Signer};
-pub use v1::helpers::nonce::Reservations;
+pub use v1::helpers::{
+    gas_price_oracle::{GasPriceOracle, MAX_HISTORY_BLOCKS as MAX_GAS_PRICE_HISTORY_BLOCKS},
+    nonce::Reservations,
+};
 
 use std::{fmt::Debug, ops::Deref, sync::Arc};
 
@@ -101,7 +104,10 @@ use jsonrpc_core::{
     BoxFuture, Error, Result,
 };
 use v1::{
-    helpers::{ConfirmationPayload, FilledTransactionRequest, TransactionRequest},
+    helpers::{
+        replacement::{self, Replacement},
+        ConfirmationPayload, FilledTransactionRequest, TransactionRequest,
+    },
     types::{
         Bytes as RpcBytes, ConfirmationPayload as RpcConfirmationPayload, ConfirmationResponse,
         DecryptRequest as RpcDecryptRequest, EIP191SignRequest as RpcSignRequest,
@@ -142,6 +148,13 @@ pub trait Dispatcher: Send + Sync + Clone {
 
     /// "Dispatch" a local transaction.
     fn dispatch_transaction(&self, signed_transaction: PendingTransaction) -> Result<H256>;
+
+    /// Build the request of a transaction replacing the pending transaction with given hash.
+    fn replacement_request(
+        &self,
+        hash: H256,
+        replacement: Replacement,
+    ) -> Result<FilledTransactionRequest>;
 }
 
 /// Payload to sign
@@ -388,44 +401,26 @@ pub fn eth_data_hash(mut data: Bytes) -> H256 {
 }
 
 /// Extract the default gas price from a client and miner.
-pub fn default_gas_price<C, M>(client: &C, miner: &M, percentile: usize) -> U256
+pub fn default_gas_price<C, M>(client: &C, miner: &M, oracle: &GasPriceOracle) -> U256
 where
     C: BlockChainClient,
     M: MinerService,
 {
-    // check out history for open ethereum legacy code.
-    // this is a dmd node specific implementation.
-
-    // client.priority_gas_price_corpus(sample_size, eip1559_transition)
-
-    //if the engine of the client defines a minimum gas price, we can return this here.
-    if let Some(gas_price) = client.minimum_gas_price() {
-        return gas_price;
-    }
-
-    client
-        .gas_price_corpus(100)
-        .percentile(percentile)
-        .cloned()
-        .unwrap_or_else(|| miner.sensible_gas_price())
+    oracle.suggest_gas_price(client, miner)
 }
 
 /// Extract the default priority gas price from a client and miner.
 pub fn default_max_priority_fee_per_gas<C, M>(
     client: &C,
     miner: &M,
-    percentile: usize,
+    oracle: &GasPriceOracle,
     eip1559_transition: BlockNumber,
 ) -> U256
 where
     C: BlockChainClient,
     M: MinerService,
 {
-    client
-        .priority_gas_price_corpus(100, eip1559_transition)
-        .percentile(percentile)
-        .cloned()
-        .unwrap_or_else(|| miner.sensible_max_priority_fee())
+    oracle.suggest_max_priority_fee(client, miner, eip1559_transition)
 }
 
 /// Convert RPC confirmation payload to signer confirmation payload.
@@ -22,6 +22,7 @@ use ethcore::{
     client::{BlockChainClient, BlockId},
     error::{CallError, Error as EthcoreError, ErrorKind},
 };
+use ethereum_types::H256;
 use jsonrpc_core::{Error, ErrorCode, Result as RpcResult, Value};
 use rlp::DecoderError;
 use types::{blockchain_info::BlockChainInfo, transaction::Error as TransactionError};
@@ -49,6 +50,7 @@ mod codes {
     pub const REQUEST_REJECTED: i64 = -32040;
     pub const REQUEST_REJECTED_LIMIT: i64 = -32041;
     pub const REQUEST_NOT_FOUND: i64 = -32042;
+    pub const REQUEST_RATE_LIMITED: i64 = -32043;
     pub const ENCRYPTION_ERROR: i64 = -32055;
     #[cfg(any(test, feature = "accounts"))]
     pub const ENCODING_ERROR: i64 = -32058;
@@ -92,6 +94,17 @@ pub fn request_rejected() -> Error {
     }
 }
 
+pub fn api_not_allowed(method: &str) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::REQUEST_REJECTED),
+        message: "Request has been rejected.".into(),
+        data: Some(Value::String(format!(
+            "API of {} is not granted by the token.",
+            method
+        ))),
+    }
+}
+
 pub fn request_rejected_limit() -> Error {
     Error {
         code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
@@ -100,6 +113,14 @@ pub fn request_rejected_limit() -> Error {
     }
 }
 
+pub fn request_rate_limited(details: &str) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::REQUEST_RATE_LIMITED),
+        message: "Request has been rejected because of rate limit.".into(),
+        data: Some(Value::String(details.into())),
+    }
+}
+
 pub fn account<T: fmt::Debug>(error: &str, details: T) -> Error {
     Error {
         code: ErrorCode::ServerError(codes::ACCOUNT_ERROR),
@@ -409,9 +430,28 @@ pub fn transaction_message(error: &TransactionError) -> String {
         InvalidRlp(ref descr) => format!("Invalid RLP data: {}", descr),
         TransactionTypeNotEnabled => format!("Transaction type is not enabled for current block"),
         SenderIsNotEOA => "Transaction sender is not an EOA (see EIP-3607)".into(),
+        InitCodeTooBig { limit, got } => {
+            format!("Contract creation code is too big. Limit: {}, got: {}.", limit, got)
+        }
 	}
 }
 
+pub fn transaction_not_pending(hash: H256) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::TRANSACTION_ERROR),
+        message: "Transaction is not pending in the transaction queue.".into(),
+        data: Some(Value::String(format!("{:?}", hash))),
+    }
+}
+
+pub fn private_transaction_not_forwarded(hash: H256) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::TRANSACTION_ERROR),
+        message: "Transaction was not forwarded to any validator.".into(),
+        data: Some(Value::String(format!("{:?}", hash))),
+    }
+}
+
 pub fn transaction<T: Into<EthcoreError>>(error: T) -> Error {
     let error = error.into();
     if let ErrorKind::Transaction(ref e) = *error.kind() {
@@ -455,6 +495,11 @@ pub fn call(error: CallError) -> Error {
         CallError::StateCorrupt => state_corrupt(),
         CallError::Exceptional(e) => exceptional(e),
         CallError::Execution(e) => execution(e),
+        CallError::InvalidSimulation(e) => Error {
+            code: ErrorCode::InvalidParams,
+            message: "Invalid simulation request".into(),
+            data: Some(Value::String(e)),
+        },
         CallError::TransactionNotFound => internal(
             "{}, this should not be the case with eth_call, most likely a bug.",
             CallError::TransactionNotFound,
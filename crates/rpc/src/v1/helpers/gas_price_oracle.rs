@@ -0,0 +1,231 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas price oracle based on the recent block history.
+
+use std::cmp;
+
+use ethcore::{client::BlockChainClient, miner::MinerService};
+use ethereum_types::{H256, U256};
+use parking_lot::{Mutex, MutexGuard};
+use types::BlockNumber;
+
+/// Default number of recent blocks the suggestions are based on.
+pub const DEFAULT_HISTORY_BLOCKS: usize = 100;
+
+/// Maximal number of recent blocks the suggestions can be based on.
+pub const MAX_HISTORY_BLOCKS: usize = 1024;
+
+/// Percentiles of the fees paid in the recent blocks, computed on top of `best_block`.
+#[derive(Debug, Default)]
+struct CachedFees {
+    best_block: H256,
+    gas_price: Option<Option<U256>>,
+    max_priority_fee: Option<Option<U256>>,
+}
+
+/// Suggests gas prices and max priority fees from the fees paid in the recent blocks.
+///
+/// The percentiles are computed once per best block.
+#[derive(Debug)]
+pub struct GasPriceOracle {
+    percentile: usize,
+    history_blocks: usize,
+    cache: Mutex<CachedFees>,
+}
+
+impl GasPriceOracle {
+    /// Creates an oracle suggesting the `percentile` of the fees paid in the last
+    /// `history_blocks` blocks, at most `MAX_HISTORY_BLOCKS`.
+    pub fn new(percentile: usize, history_blocks: usize) -> Self {
+        GasPriceOracle {
+            percentile,
+            history_blocks: cmp::min(history_blocks, MAX_HISTORY_BLOCKS),
+            cache: Mutex::new(CachedFees::default()),
+        }
+    }
+
+    /// Suggests a gas price: the percentile of the effective gas prices paid in the recent blocks.
+    ///
+    /// Falls back to the miner's suggestion if no transaction paid for gas in that period.
+    pub fn suggest_gas_price<C, M>(&self, client: &C, miner: &M) -> U256
+    where
+        C: BlockChainClient,
+        M: MinerService,
+    {
+        let price = {
+            let mut cache = self.cache_for(client);
+            let (percentile, history_blocks) = (self.percentile, self.history_blocks);
+            *cache.gas_price.get_or_insert_with(|| {
+                client
+                    .gas_price_history(history_blocks)
+                    .percentile(percentile)
+                    .cloned()
+            })
+        };
+        at_least_minimum_gas_price(client, price.unwrap_or_else(|| miner.sensible_gas_price()))
+    }
+
+    /// Suggests a max priority fee: the percentile of the effective priority fees paid
+    /// in the recent blocks since the eip1559 transition.
+    ///
+    /// Falls back to the miner's suggestion if no transaction paid for gas in that period.
+    pub fn suggest_max_priority_fee<C, M>(
+        &self,
+        client: &C,
+        miner: &M,
+        eip1559_transition: BlockNumber,
+    ) -> U256
+    where
+        C: BlockChainClient,
+        M: MinerService,
+    {
+        let fee = {
+            let mut cache = self.cache_for(client);
+            let (percentile, history_blocks) = (self.percentile, self.history_blocks);
+            *cache.max_priority_fee.get_or_insert_with(|| {
+                client
+                    .priority_gas_price_history(history_blocks, eip1559_transition)
+                    .percentile(percentile)
+                    .cloned()
+            })
+        };
+        at_least_minimum_gas_price(
+            client,
+            fee.unwrap_or_else(|| miner.sensible_max_priority_fee()),
+        )
+    }
+
+    /// The cached fees, reset if the best block changed since they were computed.
+    fn cache_for<C: BlockChainClient>(&self, client: &C) -> MutexGuard<CachedFees> {
+        let best_block = client.chain_info().best_block_hash;
+        let mut cache = self.cache.lock();
+        if cache.best_block != best_block {
+            *cache = CachedFees {
+                best_block,
+                ..Default::default()
+            };
+        }
+        cache
+    }
+}
+
+/// Engines like hbbft define a minimum gas price, suggestions below are never accepted.
+fn at_least_minimum_gas_price<C: BlockChainClient>(client: &C, price: U256) -> U256 {
+    match client.minimum_gas_price() {
+        Some(minimum) => cmp::max(minimum, price),
+        None => price,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethcore::client::{EachBlockWith, TestBlockChainClient};
+    use v1::tests::helpers::TestMinerService;
+
+    const TX_GAS_PRICE: u64 = 200_000_000_000;
+    const SENSIBLE_GAS_PRICE: u64 = 20_000_000_000;
+
+    #[test]
+    fn should_cap_history_blocks() {
+        let oracle = GasPriceOracle::new(50, MAX_HISTORY_BLOCKS + 1);
+        assert_eq!(oracle.history_blocks, MAX_HISTORY_BLOCKS);
+    }
+
+    #[test]
+    fn should_fall_back_to_miner_without_recent_transactions() {
+        let client = TestBlockChainClient::new();
+        let miner = TestMinerService::default();
+        client.add_blocks(5, EachBlockWith::Nothing);
+        let oracle = GasPriceOracle::new(50, DEFAULT_HISTORY_BLOCKS);
+
+        assert_eq!(
+            oracle.suggest_gas_price(&client, &miner),
+            SENSIBLE_GAS_PRICE.into()
+        );
+    }
+
+    #[test]
+    fn should_suggest_percentile_of_recent_gas_prices() {
+        let client = TestBlockChainClient::new();
+        let miner = TestMinerService::default();
+        client.add_blocks(5, EachBlockWith::Transactions(2));
+        let oracle = GasPriceOracle::new(50, DEFAULT_HISTORY_BLOCKS);
+
+        assert_eq!(
+            oracle.suggest_gas_price(&client, &miner),
+            TX_GAS_PRICE.into()
+        );
+    }
+
+    #[test]
+    fn should_only_sample_history_blocks() {
+        let client = TestBlockChainClient::new();
+        let miner = TestMinerService::default();
+        client.add_blocks(5, EachBlockWith::Transaction);
+        client.add_blocks(3, EachBlockWith::Nothing);
+
+        let oracle = GasPriceOracle::new(50, 3);
+        assert_eq!(
+            oracle.suggest_gas_price(&client, &miner),
+            SENSIBLE_GAS_PRICE.into()
+        );
+
+        let oracle = GasPriceOracle::new(50, 4);
+        assert_eq!(
+            oracle.suggest_gas_price(&client, &miner),
+            TX_GAS_PRICE.into()
+        );
+    }
+
+    #[test]
+    fn should_recompute_suggestions_on_new_best_block() {
+        let client = TestBlockChainClient::new();
+        let miner = TestMinerService::default();
+        client.add_blocks(2, EachBlockWith::Nothing);
+        let oracle = GasPriceOracle::new(50, DEFAULT_HISTORY_BLOCKS);
+
+        assert_eq!(
+            oracle.suggest_gas_price(&client, &miner),
+            SENSIBLE_GAS_PRICE.into()
+        );
+
+        client.add_blocks(1, EachBlockWith::Transaction);
+        assert_eq!(
+            oracle.suggest_gas_price(&client, &miner),
+            TX_GAS_PRICE.into()
+        );
+    }
+
+    #[test]
+    fn should_not_suggest_below_engine_minimum() {
+        let client = TestBlockChainClient::new();
+        let miner = TestMinerService::default();
+        client.add_blocks(2, EachBlockWith::Transaction);
+        *client.minimum_gas_price.write() = Some(U256::from(2 * TX_GAS_PRICE));
+        let oracle = GasPriceOracle::new(50, DEFAULT_HISTORY_BLOCKS);
+
+        assert_eq!(
+            oracle.suggest_gas_price(&client, &miner),
+            (2 * TX_GAS_PRICE).into()
+        );
+        assert_eq!(
+            oracle.suggest_max_priority_fee(&client, &miner, 0),
+            (2 * TX_GAS_PRICE).into()
+        );
+    }
+}
@@ -26,7 +26,9 @@ pub mod eip191;
 pub mod engine_signer;
 pub mod external_signer;
 pub mod fake_sign;
+pub mod gas_price_oracle;
 pub mod nonce;
+pub mod replacement;
 #[cfg(any(test, feature = "accounts"))]
 pub mod secretstore;
 
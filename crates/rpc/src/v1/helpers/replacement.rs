@@ -0,0 +1,251 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Replacement of pending transactions.
+
+use std::cmp;
+
+use ethereum_types::U256;
+use miner::pool::scoring::bump_gas_price;
+use types::transaction::{Action, SignedTransaction, TypedTxId};
+use v1::helpers::FilledTransactionRequest;
+
+/// Gas of a plain value transfer.
+const TRANSFER_GAS: u64 = 21_000;
+
+/// How to replace a pending transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Replacement {
+    /// Send the same transaction with higher fees.
+    SpeedUp,
+    /// Send a zero value transfer to the sender itself instead.
+    Cancel,
+}
+
+/// Builds the request of a transaction replacing `original` in the transaction pool.
+///
+/// The replacement has the same sender, nonce and type and pays the lowest fees
+/// the pool accepts for a replacement at the base fee of the next block,
+/// but at least the pool's minimal gas price.
+pub fn replacement_request(
+    original: &SignedTransaction,
+    replacement: Replacement,
+    block_base_fee: Option<U256>,
+    minimal_gas_price: U256,
+) -> FilledTransactionRequest {
+    let tx = original.tx();
+    let tx_type = original.tx_type();
+    let from = original.sender();
+
+    let mut gas_price = bump_gas_price(tx.gas_price);
+    let max_priority_fee_per_gas = match tx_type {
+        TypedTxId::EIP1559Transaction => {
+            // the priority fee alone has to cover the bump if the max fee is above the base fee.
+            let required = bump_gas_price(original.effective_gas_price(block_base_fee));
+            let priority_fee = cmp::max(
+                bump_gas_price(original.max_priority_fee_per_gas()),
+                required
+                    .checked_sub(block_base_fee.unwrap_or_default())
+                    .unwrap_or_default(),
+            );
+            let priority_fee = cmp::max(priority_fee, minimal_gas_price);
+            // the max fee has to cover the new priority fee at the next block's base fee.
+            gas_price = cmp::max(
+                gas_price,
+                block_base_fee
+                    .unwrap_or_default()
+                    .saturating_add(priority_fee),
+            );
+            Some(priority_fee)
+        }
+        TypedTxId::AccessList | TypedTxId::Legacy => {
+            gas_price = cmp::max(gas_price, minimal_gas_price);
+            None
+        }
+    };
+
+    let (to, value, data, gas, access_list) = match replacement {
+        Replacement::SpeedUp => (
+            match tx.action {
+                Action::Call(to) => Some(to),
+                Action::Create => None,
+            },
+            tx.value,
+            tx.data.clone(),
+            tx.gas,
+            original.access_list().cloned(),
+        ),
+        Replacement::Cancel => (
+            Some(from),
+            U256::zero(),
+            Vec::new(),
+            TRANSFER_GAS.into(),
+            original.access_list().map(|_| Vec::new()),
+        ),
+    };
+
+    FilledTransactionRequest {
+        transaction_type: tx_type.to_U64_option_id(),
+        from,
+        used_default_from: false,
+        to,
+        gas_price: Some(gas_price),
+        max_fee_per_gas: max_priority_fee_per_gas.map(|_| gas_price),
+        gas,
+        value,
+        data,
+        nonce: Some(tx.nonce),
+        condition: None,
+        access_list: access_list.map(|list| list.into_iter().map(Into::into).collect()),
+        max_priority_fee_per_gas,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::publickey::{Generator, Random};
+    use ethereum_types::Address;
+    use types::transaction::{AccessListTx, EIP1559TransactionTx, Transaction, TypedTransaction};
+
+    fn transaction(gas_price: u64) -> Transaction {
+        Transaction {
+            action: Action::Call(Address::from_low_u64_be(5)),
+            value: 100.into(),
+            data: vec![1, 2, 3],
+            gas: 50_000.into(),
+            gas_price: gas_price.into(),
+            nonce: 7.into(),
+        }
+    }
+
+    #[test]
+    fn should_speed_up_legacy_transaction() {
+        let original =
+            TypedTransaction::Legacy(transaction(80)).sign(Random.generate().secret(), None);
+
+        let request = replacement_request(&original, Replacement::SpeedUp, None, 0.into());
+
+        assert_eq!(request.transaction_type, Some(0.into()));
+        assert_eq!(request.from, original.sender());
+        assert_eq!(request.to, Some(Address::from_low_u64_be(5)));
+        assert_eq!(request.gas_price, Some(90.into()));
+        assert_eq!(request.max_fee_per_gas, None);
+        assert_eq!(request.gas, 50_000.into());
+        assert_eq!(request.value, 100.into());
+        assert_eq!(request.data, vec![1, 2, 3]);
+        assert_eq!(request.nonce, Some(7.into()));
+    }
+
+    #[test]
+    fn should_cancel_with_self_transfer() {
+        let original = TypedTransaction::AccessList(AccessListTx::new(
+            transaction(80),
+            vec![(Address::from_low_u64_be(6), vec![])],
+        ))
+        .sign(Random.generate().secret(), None);
+
+        let request = replacement_request(&original, Replacement::Cancel, None, 0.into());
+
+        assert_eq!(request.transaction_type, Some(1.into()));
+        assert_eq!(request.to, Some(original.sender()));
+        assert_eq!(request.gas_price, Some(90.into()));
+        assert_eq!(request.gas, 21_000.into());
+        assert_eq!(request.value, 0.into());
+        assert!(request.data.is_empty());
+        assert_eq!(request.access_list, Some(vec![]));
+        assert_eq!(request.nonce, Some(7.into()));
+    }
+
+    #[test]
+    fn should_bump_effective_gas_price_of_eip1559_transaction() {
+        let original = TypedTransaction::EIP1559Transaction(EIP1559TransactionTx {
+            transaction: AccessListTx::new(transaction(200), vec![]),
+            max_priority_fee_per_gas: 16.into(),
+        })
+        .sign(Random.generate().secret(), None);
+        let base_fee = Some(U256::from(100));
+
+        let request = replacement_request(&original, Replacement::SpeedUp, base_fee, 0.into());
+
+        // effective gas price 116 has to be bumped to 130.
+        assert_eq!(request.max_fee_per_gas, Some(225.into()));
+        assert_eq!(request.max_priority_fee_per_gas, Some(30.into()));
+
+        let replacement = TypedTransaction::EIP1559Transaction(EIP1559TransactionTx {
+            transaction: AccessListTx::new(transaction(225), vec![]),
+            max_priority_fee_per_gas: 30.into(),
+        });
+        assert!(
+            replacement.effective_gas_price(base_fee)
+                >= bump_gas_price(original.effective_gas_price(base_fee))
+        );
+    }
+
+    #[test]
+    fn should_bump_at_next_block_base_fee() {
+        let original = TypedTransaction::EIP1559Transaction(EIP1559TransactionTx {
+            transaction: AccessListTx::new(transaction(200), vec![]),
+            max_priority_fee_per_gas: 16.into(),
+        })
+        .sign(Random.generate().secret(), None);
+        let next_base_fee = Some(U256::from(110));
+
+        let request = replacement_request(&original, Replacement::SpeedUp, next_base_fee, 0.into());
+
+        // effective gas price 126 has to be bumped to 141.
+        assert_eq!(request.max_fee_per_gas, Some(225.into()));
+        assert_eq!(request.max_priority_fee_per_gas, Some(31.into()));
+    }
+
+    #[test]
+    fn should_pay_at_least_minimal_gas_price() {
+        let legacy =
+            TypedTransaction::Legacy(transaction(80)).sign(Random.generate().secret(), None);
+        let request = replacement_request(&legacy, Replacement::SpeedUp, None, 100.into());
+        assert_eq!(request.gas_price, Some(100.into()));
+
+        let eip1559 = TypedTransaction::EIP1559Transaction(EIP1559TransactionTx {
+            transaction: AccessListTx::new(transaction(200), vec![]),
+            max_priority_fee_per_gas: 16.into(),
+        })
+        .sign(Random.generate().secret(), None);
+        let request = replacement_request(
+            &eip1559,
+            Replacement::SpeedUp,
+            Some(U256::from(100)),
+            250.into(),
+        );
+        assert_eq!(request.max_priority_fee_per_gas, Some(250.into()));
+        assert_eq!(request.max_fee_per_gas, Some(350.into()));
+    }
+
+    #[test]
+    fn should_cover_priority_fee_at_next_block_base_fee() {
+        let original = TypedTransaction::EIP1559Transaction(EIP1559TransactionTx {
+            transaction: AccessListTx::new(transaction(100), vec![]),
+            max_priority_fee_per_gas: 10.into(),
+        })
+        .sign(Random.generate().secret(), None);
+        let next_base_fee = Some(U256::from(200));
+
+        let request = replacement_request(&original, Replacement::SpeedUp, next_base_fee, 0.into());
+
+        // bumping the max fee to 112 would not even cover the base fee.
+        assert_eq!(request.max_priority_fee_per_gas, Some(11.into()));
+        assert_eq!(request.max_fee_per_gas, Some(211.into()));
+    }
+}
@@ -23,8 +23,12 @@ use types::{header::Header, transaction::LocalizedTransaction};
 
 use jsonrpc_core::Result;
 use v1::{
+    helpers::errors,
     traits::Debug,
-    types::{Block, BlockTransactions, Bytes, RichBlock, Transaction},
+    types::{
+        block_number_to_id, Block, BlockNumber, BlockProfile, BlockTransactions, Bytes, RichBlock,
+        Transaction,
+    },
 };
 
 /// Debug rpc implementation.
@@ -112,6 +116,23 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
             })
             .collect())
     }
+
+    fn profile_block(&self, block_number: BlockNumber) -> Result<BlockProfile> {
+        let id = match block_number {
+            BlockNumber::Pending => {
+                return Err(errors::unsupported(
+                    "Profiling the pending block is not supported.",
+                    None,
+                ))
+            }
+            num => block_number_to_id(num),
+        };
+
+        self.client
+            .profile_block(id)
+            .map(Into::into)
+            .map_err(errors::call)
+    }
 }
 
 fn serialize<T: ::serde::Serialize>(t: &T) -> String {
@@ -17,6 +17,7 @@
 //! Eth rpc implementation.
 
 use std::{
+    cmp,
     sync::Arc,
     thread,
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
@@ -28,9 +29,11 @@ use parking_lot::Mutex;
 use ethash::{self, SeedHashCompute};
 use ethcore::{
     client::{
+        simulation::{SimulationBlock, StateOverrides},
         BlockChainClient, BlockId, Call, EngineInfo, ProvingBlockChainClient, StateClient,
         StateInfo, StateOrBlock, TransactionId, UncleId,
     },
+    engines::PendingBlockPreview,
     miner::{self, MinerService},
     snapshot::SnapshotService,
 };
@@ -52,14 +55,16 @@ use v1::{
         self,
         block_import::is_major_importing,
         deprecated::{self, DeprecationNotice},
-        dispatch::{default_gas_price, default_max_priority_fee_per_gas, FullDispatcher},
-        errors, fake_sign, limit_logs,
+        dispatch::{
+            default_gas_price, default_max_priority_fee_per_gas, FullDispatcher, GasPriceOracle,
+        },
+        errors, fake_sign, gas_price_oracle, limit_logs,
     },
     traits::Eth,
     types::{
         block_number_to_id, Block, BlockNumber, BlockTransactions, Bytes, CallRequest, EthAccount,
-        EthFeeHistory, Filter, Index, Log, Receipt, RichBlock, StorageProof, SyncInfo, SyncStatus,
-        Transaction, Work,
+        EthFeeHistory, Filter, Index, Log, Receipt, RichBlock, SimulatePayload, SimulatedBlock,
+        StorageProof, SyncInfo, SyncStatus, Transaction, Work,
     },
 };
 
@@ -70,6 +75,8 @@ const EXTRA_INFO_PROOF: &str = "Object exists in blockchain (fetched earlier), e
 pub struct EthClientOptions {
     /// Gas Price Percentile used as default gas price.
     pub gas_price_percentile: usize,
+    /// Number of recent blocks the default gas price is computed from.
+    pub gas_price_history_blocks: usize,
     /// Return 'null' instead of an error if ancient block sync is still in
     /// progress and the block information requested could not be found.
     pub allow_missing_blocks: bool,
@@ -93,6 +100,7 @@ impl Default for EthClientOptions {
     fn default() -> Self {
         EthClientOptions {
             gas_price_percentile: 50,
+            gas_price_history_blocks: gas_price_oracle::DEFAULT_HISTORY_BLOCKS,
             allow_missing_blocks: false,
             allow_experimental_rpcs: false,
             no_ancient_blocks: false,
@@ -116,6 +124,7 @@ where
     miner: Arc<M>,
     external_miner: Arc<EM>,
     seed_compute: Mutex<SeedHashCompute>,
+    gas_price_oracle: GasPriceOracle,
     options: EthClientOptions,
     deprecation_notice: DeprecationNotice,
 }
@@ -183,6 +192,10 @@ where
             accounts: accounts.clone(),
             external_miner: em.clone(),
             seed_compute: Mutex::new(SeedHashCompute::default()),
+            gas_price_oracle: GasPriceOracle::new(
+                options.gas_price_percentile,
+                options.gas_price_history_blocks,
+            ),
             options,
             deprecation_notice: Default::default(),
         }
@@ -203,7 +216,8 @@ where
         let (block, difficulty, extra, is_pending) = match id {
             BlockNumberOrId::Number(BlockNumber::Pending) => {
                 let info = self.client.chain_info();
-                match self.miner.pending_block(info.best_block_number) {
+                let (preview, pending_block) = self.pending_block();
+                match pending_block {
                     Some(pending_block) => {
                         warn!("`Pending` is deprecated and may be removed in future versions.");
 
@@ -224,7 +238,15 @@ where
                             }
                         };
 
-                        let extra = self.client.engine().extra_info(&pending_block.header);
+                        let mut extra = self.client.engine().extra_info(&pending_block.header);
+                        let stage = match preview {
+                            Some(PendingBlockPreview::Contribution(_)) => Some("contribution"),
+                            Some(PendingBlockPreview::Batch) => Some("batch"),
+                            None => None,
+                        };
+                        if let Some(stage) = stage {
+                            extra.insert("pendingStage".into(), stage.into());
+                        }
 
                         (
                             Some(encoded::Block::new(pending_block.rlp_bytes())),
@@ -318,6 +340,76 @@ where
         }
     }
 
+    /// Returns the block reported for the `pending` block tag, together with the engine's preview.
+    ///
+    /// Engines agreeing on the block contents before sealing report the transactions
+    /// this node contributes until a batch is agreed on, and the miner's pending block afterwards.
+    fn pending_block(&self) -> (Option<PendingBlockPreview>, Option<types::block::Block>) {
+        let best_block_number = self.client.chain_info().best_block_number;
+        let preview = self.client.engine().pending_block_preview();
+        let pending_block = match preview {
+            Some(PendingBlockPreview::Contribution(ref transactions)) => {
+                Some(self.contribution_block(transactions))
+            }
+            // the miner's pending block may be left over from an earlier batch.
+            Some(PendingBlockPreview::Batch) => self
+                .miner
+                .pending_block(best_block_number)
+                .filter(|block| block.header.number() > best_block_number),
+            None => self.miner.pending_block(best_block_number),
+        };
+        (preview, pending_block)
+    }
+
+    /// Returns the state and header of the block reported for the `pending` block tag.
+    ///
+    /// Contributions are not executed, so the latest state is returned for them.
+    fn pending_state_and_header(
+        &self,
+        preview: &Option<PendingBlockPreview>,
+    ) -> Option<(T, Header)> {
+        let best_block_number = self.client.chain_info().best_block_number;
+        match *preview {
+            Some(PendingBlockPreview::Contribution(ref transactions)) => {
+                let (state, _) = self.client.latest_state_and_header();
+                Some((state, self.contribution_block(transactions).header))
+            }
+            Some(PendingBlockPreview::Batch) | None => {
+                let header = self.miner.pending_block_header(best_block_number)?;
+                if preview.is_some() && header.number() <= best_block_number {
+                    return None;
+                }
+                let state = self.miner.pending_state(best_block_number)?;
+                Some((state, header))
+            }
+        }
+    }
+
+    /// Builds the next block from the transactions this node contributes to it.
+    ///
+    /// The block is not executed, so state related fields are left empty.
+    fn contribution_block(&self, transactions: &[SignedTransaction]) -> types::block::Block {
+        let parent = self.client.best_block_header();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let mut header = Header::new();
+        header.set_parent_hash(parent.hash());
+        header.set_number(parent.number() + 1);
+        header.set_author(self.miner.authoring_params().author);
+        header.set_timestamp(cmp::max(timestamp, parent.timestamp() + 1));
+        header.set_gas_limit(parent.gas_limit());
+        header.set_base_fee(self.client.engine().calculate_base_fee(&parent));
+
+        types::block::Block {
+            header,
+            transactions: transactions.iter().cloned().map(Into::into).collect(),
+            uncles: Vec::new(),
+        }
+    }
+
     fn transaction(&self, id: PendingTransactionId) -> Result<Option<Transaction>> {
         let client_transaction = |id| match self.client.block_transaction(id) {
             Some(t) => {
@@ -348,10 +440,9 @@ where
             }
 
             PendingTransactionId::Location(PendingOrBlock::Pending, index) => {
-                let info = self.client.chain_info();
-                let pending_block = match self.miner.pending_block(info.best_block_number) {
-                    Some(block) => block,
-                    None => return Ok(None),
+                let pending_block = match self.pending_block() {
+                    (_, Some(block)) => block,
+                    (_, None) => return Ok(None),
                 };
 
                 // Implementation stolen from `extract_transaction_at_index`
@@ -392,9 +483,9 @@ where
             } => {
                 let info = self.client.chain_info();
 
-                let pending_block = match self.miner.pending_block(info.best_block_number) {
-                    Some(block) => block,
-                    None => return Ok(None),
+                let pending_block = match self.pending_block() {
+                    (_, Some(block)) => block,
+                    (_, None) => return Ok(None),
                 };
 
                 let uncle = match pending_block.uncles.get(position) {
@@ -503,11 +594,10 @@ where
             BlockNumber::Earliest => BlockId::Earliest.into(),
             BlockNumber::Latest => BlockId::Latest.into(),
             BlockNumber::Pending => {
-                let info = self.client.chain_info();
+                let preview = self.client.engine().pending_block_preview();
 
-                self.miner
-					.pending_state(info.best_block_number)
-					.map(|s| Box::new(s) as Box<dyn StateInfo>)
+                self.pending_state_and_header(&preview)
+					.map(|(s, _)| Box::new(s) as Box<dyn StateInfo>)
 					.unwrap_or_else(|| {
 						warn!("Asked for best pending state, but none found. Falling back to latest state");
 						let (state, _) = self.client.latest_state_and_header();
@@ -521,15 +611,10 @@ where
     /// Get the state and header of best pending block. On failure, fall back to the best imported
     /// blocks state&header.
     fn pending_state_and_header_with_fallback(&self) -> (T, Header) {
-        let best_block_number = self.client.chain_info().best_block_number;
-        let (maybe_state, maybe_header) = self.miner.pending_state(best_block_number).map_or_else(
-            || (None, None),
-            |s| (Some(s), self.miner.pending_block_header(best_block_number)),
-        );
-
-        match (maybe_state, maybe_header) {
-            (Some(state), Some(header)) => (state, header),
-            _ => {
+        let preview = self.client.engine().pending_block_preview();
+        match self.pending_state_and_header(&preview) {
+            Some((state, header)) => (state, header),
+            None => {
                 warn!("Falling back to \"Latest\"");
                 self.client.latest_state_and_header()
             }
@@ -689,7 +774,7 @@ where
         Box::new(future::ok(default_gas_price(
             &*self.client,
             &*self.miner,
-            self.options.gas_price_percentile,
+            &self.gas_price_oracle,
         )))
     }
 
@@ -701,7 +786,7 @@ where
             Box::new(future::ok(default_max_priority_fee_per_gas(
                 &*self.client,
                 &*self.miner,
-                self.options.gas_price_percentile,
+                &self.gas_price_oracle,
                 eip1559_transition,
             )))
         } else {
@@ -1014,15 +1099,26 @@ where
     fn transaction_count(&self, address: H160, num: Option<BlockNumber>) -> BoxFuture<U256> {
         let res = match num.unwrap_or_default() {
             BlockNumber::Pending => {
-                let info = self.client.chain_info();
-                let nonce = self
-                    .miner
-                    .pending_state(info.best_block_number)
-                    .and_then(|s| s.nonce(&address).ok())
-                    .or_else(|| {
-                        warn!("Fallback to `BlockId::Latest`");
-                        self.client.nonce(&address, BlockId::Latest)
-                    });
+                let preview = self.client.engine().pending_block_preview();
+                let nonce = match preview {
+                    // contributions are not executed, so count the sender's contributed transactions.
+                    Some(PendingBlockPreview::Contribution(ref transactions)) => {
+                        let contributed = transactions
+                            .iter()
+                            .filter(|tx| tx.sender() == address)
+                            .count();
+                        self.client
+                            .nonce(&address, BlockId::Latest)
+                            .map(|nonce| nonce + contributed)
+                    }
+                    _ => self
+                        .pending_state_and_header(&preview)
+                        .and_then(|(s, _)| s.nonce(&address).ok())
+                        .or_else(|| {
+                            warn!("Fallback to `BlockId::Latest`");
+                            self.client.nonce(&address, BlockId::Latest)
+                        }),
+                };
 
                 nonce.ok_or_else(|| errors::database("latest nonce missing"))
             }
@@ -1048,12 +1144,20 @@ where
 
     fn block_transaction_count_by_number(&self, num: BlockNumber) -> BoxFuture<Option<U256>> {
         Box::new(future::done(match num {
-            BlockNumber::Pending => Ok(Some(
-                self.miner
-                    .pending_transaction_hashes(&*self.client)
-                    .len()
-                    .into(),
-            )),
+            BlockNumber::Pending => match self.pending_block() {
+                (None, _) => Ok(Some(
+                    self.miner
+                        .pending_transaction_hashes(&*self.client)
+                        .len()
+                        .into(),
+                )),
+                (Some(_), Some(block)) => Ok(Some(block.transactions.len().into())),
+                // like `eth_getBlockByNumber`, fall back to the latest block.
+                (Some(_), None) => Ok(self
+                    .client
+                    .block(BlockId::Latest)
+                    .map(|block| block.transactions_count().into())),
+            },
             _ => {
                 let trx_count = self
                     .client
@@ -1183,6 +1287,33 @@ where
         Box::new(future::done(result))
     }
 
+    fn block_receipts(&self, num: BlockNumber) -> BoxFuture<Option<Vec<Receipt>>> {
+        if num == BlockNumber::Pending {
+            let best_block = self.client.chain_info().best_block_number;
+            let receipts = self
+                .miner
+                .pending_receipts(best_block)
+                .map(|receipts| receipts.into_iter().map(Into::into).collect());
+            return Box::new(future::ok(receipts));
+        }
+        if let BlockNumber::Hash {
+            ref hash,
+            require_canonical: true,
+        } = num
+        {
+            if !self.client.is_canon(hash) {
+                return Box::new(future::err(errors::invalid_input()));
+            }
+        }
+
+        let receipts = self
+            .client
+            .localized_block_receipts(block_number_to_id(num))
+            .map(|receipts| receipts.into_iter().map(Into::into).collect());
+        let result = Ok(receipts).and_then(errors::check_block_gap(&*self.client, self.options));
+        Box::new(future::done(result))
+    }
+
     fn uncle_by_block_hash_and_index(
         &self,
         hash: H256,
@@ -1347,6 +1478,20 @@ where
         self.send_raw_transaction(raw)
     }
 
+    fn send_private_raw_transaction(&self, raw: Bytes) -> Result<H256> {
+        TypedTransaction::decode(&raw.into_vec())
+            .map_err(errors::rlp)
+            .and_then(|tx| SignedTransaction::new(tx).map_err(errors::transaction))
+            .and_then(|signed_transaction| {
+                FullDispatcher::dispatch_private_transaction(
+                    &*self.client,
+                    &*self.miner,
+                    signed_transaction.into(),
+                )
+            })
+            .map(Into::into)
+    }
+
     fn call(&self, request: CallRequest, num: Option<BlockNumber>) -> BoxFuture<Bytes> {
         let request = CallRequest::into(request);
         let signed = try_bf!(fake_sign::sign_call(request));
@@ -1391,6 +1536,79 @@ where
         ))
     }
 
+    fn simulate_v1(
+        &self,
+        payload: SimulatePayload,
+        num: Option<BlockNumber>,
+    ) -> BoxFuture<Vec<SimulatedBlock>> {
+        let unsupported = [
+            ("validation", payload.validation),
+            ("traceTransfers", payload.trace_transfers),
+            ("returnFullTransactions", payload.return_full_transactions),
+        ];
+        if let Some(&(param, _)) = unsupported.iter().find(|&&(_, enabled)| enabled) {
+            return Box::new(future::err(errors::invalid_params(
+                param,
+                "Only `false` is supported",
+            )));
+        }
+
+        let mut blocks = Vec::with_capacity(payload.block_state_calls.len());
+        for block in payload.block_state_calls {
+            let mut state_overrides = StateOverrides::new();
+            for (address, account) in block.state_overrides.unwrap_or_default() {
+                if account.state.is_some() && account.state_diff.is_some() {
+                    return Box::new(future::err(errors::invalid_params(
+                        "stateOverrides",
+                        "Both `state` and `stateDiff` given for the same account",
+                    )));
+                }
+                state_overrides.insert(address, account.into());
+            }
+            let mut transactions = Vec::with_capacity(block.calls.len());
+            for call in block.calls {
+                transactions.push(try_bf!(fake_sign::sign_call(call.into())));
+            }
+            blocks.push(SimulationBlock {
+                block_overrides: block.block_overrides.map(Into::into).unwrap_or_default(),
+                state_overrides,
+                transactions,
+            });
+        }
+
+        let num = num.unwrap_or_default();
+
+        let (mut state, header) = if num == BlockNumber::Pending {
+            self.pending_state_and_header_with_fallback()
+        } else {
+            let id = match num {
+                BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
+                BlockNumber::Num(num) => BlockId::Number(num),
+                BlockNumber::Earliest => BlockId::Earliest,
+                BlockNumber::Latest => BlockId::Latest,
+                BlockNumber::Pending => unreachable!(), // Already covered
+            };
+
+            let state = try_bf!(self.client.state_at(id).ok_or_else(errors::state_pruned));
+            let header = try_bf!(self
+                .client
+                .block_header(id)
+                .ok_or_else(errors::state_pruned)
+                .and_then(|h| h
+                    .decode(self.client.engine().params().eip1559_transition)
+                    .map_err(errors::decode)));
+
+            (state, header)
+        };
+
+        Box::new(future::done(
+            self.client
+                .simulate_blocks(&blocks, &mut state, &header)
+                .map_err(errors::call)
+                .map(|blocks| blocks.into_iter().map(Into::into).collect()),
+        ))
+    }
+
     fn estimate_gas(&self, request: CallRequest, num: Option<BlockNumber>) -> BoxFuture<U256> {
         let request = CallRequest::into(request);
         let signed = try_bf!(fake_sign::sign_call(request));
@@ -41,6 +41,7 @@ use ethcore::client::{
     BlockChainClient, BlockId, ChainNotify, ChainRouteType, EngineInfo, NewBlocks,
 };
 use ethereum_types::H256;
+use miner::pool;
 use parity_runtime::Executor;
 use parking_lot::RwLock;
 
@@ -54,6 +55,7 @@ pub struct EthPubSubClient<C> {
     heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
     logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
     transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
+    transaction_events_subscribers: Arc<RwLock<Subscribers<Client>>>,
 }
 
 impl<C> EthPubSubClient<C> {
@@ -62,6 +64,7 @@ impl<C> EthPubSubClient<C> {
         let heads_subscribers = Arc::new(RwLock::new(Subscribers::default()));
         let logs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
         let transactions_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+        let transaction_events_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 
         EthPubSubClient {
             handler: Arc::new(ChainNotificationHandler {
@@ -70,10 +73,12 @@ impl<C> EthPubSubClient<C> {
                 heads_subscribers: heads_subscribers.clone(),
                 logs_subscribers: logs_subscribers.clone(),
                 transactions_subscribers: transactions_subscribers.clone(),
+                transaction_events_subscribers: transaction_events_subscribers.clone(),
             }),
             heads_subscribers,
             logs_subscribers,
             transactions_subscribers,
+            transaction_events_subscribers,
         }
     }
 
@@ -84,6 +89,7 @@ impl<C> EthPubSubClient<C> {
         *client.heads_subscribers.write() = Subscribers::default();
         *client.logs_subscribers.write() = Subscribers::default();
         *client.transactions_subscribers.write() = Subscribers::default();
+        *client.transaction_events_subscribers.write() = Subscribers::default();
         client
     }
 
@@ -100,6 +106,7 @@ pub struct ChainNotificationHandler<C> {
     heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
     logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
     transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
+    transaction_events_subscribers: Arc<RwLock<Subscribers<Client>>>,
 }
 
 impl<C> ChainNotificationHandler<C>
@@ -180,6 +187,19 @@ where
             }
         }
     }
+
+    /// Notify all subscribers about transaction lifecycle events.
+    pub fn notify_transaction_events(&self, events: &[pool::TransactionEvent]) {
+        for subscriber in self.transaction_events_subscribers.read().values() {
+            for event in events {
+                Self::notify(
+                    &self.executor,
+                    subscriber,
+                    pubsub::Result::TransactionEvent(Box::new(event.clone().into())),
+                );
+            }
+        }
+    }
 }
 
 impl<C: BlockChainClient + EngineInfo> ChainNotify for ChainNotificationHandler<C> {
@@ -269,6 +289,13 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
             (pubsub::Kind::NewPendingTransactions, _) => {
                 errors::invalid_params("newPendingTransactions", "Expected no parameters.")
             }
+            (pubsub::Kind::TransactionEvents, None) => {
+                self.transaction_events_subscribers.write().push(subscriber);
+                return;
+            }
+            (pubsub::Kind::TransactionEvents, _) => {
+                errors::invalid_params("transactionEvents", "Expected no parameters.")
+            }
             _ => errors::unimplemented(None),
         };
 
@@ -279,7 +306,12 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
         let res = self.heads_subscribers.write().remove(&id).is_some();
         let res2 = self.logs_subscribers.write().remove(&id).is_some();
         let res3 = self.transactions_subscribers.write().remove(&id).is_some();
+        let res4 = self
+            .transaction_events_subscribers
+            .write()
+            .remove(&id)
+            .is_some();
 
-        Ok(res || res2 || res3)
+        Ok(res || res2 || res3 || res4)
     }
 }
@@ -15,7 +15,7 @@
 // along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Parity-specific rpc implementation.
-use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+use std::{cmp::min, collections::BTreeMap, str::FromStr, sync::Arc};
 
 use crypto::{publickey::ecies, DEFAULT_MAC};
 use ethcore::{
@@ -42,14 +42,19 @@ use v1::{
     },
     traits::Parity,
     types::{
-        block_number_to_id, BlockNumber, Bytes, CallRequest, ChainStatus, Header, Histogram,
-        LocalTransactionStatus, Peers, Receipt, RecoveredAccount, RichHeader, RpcSettings,
-        Transaction, TransactionStats,
+        block_number_to_id, BlockNumber, BlockReceipts, BlockReceiptsPage, Bytes, CallRequest,
+        ChainStatus, Header, Histogram, LocalTransactionStatus, Peers, Receipt, RecoveredAccount,
+        RichHeader, RpcSettings, Transaction, TransactionStats,
     },
 };
 use version::version_data;
 use Host;
 
+/// Maximal number of blocks returned by a single `parity_getBlockReceiptsRange` call.
+const MAX_BLOCKS_PER_RECEIPTS_PAGE: u64 = 1024;
+/// Maximal number of receipts returned by a single `parity_getBlockReceiptsRange` call.
+const MAX_RECEIPTS_PER_PAGE: usize = 10_000;
+
 /// Parity implementation.
 pub struct ParityClient<C, M>
 where
@@ -421,6 +426,80 @@ where
         Box::new(future::ok(receipts.into_iter().map(Into::into).collect()))
     }
 
+    fn block_receipts_range(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+        limit: Option<usize>,
+    ) -> BoxFuture<BlockReceiptsPage> {
+        if from == BlockNumber::Pending || to == BlockNumber::Pending {
+            return Box::new(future::err(errors::invalid_params(
+                "blockNumber",
+                "`pending` is not supported for receipt ranges",
+            )));
+        }
+        for bound in &[&from, &to] {
+            if let BlockNumber::Hash {
+                ref hash,
+                require_canonical: true,
+            } = **bound
+            {
+                if !self.client.is_canon(hash) {
+                    return Box::new(future::err(errors::invalid_input()));
+                }
+            }
+        }
+        let from = try_bf!(self
+            .client
+            .block_number(block_number_to_id(from))
+            .ok_or_else(errors::unknown_block));
+        let to = try_bf!(self
+            .client
+            .block_number(block_number_to_id(to))
+            .ok_or_else(errors::unknown_block));
+        if from > to {
+            return Box::new(future::err(errors::invalid_params(
+                "blockNumber",
+                "`from` is greater than `to`",
+            )));
+        }
+
+        let limit = min(
+            limit.unwrap_or(MAX_RECEIPTS_PER_PAGE),
+            MAX_RECEIPTS_PER_PAGE,
+        );
+        let last = min(to, from.saturating_add(MAX_BLOCKS_PER_RECEIPTS_PAGE - 1));
+        let mut blocks = Vec::new();
+        let mut receipts_count = 0;
+        let mut next = from;
+
+        while next <= last {
+            let id = BlockId::Number(next);
+            let receipts = try_bf!(self
+                .client
+                .localized_block_receipts(id)
+                .ok_or_else(errors::unknown_block));
+            // always return at least one block, even if it exceeds the limit on its own
+            if !blocks.is_empty() && receipts_count + receipts.len() > limit {
+                break;
+            }
+            let block_hash = try_bf!(self.client.block_hash(id).ok_or_else(errors::unknown_block));
+
+            receipts_count += receipts.len();
+            blocks.push(BlockReceipts {
+                block_number: next.into(),
+                block_hash,
+                receipts: receipts.into_iter().map(Into::into).collect(),
+            });
+            next += 1;
+        }
+
+        Box::new(future::ok(BlockReceiptsPage {
+            blocks,
+            next_block: if next <= to { Some(next.into()) } else { None },
+        }))
+    }
+
     fn call(&self, requests: Vec<CallRequest>, num: Option<BlockNumber>) -> Result<Vec<Bytes>> {
         let requests = requests
             .into_iter()
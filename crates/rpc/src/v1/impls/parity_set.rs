@@ -119,6 +119,12 @@ where
         Ok(false)
     }
 
+    fn reload_admission_policy(&self) -> Result<bool> {
+        self.miner
+            .reload_admission_policy()
+            .map_err(|e| errors::internal("Unable to reload admission policy", e))
+    }
+
     fn set_tx_gas_limit(&self, _limit: U256) -> Result<bool> {
         warn!("setTxGasLimit is deprecated. Ignoring request.");
         Ok(false)
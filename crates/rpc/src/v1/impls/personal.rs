@@ -34,6 +34,7 @@ use v1::{
         deprecated::{self, DeprecationNotice},
         dispatch::{self, eth_data_hash, Dispatcher, PostSign, SignWith, WithToken},
         eip191, errors,
+        replacement::Replacement,
     },
     metadata::Metadata,
     traits::Personal,
@@ -109,6 +110,26 @@ impl<D: Dispatcher + 'static> PersonalClient<D> {
                 }),
         )
     }
+
+    fn replace_transaction(
+        &self,
+        hash: H256,
+        replacement: Replacement,
+        password: String,
+    ) -> BoxFuture<H256> {
+        let filled = try_bf!(self.dispatcher.replacement_request(hash, replacement));
+        let accounts = Arc::new(dispatch::Signer::new(self.accounts.clone())) as _;
+        let dispatcher = self.dispatcher.clone();
+
+        self.dispatcher.sign(
+            filled,
+            &accounts,
+            SignWith::Password(password.into()),
+            move |signed: WithToken<SignedTransaction>| {
+                dispatcher.dispatch_transaction(PendingTransaction::new(signed.into_value(), None))
+            },
+        )
+    }
 }
 
 impl<D: Dispatcher + 'static> Personal for PersonalClient<D> {
@@ -332,6 +353,23 @@ impl<D: Dispatcher + 'static> Personal for PersonalClient<D> {
         ))
     }
 
+    fn speed_up_transaction(
+        &self,
+        _meta: Metadata,
+        hash: H256,
+        password: String,
+    ) -> BoxFuture<H256> {
+        self.deprecation_notice
+            .print("personal_speedUpTransaction", deprecated::msgs::ACCOUNTS);
+        self.replace_transaction(hash, Replacement::SpeedUp, password)
+    }
+
+    fn cancel_transaction(&self, _meta: Metadata, hash: H256, password: String) -> BoxFuture<H256> {
+        self.deprecation_notice
+            .print("personal_cancelTransaction", deprecated::msgs::ACCOUNTS);
+        self.replace_transaction(hash, Replacement::Cancel, password)
+    }
+
     fn sign_and_send_transaction(
         &self,
         meta: Metadata,
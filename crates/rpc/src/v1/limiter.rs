@@ -0,0 +1,510 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-method cost accounting and rate limiting of RPC requests.
+//!
+//! Every method has a cost. Each client gets a budget of cost units that is refilled
+//! every second, and requests that exceed the budget or the number of requests a client
+//! may have in flight are rejected. Clients authorized with a token that names its holder
+//! are identified by the holder, other HTTP clients by their address and other WebSockets
+//! clients by their session. IPC and authorized signer sessions are never limited.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use jsonrpc_core as core;
+use jsonrpc_core::futures::{
+    future::{self, Either},
+    Future,
+};
+use parking_lot::Mutex;
+use stats::{PrometheusMetrics, PrometheusRegistry};
+
+use v1::{helpers::errors, Metadata, Origin};
+
+/// Clients that have been idle for this long are forgotten.
+const CLIENT_EXPIRY: Duration = Duration::from_secs(300);
+/// Idle clients are pruned once this many clients are tracked.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Costs of methods that are more expensive than a simple state lookup.
+const DEFAULT_METHOD_COSTS: &[(&str, u64)] = &[
+    ("eth_call", 10),
+    ("eth_estimateGas", 10),
+    ("eth_getBlockReceipts", 20),
+    ("eth_getFilterLogs", 50),
+    ("eth_getLogs", 50),
+    ("eth_simulateV1", 50),
+    ("graphql", 50),
+    ("parity_getBlockReceiptsRange", 50),
+    ("trace_block", 50),
+    ("trace_call", 50),
+    ("trace_callMany", 50),
+    ("trace_rawTransaction", 50),
+    ("trace_filter", 100),
+    ("trace_replayBlockTransactions", 100),
+    ("trace_replayTransaction", 100),
+];
+
+/// Rate limiter configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    /// Cost units refilled to every client each second (0 disables budgets).
+    pub budget_per_second: u64,
+    /// Maximal number of cost units a client may accumulate while idle.
+    /// Never lower than `budget_per_second`.
+    pub burst: u64,
+    /// Cost of methods without an entry in `method_costs`.
+    pub default_cost: u64,
+    /// Costs of single methods.
+    pub method_costs: HashMap<String, u64>,
+    /// Maximal number of requests a single client may have in flight (0 for no limit).
+    pub max_concurrent: usize,
+    /// Maximal number of calls in a batch request (0 for no limit).
+    pub max_batch_size: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            budget_per_second: 0,
+            burst: 0,
+            default_cost: 1,
+            method_costs: default_method_costs(),
+            max_concurrent: 0,
+            max_batch_size: 0,
+        }
+    }
+}
+
+/// Returns default costs of expensive methods.
+pub fn default_method_costs() -> HashMap<String, u64> {
+    DEFAULT_METHOD_COSTS
+        .iter()
+        .map(|&(method, cost)| (method.to_owned(), cost))
+        .collect()
+}
+
+/// Reason of a rejected request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rejection {
+    /// Client has exhausted its budget.
+    RateLimited,
+    /// Client has too many requests in flight.
+    TooManyConcurrent,
+    /// Batch request contains too many calls.
+    BatchTooLarge,
+}
+
+impl Rejection {
+    /// Returns description of the rejection reported to the client.
+    pub fn details(&self) -> &'static str {
+        match *self {
+            Rejection::RateLimited => "Request budget exceeded, try again later.",
+            Rejection::TooManyConcurrent => "Too many concurrent requests.",
+            Rejection::BatchTooLarge => "Batch request contains too many calls.",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClientBudget {
+    tokens: f64,
+    updated: Instant,
+    in_flight: usize,
+}
+
+/// Tracks budgets of RPC clients.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    clients: Mutex<HashMap<String, ClientBudget>>,
+    rate_limited: AtomicUsize,
+    too_many_concurrent: AtomicUsize,
+    batch_too_large: AtomicUsize,
+}
+
+impl RateLimiter {
+    /// Creates new rate limiter.
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config,
+            clients: Mutex::new(HashMap::new()),
+            rate_limited: AtomicUsize::new(0),
+            too_many_concurrent: AtomicUsize::new(0),
+            batch_too_large: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns true if any limit is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.config.budget_per_second > 0
+            || self.config.max_concurrent > 0
+            || self.config.max_batch_size > 0
+    }
+
+    /// Returns cost of given method.
+    pub fn cost(&self, method: &str) -> u64 {
+        self.config
+            .method_costs
+            .get(method)
+            .cloned()
+            .unwrap_or(self.config.default_cost)
+    }
+
+    /// Returns number of requests rejected for given reason.
+    pub fn rejected(&self, reason: Rejection) -> usize {
+        self.counter(reason).load(Ordering::Relaxed)
+    }
+
+    /// Checks the size of a batch request.
+    pub fn check_batch(&self, size: usize) -> Result<(), Rejection> {
+        if self.config.max_batch_size > 0 && size > self.config.max_batch_size {
+            return Err(self.reject(Rejection::BatchTooLarge));
+        }
+        Ok(())
+    }
+
+    /// Charges `client` for a call of `method` and marks the call as in flight.
+    /// Every successful `acquire` has to be followed by a `release`.
+    pub fn acquire(&self, client: &str, method: &str) -> Result<(), Rejection> {
+        self.acquire_at(client, method, Instant::now())
+    }
+
+    /// Charges `client` for a call of `method` and returns a guard that marks the call
+    /// as finished when dropped.
+    pub fn acquire_guard(
+        self: &Arc<Self>,
+        client: String,
+        method: &str,
+    ) -> Result<InFlight, Rejection> {
+        self.acquire(&client, method)?;
+        Ok(InFlight {
+            limiter: self.clone(),
+            client,
+        })
+    }
+
+    /// Marks a call of `client` as finished.
+    pub fn release(&self, client: &str) {
+        if let Some(budget) = self.clients.lock().get_mut(client) {
+            budget.in_flight = budget.in_flight.saturating_sub(1);
+        }
+    }
+
+    fn capacity(&self) -> f64 {
+        ::std::cmp::max(self.config.burst, self.config.budget_per_second) as f64
+    }
+
+    fn acquire_at(&self, client: &str, method: &str, now: Instant) -> Result<(), Rejection> {
+        let capacity = self.capacity();
+        // Calls more expensive than the whole capacity are allowed on a full budget.
+        let cost = (self.cost(method) as f64).min(capacity);
+
+        let mut clients = self.clients.lock();
+        if clients.len() >= MAX_TRACKED_CLIENTS && !clients.contains_key(client) {
+            clients.retain(|_, budget| {
+                budget.in_flight > 0 || now.duration_since(budget.updated) < CLIENT_EXPIRY
+            });
+        }
+
+        let budget = clients
+            .entry(client.to_owned())
+            .or_insert_with(|| ClientBudget {
+                tokens: capacity,
+                updated: now,
+                in_flight: 0,
+            });
+
+        if self.config.max_concurrent > 0 && budget.in_flight >= self.config.max_concurrent {
+            return Err(self.reject(Rejection::TooManyConcurrent));
+        }
+
+        let elapsed = now.duration_since(budget.updated);
+        budget.updated = now;
+        if self.config.budget_per_second > 0 {
+            let refill = elapsed.as_secs_f64() * self.config.budget_per_second as f64;
+            budget.tokens = (budget.tokens + refill).min(capacity);
+
+            if budget.tokens < cost {
+                return Err(self.reject(Rejection::RateLimited));
+            }
+            budget.tokens -= cost;
+        }
+
+        budget.in_flight += 1;
+        Ok(())
+    }
+
+    fn counter(&self, reason: Rejection) -> &AtomicUsize {
+        match reason {
+            Rejection::RateLimited => &self.rate_limited,
+            Rejection::TooManyConcurrent => &self.too_many_concurrent,
+            Rejection::BatchTooLarge => &self.batch_too_large,
+        }
+    }
+
+    fn reject(&self, reason: Rejection) -> Rejection {
+        self.counter(reason).fetch_add(1, Ordering::Relaxed);
+        reason
+    }
+}
+
+impl PrometheusMetrics for RateLimiter {
+    fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+        r.register_counter(
+            "rpc_rejected_rate_limited",
+            "RPC requests rejected because the client budget was exceeded",
+            self.rejected(Rejection::RateLimited) as i64,
+        );
+        r.register_counter(
+            "rpc_rejected_concurrency",
+            "RPC requests rejected because the client had too many requests in flight",
+            self.rejected(Rejection::TooManyConcurrent) as i64,
+        );
+        r.register_counter(
+            "rpc_rejected_batch_size",
+            "RPC batch requests rejected because they contained too many calls",
+            self.rejected(Rejection::BatchTooLarge) as i64,
+        );
+        r.register_gauge(
+            "rpc_rate_limited_clients",
+            "Number of RPC clients tracked by the rate limiter",
+            self.clients.lock().len() as i64,
+        );
+    }
+}
+
+/// Releases an in-flight call when dropped.
+pub struct InFlight {
+    limiter: Arc<RateLimiter>,
+    client: String,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.limiter.release(&self.client);
+    }
+}
+
+/// Rate limiting RPC middleware.
+pub struct RateLimitMiddleware {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitMiddleware {
+    /// Creates new middleware enforcing limits of given `limiter`.
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        RateLimitMiddleware { limiter }
+    }
+}
+
+/// Returns the key budgets of the request sender are tracked with,
+/// or `None` if the sender is not limited.
+fn client_key(meta: &Metadata) -> Option<String> {
+    match meta.origin {
+        Origin::Ipc(_) | Origin::Signer { .. } => None,
+        // requests of unknown clients share a single budget.
+        _ => Some(meta.client.clone().unwrap_or_else(|| "unknown".into())),
+    }
+}
+
+impl core::Middleware<Metadata> for RateLimitMiddleware {
+    type Future = core::FutureResponse;
+    type CallFuture = core::FutureOutput;
+
+    fn on_request<F, X>(
+        &self,
+        request: core::Request,
+        meta: Metadata,
+        process: F,
+    ) -> Either<Self::Future, X>
+    where
+        F: FnOnce(core::Request, Metadata) -> X,
+        X: Future<Item = Option<core::Response>, Error = ()> + Send + 'static,
+    {
+        if let core::Request::Batch(ref calls) = request {
+            if client_key(&meta).is_some() {
+                if let Err(reason) = self.limiter.check_batch(calls.len()) {
+                    let error = errors::request_rate_limited(reason.details());
+                    let response = core::Response::from(error, Some(core::Version::V2));
+                    return Either::A(Box::new(future::ok(Some(response))));
+                }
+            }
+        }
+
+        Either::B(process(request, meta))
+    }
+
+    fn on_call<F, X>(
+        &self,
+        call: core::Call,
+        meta: Metadata,
+        next: F,
+    ) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(core::Call, Metadata) -> X + Send + Sync,
+        X: Future<Item = Option<core::Output>, Error = ()> + Send + 'static,
+    {
+        if !self.limiter.is_enabled() {
+            return Either::B(next(call, meta));
+        }
+        let client = match client_key(&meta) {
+            Some(client) => client,
+            None => return Either::B(next(call, meta)),
+        };
+        let (method, id, jsonrpc) = match call {
+            core::Call::MethodCall(ref call) => {
+                (call.method.clone(), Some(call.id.clone()), call.jsonrpc)
+            }
+            core::Call::Notification(ref notification) => {
+                (notification.method.clone(), None, notification.jsonrpc)
+            }
+            core::Call::Invalid { .. } => return Either::B(next(call, meta)),
+        };
+
+        match self.limiter.acquire_guard(client.clone(), &method) {
+            Ok(guard) => Either::A(Box::new(next(call, meta).then(move |output| {
+                drop(guard);
+                output
+            }))),
+            Err(reason) => {
+                trace!(target: "rpc", "Rejected {} from {}: {:?}", method, client, reason);
+                let error = errors::request_rate_limited(reason.details());
+                let output = id.map(|id| core::Output::from(Err(error), id, jsonrpc));
+                Either::A(Box::new(future::ok(output)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::{MetaIoHandler, Params, Value};
+
+    fn limiter(budget_per_second: u64, max_concurrent: usize) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            budget_per_second,
+            burst: budget_per_second,
+            max_concurrent,
+            max_batch_size: 2,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn should_charge_method_costs() {
+        let limiter = limiter(100, 0);
+        let now = Instant::now();
+
+        assert_eq!(limiter.cost("eth_getLogs"), 50);
+        assert_eq!(limiter.cost("eth_blockNumber"), 1);
+        assert_eq!(limiter.acquire_at("a", "eth_getLogs", now), Ok(()));
+        assert_eq!(limiter.acquire_at("a", "eth_getLogs", now), Ok(()));
+        assert_eq!(
+            limiter.acquire_at("a", "eth_blockNumber", now),
+            Err(Rejection::RateLimited)
+        );
+        // other clients have their own budget
+        assert_eq!(limiter.acquire_at("b", "eth_getLogs", now), Ok(()));
+        // budget is refilled over time
+        let later = now + Duration::from_millis(500);
+        assert_eq!(limiter.acquire_at("a", "eth_getLogs", later), Ok(()));
+        assert_eq!(limiter.rejected(Rejection::RateLimited), 1);
+    }
+
+    #[test]
+    fn should_allow_calls_more_expensive_than_budget() {
+        let limiter = limiter(10, 0);
+        let now = Instant::now();
+
+        assert_eq!(limiter.acquire_at("a", "trace_filter", now), Ok(()));
+        assert_eq!(
+            limiter.acquire_at("a", "trace_filter", now),
+            Err(Rejection::RateLimited)
+        );
+    }
+
+    #[test]
+    fn should_limit_concurrent_requests() {
+        let limiter = limiter(0, 2);
+
+        assert_eq!(limiter.acquire("a", "eth_call"), Ok(()));
+        assert_eq!(limiter.acquire("a", "eth_call"), Ok(()));
+        assert_eq!(
+            limiter.acquire("a", "eth_call"),
+            Err(Rejection::TooManyConcurrent)
+        );
+        limiter.release("a");
+        assert_eq!(limiter.acquire("a", "eth_call"), Ok(()));
+        assert_eq!(limiter.rejected(Rejection::TooManyConcurrent), 1);
+    }
+
+    #[test]
+    fn should_release_guarded_calls_when_dropped() {
+        let limiter = Arc::new(limiter(0, 1));
+
+        let guard = limiter.acquire_guard("a".into(), "eth_call").unwrap();
+        assert_eq!(
+            limiter.acquire("a", "eth_call"),
+            Err(Rejection::TooManyConcurrent)
+        );
+        drop(guard);
+        assert_eq!(limiter.acquire("a", "eth_call"), Ok(()));
+    }
+
+    #[test]
+    fn should_reject_large_batches() {
+        let mut io =
+            MetaIoHandler::with_middleware(RateLimitMiddleware::new(Arc::new(limiter(100, 0))));
+        io.add_method("eth_blockNumber", |_: Params| {
+            Ok(Value::String("0x1".into()))
+        });
+        let meta = Metadata {
+            origin: Origin::Rpc("test".into()),
+            ..Default::default()
+        };
+
+        let request = r#"[
+            {"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 1},
+            {"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 2},
+            {"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 3}
+        ]"#;
+        let response = r#"{"jsonrpc":"2.0","error":{"code":-32043,"message":"Request has been rejected because of rate limit.","data":"Batch request contains too many calls."},"id":null}"#;
+        assert_eq!(
+            io.handle_request_sync(request, meta.clone()),
+            Some(response.to_owned())
+        );
+
+        // IPC clients are not limited
+        let meta = Metadata {
+            origin: Origin::Ipc(Default::default()),
+            ..Default::default()
+        };
+        let response = r#"[{"jsonrpc":"2.0","result":"0x1","id":1},{"jsonrpc":"2.0","result":"0x1","id":2},{"jsonrpc":"2.0","result":"0x1","id":3}]"#;
+        assert_eq!(
+            io.handle_request_sync(request, meta),
+            Some(response.to_owned())
+        );
+    }
+}
@@ -29,6 +29,10 @@ pub struct Metadata {
     pub origin: Origin,
     /// Request PubSub Session
     pub session: Option<Arc<Session>>,
+    /// Client identifier used for rate limiting (peer address or WebSockets session)
+    pub client: Option<String>,
+    /// APIs the request is restricted to by its JWT, `None` if not restricted.
+    pub apis: Option<Vec<String>>,
 }
 
 impl jsonrpc_core::Metadata for Metadata {}
@@ -36,8 +36,10 @@ mod impls;
 mod tests;
 mod types;
 
+pub mod access;
 pub mod extractors;
 pub mod informant;
+pub mod limiter;
 pub mod metadata;
 pub mod traits;
 
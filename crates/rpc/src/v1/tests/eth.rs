@@ -146,6 +146,7 @@ impl EthTester {
             &external_miner,
             EthClientOptions {
                 gas_price_percentile: 50,
+                gas_price_history_blocks: 100,
                 allow_experimental_rpcs: true,
                 allow_missing_blocks: false,
                 no_ancient_blocks: false,
@@ -155,7 +156,7 @@ impl EthTester {
         let reservations = Arc::new(Mutex::new(nonce::Reservations::new(runtime.executor())));
 
         let dispatcher =
-            FullDispatcher::new(client.clone(), miner_service.clone(), reservations, 50);
+            FullDispatcher::new(client.clone(), miner_service.clone(), reservations, 50, 100);
         let signer = Arc::new(dispatch::Signer::new(account_provider.clone())) as _;
         let eth_sign = SigningUnsafeClient::new(&signer, dispatcher);
 
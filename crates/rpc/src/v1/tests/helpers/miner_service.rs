@@ -17,7 +17,7 @@
 //! Test implementation of miner service.
 
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     sync::Arc,
 };
 
@@ -52,6 +52,8 @@ use types::{
 pub struct TestMinerService {
     /// Imported transactions.
     pub imported_transactions: Mutex<Vec<SignedTransaction>>,
+    /// Hashes of transactions imported as private.
+    pub private_transactions: Mutex<HashSet<H256>>,
     /// Pre-existed pending transactions
     pub pending_transactions: Mutex<HashMap<H256, SignedTransaction>>,
     /// Pre-existed local transactions
@@ -72,6 +74,7 @@ impl Default for TestMinerService {
     fn default() -> TestMinerService {
         TestMinerService {
             imported_transactions: Default::default(),
+            private_transactions: Default::default(),
             pending_transactions: Default::default(),
             local_transactions: Default::default(),
             pending_receipts: Default::default(),
@@ -207,6 +210,30 @@ impl MinerService for TestMinerService {
         Ok(())
     }
 
+    fn import_private_transaction<C: Nonce + Sync>(
+        &self,
+        chain: &C,
+        pending: PendingTransaction,
+    ) -> Result<(), transaction::Error> {
+        self.private_transactions.lock().insert(pending.hash());
+        self.import_claimed_local_transaction(chain, pending, true)
+    }
+
+    fn import_external_private_transactions<C: Nonce + Sync>(
+        &self,
+        chain: &C,
+        transactions: Vec<UnverifiedTransaction>,
+    ) -> Vec<Result<(), transaction::Error>> {
+        self.private_transactions
+            .lock()
+            .extend(transactions.iter().map(|tx| tx.hash()));
+        self.import_external_transactions(chain, transactions)
+    }
+
+    fn is_private_transaction(&self, hash: &H256) -> bool {
+        self.private_transactions.lock().contains(hash)
+    }
+
     /// Called when blocks are imported to chain, updates transactions queue.
     fn chain_new_blocks<C>(
         &self,
@@ -399,4 +426,8 @@ impl MinerService for TestMinerService {
             }
         }
     }
+
+    fn reload_admission_policy(&self) -> Result<bool, String> {
+        Ok(false)
+    }
 }
@@ -26,7 +26,7 @@ use ethcore::{
     client::{BlockChainClient, EachBlockWith, EvmTestClient, Executed, TestBlockChainClient},
     miner::{self, MinerService},
 };
-use ethereum_types::{Address, Bloom, H160, H256, U256};
+use ethereum_types::{Address, Bloom, H160, H256, H512, U256};
 use miner::external::ExternalMiner;
 use parity_runtime::Runtime;
 use parking_lot::Mutex;
@@ -555,6 +555,54 @@ fn rpc_eth_gas_price() {
     );
 }
 
+#[test]
+fn rpc_eth_gas_price_from_recent_blocks() {
+    let tester = EthTester::default();
+    tester.add_blocks(10, EachBlockWith::Transaction);
+
+    let request = r#"{"jsonrpc": "2.0", "method": "eth_gasPrice", "params": [], "id": 1}"#;
+    let response = r#"{"jsonrpc":"2.0","result":"0x2e90edd000","id":1}"#; // 200 GWei
+
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.to_owned())
+    );
+}
+
+#[test]
+fn rpc_eth_gas_price_is_recomputed_on_new_blocks() {
+    let tester = EthTester::default();
+    let request = r#"{"jsonrpc": "2.0", "method": "eth_gasPrice", "params": [], "id": 1}"#;
+
+    let response = r#"{"jsonrpc":"2.0","result":"0x4a817c800","id":1}"#; // 20 GWei
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.to_owned())
+    );
+
+    tester.add_blocks(10, EachBlockWith::Transaction);
+    let response = r#"{"jsonrpc":"2.0","result":"0x2e90edd000","id":1}"#; // 200 GWei
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.to_owned())
+    );
+}
+
+#[test]
+fn rpc_eth_gas_price_is_at_least_engine_minimum() {
+    let tester = EthTester::default();
+    tester.add_blocks(10, EachBlockWith::Transaction);
+    *tester.client.minimum_gas_price.write() = Some(U256::from(300_000_000_000u64));
+
+    let request = r#"{"jsonrpc": "2.0", "method": "eth_gasPrice", "params": [], "id": 1}"#;
+    let response = r#"{"jsonrpc":"2.0","result":"0x45d964b800","id":1}"#; // 300 GWei
+
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.to_owned())
+    );
+}
+
 #[test]
 fn rpc_eth_get_max_priority_fee_per_gas() {
     let tester = EthTester::new_eip1559_with_options(Default::default());
@@ -987,6 +1035,184 @@ fn rpc_eth_call_default_block() {
     );
 }
 
+#[test]
+fn rpc_eth_simulate_v1() {
+    let tester = EthTester::default();
+    tester.client.set_execution_result(Ok(Executed {
+        exception: None,
+        gas: U256::zero(),
+        gas_used: U256::from(0x5208),
+        refunded: U256::zero(),
+        cumulative_gas_used: U256::from(0x5208),
+        logs: vec![],
+        contracts_created: vec![],
+        output: vec![0x12, 0x34, 0xff],
+        trace: vec![],
+        vm_trace: None,
+        state_diff: None,
+    }));
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_simulateV1",
+		"params": [{
+			"blockStateCalls": [{
+				"blockOverrides": {"number": "0x5"},
+				"calls": [{
+					"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+					"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567"
+				}]
+			}]
+		},
+		"latest"],
+		"id": 1
+	}"#;
+
+    let response = tester.io.handle_request_sync(request).unwrap();
+    assert!(response.contains(r#""number":"0x5""#));
+    assert!(response.contains(
+        r#""calls":[{"returnData":"0x1234ff","logs":[],"gasUsed":"0x5208","status":"0x1"}]"#
+    ));
+}
+
+#[test]
+fn rpc_eth_simulate_v1_rejects_conflicting_overrides() {
+    let tester = EthTester::default();
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_simulateV1",
+		"params": [{
+			"blockStateCalls": [{
+				"stateOverrides": {
+					"0xb60e8dd61c5d32be8058bb8eb970870f07233155": {
+						"state": {},
+						"stateDiff": {}
+					}
+				},
+				"calls": []
+			}]
+		},
+		"latest"],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: stateOverrides","data":"\"Both `state` and `stateDiff` given for the same account\""},"id":1}"#;
+
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.to_owned())
+    );
+}
+
+#[test]
+fn rpc_eth_simulate_v1_rejects_unsupported_options() {
+    let tester = EthTester::default();
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_simulateV1",
+		"params": [{
+			"blockStateCalls": [],
+			"traceTransfers": true
+		},
+		"latest"],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: traceTransfers","data":"\"Only `false` is supported\""},"id":1}"#;
+
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.to_owned())
+    );
+}
+
+#[test]
+fn rpc_eth_block_receipts() {
+    let tester = EthTester::default();
+    tester.client.receipts.write().insert(
+        TransactionId::Hash(H256::from_low_u64_be(1)),
+        LocalizedReceipt {
+            transaction_hash: H256::from_low_u64_be(1),
+            transaction_type: TypedTxId::Legacy,
+            transaction_index: 0,
+            block_hash: H256::from_low_u64_be(3),
+            block_number: 0,
+            cumulative_gas_used: 21_000.into(),
+            gas_used: 21_000.into(),
+            contract_address: None,
+            logs: vec![],
+            log_bloom: Bloom::from_low_u64_be(1),
+            outcome: TransactionOutcome::StatusCode(1),
+            to: None,
+            from: Address::from_low_u64_be(9),
+            effective_gas_price: Default::default(),
+        },
+    );
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getBlockReceipts",
+		"params": ["latest"],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","result":[{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000003","blockNumber":"0x0","contractAddress":null,"cumulativeGasUsed":"0x5208","effectiveGasPrice":"0x0","from":"0x0000000000000000000000000000000000000009","gasUsed":"0x5208","logs":[],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001","status":"0x1","to":null,"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000001","transactionIndex":"0x0","type":"0x0"}],"id":1}"#;
+
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.to_owned())
+    );
+}
+
+#[test]
+fn rpc_eth_block_receipts_honours_require_canonical() {
+    let tester = EthTester::default();
+    // a block known to the client, but not on the canonical chain
+    let side_block = H256::from_low_u64_be(5);
+    tester.client.blocks.write().insert(side_block, vec![]);
+    tester.client.receipts.write().insert(
+        TransactionId::Hash(H256::from_low_u64_be(1)),
+        LocalizedReceipt {
+            transaction_hash: H256::from_low_u64_be(1),
+            transaction_type: TypedTxId::Legacy,
+            transaction_index: 0,
+            block_hash: side_block,
+            block_number: 0,
+            cumulative_gas_used: 21_000.into(),
+            gas_used: 21_000.into(),
+            contract_address: None,
+            logs: vec![],
+            log_bloom: Bloom::zero(),
+            outcome: TransactionOutcome::StatusCode(1),
+            to: None,
+            from: Address::from_low_u64_be(9),
+            effective_gas_price: Default::default(),
+        },
+    );
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getBlockReceipts",
+		"params": [{"blockHash": "0x0000000000000000000000000000000000000000000000000000000000000005", "requireCanonical": true}],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Invalid input"},"id":1}"#;
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.to_owned())
+    );
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getBlockReceipts",
+		"params": [{"blockHash": "0x0000000000000000000000000000000000000000000000000000000000000005"}],
+		"id": 1
+	}"#;
+    let response = tester.io.handle_request_sync(request).unwrap();
+    assert!(response.contains(
+        r#""transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000001""#
+    ));
+}
+
 #[test]
 fn rpc_eth_estimate_gas() {
     let tester = EthTester::default();
@@ -1186,6 +1412,117 @@ fn rpc_eth_send_raw_transaction() {
     assert_eq!(tester.io.handle_request_sync(&req), Some(res));
 }
 
+#[test]
+fn rpc_eth_send_private_raw_transaction() {
+    let tester = EthTester::default();
+    *tester.client.validator_node_ids.write() = vec![H512::from_low_u64_be(1)];
+    let address = tester
+        .accounts_provider
+        .new_account(&"abcd".into())
+        .unwrap();
+    tester
+        .accounts_provider
+        .unlock_account_permanently(address, "abcd".into())
+        .unwrap();
+
+    let t = TypedTransaction::Legacy(Transaction {
+        nonce: U256::zero(),
+        gas_price: U256::from(0x9184e72a000u64),
+        gas: U256::from(0x76c0),
+        action: Action::Call(
+            Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap(),
+        ),
+        value: U256::from(0x9184e72au64),
+        data: vec![],
+    });
+    let signature = tester
+        .accounts_provider
+        .sign(address, None, t.signature_hash(None))
+        .unwrap();
+    let t = t.with_signature(signature, None);
+
+    let rlp = t.encode().to_hex();
+
+    let req = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_sendPrivateRawTransaction",
+		"params": [
+			"0x"#
+        .to_owned()
+        + &rlp
+        + r#""
+		],
+		"id": 1
+	}"#;
+
+    let res =
+        r#"{"jsonrpc":"2.0","result":""#.to_owned() + &format!("0x{:x}", t.hash()) + r#"","id":1}"#;
+
+    assert_eq!(tester.io.handle_request_sync(&req), Some(res));
+    assert!(tester.miner.is_private_transaction(&t.hash()));
+    assert_eq!(tester.client.private_transactions_sent.read().len(), 1);
+    assert_eq!(
+        tester.client.private_transactions_sent.read()[0].hash(),
+        t.hash()
+    );
+}
+
+#[test]
+fn rpc_eth_send_private_raw_transaction_without_validators() {
+    let tester = EthTester::default();
+    let address = tester
+        .accounts_provider
+        .new_account(&"abcd".into())
+        .unwrap();
+    tester
+        .accounts_provider
+        .unlock_account_permanently(address, "abcd".into())
+        .unwrap();
+
+    let t = TypedTransaction::Legacy(Transaction {
+        nonce: U256::zero(),
+        gas_price: U256::from(0x9184e72a000u64),
+        gas: U256::from(0x76c0),
+        action: Action::Call(
+            Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap(),
+        ),
+        value: U256::from(0x9184e72au64),
+        data: vec![],
+    });
+    let signature = tester
+        .accounts_provider
+        .sign(address, None, t.signature_hash(None))
+        .unwrap();
+    let t = t.with_signature(signature, None);
+
+    let rlp = t.encode().to_hex();
+
+    let req = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_sendPrivateRawTransaction",
+		"params": [
+			"0x"#
+        .to_owned()
+        + &rlp
+        + r#""
+		],
+		"id": 1
+	}"#;
+
+    let res = r#"{"jsonrpc":"2.0","error":{"code":-32010,"message":"Transaction was not forwarded to any validator.","data":""#
+        .to_owned()
+        + &format!("{:?}", t.hash())
+        + r#""},"id":1}"#;
+
+    assert_eq!(tester.io.handle_request_sync(&req), Some(res));
+    assert!(tester
+        .miner
+        .pending_transactions
+        .lock()
+        .get(&t.hash())
+        .is_none());
+}
+
 #[test]
 fn rpc_eth_transaction_receipt() {
     let receipt = LocalizedReceipt {
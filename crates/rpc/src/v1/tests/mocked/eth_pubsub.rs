@@ -30,6 +30,7 @@ use ethcore::client::{
     ChainNotify, ChainRoute, ChainRouteType, EachBlockWith, NewBlocks, TestBlockChainClient,
 };
 use ethereum_types::{Address, H256};
+use miner::pool;
 use parity_runtime::Runtime;
 
 const DURATION_ZERO: Duration = Duration::from_millis(0);
@@ -268,6 +269,60 @@ fn should_subscribe_to_pending_transactions() {
     assert_eq!(res, None);
 }
 
+#[test]
+fn should_subscribe_to_transaction_events() {
+    // given
+    let el = Runtime::with_thread_count(1);
+    let client = TestBlockChainClient::new();
+
+    let pubsub = EthPubSubClient::new_test(Arc::new(client), el.executor());
+    let handler = pubsub.handler().upgrade().unwrap();
+    let pubsub = pubsub.to_delegate();
+
+    let mut io = MetaIoHandler::default();
+    io.extend_with(pubsub);
+
+    let mut metadata = Metadata::default();
+    let (sender, receiver) = futures::sync::mpsc::channel(8);
+    metadata.session = Some(Arc::new(Session::new(sender)));
+
+    // Subscribe
+    let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["transactionEvents"], "id": 1}"#;
+    let response = r#"{"jsonrpc":"2.0","result":"0x43ca64edf03768e1","id":1}"#;
+    assert_eq!(
+        io.handle_request_sync(request, metadata.clone()),
+        Some(response.to_owned())
+    );
+
+    // Send transaction events
+    handler.notify_transaction_events(&[
+        pool::TransactionEvent::Added(H256::from_low_u64_be(7)),
+        pool::TransactionEvent::Replaced {
+            hash: H256::from_low_u64_be(5),
+            by: H256::from_low_u64_be(7),
+        },
+    ]);
+
+    let (res, receiver) = receiver.into_future().wait().unwrap();
+    let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":{"event":"added","hash":"0x0000000000000000000000000000000000000000000000000000000000000007"},"subscription":"0x43ca64edf03768e1"}}"#;
+    assert_eq!(res, Some(response.into()));
+
+    let (res, receiver) = receiver.into_future().wait().unwrap();
+    let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":{"by":"0x0000000000000000000000000000000000000000000000000000000000000007","event":"replaced","hash":"0x0000000000000000000000000000000000000000000000000000000000000005"},"subscription":"0x43ca64edf03768e1"}}"#;
+    assert_eq!(res, Some(response.into()));
+
+    // And unsubscribe
+    let request = r#"{"jsonrpc": "2.0", "method": "eth_unsubscribe", "params": ["0x43ca64edf03768e1"], "id": 1}"#;
+    let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+    assert_eq!(
+        io.handle_request_sync(request, metadata),
+        Some(response.to_owned())
+    );
+
+    let (res, _receiver) = receiver.into_future().wait().unwrap();
+    assert_eq!(res, None);
+}
+
 #[test]
 fn should_return_unimplemented() {
     // given
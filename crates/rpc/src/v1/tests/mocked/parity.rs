@@ -15,7 +15,7 @@
 // along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use crypto::publickey::{Generator, Random};
-use ethcore::client::{Executed, TestBlockChainClient, TransactionId};
+use ethcore::client::{EachBlockWith, Executed, TestBlockChainClient, TransactionId};
 use ethcore_logger::RotatingLogger;
 use ethereum_types::{Address, BigEndianHash, Bloom, H256, U256};
 use miner::pool::local_transactions::Status as LocalTransactionStatus;
@@ -577,6 +577,104 @@ fn rpc_parity_block_receipts() {
     assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+fn block_receipt(block_number: u64, transaction_index: usize, hash: u64) -> LocalizedReceipt {
+    LocalizedReceipt {
+        transaction_hash: H256::from_low_u64_be(hash),
+        transaction_type: TypedTxId::Legacy,
+        transaction_index,
+        block_hash: H256::from_low_u64_be(0x100 + block_number),
+        block_number,
+        cumulative_gas_used: (21_000 * (transaction_index + 1)).into(),
+        gas_used: 21_000.into(),
+        contract_address: None,
+        logs: vec![],
+        log_bloom: Bloom::zero(),
+        outcome: TransactionOutcome::StatusCode(1),
+        to: None,
+        from: Address::from_low_u64_be(9),
+        effective_gas_price: Default::default(),
+    }
+}
+
+#[test]
+fn rpc_parity_block_receipts_range() {
+    let deps = Dependencies::new();
+    deps.client.add_blocks(2, EachBlockWith::Nothing);
+    for receipt in vec![
+        block_receipt(1, 1, 0x12),
+        block_receipt(0, 0, 0x01),
+        block_receipt(1, 0, 0x11),
+        block_receipt(2, 0, 0x21),
+    ] {
+        deps.client
+            .receipts
+            .write()
+            .insert(TransactionId::Hash(receipt.transaction_hash), receipt);
+    }
+    let io = deps.default_client();
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_getBlockReceiptsRange",
+		"params": ["earliest", "latest", 3],
+		"id": 1
+	}"#;
+    let response = io.handle_request_sync(request).unwrap();
+    let response = serde_json::Value::from_str(&response).unwrap();
+    let page = &response["result"];
+
+    let blocks = page["blocks"].as_array().unwrap();
+    assert_eq!(blocks.len(), 2);
+    let hashes = |block: &serde_json::Value| {
+        block["receipts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|receipt| receipt["transactionHash"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(blocks[0]["blockNumber"], "0x0");
+    assert_eq!(
+        hashes(&blocks[0]),
+        vec![format!("{:?}", H256::from_low_u64_be(0x01))]
+    );
+    assert_eq!(blocks[1]["blockNumber"], "0x1");
+    assert_eq!(
+        hashes(&blocks[1]),
+        vec![
+            format!("{:?}", H256::from_low_u64_be(0x11)),
+            format!("{:?}", H256::from_low_u64_be(0x12)),
+        ]
+    );
+    assert_eq!(page["nextBlock"], "0x2");
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_getBlockReceiptsRange",
+		"params": ["0x2", "latest"],
+		"id": 1
+	}"#;
+    let response = io.handle_request_sync(request).unwrap();
+    let response = serde_json::Value::from_str(&response).unwrap();
+    let blocks = response["result"]["blocks"].as_array().unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(
+        hashes(&blocks[0]),
+        vec![format!("{:?}", H256::from_low_u64_be(0x21))]
+    );
+    assert_eq!(response["result"]["nextBlock"], serde_json::Value::Null);
+
+    let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_getBlockReceiptsRange",
+		"params": ["latest", "earliest"],
+		"id": 1
+	}"#;
+    let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: blockNumber","data":"\"`from` is greater than `to`\""},"id":1}"#;
+
+    assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_status_ok() {
     let deps = Dependencies::new();
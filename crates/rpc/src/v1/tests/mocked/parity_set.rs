@@ -146,6 +146,22 @@ fn rpc_parity_set_author() {
     );
 }
 
+#[test]
+fn rpc_parity_reload_admission_policy() {
+    let miner = miner_service();
+    let client = client_service();
+    let network = network_service();
+
+    let mut io = IoHandler::new();
+    io.extend_with(parity_set_client(&client, &miner, &network).to_delegate());
+
+    let request =
+        r#"{"jsonrpc": "2.0", "method": "parity_reloadAdmissionPolicy", "params":[], "id": 1}"#;
+    let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+
+    assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_set_transactions_limit() {
     let miner = miner_service();
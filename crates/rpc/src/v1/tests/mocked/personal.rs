@@ -25,7 +25,7 @@ use hash::keccak;
 use jsonrpc_core::IoHandler;
 use parity_runtime::Runtime;
 use parking_lot::Mutex;
-use types::transaction::{Action, Transaction, TypedTransaction};
+use types::transaction::{Action, SignedTransaction, Transaction, TypedTransaction};
 
 use serde_json::to_value;
 use v1::{
@@ -75,7 +75,7 @@ fn setup_with(c: Config) -> PersonalTester {
     let miner = miner_service();
     let reservations = Arc::new(Mutex::new(nonce::Reservations::new(runtime.executor())));
 
-    let dispatcher = FullDispatcher::new(client, miner.clone(), reservations, 50);
+    let dispatcher = FullDispatcher::new(client, miner.clone(), reservations, 50, 100);
     let personal = PersonalClient::new(&accounts, dispatcher, c.allow_experimental_rpcs);
 
     let mut io = IoHandler::default();
@@ -323,6 +323,83 @@ fn sign_and_send_test(method: &str) {
     );
 }
 
+fn signed_transaction(
+    tester: &PersonalTester,
+    address: Address,
+    transaction: Transaction,
+) -> SignedTransaction {
+    let t = TypedTransaction::Legacy(transaction);
+    let signature = tester
+        .accounts
+        .sign(address, Some("password123".into()), t.signature_hash(None))
+        .unwrap();
+    SignedTransaction::new(t.with_signature(signature, None)).unwrap()
+}
+
+#[test]
+fn should_cancel_pending_transaction() {
+    let tester = setup();
+    let address = tester.accounts.new_account(&"password123".into()).unwrap();
+    let original = signed_transaction(
+        &tester,
+        address,
+        Transaction {
+            nonce: U256::from(3),
+            gas_price: U256::from(80_000_000),
+            gas: U256::from(0x76c0),
+            action: Action::Call(Address::from_low_u64_be(5)),
+            value: U256::from(0x9184e72au64),
+            data: vec![1, 2, 3],
+        },
+    );
+    tester
+        .miner
+        .pending_transactions
+        .lock()
+        .insert(original.hash(), original.clone());
+
+    let request = format!(
+        r#"{{"jsonrpc": "2.0", "method": "personal_cancelTransaction", "params": ["0x{:x}", "password123"], "id": 1}}"#,
+        original.hash()
+    );
+
+    let cancel = signed_transaction(
+        &tester,
+        address,
+        Transaction {
+            nonce: U256::from(3),
+            gas_price: U256::from(90_000_000),
+            gas: U256::from(21_000),
+            action: Action::Call(address),
+            value: U256::zero(),
+            data: vec![],
+        },
+    );
+    let response = format!(
+        r#"{{"jsonrpc":"2.0","result":"0x{:x}","id":1}}"#,
+        cancel.hash()
+    );
+
+    assert_eq!(tester.io.handle_request_sync(&request), Some(response));
+    assert_eq!(
+        tester.miner.imported_transactions.lock().last().cloned(),
+        Some(cancel)
+    );
+}
+
+#[test]
+fn should_not_replace_unknown_transaction() {
+    let tester = setup();
+
+    let request = r#"{"jsonrpc": "2.0", "method": "personal_speedUpTransaction", "params": ["0x0000000000000000000000000000000000000000000000000000000000000005", "password123"], "id": 1}"#;
+    let response = r#"{"jsonrpc":"2.0","error":{"code":-32010,"message":"Transaction is not pending in the transaction queue.","data":"0x0000000000000000000000000000000000000000000000000000000000000005"},"id":1}"#;
+
+    assert_eq!(
+        tester.io.handle_request_sync(request),
+        Some(response.into())
+    );
+}
+
 #[test]
 fn ec_recover() {
     let tester = setup();
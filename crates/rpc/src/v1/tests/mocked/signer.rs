@@ -68,7 +68,7 @@ fn signer_tester() -> SignerTester {
     let miner = miner_service();
     let reservations = Arc::new(Mutex::new(nonce::Reservations::new(runtime.executor())));
 
-    let dispatcher = FullDispatcher::new(client, miner.clone(), reservations, 50);
+    let dispatcher = FullDispatcher::new(client, miner.clone(), reservations, 50, 100);
     let mut io = IoHandler::default();
     io.extend_with(
         SignerClient::new(account_signer, dispatcher, &signer, runtime.executor()).to_delegate(),
@@ -76,6 +76,7 @@ impl EthTester {
             miner.clone(),
             reservations,
             gas_price_percentile,
+            options.gas_price_history_blocks,
         );
         let sign = SigningUnsafeClient::new(&ap, dispatcher).to_delegate();
         let mut io: IoHandler<Metadata> = IoHandler::default();
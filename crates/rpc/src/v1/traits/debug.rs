@@ -19,7 +19,7 @@
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 
-use v1::types::RichBlock;
+use v1::types::{BlockNumber, BlockProfile, RichBlock};
 
 /// Debug RPC interface.
 #[rpc(server)]
@@ -27,4 +27,9 @@ pub trait Debug {
     /// Returns recently seen bad blocks.
     #[rpc(name = "debug_getBadBlocks")]
     fn bad_blocks(&self) -> Result<Vec<RichBlock>>;
+
+    /// Re-executes the transactions of a block and returns the gas used, execution time and
+    /// number of executions of each opcode, per contract.
+    #[rpc(name = "debug_profileBlock")]
+    fn profile_block(&self, _: BlockNumber) -> Result<BlockProfile>;
 }
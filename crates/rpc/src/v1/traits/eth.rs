@@ -21,7 +21,7 @@ use jsonrpc_derive::rpc;
 
 use v1::types::{
     BlockNumber, Bytes, CallRequest, EthAccount, EthFeeHistory, Filter, FilterChanges, Index, Log,
-    Receipt, RichBlock, SyncStatus, Transaction, Work,
+    Receipt, RichBlock, SimulatePayload, SimulatedBlock, SyncStatus, Transaction, Work,
 };
 
 /// Eth rpc interface.
@@ -126,10 +126,26 @@ pub trait Eth {
     #[rpc(name = "eth_submitTransaction")]
     fn submit_transaction(&self, _: Bytes) -> Result<H256>;
 
+    /// Sends signed transaction to the current validators only, returning its hash.
+    ///
+    /// The transaction is not gossiped to the network unless it is not included
+    /// within the configured number of blocks.
+    #[rpc(name = "eth_sendPrivateRawTransaction")]
+    fn send_private_raw_transaction(&self, _: Bytes) -> Result<H256>;
+
     /// Call contract, returning the output data.
     #[rpc(name = "eth_call")]
     fn call(&self, _: CallRequest, _: Option<BlockNumber>) -> BoxFuture<Bytes>;
 
+    /// Simulates consecutive blocks of calls on top of the given block. Every call sees the
+    /// effects of the calls before it. State and header fields can be overridden per block.
+    #[rpc(name = "eth_simulateV1")]
+    fn simulate_v1(
+        &self,
+        _: SimulatePayload,
+        _: Option<BlockNumber>,
+    ) -> BoxFuture<Vec<SimulatedBlock>>;
+
     /// Estimate gas needed for execution of given contract.
     #[rpc(name = "eth_estimateGas")]
     fn estimate_gas(&self, _: CallRequest, _: Option<BlockNumber>) -> BoxFuture<U256>;
@@ -158,6 +174,10 @@ pub trait Eth {
     #[rpc(name = "eth_getTransactionReceipt")]
     fn transaction_receipt(&self, _: H256) -> BoxFuture<Option<Receipt>>;
 
+    /// Returns receipts of all transactions in the given block.
+    #[rpc(name = "eth_getBlockReceipts")]
+    fn block_receipts(&self, _: BlockNumber) -> BoxFuture<Option<Vec<Receipt>>>;
+
     /// Returns an uncles at given block and index.
     #[rpc(name = "eth_getUncleByBlockHashAndIndex")]
     fn uncle_by_block_hash_and_index(&self, _: H256, _: Index) -> BoxFuture<Option<RichBlock>>;
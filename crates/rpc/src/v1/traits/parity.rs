@@ -24,8 +24,9 @@ use jsonrpc_derive::rpc;
 
 use ethcore::miner::TransactionFilter;
 use v1::types::{
-    BlockNumber, Bytes, CallRequest, ChainStatus, Histogram, LocalTransactionStatus, Peers,
-    Receipt, RecoveredAccount, RichHeader, RpcSettings, Transaction, TransactionStats,
+    BlockNumber, BlockReceiptsPage, Bytes, CallRequest, ChainStatus, Histogram,
+    LocalTransactionStatus, Peers, Receipt, RecoveredAccount, RichHeader, RpcSettings, Transaction,
+    TransactionStats,
 };
 
 /// Parity-specific rpc interface.
@@ -202,6 +203,17 @@ pub trait Parity {
     #[rpc(name = "parity_getBlockReceipts")]
     fn block_receipts(&self, _: Option<BlockNumber>) -> BoxFuture<Vec<Receipt>>;
 
+    /// Returns receipts of all blocks in the inclusive range `from..=to`, grouped by block.
+    /// The response is paged: it stops at the last whole block that fits into the given
+    /// (capped) number of receipts and reports the first block of the next page.
+    #[rpc(name = "parity_getBlockReceiptsRange")]
+    fn block_receipts_range(
+        &self,
+        _: BlockNumber,
+        _: BlockNumber,
+        _: Option<usize>,
+    ) -> BoxFuture<BlockReceiptsPage>;
+
     /// Call contract, returning the output data.
     #[rpc(name = "parity_call")]
     fn call(&self, _: Vec<CallRequest>, _: Option<BlockNumber>) -> Result<Vec<Bytes>>;
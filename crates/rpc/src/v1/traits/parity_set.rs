@@ -65,6 +65,11 @@ pub trait ParitySet {
     #[rpc(name = "parity_setTransactionsLimit")]
     fn set_transactions_limit(&self, _: usize) -> Result<bool>;
 
+    /// Reloads the local transaction admission policy from its file.
+    /// Returns `false` if no admission policy file is configured.
+    #[rpc(name = "parity_reloadAdmissionPolicy")]
+    fn reload_admission_policy(&self) -> Result<bool>;
+
     /// Sets the maximum amount of gas a single transaction may consume.
     #[rpc(name = "parity_setMaxTransactionGas")]
     fn set_tx_gas_limit(&self, _: U256) -> Result<bool>;
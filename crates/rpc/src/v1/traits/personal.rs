@@ -79,6 +79,16 @@ pub trait Personal {
         _: String,
     ) -> BoxFuture<H256>;
 
+    /// Replaces a pending transaction of the account with the same transaction paying higher fees.
+    /// The account is not unlocked in such case.
+    #[rpc(meta, name = "personal_speedUpTransaction")]
+    fn speed_up_transaction(&self, _: Self::Metadata, _: H256, _: String) -> BoxFuture<H256>;
+
+    /// Cancels a pending transaction of the account by replacing it with a zero value transfer
+    /// to the account itself. The account is not unlocked in such case.
+    #[rpc(meta, name = "personal_cancelTransaction")]
+    fn cancel_transaction(&self, _: Self::Metadata, _: H256, _: String) -> BoxFuture<H256>;
+
     /// @deprecated alias for `personal_sendTransaction`.
     #[rpc(meta, name = "personal_signAndSendTransaction")]
     fn sign_and_send_transaction(
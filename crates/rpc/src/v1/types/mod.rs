@@ -36,16 +36,19 @@ pub use self::{
     index::Index,
     log::Log,
     node_kind::{Availability, Capability, NodeKind},
+    profile::{BlockProfile, ContractProfile, OpcodeStats},
     provenance::Origin,
-    receipt::Receipt,
+    receipt::{BlockReceipts, BlockReceiptsPage, Receipt},
     rpc_settings::RpcSettings,
     secretstore::EncryptedDocumentKey,
+    simulate::{SimulatePayload, SimulatedBlock},
     sync::{ChainStatus, Peers, SyncInfo, SyncStatus, TransactionStats},
     trace::{LocalizedTrace, TraceResults, TraceResultsWithTransactionHash},
     trace_filter::TraceFilter,
     transaction::{LocalTransactionStatus, RichRawTransaction, Transaction},
     transaction_access_list::AccessList,
     transaction_condition::TransactionCondition,
+    transaction_event::{TransactionEvent, TransactionEventKind},
     transaction_request::TransactionRequest,
     work::Work,
 };
@@ -66,16 +69,19 @@ mod histogram;
 mod index;
 mod log;
 mod node_kind;
+mod profile;
 mod provenance;
 mod receipt;
 mod rpc_settings;
 mod secretstore;
+mod simulate;
 mod sync;
 mod trace;
 mod trace_filter;
 mod transaction;
 mod transaction_access_list;
 mod transaction_condition;
+mod transaction_event;
 mod transaction_request;
 mod work;
 
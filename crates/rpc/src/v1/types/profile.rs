@@ -0,0 +1,136 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Opcode profile of a block.
+
+use std::collections::BTreeMap;
+
+use ethcore::trace;
+use ethereum_types::{H160, U256};
+use evm::Instruction;
+
+/// Aggregated executions of an opcode.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpcodeStats {
+    /// Opcode.
+    pub opcode: u8,
+    /// Instruction name, if the opcode is defined.
+    pub name: Option<&'static str>,
+    /// Number of executions.
+    pub count: u64,
+    /// Gas used, without the gas used in nested frames.
+    pub gas_used: U256,
+    /// Execution time in nanoseconds, without the time spent in nested frames.
+    pub time_ns: u64,
+}
+
+impl OpcodeStats {
+    fn new(opcode: u8, stats: trace::OpcodeStats) -> Self {
+        OpcodeStats {
+            opcode,
+            name: Instruction::from_u8(opcode).map(|i| i.info().name),
+            count: stats.count,
+            gas_used: stats.gas_used,
+            time_ns: stats.time.as_nanos() as u64,
+        }
+    }
+}
+
+/// Opcode statistics of a contract.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractProfile {
+    /// Address of the executed code.
+    pub address: H160,
+    /// Total gas used by the contract's own instructions.
+    pub gas_used: U256,
+    /// Total execution time in nanoseconds of the contract's own instructions.
+    pub time_ns: u64,
+    /// Statistics of each executed opcode.
+    pub opcodes: Vec<OpcodeStats>,
+}
+
+/// Opcode statistics of the transactions of a block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockProfile {
+    /// Statistics of each opcode over all contracts.
+    pub opcodes: Vec<OpcodeStats>,
+    /// Statistics of each executed contract.
+    pub contracts: Vec<ContractProfile>,
+}
+
+fn opcodes(stats: BTreeMap<u8, trace::OpcodeStats>) -> Vec<OpcodeStats> {
+    stats
+        .into_iter()
+        .map(|(opcode, stats)| OpcodeStats::new(opcode, stats))
+        .collect()
+}
+
+impl From<trace::Profile> for BlockProfile {
+    fn from(profile: trace::Profile) -> Self {
+        let contracts = profile
+            .contracts
+            .iter()
+            .map(|(address, stats)| {
+                let total = profile.contract_total(address);
+                ContractProfile {
+                    address: *address,
+                    gas_used: total.gas_used,
+                    time_ns: total.time.as_nanos() as u64,
+                    opcodes: opcodes(stats.clone()),
+                }
+            })
+            .collect();
+        BlockProfile {
+            opcodes: opcodes(profile.opcodes()),
+            contracts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockProfile;
+    use ethcore::trace::{OpcodeStats, Profile};
+    use ethereum_types::H160;
+    use serde_json;
+    use std::time::Duration;
+
+    #[test]
+    fn block_profile_serialization() {
+        let mut profile = Profile::default();
+        profile
+            .contracts
+            .entry(H160::from_low_u64_be(1))
+            .or_default()
+            .insert(
+                0x01,
+                OpcodeStats {
+                    count: 2,
+                    gas_used: 6.into(),
+                    time: Duration::from_nanos(10),
+                },
+            );
+
+        let serialized = serde_json::to_string(&BlockProfile::from(profile)).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"opcodes":[{"opcode":1,"name":"ADD","count":2,"gasUsed":"0x6","timeNs":10}],"contracts":[{"address":"0x0000000000000000000000000000000000000001","gasUsed":"0x6","timeNs":10,"opcodes":[{"opcode":1,"name":"ADD","count":2,"gasUsed":"0x6","timeNs":10}]}]}"#
+        );
+    }
+}
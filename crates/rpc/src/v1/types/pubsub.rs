@@ -19,7 +19,7 @@
 use ethereum_types::H256;
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{from_value, Value};
-use v1::types::{Filter, Log, RichHeader};
+use v1::types::{Filter, Log, RichHeader, TransactionEvent};
 
 /// Subscription result.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -30,6 +30,8 @@ pub enum Result {
     Log(Box<Log>),
     /// Transaction hash
     TransactionHash(H256),
+    /// Transaction lifecycle event
+    TransactionEvent(Box<TransactionEvent>),
 }
 
 impl Serialize for Result {
@@ -41,6 +43,7 @@ impl Serialize for Result {
             Result::Header(ref header) => header.serialize(serializer),
             Result::Log(ref log) => log.serialize(serializer),
             Result::TransactionHash(ref hash) => hash.serialize(serializer),
+            Result::TransactionEvent(ref event) => event.serialize(serializer),
         }
     }
 }
@@ -56,6 +59,8 @@ pub enum Kind {
     Logs,
     /// New Pending Transactions subscription.
     NewPendingTransactions,
+    /// Transaction queue lifecycle events subscription.
+    TransactionEvents,
     /// Node syncing status subscription.
     Syncing,
 }
@@ -112,6 +117,10 @@ mod tests {
             serde_json::from_str::<Kind>(r#""newPendingTransactions""#).unwrap(),
             Kind::NewPendingTransactions
         );
+        assert_eq!(
+            serde_json::from_str::<Kind>(r#""transactionEvents""#).unwrap(),
+            Kind::TransactionEvents
+        );
         assert_eq!(
             serde_json::from_str::<Kind>(r#""syncing""#).unwrap(),
             Kind::Syncing
@@ -59,6 +59,28 @@ pub struct Receipt {
     pub effective_gas_price: U256,
 }
 
+/// Receipts of a single block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockReceipts {
+    /// Block number
+    pub block_number: U256,
+    /// Block hash
+    pub block_hash: H256,
+    /// Receipts of all transactions in the block
+    pub receipts: Vec<Receipt>,
+}
+
+/// A page of receipts for a range of blocks.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockReceiptsPage {
+    /// Receipts grouped by block, in ascending block order
+    pub blocks: Vec<BlockReceipts>,
+    /// First block of the range that is not part of this page, if any
+    pub next_block: Option<U256>,
+}
+
 impl Receipt {
     fn outcome_to_state_root(outcome: TransactionOutcome) -> Option<H256> {
         match outcome {
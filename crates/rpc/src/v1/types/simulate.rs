@@ -0,0 +1,293 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types for `eth_simulateV1`.
+
+use std::collections::BTreeMap;
+
+use ethcore::client::{
+    simulation::{self, SimulatedBlock as EthSimulatedBlock},
+    Executed,
+};
+use ethereum_types::{H160, H256, U256, U64};
+use v1::types::{Bytes, CallRequest, Log};
+use vm::Error as VMError;
+
+/// Error code of a reverted call, as used by other clients.
+const REVERTED_CALL_ERROR: i64 = 3;
+/// Error code of a call that failed for other reasons than a revert.
+const FAILED_CALL_ERROR: i64 = -32015;
+
+/// Simulation request.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatePayload {
+    /// Blocks of calls to simulate, in order.
+    pub block_state_calls: Vec<SimulateBlock>,
+    /// Validate the calls like transactions. Not supported.
+    #[serde(default)]
+    pub validation: bool,
+    /// Report ether transfers as logs. Not supported.
+    #[serde(default)]
+    pub trace_transfers: bool,
+    /// Return the full transactions of the simulated blocks. Not supported.
+    #[serde(default)]
+    pub return_full_transactions: bool,
+}
+
+/// A block of calls to simulate.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateBlock {
+    /// Header overrides.
+    pub block_overrides: Option<BlockOverrides>,
+    /// Account overrides applied before the calls are executed.
+    pub state_overrides: Option<BTreeMap<H160, AccountOverride>>,
+    /// Calls to execute.
+    #[serde(default)]
+    pub calls: Vec<CallRequest>,
+}
+
+/// Header overrides of a simulated block.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverrides {
+    /// Block number.
+    pub number: Option<U64>,
+    /// Block timestamp.
+    pub time: Option<U64>,
+    /// Block gas limit.
+    pub gas_limit: Option<U64>,
+    /// Block author.
+    pub fee_recipient: Option<H160>,
+    /// Base fee.
+    pub base_fee_per_gas: Option<U256>,
+    /// Block difficulty.
+    pub difficulty: Option<U256>,
+}
+
+/// Account override.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    /// Balance.
+    pub balance: Option<U256>,
+    /// Nonce.
+    pub nonce: Option<U64>,
+    /// Code.
+    pub code: Option<Bytes>,
+    /// Replaces the whole storage.
+    pub state: Option<BTreeMap<H256, H256>>,
+    /// Patches single storage slots.
+    pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+/// Result of a simulated block.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedBlock {
+    /// Block number.
+    pub number: U256,
+    /// Block hash.
+    pub hash: H256,
+    /// Parent hash.
+    pub parent_hash: H256,
+    /// Block timestamp.
+    pub timestamp: U256,
+    /// Gas limit.
+    pub gas_limit: U256,
+    /// Gas used by all calls of the block.
+    pub gas_used: U256,
+    /// Block author.
+    pub miner: H160,
+    /// Base fee.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<U256>,
+    /// Results of the calls.
+    pub calls: Vec<SimulatedCall>,
+}
+
+/// Result of a simulated call.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedCall {
+    /// Returned data, or revert data if the call was reverted.
+    pub return_data: Bytes,
+    /// Logs emitted by the call.
+    pub logs: Vec<Log>,
+    /// Gas used by the call.
+    pub gas_used: U256,
+    /// 1 on success, 0 on failure.
+    pub status: U64,
+    /// Error, if the call failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<SimulatedCallError>,
+}
+
+/// Error of a failed simulated call.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SimulatedCallError {
+    /// Error code.
+    pub code: i64,
+    /// Error message.
+    pub message: String,
+}
+
+impl Into<simulation::BlockOverrides> for BlockOverrides {
+    fn into(self) -> simulation::BlockOverrides {
+        simulation::BlockOverrides {
+            number: self.number.map(|n| n.as_u64()),
+            timestamp: self.time.map(|t| t.as_u64()),
+            gas_limit: self.gas_limit.map(|g| g.as_u64().into()),
+            author: self.fee_recipient,
+            base_fee: self.base_fee_per_gas,
+            difficulty: self.difficulty,
+        }
+    }
+}
+
+impl Into<simulation::AccountOverride> for AccountOverride {
+    fn into(self) -> simulation::AccountOverride {
+        simulation::AccountOverride {
+            balance: self.balance,
+            nonce: self.nonce.map(|n| n.as_u64().into()),
+            code: self.code.map(Into::into),
+            storage: self.state.map(|s| s.into_iter().collect()),
+            storage_diff: self.state_diff.map(|s| s.into_iter().collect()),
+        }
+    }
+}
+
+impl From<Executed> for SimulatedCall {
+    fn from(executed: Executed) -> Self {
+        let error = executed.exception.as_ref().map(|e| match e {
+            VMError::Reverted => SimulatedCallError {
+                code: REVERTED_CALL_ERROR,
+                message: "execution reverted".into(),
+            },
+            e => SimulatedCallError {
+                code: FAILED_CALL_ERROR,
+                message: format!("{}", e),
+            },
+        });
+
+        SimulatedCall {
+            return_data: executed.output.into(),
+            logs: executed.logs.into_iter().map(Into::into).collect(),
+            gas_used: executed.gas_used,
+            status: U64::from(if error.is_some() { 0u64 } else { 1u64 }),
+            error,
+        }
+    }
+}
+
+impl From<EthSimulatedBlock> for SimulatedBlock {
+    fn from(block: EthSimulatedBlock) -> Self {
+        let header = block.header;
+        let hash = header.hash();
+        let calls: Vec<SimulatedCall> = block.executed.into_iter().map(Into::into).collect();
+
+        SimulatedBlock {
+            number: header.number().into(),
+            hash,
+            parent_hash: *header.parent_hash(),
+            timestamp: header.timestamp().into(),
+            gas_limit: *header.gas_limit(),
+            gas_used: *header.gas_used(),
+            miner: *header.author(),
+            base_fee_per_gas: header.base_fee(),
+            calls,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_types::{H160, U256};
+    use serde_json;
+    use std::str::FromStr;
+
+    #[test]
+    fn simulate_payload_deserialization() {
+        let s = r#"{
+            "blockStateCalls": [{
+                "blockOverrides": {"number": "0x10", "time": "0x20"},
+                "stateOverrides": {
+                    "0x0000000000000000000000000000000000000001": {
+                        "balance": "0x64",
+                        "stateDiff": {
+                            "0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+                        }
+                    }
+                },
+                "calls": [{"from": "0x0000000000000000000000000000000000000001", "to": "0x0000000000000000000000000000000000000002"}]
+            }, {
+                "calls": []
+            }],
+            "validation": false
+        }"#;
+        let payload: SimulatePayload = serde_json::from_str(s).unwrap();
+
+        assert_eq!(payload.block_state_calls.len(), 2);
+        assert!(!payload.validation);
+        let block = &payload.block_state_calls[0];
+        let overrides = block.block_overrides.as_ref().unwrap();
+        assert_eq!(overrides.number, Some(0x10.into()));
+        assert_eq!(overrides.time, Some(0x20.into()));
+        let account = &block.state_overrides.as_ref().unwrap()[&H160::from_low_u64_be(1)];
+        assert_eq!(account.balance, Some(U256::from(100)));
+        assert_eq!(account.state_diff.as_ref().unwrap().len(), 1);
+        assert_eq!(block.calls.len(), 1);
+        assert_eq!(
+            block.calls[0].to,
+            Some(H160::from_str("0000000000000000000000000000000000000002").unwrap())
+        );
+    }
+
+    #[test]
+    fn reverted_call_serialization() {
+        let executed = Executed {
+            exception: Some(VMError::Reverted),
+            gas: 100_000.into(),
+            gas_used: 21_500.into(),
+            refunded: 0.into(),
+            cumulative_gas_used: 21_500.into(),
+            logs: vec![],
+            contracts_created: vec![],
+            output: vec![0x01, 0x02],
+            trace: vec![],
+            vm_trace: None,
+            state_diff: None,
+        };
+
+        let serialized = serde_json::to_string(&SimulatedCall::from(executed)).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"returnData":"0x0102","logs":[],"gasUsed":"0x53fc","status":"0x0","error":{"code":3,"message":"execution reverted"}}"#
+        );
+    }
+
+    #[test]
+    fn simulate_payload_rejects_unknown_fields() {
+        let s = r#"{"blockStateCalls": [], "traceTransfer": true}"#;
+        assert!(serde_json::from_str::<SimulatePayload>(s).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction lifecycle events.
+
+use ethereum_types::H256;
+use miner::pool;
+
+/// Kind of a transaction lifecycle event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionEventKind {
+    /// Transaction has been added to the queue.
+    Added,
+    /// Transaction has been promoted to the pending set.
+    Pending,
+    /// Transaction has been replaced by another one with the same sender and nonce.
+    Replaced,
+    /// Transaction has been dropped from the queue.
+    Dropped,
+    /// Transaction has been rejected on import.
+    Rejected,
+    /// Transaction has been marked as invalid.
+    Invalid,
+    /// Transaction has been canceled.
+    Canceled,
+    /// Transaction has been culled from the queue as stale.
+    Culled,
+    /// Transaction has been included in a block.
+    Mined,
+    /// Sender of the transaction has been penalized.
+    Penalized,
+}
+
+/// Transaction lifecycle event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionEvent {
+    /// Transaction hash.
+    pub hash: H256,
+    /// What happened to the transaction.
+    pub event: TransactionEventKind,
+    /// Hash of the transaction that replaced or pushed out this one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub by: Option<H256>,
+    /// Reason of the rejection or of dropping the transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl From<pool::TransactionEvent> for TransactionEvent {
+    fn from(event: pool::TransactionEvent) -> Self {
+        use self::TransactionEventKind::*;
+
+        let (hash, event, by, reason) = match event {
+            pool::TransactionEvent::Added(hash) => (hash, Added, None, None),
+            pool::TransactionEvent::Pending(hash) => (hash, Pending, None, None),
+            pool::TransactionEvent::Replaced { hash, by } => (hash, Replaced, Some(by), None),
+            pool::TransactionEvent::Dropped { hash, by, reason } => {
+                (hash, Dropped, by, Some(reason.as_str().into()))
+            }
+            pool::TransactionEvent::Rejected { hash, reason } => {
+                (hash, Rejected, None, Some(reason))
+            }
+            pool::TransactionEvent::Invalid(hash) => (hash, Invalid, None, None),
+            pool::TransactionEvent::Canceled(hash) => (hash, Canceled, None, None),
+            pool::TransactionEvent::Culled(hash) => (hash, Culled, None, None),
+            pool::TransactionEvent::Mined(hash) => (hash, Mined, None, None),
+            pool::TransactionEvent::Penalized(hash) => (hash, Penalized, None, None),
+        };
+
+        TransactionEvent {
+            hash,
+            event,
+            by,
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransactionEvent;
+    use ethereum_types::H256;
+    use miner::pool;
+    use serde_json;
+
+    #[test]
+    fn should_serialize_transaction_events() {
+        let replaced: TransactionEvent = pool::TransactionEvent::Replaced {
+            hash: H256::from_low_u64_be(1),
+            by: H256::from_low_u64_be(2),
+        }
+        .into();
+        let rejected: TransactionEvent = pool::TransactionEvent::Rejected {
+            hash: H256::from_low_u64_be(1),
+            reason: "Transaction fee is too low.".into(),
+        }
+        .into();
+        let mined: TransactionEvent =
+            pool::TransactionEvent::Mined(H256::from_low_u64_be(1)).into();
+        let dropped: TransactionEvent = pool::TransactionEvent::Dropped {
+            hash: H256::from_low_u64_be(1),
+            by: None,
+            reason: pool::DropReason::Expired,
+        }
+        .into();
+
+        assert_eq!(
+            serde_json::to_string(&replaced).unwrap(),
+            r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","event":"replaced","by":"0x0000000000000000000000000000000000000000000000000000000000000002"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&rejected).unwrap(),
+            r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","event":"rejected","reason":"Transaction fee is too low."}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&mined).unwrap(),
+            r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","event":"mined"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&dropped).unwrap(),
+            r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","event":"dropped","reason":"expired"}"#
+        );
+    }
+}
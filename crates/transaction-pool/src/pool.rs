@@ -407,6 +407,20 @@ where
         }
     }
 
+    /// Evicts single transaction from the pool.
+    /// The listener will get a `dropped` notification without a replacement.
+    ///
+    /// Should be used to enforce limits that the pool is not aware of.
+    pub fn evict(&mut self, hash: &T::Hash) -> Option<Arc<T>> {
+        if let Some(tx) = self.finalize_remove(hash) {
+            self.remove_from_set(tx.sender(), |set, scoring| set.remove(&tx, scoring));
+            self.listener.dropped(&tx, None);
+            Some(tx)
+        } else {
+            None
+        }
+    }
+
     /// Removes all stalled transactions from given sender.
     fn remove_stalled<R: Ready<T>>(&mut self, sender: &T::Sender, ready: &mut R) -> usize {
         let removed_from_set = self.remove_from_set(sender, |transactions, scoring| {
@@ -451,6 +465,18 @@ where
         self.by_hash.get(hash).map(|t| t.transaction.clone())
     }
 
+    /// Returns all transactions from given sender ordered by their score.
+    pub fn transactions_from_sender(&self, sender: &T::Sender) -> Vec<Arc<T>> {
+        self.transactions
+            .get(sender)
+            .map(|txs| {
+                txs.iter_transactions()
+                    .map(|tx| tx.transaction.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Returns worst transaction in the queue (if any).
     pub fn worst_transaction(&self) -> Option<Arc<T>> {
         self.worst_transactions
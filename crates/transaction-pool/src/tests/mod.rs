@@ -523,6 +523,32 @@ fn should_remove_transaction() {
     assert_eq!(pending.next(), None);
 }
 
+#[test]
+fn should_evict_transaction() {
+    // given
+    let b = TransactionBuilder::default();
+    let mut txq = TestPool::default();
+
+    let tx1 = import(&mut txq, b.tx().nonce(0).new()).unwrap();
+    let tx2 = import(&mut txq, b.tx().nonce(1).new()).unwrap();
+    let tx3 = import(&mut txq, b.tx().nonce(2).new()).unwrap();
+    assert_eq!(
+        txq.transactions_from_sender(tx1.sender()),
+        vec![tx1.clone(), tx2, tx3.clone()]
+    );
+
+    // when
+    assert!(txq.evict(&tx3.hash()).is_some());
+    assert!(txq.evict(&tx3.hash()).is_none());
+
+    // then
+    assert_eq!(txq.light_status().transaction_count, 2);
+    assert_eq!(txq.transactions_from_sender(tx1.sender()).len(), 2);
+    assert!(txq
+        .transactions_from_sender(&Address::from_low_u64_be(1))
+        .is_empty());
+}
+
 #[test]
 fn should_cull_stalled_transactions() {
     // given
@@ -926,6 +952,24 @@ mod listener {
         assert_eq!(txq.light_status().transaction_count, 0);
     }
 
+    #[test]
+    fn evict_transaction() {
+        let b = TransactionBuilder::default();
+        let listener = MyListener::default();
+        let results = listener.0.clone();
+        let mut txq = Pool::new(listener, DummyScoring::default(), Options::default());
+
+        // insert
+        let tx1 = import(&mut txq, b.tx().nonce(1).new()).unwrap();
+
+        // when
+        txq.evict(&tx1.hash());
+
+        // then
+        assert_eq!(*results.borrow(), &["added", "dropped"]);
+        assert_eq!(txq.light_status().transaction_count, 0);
+    }
+
     #[test]
     fn clear_queue() {
         let b = TransactionBuilder::default();
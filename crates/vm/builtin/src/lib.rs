@@ -38,6 +38,10 @@ use ethjson;
 use keccak_hash::keccak;
 use log::{trace, warn};
 use num::{BigUint, One, Zero};
+use p256::ecdsa::{
+    signature::hazmat::PrehashVerifier, Signature as P256Signature,
+    VerifyingKey as P256VerifyingKey,
+};
 use parity_bytes::BytesRef;
 use parity_crypto::{
     digest,
@@ -88,6 +92,7 @@ enum Pricing {
     Bls12ConstOperations(Bls12ConstOperations),
     Bls12MultiexpG1(Bls12MultiexpPricerG1),
     Bls12MultiexpG2(Bls12MultiexpPricerG2),
+    P256Verify(P256VerifyPricer),
 }
 
 impl Pricer for Pricing {
@@ -103,6 +108,7 @@ impl Pricer for Pricing {
             Pricing::Bls12ConstOperations(inner) => inner.cost(input),
             Pricing::Bls12MultiexpG1(inner) => inner.cost(input),
             Pricing::Bls12MultiexpG2(inner) => inner.cost(input),
+            Pricing::P256Verify(inner) => inner.cost(input),
         }
     }
 }
@@ -323,6 +329,19 @@ pub struct Bls12ConstOperations {
     pub price: u64,
 }
 
+/// Pricing for secp256r1 signature verification (EIP-7212)
+#[derive(Debug, Copy, Clone)]
+pub struct P256VerifyPricer {
+    /// Fixed price.
+    pub price: u64,
+}
+
+impl Pricer for P256VerifyPricer {
+    fn cost(&self, _input: &[u8]) -> U256 {
+        self.price.into()
+    }
+}
+
 /// Discount table for multiexponentiation (Pippenger's Algorithm)
 /// Later on is normalized using the divisor
 pub const BLS12_MULTIEXP_DISCOUNTS_TABLE: [[u64; 2]; BLS12_MULTIEXP_PAIRS_FOR_MAX_DISCOUNT] = [
@@ -643,6 +662,11 @@ impl From<ethjson::spec::builtin::Pricing> for Pricing {
                     _marker: std::marker::PhantomData,
                 })
             }
+            ethjson::spec::builtin::Pricing::P256Verify(pricer) => {
+                Pricing::P256Verify(P256VerifyPricer {
+                    price: pricer.price,
+                })
+            }
         }
     }
 }
@@ -685,6 +709,8 @@ enum EthereumBuiltin {
     Bls12MapFpToG1(Bls12MapFpToG1),
     /// bls12_381 fp2 to g2 mapping
     Bls12MapFp2ToG2(Bls12MapFp2ToG2),
+    /// secp256r1 signature verification (EIP-7212)
+    P256Verify(P256Verify),
 }
 
 impl FromStr for EthereumBuiltin {
@@ -710,6 +736,7 @@ impl FromStr for EthereumBuiltin {
             "bls12_381_pairing" => Ok(EthereumBuiltin::Bls12Pairing(Bls12Pairing)),
             "bls12_381_fp_to_g1" => Ok(EthereumBuiltin::Bls12MapFpToG1(Bls12MapFpToG1)),
             "bls12_381_fp2_to_g2" => Ok(EthereumBuiltin::Bls12MapFp2ToG2(Bls12MapFp2ToG2)),
+            "p256_verify" => Ok(EthereumBuiltin::P256Verify(P256Verify)),
             _ => return Err(format!("invalid builtin name: {}", name)),
         }
     }
@@ -736,6 +763,7 @@ impl Implementation for EthereumBuiltin {
             EthereumBuiltin::Bls12Pairing(inner) => inner.execute(input, output),
             EthereumBuiltin::Bls12MapFpToG1(inner) => inner.execute(input, output),
             EthereumBuiltin::Bls12MapFp2ToG2(inner) => inner.execute(input, output),
+            EthereumBuiltin::P256Verify(inner) => inner.execute(input, output),
         }
     }
 }
@@ -803,6 +831,10 @@ pub struct Bls12MapFpToG1;
 /// The Bls12MapFp2ToG2 builtin.
 pub struct Bls12MapFp2ToG2;
 
+#[derive(Debug)]
+/// The secp256r1 signature verification builtin.
+pub struct P256Verify;
+
 impl Implementation for Identity {
     fn execute(&self, input: &[u8], output: &mut BytesRef) -> Result<(), &'static str> {
         output.write(0, input);
@@ -1200,6 +1232,42 @@ impl Implementation for Bls12MapFp2ToG2 {
     }
 }
 
+impl Implementation for P256Verify {
+    fn execute(&self, input: &[u8], output: &mut BytesRef) -> Result<(), &'static str> {
+        // Input is `hash || r || s || x || y`. Any malformed input or failed
+        // verification returns empty output instead of an error, the call
+        // still succeeds and consumes the fixed price.
+        if input.len() != 160 {
+            return Ok(());
+        }
+
+        let signature = match P256Signature::from_scalars(
+            p256::FieldBytes::clone_from_slice(&input[32..64]),
+            p256::FieldBytes::clone_from_slice(&input[64..96]),
+        ) {
+            Ok(signature) => signature,
+            Err(_) => return Ok(()),
+        };
+        let point = p256::EncodedPoint::from_affine_coordinates(
+            &p256::FieldBytes::clone_from_slice(&input[96..128]),
+            &p256::FieldBytes::clone_from_slice(&input[128..160]),
+            false,
+        );
+        let key = match P256VerifyingKey::from_encoded_point(&point) {
+            Ok(key) => key,
+            Err(_) => return Ok(()),
+        };
+
+        if key.verify_prehash(&input[0..32], &signature).is_ok() {
+            let mut result = [0u8; 32];
+            result[31] = 1;
+            output.write(0, &result);
+        }
+
+        Ok(())
+    }
+}
+
 fn read_fr(reader: &mut io::Chain<&[u8], io::Repeat>) -> Result<bn::Fr, &'static str> {
     let mut buf = [0u8; 32];
 
@@ -1371,7 +1439,7 @@ mod tests {
     use super::{
         modexp as me, BTreeMap, Bls12ConstOperations, Bls12PairingPrice, Bls12PairingPricer,
         Builtin, EthereumBuiltin, FromStr, Implementation, Linear, Modexp2565Pricer, ModexpPricer,
-        Pricer, Pricing,
+        P256VerifyPricer, Pricer, Pricing,
     };
     use ethereum_types::U256;
     use ethjson::spec::builtin::{
@@ -2683,6 +2751,278 @@ mod tests {
         assert_eq!(&output[..], &expected[..]);
     }
 
+    #[test]
+    fn p256_verify() {
+        let f = Builtin {
+            pricer: btreemap![0 => Pricing::P256Verify(P256VerifyPricer{price: 3450})],
+            native: EthereumBuiltin::from_str("p256_verify").unwrap(),
+        };
+        assert_eq!(f.cost(&[0; 160], 0), U256::from(3450));
+
+        // NIST FIPS 186-4 P-256 SHA-256 vectors, `hash || r || s || x || y`.
+        let valid = [
+            hex!(
+                "
+				44acf6b7e36c1342c2c5897204fe09504e1e2efb1a900377dbc4e7a6a133ec56
+				f3ac8061b514795b8843e3d6629527ed2afd6b1f6a555a7acabb5e6f79c8c2ac
+				8bf77819ca05a6b2786c76262bf7371cef97b218e96f175a3ccdda2acc058903
+				1ccbe91c075fc7f4f033bfa248db8fccd3565de94bbfb12f3c59ff46c271bf83
+				ce4014c68811f9a21a1fdb2c0e6113e06db7ca93b7404e78dc7ccd5ca89a4ca9
+			"
+            ),
+            hex!(
+                "
+				9b2db89cb0e8fa3cc7608b4d6cc1dec0114e0b9ff4080bea12b134f489ab2bbc
+				976d3a4e9d23326dc0baa9fa560b7c4e53f42864f508483a6473b6a11079b2db
+				1b766e9ceb71ba6c01dcd46e0af462cd4cfa652ae5017d4555b8eeefe36e1932
+				e266ddfdc12668db30d4ca3e8f7749432c416044f2d2b8c10bf3d4012aeffa8a
+				bfa86404a2e9ffe67d47c587ef7a97a7f456b863b4d02cfc6928973ab5b1cb39
+			"
+            ),
+        ];
+        // order of the curve
+        let n = hex!("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551");
+
+        let mut valid = valid.iter().map(|input| input.to_vec()).collect::<Vec<_>>();
+        // high s (n - s) is accepted, signatures are not checked for malleability
+        let mut input = valid[0].clone();
+        input[64..96].copy_from_slice(&hex!(
+            "740887e535fa594e879389d9d408c8e2cd4f4894bda8872ab6ebf098305d9c4e"
+        ));
+        valid.push(input);
+
+        for input in valid.iter() {
+            let mut output = vec![];
+            f.execute(&input[..], &mut BytesRef::Flexible(&mut output))
+                .expect("Builtin should not fail");
+            assert_eq!(
+                &output[..],
+                &hex!("0000000000000000000000000000000000000000000000000000000000000001")[..]
+            );
+        }
+
+        let mut invalid = Vec::new();
+        // wrong message hash
+        let mut input = valid[0].to_vec();
+        input[0] ^= 1;
+        invalid.push(input);
+        // signature of another key
+        let mut input = valid[0].to_vec();
+        input[96..160].copy_from_slice(&valid[1][96..160]);
+        invalid.push(input);
+        // zero r
+        let mut input = valid[0].to_vec();
+        input[32..64].copy_from_slice(&[0u8; 32]);
+        invalid.push(input);
+        // zero s
+        let mut input = valid[0].to_vec();
+        input[64..96].copy_from_slice(&[0u8; 32]);
+        invalid.push(input);
+        // r equal to the curve order
+        let mut input = valid[0].to_vec();
+        input[32..64].copy_from_slice(&n);
+        invalid.push(input);
+        // s equal to the curve order
+        let mut input = valid[0].to_vec();
+        input[64..96].copy_from_slice(&n);
+        invalid.push(input);
+        // r above the curve order
+        let mut input = valid[0].to_vec();
+        input[32..64].copy_from_slice(&[0xffu8; 32]);
+        invalid.push(input);
+        // public key at infinity
+        let mut input = valid[0].to_vec();
+        input[96..160].copy_from_slice(&[0u8; 64]);
+        invalid.push(input);
+        // public key not on the curve
+        let mut input = valid[0].to_vec();
+        input[159] ^= 1;
+        invalid.push(input);
+        // wrong input length
+        invalid.push(valid[0][..159].to_vec());
+        invalid.push(valid[0][..128].to_vec());
+        invalid.push(valid[0][..32].to_vec());
+        let mut input = valid[0].to_vec();
+        input.push(0);
+        invalid.push(input);
+        invalid.push(vec![]);
+
+        for input in invalid.iter() {
+            let mut output = vec![];
+            f.execute(&input[..], &mut BytesRef::Flexible(&mut output))
+                .expect("Builtin should not fail");
+            assert!(output.is_empty());
+        }
+    }
+
+    #[test]
+    fn p256_verify_wycheproof() {
+        let f = Builtin {
+            pricer: btreemap![0 => Pricing::P256Verify(P256VerifyPricer{price: 3450})],
+            native: EthereumBuiltin::from_str("p256_verify").unwrap(),
+        };
+
+        // Valid vectors of Wycheproof `ecdsa_secp256r1_sha256_test`, the message
+        // hashed with SHA-256: edge case scalars, hashes and public key coordinates.
+        let valid = [
+            hex!(
+                "
+				bb5a52f42f9c9261ed4361f59422a1e30036e7c32b270c8807a419feca605023
+				2ba3a8be6b94d5ec80a6d9d1190a436effe50d85a1eee859b8cc6af9bd5c2e18
+				4cd60b855d442f5b3c7b11eb6c4e0ae7525fe710fab9aa7c77a67f79e6fadd76
+				2927b10512bae3eddcfe467828128bad2903269919f7086069c8c4df6c732838
+				c7787964eaac00e5921fb1498a60f4606766b3d9685001558d1a974e7341513e
+			"
+            ),
+            hex!(
+                "
+				00000000690ed426ccf17803ebe2bd0884bcd58a1bb5e7477ead3645f356e7a9
+				16aea964a2f6506d6f78c81c91fc7e8bded7d397738448de1e19a0ec580bf266
+				252cd762130c6667cfe8b7bc47d27d78391e8e80c578d1cd38c3ff033be928e9
+				2927b10512bae3eddcfe467828128bad2903269919f7086069c8c4df6c732838
+				c7787964eaac00e5921fb1498a60f4606766b3d9685001558d1a974e7341513e
+			"
+            ),
+            hex!(
+                "
+				ffffffff293886d3086fd567aafd598f0fe975f735887194a764a231e82d289a
+				a0c30e8026fdb2b4b4968a27d16a6d08f7098f1a98d21620d7454ba9790f1ba6
+				5e470453a8a399f15baf463f9deceb53acc5ca64459149688bd2760c65424339
+				2927b10512bae3eddcfe467828128bad2903269919f7086069c8c4df6c732838
+				c7787964eaac00e5921fb1498a60f4606766b3d9685001558d1a974e7341513e
+			"
+            ),
+            hex!(
+                "
+				bb5a52f42f9c9261ed4361f59422a1e30036e7c32b270c8807a419feca605023
+				000000000000000000000000000000004319055358e8617b0c46353d039cdaab
+				ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc63254e
+				0ad99500288d466940031d72a9f5445a4d43784640855bf0a69874d2de5fe103
+				c5011e6ef2c42dcd50d5d3d29f99ae6eba2c80c9244f4c5422f0979ff0c3ba5e
+			"
+            ),
+            hex!(
+                "
+				bb5a52f42f9c9261ed4361f59422a1e30036e7c32b270c8807a419feca605023
+				0000000000000000000000000000000000000000000000000000000000000005
+				0000000000000000000000000000000000000000000000000000000000000001
+				a71af64de5126a4a4e02b7922d66ce9415ce88a4c9d25514d91082c8725ac957
+				5d47723c8fbe580bb369fec9c2665d8e30a435b9932645482e7c9f11e872296b
+			"
+            ),
+            hex!(
+                "
+				bb5a52f42f9c9261ed4361f59422a1e30036e7c32b270c8807a419feca605023
+				7fffffff800000007fffffffffffffffde737d56d38bcf4279dce5617e3192a9
+				7fffffff800000007fffffffffffffffde737d56d38bcf4279dce5617e3192a8
+				f50d371b91bfb1d7d14e1323523bc3aa8cbf2c57f9e284de628c8b4536787b86
+				f94ad887ac94d527247cd2e7d0c8b1291c553c9730405380b14cbb209f5fa2dd
+			"
+            ),
+            hex!(
+                "
+				bb5a52f42f9c9261ed4361f59422a1e30036e7c32b270c8807a419feca605023
+				7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffd
+				bfffffff3ea3677e082b9310572620ae19933a9e65b285598711c77298815ad3
+				d0fb17ccd8fafe827e0c1afc5d8d80366e2b20e7f14a563a2ba50469d84375e8
+				68612569d39e2bb9f554355564646de99ac602cc6349cf8c1e236a7de7637d93
+			"
+            ),
+            hex!(
+                "
+				bb5a52f42f9c9261ed4361f59422a1e30036e7c32b270c8807a419feca605023
+				6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296
+				555555550000000055555555555555553ef7a8e48d07df81a693439654210c70
+				5e59f50708646be8a589355014308e60b668fb670196206c41e748e64e4dca21
+				5de37fee5c97bcaf7144d5b459982f52eeeafbdf03aacbafef38e213624a01de
+			"
+            ),
+            hex!(
+                "
+				e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855
+				b292a619339f6e567a305c951c0dcbcc42d16e47f219f9e98e76e09d8770b34a
+				0177e60492c5a8242f76f07bfe3661bde59ec2a17ce5bd2dab2abebdf89a62e2
+				04aaec73635726f213fb8a9e64da3b8632e41495a944d0045b522eba7240fad5
+				87d9315798aaa3a5ba01775787ced05eaaf7b4e09fc81d6d1aa546e8365d525d
+			"
+            ),
+            hex!(
+                "
+				2f77668a9dfbf8d5848b9eeb4a7145ca94c6ed9236e4a773f6dcafa5132b2f91
+				766456dce1857c906f9996af729339464d27e9d98edc2d0e3b760297067421f6
+				402385ecadae0d8081dccaf5d19037ec4e55376eced699e93646bfbbf19d0b41
+				fffffff948081e6a0458dd8f9e738f2665ff9059ad6aac0708318c4ca9a7a4f5
+				5a8abcba2dda8474311ee54149b973cae0c0fb89557ad0bf78e6529a1663bd73
+			"
+            ),
+            hex!(
+                "
+				2f77668a9dfbf8d5848b9eeb4a7145ca94c6ed9236e4a773f6dcafa5132b2f91
+				b7c81457d4aeb6aa65957098569f0479710ad7f6595d5874c35a93d12a5dd4c7
+				b7961a0b652878c2d568069a432ca18a1a9199f2ca574dad4b9e3a05c0a1cdb3
+				00000003fa15f963949d5f03a6f5c7f86f9e0015eeb23aebbff1173937ba748e
+				1099872070e8e87c555fa13659cca5d7fadcfcb0023ea889548ca48af2ba7e71
+			"
+            ),
+            hex!(
+                "
+				2f77668a9dfbf8d5848b9eeb4a7145ca94c6ed9236e4a773f6dcafa5132b2f91
+				31230428405560dcb88fb5a646836aea9b23a23dd973dcbe8014c87b8b20eb07
+				0f9344d6e812ce166646747694a41b0aaf97374e19f3c5fb8bd7ae3d9bd0beff
+				bcbb2914c79f045eaa6ecbbc612816b3be5d2d6796707d8125e9f851c18af015
+				000000001352bb4a0fa2ea4cceb9ab63dd684ade5a1127bcf300a698a7193bc2
+			"
+            ),
+            hex!(
+                "
+				2f77668a9dfbf8d5848b9eeb4a7145ca94c6ed9236e4a773f6dcafa5132b2f91
+				d7d70c581ae9e3f66dc6a480bf037ae23f8a1e4a2136fe4b03aa69f0ca25b356
+				89c460f8a5a5c2bbba962c8a3ee833a413e85658e62a59e2af41d9127cc47224
+				bcbb2914c79f045eaa6ecbbc612816b3be5d2d6796707d8125e9f851c18af015
+				fffffffeecad44b6f05d15b33146549c2297b522a5eed8430cff596758e6c43d
+			"
+            ),
+        ];
+
+        for input in valid.iter() {
+            let mut output = vec![];
+            f.execute(&input[..], &mut BytesRef::Flexible(&mut output))
+                .expect("Builtin should not fail");
+            assert_eq!(
+                &output[..],
+                &hex!("0000000000000000000000000000000000000000000000000000000000000001")[..]
+            );
+        }
+    }
+
+    #[test]
+    fn p256_verify_init_from_spec() {
+        use ethjson::spec::builtin::{P256Verify, Pricing};
+
+        let b = Builtin::try_from(JsonBuiltin {
+            name: "p256_verify".to_owned(),
+            pricing: btreemap![
+                10000000 => PricingAt {
+                    info: None,
+                    price: Pricing::P256Verify(P256Verify{
+                            price: 3450,
+                    }),
+                }
+            ],
+        })
+        .unwrap();
+
+        assert!(!b.is_active(9999999));
+        assert!(b.is_active(10000000));
+        assert_eq!(b.cost(&[0; 160], 10000000), U256::from(3450));
+        match b.native {
+            EthereumBuiltin::P256Verify(..) => {}
+            _ => {
+                panic!("invalid precompile type");
+            }
+        }
+    }
+
     #[test]
     fn bls12_381_g1_multiexp_init_from_spec() {
         use ethjson::spec::builtin::{Bls12G1Multiexp, Pricing};
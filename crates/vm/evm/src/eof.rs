@@ -0,0 +1,629 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EVM Object Format (EOF) v1 containers.
+//!
+//! Container layout (EIP-3540):
+//!
+//! ```text
+//! magic 0xef00, version 0x01,
+//! 0x01 types_size:u16, 0x02 num_code_sections:u16 code_size:u16+, 0x03 data_size:u16, 0x00,
+//! (inputs:u8 outputs:u8 max_stack_height:u16)+, code_section+, data_section
+//! ```
+//!
+//! Code sections are validated once, before the container is executed: all
+//! instructions must be defined (EIP-3670), relative jumps must target instructions of the
+//! same section (EIP-4200), `CALLF` must target an existing section (EIP-4750) and the
+//! stack height must be the same on all paths to an instruction (EIP-5450).
+//! Whether the instructions are activated depends on the block, so it is checked
+//! separately with `Container::check_schedule`.
+
+use instructions::{self, Instruction};
+use std::{fmt, ops::Range};
+use vm::Schedule;
+
+/// Prefix of EOF containers.
+pub const MAGIC: [u8; 2] = [0xef, 0x00];
+/// Supported container version.
+pub const VERSION: u8 = 0x01;
+/// Maximal depth of the `CALLF` return stack.
+pub const MAX_RETURN_STACK_DEPTH: usize = 1024;
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_DATA: u8 = 0x03;
+const TERMINATOR: u8 = 0x00;
+
+const MAX_CODE_SECTIONS: usize = 1024;
+const MAX_SECTION_IO: u8 = 0x7f;
+const MAX_STACK_HEIGHT: u16 = 1023;
+/// The designated invalid instruction, which is not part of the instruction set.
+const INVALID: u8 = 0xfe;
+
+/// Type of a code section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionType {
+    /// Number of stack items the section takes.
+    pub inputs: u8,
+    /// Number of stack items the section returns.
+    pub outputs: u8,
+    /// Maximal stack height reached by the section, including its inputs.
+    pub max_stack_height: u16,
+}
+
+/// Parsed and validated EOF container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Container {
+    /// Types of code sections.
+    pub types: Vec<SectionType>,
+    /// Positions of code sections in the container.
+    pub code: Vec<Range<usize>>,
+    /// Position of the data section in the container.
+    pub data: Range<usize>,
+    /// Distinct instructions of the code sections.
+    pub instructions: Vec<Instruction>,
+}
+
+/// Reason for an EOF container to be invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Code does not start with the EOF magic.
+    InvalidMagic,
+    /// Container version is not supported.
+    UnsupportedVersion,
+    /// A section header is missing or out of order.
+    MissingSection(u8),
+    /// Header ends before the terminator.
+    TruncatedHeader,
+    /// There are no or too many code sections.
+    InvalidCodeSectionCount,
+    /// A code section is empty.
+    EmptyCodeSection,
+    /// Size of the types section does not match the number of code sections.
+    InvalidTypesSize,
+    /// Container size does not match the sizes in the header.
+    InvalidContainerSize,
+    /// A section type is out of bounds, or the first section takes or returns items.
+    InvalidSectionType(usize),
+    /// An undefined instruction or one not allowed in EOF code.
+    UndefinedInstruction(usize),
+    /// An instruction not activated by the schedule.
+    InactiveInstruction(u8),
+    /// Immediate argument exceeds the code section.
+    TruncatedImmediate(usize),
+    /// Relative jump does not target an instruction of the same section.
+    InvalidJumpTarget(usize),
+    /// `CALLF` targets a missing section.
+    InvalidSectionIndex(usize),
+    /// Code section does not end with a terminating instruction.
+    MissingTerminatingInstruction(usize),
+    /// Instruction cannot be reached.
+    UnreachableCode(usize),
+    /// Instruction takes more items than are on the section's stack.
+    StackUnderflow(usize),
+    /// Stack height differs between paths to an instruction, or at `RETF`.
+    StackHeightMismatch(usize),
+    /// Declared maximal stack height does not match the code.
+    InvalidMaxStackHeight(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::Error::*;
+        match *self {
+            InvalidMagic => write!(f, "Invalid EOF magic"),
+            UnsupportedVersion => write!(f, "Unsupported EOF version"),
+            MissingSection(kind) => write!(f, "Missing section header of kind {}", kind),
+            TruncatedHeader => write!(f, "Truncated EOF header"),
+            InvalidCodeSectionCount => write!(f, "Invalid number of code sections"),
+            EmptyCodeSection => write!(f, "Empty code section"),
+            InvalidTypesSize => write!(f, "Types section size does not match code sections"),
+            InvalidContainerSize => write!(f, "Container size does not match header"),
+            InvalidSectionType(section) => write!(f, "Invalid type of section {}", section),
+            UndefinedInstruction(pos) => write!(f, "Undefined instruction at {}", pos),
+            InactiveInstruction(opcode) => write!(f, "Instruction {:#04x} is not active", opcode),
+            TruncatedImmediate(pos) => write!(f, "Truncated immediate at {}", pos),
+            InvalidJumpTarget(pos) => write!(f, "Invalid relative jump target at {}", pos),
+            InvalidSectionIndex(pos) => write!(f, "Invalid code section index at {}", pos),
+            MissingTerminatingInstruction(section) => {
+                write!(
+                    f,
+                    "Section {} does not end with a terminating instruction",
+                    section
+                )
+            }
+            UnreachableCode(pos) => write!(f, "Unreachable instruction at {}", pos),
+            StackUnderflow(pos) => write!(f, "Stack underflow at {}", pos),
+            StackHeightMismatch(pos) => write!(f, "Stack height mismatch at {}", pos),
+            InvalidMaxStackHeight(section) => {
+                write!(f, "Invalid maximal stack height of section {}", section)
+            }
+        }
+    }
+}
+
+/// Returns true if the code is meant to be an EOF container.
+pub fn is_eof(code: &[u8]) -> bool {
+    code.starts_with(&MAGIC)
+}
+
+fn read_u16(code: &[u8], pos: usize) -> Option<u16> {
+    match (code.get(pos), code.get(pos + 1)) {
+        (Some(&hi), Some(&lo)) => Some(u16::from(hi) << 8 | u16::from(lo)),
+        _ => None,
+    }
+}
+
+fn read_i16(code: &[u8], pos: usize) -> Option<isize> {
+    read_u16(code, pos).map(|v| v as i16 as isize)
+}
+
+impl Container {
+    /// Parse and validate an EOF container.
+    pub fn new(code: &[u8]) -> Result<Container, Error> {
+        let mut container = Self::parse(code)?;
+        let mut used = [false; 256];
+        for section in 0..container.code.len() {
+            container.validate_section(code, section, &mut used)?;
+        }
+        container.instructions = (0..used.len())
+            .filter(|opcode| used[*opcode])
+            .filter_map(|opcode| Instruction::from_u8(opcode as u8))
+            .collect();
+        Ok(container)
+    }
+
+    /// Checks that all instructions of the container are activated by the schedule.
+    pub fn check_schedule(&self, schedule: &Schedule) -> Result<(), Error> {
+        match self
+            .instructions
+            .iter()
+            .find(|instruction| !instruction.is_enabled(schedule))
+        {
+            Some(instruction) => Err(Error::InactiveInstruction(*instruction as u8)),
+            None => Ok(()),
+        }
+    }
+
+    fn parse(code: &[u8]) -> Result<Container, Error> {
+        if !is_eof(code) {
+            return Err(Error::InvalidMagic);
+        }
+        if code.get(2) != Some(&VERSION) {
+            return Err(Error::UnsupportedVersion);
+        }
+
+        let mut pos = 3;
+        let mut section_header = |kind: u8| -> Result<u16, Error> {
+            match code.get(pos) {
+                Some(k) if *k == kind => {}
+                Some(_) => return Err(Error::MissingSection(kind)),
+                None => return Err(Error::TruncatedHeader),
+            }
+            let size = read_u16(code, pos + 1).ok_or(Error::TruncatedHeader)?;
+            pos += 3;
+            Ok(size)
+        };
+
+        let types_size = section_header(KIND_TYPES)? as usize;
+        let num_code_sections = section_header(KIND_CODE)? as usize;
+        if num_code_sections == 0 || num_code_sections > MAX_CODE_SECTIONS {
+            return Err(Error::InvalidCodeSectionCount);
+        }
+        if types_size != num_code_sections * 4 {
+            return Err(Error::InvalidTypesSize);
+        }
+        let mut code_sizes = Vec::with_capacity(num_code_sections);
+        for _ in 0..num_code_sections {
+            let size = read_u16(code, pos).ok_or(Error::TruncatedHeader)? as usize;
+            if size == 0 {
+                return Err(Error::EmptyCodeSection);
+            }
+            code_sizes.push(size);
+            pos += 2;
+        }
+        let data_size = match code.get(pos) {
+            Some(&KIND_DATA) => read_u16(code, pos + 1).ok_or(Error::TruncatedHeader)? as usize,
+            Some(_) => return Err(Error::MissingSection(KIND_DATA)),
+            None => return Err(Error::TruncatedHeader),
+        };
+        pos += 3;
+        match code.get(pos) {
+            Some(&TERMINATOR) => pos += 1,
+            Some(_) => return Err(Error::MissingSection(TERMINATOR)),
+            None => return Err(Error::TruncatedHeader),
+        }
+
+        let body_size = types_size + code_sizes.iter().sum::<usize>() + data_size;
+        if code.len() != pos + body_size {
+            return Err(Error::InvalidContainerSize);
+        }
+
+        let types: Vec<_> = code[pos..pos + types_size]
+            .chunks(4)
+            .map(|t| SectionType {
+                inputs: t[0],
+                outputs: t[1],
+                max_stack_height: u16::from(t[2]) << 8 | u16::from(t[3]),
+            })
+            .collect();
+        for (section, t) in types.iter().enumerate() {
+            if t.inputs > MAX_SECTION_IO
+                || t.outputs > MAX_SECTION_IO
+                || t.max_stack_height > MAX_STACK_HEIGHT
+                || (section == 0 && (t.inputs != 0 || t.outputs != 0))
+            {
+                return Err(Error::InvalidSectionType(section));
+            }
+        }
+        pos += types_size;
+
+        let code_ranges = code_sizes
+            .into_iter()
+            .map(|size| {
+                pos += size;
+                pos - size..pos
+            })
+            .collect();
+
+        Ok(Container {
+            types,
+            code: code_ranges,
+            data: pos..pos + data_size,
+            instructions: Vec::new(),
+        })
+    }
+
+    /// Validates instructions and stack heights of a code section, marking its opcodes as `used`.
+    fn validate_section(
+        &self,
+        container: &[u8],
+        section: usize,
+        used: &mut [bool],
+    ) -> Result<(), Error> {
+        let offset = self.code[section].start;
+        let code = &container[self.code[section].clone()];
+
+        // instructions and their successors, positions relative to the section.
+        let mut is_instruction = vec![false; code.len()];
+        let mut successors = Vec::new();
+        let mut pos = 0;
+        let mut last = 0;
+        while pos < code.len() {
+            let opcode = code[pos];
+            is_instruction[pos] = true;
+            used[opcode as usize] = true;
+            last = opcode;
+            let (size, targets) = self.immediates(code, pos).map_err(|e| match e {
+                Error::TruncatedImmediate(p) => Error::TruncatedImmediate(offset + p),
+                Error::InvalidSectionIndex(p) => Error::InvalidSectionIndex(offset + p),
+                e => e,
+            })?;
+            let next = pos + 1 + size;
+            if opcode != INVALID {
+                match Instruction::from_u8(opcode) {
+                    Some(i) if !i.is_legacy_only() => {}
+                    _ => return Err(Error::UndefinedInstruction(offset + pos)),
+                }
+            }
+            for target in &targets {
+                if *target < 0 || *target as usize >= code.len() {
+                    return Err(Error::InvalidJumpTarget(offset + pos));
+                }
+            }
+            successors.push((pos, next, targets));
+            pos = next;
+        }
+
+        if !is_terminating(last) && last != instructions::RJUMP as u8 {
+            return Err(Error::MissingTerminatingInstruction(section));
+        }
+        for &(pos, _, ref targets) in &successors {
+            if targets.iter().any(|t| !is_instruction[*t as usize]) {
+                return Err(Error::InvalidJumpTarget(offset + pos));
+            }
+        }
+
+        self.validate_stack(code, offset, section, &successors)
+    }
+
+    /// Size and relative jump targets of immediate arguments of the instruction at `pos`.
+    fn immediates(&self, code: &[u8], pos: usize) -> Result<(usize, Vec<isize>), Error> {
+        let truncated = |size: usize| {
+            if pos + size >= code.len() {
+                Err(Error::TruncatedImmediate(pos))
+            } else {
+                Ok(())
+            }
+        };
+        let instruction = match Instruction::from_u8(code[pos]) {
+            Some(i) => i,
+            None => return Ok((0, vec![])),
+        };
+        match instruction {
+            instructions::RJUMP | instructions::RJUMPI => {
+                truncated(2)?;
+                let next = (pos + 3) as isize;
+                Ok((
+                    2,
+                    vec![next + read_i16(code, pos + 1).expect("checked above; qed")],
+                ))
+            }
+            instructions::RJUMPV => {
+                truncated(1)?;
+                let count = code[pos + 1] as usize + 1;
+                let size = 1 + count * 2;
+                truncated(size)?;
+                let next = (pos + 1 + size) as isize;
+                let targets = (0..count)
+                    .map(|i| next + read_i16(code, pos + 2 + i * 2).expect("checked above; qed"))
+                    .collect();
+                Ok((size, targets))
+            }
+            instructions::CALLF => {
+                truncated(2)?;
+                let target = read_u16(code, pos + 1).expect("checked above; qed") as usize;
+                if target >= self.types.len() {
+                    return Err(Error::InvalidSectionIndex(pos));
+                }
+                Ok((2, vec![]))
+            }
+            _ => match instruction.push_bytes() {
+                Some(size) => truncated(size).map(|_| (size, vec![])),
+                None => Ok((0, vec![])),
+            },
+        }
+    }
+
+    /// Follows all paths through the section, checking stack heights (EIP-5450).
+    fn validate_stack(
+        &self,
+        code: &[u8],
+        offset: usize,
+        section: usize,
+        successors: &[(usize, usize, Vec<isize>)],
+    ) -> Result<(), Error> {
+        let section_type = self.types[section];
+        let index: ::std::collections::HashMap<usize, usize> = successors
+            .iter()
+            .enumerate()
+            .map(|(i, &(pos, _, _))| (pos, i))
+            .collect();
+        let mut heights: Vec<Option<usize>> = vec![None; successors.len()];
+        let mut max_height = section_type.inputs as usize;
+        let mut queue = vec![0];
+        heights[0] = Some(section_type.inputs as usize);
+
+        while let Some(i) = queue.pop() {
+            let (pos, next, ref targets) = successors[i];
+            let height = heights[i].expect("only visited instructions are queued; qed");
+            let opcode = code[pos];
+            let (args, ret) = match Instruction::from_u8(opcode) {
+                Some(instructions::CALLF) => {
+                    let target = read_u16(code, pos + 1).expect("validated; qed") as usize;
+                    let t = self.types[target];
+                    (t.inputs as usize, t.outputs as usize)
+                }
+                Some(instructions::RETF) => {
+                    if height != section_type.outputs as usize {
+                        return Err(Error::StackHeightMismatch(offset + pos));
+                    }
+                    (height, 0)
+                }
+                Some(i) => (i.info().args, i.info().ret),
+                None => (0, 0),
+            };
+            if height < args {
+                return Err(Error::StackUnderflow(offset + pos));
+            }
+            let new_height = height - args + ret;
+            max_height = ::std::cmp::max(max_height, new_height);
+
+            let mut follow = targets.iter().map(|t| *t as usize).collect::<Vec<_>>();
+            if !is_terminating(opcode) && opcode != instructions::RJUMP as u8 {
+                follow.push(next);
+            }
+            for target in follow {
+                let j = index[&target];
+                match heights[j] {
+                    None => {
+                        heights[j] = Some(new_height);
+                        queue.push(j);
+                    }
+                    Some(h) if h != new_height => {
+                        return Err(Error::StackHeightMismatch(offset + target));
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        if let Some(i) = heights.iter().position(Option::is_none) {
+            return Err(Error::UnreachableCode(offset + successors[i].0));
+        }
+        if max_height != section_type.max_stack_height as usize {
+            return Err(Error::InvalidMaxStackHeight(section));
+        }
+        Ok(())
+    }
+}
+
+fn is_terminating(opcode: u8) -> bool {
+    opcode == INVALID
+        || matches!(
+            Instruction::from_u8(opcode),
+            Some(instructions::STOP)
+                | Some(instructions::RETURN)
+                | Some(instructions::REVERT)
+                | Some(instructions::RETF)
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::FromHex;
+
+    /// Container with the given sections of (inputs, outputs, max_stack_height, code).
+    fn container(sections: &[(u8, u8, u16, &str)], data: &str) -> Vec<u8> {
+        let codes: Vec<Vec<u8>> = sections.iter().map(|s| s.3.from_hex().unwrap()).collect();
+        let data: Vec<u8> = data.from_hex().unwrap();
+        let mut c = vec![
+            0xef,
+            0x00,
+            0x01,
+            0x01,
+            0x00,
+            (sections.len() * 4) as u8,
+            0x02,
+        ];
+        c.extend_from_slice(&[0x00, sections.len() as u8]);
+        for code in &codes {
+            c.extend_from_slice(&[(code.len() >> 8) as u8, code.len() as u8]);
+        }
+        c.extend_from_slice(&[0x03, (data.len() >> 8) as u8, data.len() as u8, 0x00]);
+        for s in sections {
+            c.extend_from_slice(&[s.0, s.1, (s.2 >> 8) as u8, s.2 as u8]);
+        }
+        for code in codes {
+            c.extend(code);
+        }
+        c.extend(data);
+        c
+    }
+
+    #[test]
+    fn should_parse_valid_container() {
+        // PUSH1 1 PUSH1 0 SSTORE STOP
+        let code = container(&[(0, 0, 2, "600160005500")], "aabb");
+        let c = Container::new(&code).unwrap();
+        assert_eq!(c.code, vec![19..25]);
+        assert_eq!(c.data, 25..27);
+    }
+
+    #[test]
+    fn should_reject_invalid_headers() {
+        let valid = container(&[(0, 0, 0, "00")], "");
+        assert_eq!(Container::new(&valid[..2]), Err(Error::UnsupportedVersion));
+        assert_eq!(Container::new(&valid[..8]), Err(Error::TruncatedHeader));
+        assert_eq!(Container::new(&[0xef, 0x01]), Err(Error::InvalidMagic));
+
+        let mut wrong_version = valid.clone();
+        wrong_version[2] = 0x02;
+        assert_eq!(
+            Container::new(&wrong_version),
+            Err(Error::UnsupportedVersion)
+        );
+
+        let mut trailing = valid.clone();
+        trailing.push(0x00);
+        assert_eq!(Container::new(&trailing), Err(Error::InvalidContainerSize));
+
+        let inputs = container(&[(1, 0, 1, "00")], "");
+        assert_eq!(Container::new(&inputs), Err(Error::InvalidSectionType(0)));
+    }
+
+    #[test]
+    fn should_validate_instructions() {
+        // JUMP is not allowed
+        let code = container(&[(0, 0, 1, "600056")], "");
+        assert_eq!(Container::new(&code), Err(Error::UndefinedInstruction(21)));
+        // undefined opcode
+        let code = container(&[(0, 0, 0, "0c00")], "");
+        assert_eq!(Container::new(&code), Err(Error::UndefinedInstruction(19)));
+        // truncated PUSH2
+        let code = container(&[(0, 0, 1, "6100")], "");
+        assert_eq!(Container::new(&code), Err(Error::TruncatedImmediate(19)));
+        // missing terminating instruction
+        let code = container(&[(0, 0, 1, "6000")], "");
+        assert_eq!(
+            Container::new(&code),
+            Err(Error::MissingTerminatingInstruction(0))
+        );
+        // INVALID terminates
+        let code = container(&[(0, 0, 0, "fe")], "");
+        assert!(Container::new(&code).is_ok());
+    }
+
+    #[test]
+    fn should_check_instructions_are_active() {
+        // PUSH0 TLOAD POP STOP
+        let code = container(&[(0, 0, 1, "5f5c5000")], "");
+        let c = Container::new(&code).unwrap();
+        assert_eq!(
+            c.instructions,
+            vec![
+                instructions::STOP,
+                instructions::POP,
+                instructions::TLOAD,
+                instructions::PUSH0
+            ]
+        );
+        assert_eq!(
+            c.check_schedule(&Schedule::new_london()),
+            Err(Error::InactiveInstruction(0x5c))
+        );
+        assert_eq!(
+            c.check_schedule(&Schedule::new_shanghai()),
+            Err(Error::InactiveInstruction(0x5c))
+        );
+        assert_eq!(c.check_schedule(&Schedule::new_cancun()), Ok(()));
+    }
+
+    #[test]
+    fn should_validate_relative_jumps() {
+        // PUSH1 1 RJUMPI +1 STOP STOP
+        let code = container(&[(0, 0, 1, "6001e100010000")], "");
+        assert!(Container::new(&code).is_ok());
+        // jump into PUSH1 immediate
+        let code = container(&[(0, 0, 1, "6001e1fffc00")], "");
+        assert_eq!(Container::new(&code), Err(Error::InvalidJumpTarget(21)));
+        // jump out of the section
+        let code = container(&[(0, 0, 0, "e0000100")], "");
+        assert_eq!(Container::new(&code), Err(Error::InvalidJumpTarget(19)));
+        // PUSH1 0 RJUMPV [+0, +1] STOP STOP
+        let code = container(&[(0, 0, 1, "6000e201000000010000")], "");
+        assert!(Container::new(&code).is_ok());
+        // backwards jump into a loop
+        let code = container(&[(0, 0, 0, "5be0fffc")], "");
+        assert!(Container::new(&code).is_ok());
+    }
+
+    #[test]
+    fn should_validate_functions_and_stack() {
+        // CALLF 1 STOP | PUSH1 1 ADD RETF
+        let code = container(&[(0, 0, 1, "6001e3000100"), (1, 1, 2, "600101e4")], "");
+        assert!(Container::new(&code).is_ok());
+        // section index out of range
+        let code = container(&[(0, 0, 0, "e3000100")], "");
+        assert_eq!(Container::new(&code), Err(Error::InvalidSectionIndex(19)));
+        // stack underflow
+        let code = container(&[(0, 0, 0, "0100")], "");
+        assert_eq!(Container::new(&code), Err(Error::StackUnderflow(19)));
+        // wrong declared max stack height
+        let code = container(&[(0, 0, 2, "600000")], "");
+        assert_eq!(Container::new(&code), Err(Error::InvalidMaxStackHeight(0)));
+        // unreachable code after STOP
+        let code = container(&[(0, 0, 0, "0000")], "");
+        assert_eq!(Container::new(&code), Err(Error::UnreachableCode(20)));
+        // stack height differs on the two paths: PUSH1 1 RJUMPI +2 PUSH1 1 STOP
+        let code = container(&[(0, 0, 1, "6001e10002600100")], "");
+        assert_eq!(Container::new(&code), Err(Error::StackHeightMismatch(26)));
+        // RETF with a wrong number of outputs
+        let code = container(&[(0, 0, 1, "e3000100"), (0, 1, 0, "e4")], "");
+        assert_eq!(Container::new(&code), Err(Error::StackHeightMismatch(29)));
+    }
+}
@@ -18,6 +18,8 @@
 
 pub use self::Instruction::*;
 
+use vm::Schedule;
+
 macro_rules! enum_with_from_u8 {
 	(
 		$( #[$enum_attr:meta] )*
@@ -180,6 +182,14 @@ enum_with_from_u8! {
         GAS = 0x5a,
         #[doc = "set a potential jump destination"]
         JUMPDEST = 0x5b,
+        #[doc = "load word from transient storage"]
+        TLOAD = 0x5c,
+        #[doc = "save word to transient storage"]
+        TSTORE = 0x5d,
+        #[doc = "copy memory areas"]
+        MCOPY = 0x5e,
+        #[doc = "place zero on stack"]
+        PUSH0 = 0x5f,
 
         #[doc = "place 1 byte item on stack"]
         PUSH1 = 0x60,
@@ -323,12 +333,16 @@ enum_with_from_u8! {
         #[doc = "Makes a log entry, 4 topics."]
         LOG4 = 0xa4,
 
-        #[doc = "Marks the entry point to a subroutine."]
-        BEGINSUB = 0x5c,
-        #[doc = "Returns from a subroutine."]
-        RETURNSUB = 0x5d,
-        #[doc = "Jumps to a defined BEGINSUB subroutine."]
-        JUMPSUB = 0x5e,
+        #[doc = "relative jump, EOF only"]
+        RJUMP = 0xe0,
+        #[doc = "conditional relative jump, EOF only"]
+        RJUMPI = 0xe1,
+        #[doc = "relative jump via jump table, EOF only"]
+        RJUMPV = 0xe2,
+        #[doc = "call a code section, EOF only"]
+        CALLF = 0xe3,
+        #[doc = "return from a code section, EOF only"]
+        RETF = 0xe4,
 
         #[doc = "create a new account with associated code"]
         CREATE = 0xf0,
@@ -397,6 +411,36 @@ impl Instruction {
         }
     }
 
+    /// Returns true if the instruction is only defined in EOF code.
+    pub fn is_eof_only(&self) -> bool {
+        *self >= RJUMP && *self <= RETF
+    }
+
+    /// Returns true if the instruction is not allowed in EOF code.
+    pub fn is_legacy_only(&self) -> bool {
+        matches!(*self, JUMP | JUMPI | PC | CALLCODE | SUICIDE)
+    }
+
+    /// Returns true if the instruction is activated by the schedule.
+    pub fn is_enabled(&self, schedule: &Schedule) -> bool {
+        match *self {
+            DELEGATECALL => schedule.have_delegate_call,
+            CREATE2 => schedule.have_create2,
+            STATICCALL => schedule.have_static_call,
+            RETURNDATACOPY | RETURNDATASIZE => schedule.have_return_data,
+            REVERT => schedule.have_revert,
+            SHL | SHR | SAR => schedule.have_bitwise_shifting,
+            EXTCODEHASH => schedule.have_extcodehash,
+            CHAINID => schedule.have_chain_id,
+            SELFBALANCE => schedule.have_selfbalance,
+            BASEFEE => schedule.eip3198,
+            PUSH0 => schedule.eip3855,
+            TLOAD | TSTORE => schedule.eip1153,
+            MCOPY => schedule.eip5656,
+            _ => true,
+        }
+    }
+
     /// Returns the instruction info.
     pub fn info(&self) -> &'static InstructionInfo {
         INSTRUCTIONS[*self as usize].as_ref().expect("A instruction is defined in Instruction enum, but it is not found in InstructionInfo struct; this indicates a logic failure in the code.")
@@ -532,6 +576,10 @@ lazy_static! {
         arr[MSIZE as usize] = Some(InstructionInfo::new("MSIZE", 0, 1, GasPriceTier::Base));
         arr[GAS as usize] = Some(InstructionInfo::new("GAS", 0, 1, GasPriceTier::Base));
         arr[JUMPDEST as usize] = Some(InstructionInfo::new("JUMPDEST", 0, 0, GasPriceTier::Special));
+        arr[TLOAD as usize] = Some(InstructionInfo::new("TLOAD", 1, 1, GasPriceTier::Special));
+        arr[TSTORE as usize] = Some(InstructionInfo::new("TSTORE", 2, 0, GasPriceTier::Special));
+        arr[MCOPY as usize] = Some(InstructionInfo::new("MCOPY", 3, 0, GasPriceTier::VeryLow));
+        arr[PUSH0 as usize] = Some(InstructionInfo::new("PUSH0", 0, 1, GasPriceTier::Base));
         arr[PUSH1 as usize] = Some(InstructionInfo::new("PUSH1", 0, 1, GasPriceTier::VeryLow));
         arr[PUSH2 as usize] = Some(InstructionInfo::new("PUSH2", 0, 1, GasPriceTier::VeryLow));
         arr[PUSH3 as usize] = Some(InstructionInfo::new("PUSH3", 0, 1, GasPriceTier::VeryLow));
@@ -601,9 +649,11 @@ lazy_static! {
         arr[LOG2 as usize] = Some(InstructionInfo::new("LOG2", 4, 0, GasPriceTier::Special));
         arr[LOG3 as usize] = Some(InstructionInfo::new("LOG3", 5, 0, GasPriceTier::Special));
         arr[LOG4 as usize] = Some(InstructionInfo::new("LOG4", 6, 0, GasPriceTier::Special));
-        arr[BEGINSUB as usize] = Some(InstructionInfo::new("BEGINSUB", 0, 0, GasPriceTier::Base));
-        arr[JUMPSUB as usize] = Some(InstructionInfo::new("JUMPSUB", 1, 0, GasPriceTier::High));
-        arr[RETURNSUB as usize] = Some(InstructionInfo::new("RETURNSUB", 0, 0, GasPriceTier::Low));
+        arr[RJUMP as usize] = Some(InstructionInfo::new("RJUMP", 0, 0, GasPriceTier::Base));
+        arr[RJUMPI as usize] = Some(InstructionInfo::new("RJUMPI", 1, 0, GasPriceTier::Special));
+        arr[RJUMPV as usize] = Some(InstructionInfo::new("RJUMPV", 1, 0, GasPriceTier::Special));
+        arr[CALLF as usize] = Some(InstructionInfo::new("CALLF", 0, 0, GasPriceTier::Low));
+        arr[RETF as usize] = Some(InstructionInfo::new("RETF", 0, 0, GasPriceTier::VeryLow));
         arr[CREATE as usize] = Some(InstructionInfo::new("CREATE", 3, 1, GasPriceTier::Special));
         arr[CALL as usize] = Some(InstructionInfo::new("CALL", 7, 1, GasPriceTier::Special));
         arr[CALLCODE as usize] = Some(InstructionInfo::new("CALLCODE", 7, 1, GasPriceTier::Special));
@@ -652,6 +702,15 @@ mod tests {
         assert_eq!(SWAP10.swap_position(), Some(10));
     }
 
+    #[test]
+    fn test_eof_instructions() {
+        assert!(RJUMP.is_eof_only());
+        assert!(RETF.is_eof_only());
+        assert!(!CREATE.is_eof_only());
+        assert!(JUMPI.is_legacy_only());
+        assert!(!JUMPDEST.is_legacy_only());
+    }
+
     #[test]
     fn test_get_log_topics() {
         assert_eq!(LOG0.log_topics(), Some(0));
@@ -135,6 +135,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 
         let cost = match instruction {
             instructions::JUMPDEST => Request::Gas(Gas::from(1)),
+            instructions::RJUMPI | instructions::RJUMPV => Request::Gas(Gas::from(4)),
             instructions::SSTORE => {
                 if schedule.eip1706 && self.current_gas <= Gas::from(schedule.call_stipend) {
                     return Err(vm::Error::OutOfGas);
@@ -173,6 +174,9 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 };
                 Request::Gas(gas.into())
             }
+            // EIP-1153 charges a flat fee, independent of the EIP-2929 access costs.
+            instructions::TLOAD => Request::Gas(Gas::from(schedule.tload_gas)),
+            instructions::TSTORE => Request::Gas(Gas::from(schedule.tstore_gas)),
             instructions::BALANCE => {
                 let address = u256_to_address(stack.peek(0));
                 Request::Gas(accessed_addresses_gas(&address, schedule.balance_gas))
@@ -232,6 +236,14 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                     Gas::from_u256(*stack.peek(2))?,
                 )
             }
+            instructions::MCOPY => Request::GasMemCopy(
+                default_gas,
+                cmp::max(
+                    mem_needed(stack.peek(0), stack.peek(2))?,
+                    mem_needed(stack.peek(1), stack.peek(2))?,
+                ),
+                Gas::from_u256(*stack.peek(2))?,
+            ),
             instructions::EXTCODECOPY => {
                 let address = u256_to_address(stack.peek(0));
                 let gas = accessed_addresses_gas(&address, schedule.extcodecopy_base_gas);
@@ -303,7 +315,8 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 let start = stack.peek(1);
                 let len = stack.peek(2);
 
-                let gas = Gas::from(schedule.create_gas);
+                let base = Gas::from(schedule.create_gas);
+                let gas = overflowing!(base.overflow_add(initcode_gas(schedule, len)?));
                 let mem = mem_needed(start, len)?;
 
                 Request::GasMemProvide(gas, mem, None)
@@ -316,6 +329,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
                 let word = overflowing!(to_word_size(Gas::from_u256(*len)?));
                 let word_gas = overflowing!(Gas::from(schedule.sha3_word_gas).overflow_mul(word));
                 let gas = overflowing!(base.overflow_add(word_gas));
+                let gas = overflowing!(gas.overflow_add(initcode_gas(schedule, len)?));
                 let mem = mem_needed(start, len)?;
 
                 Request::GasMemProvide(gas, mem, None)
@@ -425,6 +439,22 @@ fn mem_needed<Gas: evm::CostType>(offset: &U256, size: &U256) -> vm::Result<Gas>
     Gas::from_u256(overflowing!(offset.overflowing_add(*size)))
 }
 
+/// Initcode metering of `CREATE` and `CREATE2` as by EIP-3860.
+#[inline]
+fn initcode_gas<Gas: evm::CostType>(schedule: &Schedule, len: &U256) -> vm::Result<Gas> {
+    if !schedule.eip3860 {
+        return Ok(Gas::from(0));
+    }
+    if *len > U256::from(schedule.max_initcode_size()) {
+        return Err(vm::Error::OutOfGas);
+    }
+
+    let word = overflowing!(to_word_size(Gas::from_u256(*len)?));
+    Ok(overflowing!(
+        Gas::from(schedule.initcode_word_gas).overflow_mul(word)
+    ))
+}
+
 #[inline]
 fn add_gas_usize<Gas: evm::CostType>(value: Gas, num: usize) -> (Gas, bool) {
     value.overflow_add(Gas::from(num))
@@ -465,7 +495,7 @@ fn calculate_eip1283_eip2929_sstore_gas<Gas: evm::CostType>(
                 }
             } else {
                 // 2.2. If original value does not equal current value (this storage slot is dirty).
-                schedule.sload_gas
+                schedule.sstore_dirty_gas.unwrap_or(schedule.sload_gas)
             }
         } + if is_cold {
             // EIP2929 SSTORE changes section
@@ -483,6 +513,10 @@ pub fn handle_eip1283_sstore_clears_refund(
     new: &U256,
 ) {
     let sstore_clears_schedule = ext.schedule().sstore_refund_gas;
+    let sstore_dirty_gas = ext
+        .schedule()
+        .sstore_dirty_gas
+        .unwrap_or(ext.schedule().sload_gas);
 
     if current == new {
         // 1. If current value equals new value (this is a no-op), 200 gas is deducted.
@@ -517,11 +551,11 @@ pub fn handle_eip1283_sstore_clears_refund(
                 // 2.2.2. If original value equals new value (this storage slot is reset)
                 if original.is_zero() {
                     // 2.2.2.1. If original value is 0, add 19800 gas to refund counter.
-                    let refund = ext.schedule().sstore_set_gas - ext.schedule().sload_gas;
+                    let refund = ext.schedule().sstore_set_gas - sstore_dirty_gas;
                     ext.add_sstore_refund(refund);
                 } else {
                     // 2.2.2.2. Otherwise, add 4800 gas to refund counter.
-                    let refund = ext.schedule().sstore_reset_gas - ext.schedule().sload_gas;
+                    let refund = ext.schedule().sstore_reset_gas - sstore_dirty_gas;
                     ext.add_sstore_refund(refund);
                 }
             }
@@ -34,6 +34,7 @@ use vm::{
     GasLeft, MessageCallResult, ParamsType, ReturnData, Schedule, TrapError, TrapKind,
 };
 
+use eof;
 use evm::CostType;
 use instructions::{self, Instruction, InstructionInfo};
 
@@ -61,10 +62,6 @@ const TWO_POW_96: U256 = U256([0, 0x100000000, 0, 0]); //0x1 00000000 00000000 0
 const TWO_POW_224: U256 = U256([0, 0, 0, 0x100000000]); //0x1 00000000 00000000 00000000 00000000 00000000 00000000 00000000
 const TWO_POW_248: U256 = U256([0, 0, 0, 0x100000000000000]); //0x1 00000000 00000000 00000000 00000000 00000000 00000000 00000000 000000
 
-/// Maximum subroutine stack size as specified in
-/// https://eips.ethereum.org/EIPS/eip-2315.
-pub const MAX_SUB_STACK_SIZE: usize = 1023;
-
 fn to_biguint(x: U256) -> BigUint {
     let mut bytes = [0u8; 32];
     x.to_little_endian(&mut bytes);
@@ -96,6 +93,16 @@ impl CodeReader {
         U256::from(&self.code[pos..max])
     }
 
+    /// Get a two byte signed offset of a relative jump from code. Move PC
+    fn read_offset(&mut self) -> isize {
+        self.read(2).low_u64() as u16 as i16 as isize
+    }
+
+    /// Move PC by `offset` bytes.
+    fn jump_relative(&mut self, offset: isize) {
+        self.position = (self.position as isize + offset) as usize;
+    }
+
     fn len(&self) -> usize {
         self.code.len()
     }
@@ -105,8 +112,6 @@ enum InstructionResult<Gas> {
     Ok,
     UnusedGas(Gas),
     JumpToPosition(U256),
-    JumpToSubroutine(U256),
-    ReturnFromSubroutine(usize),
     StopExecutionNeedsReturn {
         /// Gas left.
         gas: Gas,
@@ -192,10 +197,14 @@ pub struct Interpreter<Cost: CostType> {
     do_trace: bool,
     done: bool,
     valid_jump_destinations: Option<Arc<BitSet>>,
-    valid_subroutine_destinations: Option<Arc<BitSet>>,
+    /// Container of EOF code, `None` for legacy code.
+    eof: Option<Arc<eof::Container>>,
+    /// Code claims to be an EOF container but is invalid.
+    invalid_eof: bool,
+    /// Positions to continue at after `RETF`.
+    return_stack: Vec<ProgramCounter>,
     gasometer: Option<Gasometer<Cost>>,
     stack: VecStack<U256>,
-    return_stack: Vec<usize>,
     resume_output_range: Option<(U256, U256)>,
     resume_result: Option<InstructionResult<Cost>>,
     last_stack_ret_len: usize,
@@ -297,16 +306,27 @@ impl<Cost: CostType> Interpreter<Cost> {
         schedule: &Schedule,
         depth: usize,
     ) -> Interpreter<Cost> {
-        let reader = CodeReader::new(params.code.take().expect("VM always called with code; qed"));
+        let mut reader =
+            CodeReader::new(params.code.take().expect("VM always called with code; qed"));
         let params = InterpreterParams::from(params);
         let informant = informant::EvmInformant::new(depth);
         let valid_jump_destinations = None;
-        let valid_subroutine_destinations = None;
+        let is_eof = schedule.eip3540 && eof::is_eof(&reader.code);
+        let container = if is_eof {
+            cache
+                .eof_container(&params.code_hash, &reader.code)
+                .filter(|container| container.check_schedule(schedule).is_ok())
+        } else {
+            None
+        };
+        let invalid_eof = is_eof && container.is_none();
+        if let Some(ref container) = container {
+            reader.position = container.code[0].start;
+        }
         let gasometer = Cost::from_u256(params.gas)
             .ok()
             .map(|gas| Gasometer::<Cost>::new(gas));
         let stack = VecStack::with_capacity(schedule.stack_limit, U256::zero());
-        let return_stack = Vec::with_capacity(MAX_SUB_STACK_SIZE);
 
         Interpreter {
             cache,
@@ -314,10 +334,11 @@ impl<Cost: CostType> Interpreter<Cost> {
             reader,
             informant,
             valid_jump_destinations,
-            valid_subroutine_destinations,
+            eof: container,
+            invalid_eof,
+            return_stack: Vec::new(),
             gasometer,
             stack,
-            return_stack,
             done: false,
             // Overridden in `step_inner` based on
             // the result of `ext.trace_next_instruction`.
@@ -340,6 +361,8 @@ impl<Cost: CostType> Interpreter<Cost> {
 
         let result = if self.gasometer.is_none() {
             InterpreterResult::Done(Err(vm::Error::OutOfGas))
+        } else if self.invalid_eof {
+            InterpreterResult::Done(Err(vm::Error::InvalidCode))
         } else if self.reader.len() == 0 {
             let current_gas = self
                 .gasometer
@@ -499,8 +522,7 @@ impl<Cost: CostType> Interpreter<Cost> {
                 if self.valid_jump_destinations.is_none() {
                     self.valid_jump_destinations = Some(
                         self.cache
-                            .jump_and_sub_destinations(&self.params.code_hash, &self.reader.code)
-                            .0,
+                            .jump_destinations(&self.params.code_hash, &self.reader.code),
                     );
                 }
                 let jump_destinations = self
@@ -513,28 +535,6 @@ impl<Cost: CostType> Interpreter<Cost> {
                 };
                 self.reader.position = pos;
             }
-            InstructionResult::JumpToSubroutine(position) => {
-                if self.valid_subroutine_destinations.is_none() {
-                    self.valid_subroutine_destinations = Some(
-                        self.cache
-                            .jump_and_sub_destinations(&self.params.code_hash, &self.reader.code)
-                            .1,
-                    );
-                }
-                let subroutine_destinations = self
-                    .valid_subroutine_destinations
-                    .as_ref()
-                    .expect("subroutine_destinations are initialized on first jump; qed");
-                let pos = match self.verify_jump(position, subroutine_destinations) {
-                    Ok(x) => x,
-                    Err(e) => return InterpreterResult::Done(Err(e)),
-                };
-                self.return_stack.push(self.reader.position);
-                self.reader.position = pos + 1;
-            }
-            InstructionResult::ReturnFromSubroutine(pos) => {
-                self.reader.position = pos;
-            }
             InstructionResult::StopExecutionNeedsReturn {
                 gas,
                 init_off,
@@ -581,22 +581,7 @@ impl<Cost: CostType> Interpreter<Cost> {
     ) -> vm::Result<()> {
         let schedule = ext.schedule();
 
-        use instructions::*;
-        if (instruction == DELEGATECALL && !schedule.have_delegate_call)
-            || (instruction == CREATE2 && !schedule.have_create2)
-            || (instruction == STATICCALL && !schedule.have_static_call)
-            || ((instruction == RETURNDATACOPY || instruction == RETURNDATASIZE)
-                && !schedule.have_return_data)
-            || (instruction == REVERT && !schedule.have_revert)
-            || ((instruction == SHL || instruction == SHR || instruction == SAR)
-                && !schedule.have_bitwise_shifting)
-            || (instruction == EXTCODEHASH && !schedule.have_extcodehash)
-            || (instruction == CHAINID && !schedule.have_chain_id)
-            || (instruction == SELFBALANCE && !schedule.have_selfbalance)
-            || (instruction == BASEFEE && !schedule.eip3198)
-            || ((instruction == BEGINSUB || instruction == JUMPSUB || instruction == RETURNSUB)
-                && !schedule.have_subs)
-        {
+        if !instruction.is_enabled(schedule) || (instruction.is_eof_only() && self.eof.is_none()) {
             return Err(vm::Error::BadInstruction {
                 instruction: instruction as u8,
             });
@@ -627,6 +612,7 @@ impl<Cost: CostType> Interpreter<Cost> {
             instructions::CALLDATACOPY | instructions::CODECOPY | instructions::RETURNDATACOPY => {
                 Some((read(0), read(2)))
             }
+            instructions::MCOPY => Some((read(0), read(2))),
             instructions::EXTCODECOPY => Some((read(1), read(3))),
             instructions::CALL | instructions::CALLCODE => Some((read(5), read(6))),
             instructions::DELEGATECALL | instructions::STATICCALL => Some((read(4), read(5))),
@@ -668,29 +654,61 @@ impl<Cost: CostType> Interpreter<Cost> {
             instructions::JUMPDEST => {
                 // ignore
             }
-            instructions::BEGINSUB => {
-                return Err(vm::Error::InvalidSubEntry);
+            instructions::RJUMP => {
+                let offset = self.reader.read_offset();
+                self.reader.jump_relative(offset);
             }
-            instructions::JUMPSUB => {
-                if self.return_stack.len() >= MAX_SUB_STACK_SIZE {
-                    return Err(vm::Error::OutOfSubStack {
-                        wanted: 1,
-                        limit: MAX_SUB_STACK_SIZE,
-                    });
+            instructions::RJUMPI => {
+                let offset = self.reader.read_offset();
+                let condition = self.stack.pop_back();
+                if !condition.is_zero() {
+                    self.reader.jump_relative(offset);
                 }
-                let sub_destination = self.stack.pop_back();
-                return Ok(InstructionResult::JumpToSubroutine(sub_destination));
             }
-            instructions::RETURNSUB => {
-                if let Some(pos) = self.return_stack.pop() {
-                    return Ok(InstructionResult::ReturnFromSubroutine(pos));
-                } else {
-                    return Err(vm::Error::SubStackUnderflow {
+            instructions::RJUMPV => {
+                let max_index = self.reader.read(1).low_u64() as usize;
+                let table = self.reader.position;
+                self.reader.position += (max_index + 1) * 2;
+                let index = self.stack.pop_back();
+                if index <= U256::from(max_index) {
+                    let entry = table + index.low_u64() as usize * 2;
+                    let offset = U256::from(&self.reader.code[entry..entry + 2]).low_u64() as u16
+                        as i16 as isize;
+                    self.reader.jump_relative(offset);
+                }
+            }
+            instructions::CALLF => {
+                let section = self.reader.read(2).low_u64() as usize;
+                let (start, section_type) = {
+                    let container = self
+                        .eof
+                        .as_ref()
+                        .expect("CALLF is only allowed in EOF code; qed");
+                    (container.code[section].start, container.types[section])
+                };
+                let limit = ext.schedule().stack_limit;
+                let wanted = section_type.max_stack_height as usize - section_type.inputs as usize;
+                if self.stack.size() + wanted > limit {
+                    return Err(vm::Error::OutOfStack {
+                        instruction: "CALLF",
+                        wanted,
+                        limit,
+                    });
+                }
+                if self.return_stack.len() >= eof::MAX_RETURN_STACK_DEPTH {
+                    return Err(vm::Error::OutOfStack {
+                        instruction: "CALLF",
                         wanted: 1,
-                        on_stack: 0,
+                        limit: eof::MAX_RETURN_STACK_DEPTH,
                     });
                 }
+                self.return_stack.push(self.reader.position);
+                self.reader.position = start;
             }
+            instructions::RETF => match self.return_stack.pop() {
+                Some(position) => self.reader.position = position,
+                None => return Ok(InstructionResult::StopExecution),
+            },
             instructions::CREATE | instructions::CREATE2 => {
                 let endowment = self.stack.pop_back();
                 let init_off = self.stack.pop_back();
@@ -1036,9 +1054,34 @@ impl<Cost: CostType> Interpreter<Cost> {
                 ext.set_storage(key, BigEndianHash::from_uint(&val))?;
                 ext.al_insert_storage_key(self.params.address, key);
             }
+            instructions::TLOAD => {
+                let key = BigEndianHash::from_uint(&self.stack.pop_back());
+                let word = ext.transient_storage_at(&key)?.into_uint();
+                self.stack.push(word);
+            }
+            instructions::TSTORE => {
+                let key = BigEndianHash::from_uint(&self.stack.pop_back());
+                let val = self.stack.pop_back();
+                ext.set_transient_storage(key, BigEndianHash::from_uint(&val))?;
+            }
+            instructions::MCOPY => {
+                let dest = self.stack.pop_back();
+                let source = self.stack.pop_back();
+                let size = self.stack.pop_back();
+
+                // Memory is already expanded to cover both ranges when size is not zero.
+                if !size.is_zero() {
+                    let dest = dest.as_usize();
+                    let source = source.as_usize();
+                    self.mem.copy_within(source..source + size.as_usize(), dest);
+                }
+            }
             instructions::PC => {
                 self.stack.push(U256::from(self.reader.position - 1));
             }
+            instructions::PUSH0 => {
+                self.stack.push(U256::zero());
+            }
             instructions::GAS => {
                 self.stack.push(gas.as_u256());
             }
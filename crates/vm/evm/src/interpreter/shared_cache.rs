@@ -14,14 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::super::instructions::{self, Instruction};
+use super::super::{
+    eof,
+    instructions::{self, Instruction},
+};
 use bit_set::BitSet;
 use ethereum_types::H256;
 use hash::KECCAK_EMPTY;
 use memory_cache::MemoryLruCache;
 use parity_util_mem::{MallocSizeOf, MallocSizeOfOps};
 use parking_lot::Mutex;
-use std::sync::Arc;
+use std::{mem, ops::Range, sync::Arc};
 
 const DEFAULT_CACHE_SIZE: usize = 4 * 1024 * 1024;
 
@@ -35,55 +38,81 @@ impl MallocSizeOf for Bits {
     }
 }
 
-#[derive(MallocSizeOf, Clone)]
-struct CacheItem {
-    jump_destination: Bits,
-    sub_entrypoint: Bits,
+/// Validated EOF container, `None` if the code is not a valid container.
+/// Whether its instructions are active is checked by the interpreter.
+#[derive(Clone)]
+struct EofContainer(Option<Arc<eof::Container>>);
+
+impl MallocSizeOf for EofContainer {
+    fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
+        self.0.as_ref().map_or(0, |c| {
+            c.types.capacity() * mem::size_of::<eof::SectionType>()
+                + c.code.capacity() * mem::size_of::<Range<usize>>()
+                + c.instructions.capacity() * mem::size_of::<Instruction>()
+        })
+    }
 }
 
 /// Global cache for EVM interpreter
 pub struct SharedCache {
-    jump_destinations: Mutex<MemoryLruCache<H256, CacheItem>>,
+    jump_destinations: Mutex<MemoryLruCache<H256, Bits>>,
+    eof_containers: Mutex<MemoryLruCache<H256, EofContainer>>,
 }
 
 impl SharedCache {
-    /// Create a jump destinations cache with a maximum size in bytes
-    /// to cache.
+    /// Create a jump destinations and EOF containers cache with a maximum size
+    /// in bytes, split evenly between the two.
     pub fn new(max_size: usize) -> Self {
         SharedCache {
-            jump_destinations: Mutex::new(MemoryLruCache::new(max_size)),
+            jump_destinations: Mutex::new(MemoryLruCache::new(max_size - max_size / 2)),
+            eof_containers: Mutex::new(MemoryLruCache::new(max_size / 2)),
         }
     }
 
-    /// Get jump destinations bitmap for a contract.
-    pub fn jump_and_sub_destinations(
+    /// Get the validated EOF container of a contract, `None` if the code is invalid.
+    pub fn eof_container(
         &self,
         code_hash: &Option<H256>,
         code: &[u8],
-    ) -> (Arc<BitSet>, Arc<BitSet>) {
+    ) -> Option<Arc<eof::Container>> {
+        if let Some(ref code_hash) = code_hash {
+            if let Some(c) = self.eof_containers.lock().get_mut(code_hash) {
+                return c.0.clone();
+            }
+        }
+
+        let c = EofContainer(eof::Container::new(code).ok().map(Arc::new));
+
+        if let Some(ref code_hash) = code_hash {
+            self.eof_containers.lock().insert(*code_hash, c.clone());
+        }
+
+        c.0
+    }
+
+    /// Get jump destinations bitmap for a contract.
+    pub fn jump_destinations(&self, code_hash: &Option<H256>, code: &[u8]) -> Arc<BitSet> {
         if let Some(ref code_hash) = code_hash {
             if code_hash == &KECCAK_EMPTY {
-                let cache_item = Self::find_jump_and_sub_destinations(code);
-                return (cache_item.jump_destination.0, cache_item.sub_entrypoint.0);
+                return Self::find_jump_destinations(code).0;
             }
 
             if let Some(d) = self.jump_destinations.lock().get_mut(code_hash) {
-                return (d.jump_destination.0.clone(), d.sub_entrypoint.0.clone());
+                return d.0.clone();
             }
         }
 
-        let d = Self::find_jump_and_sub_destinations(code);
+        let d = Self::find_jump_destinations(code);
 
         if let Some(ref code_hash) = code_hash {
             self.jump_destinations.lock().insert(*code_hash, d.clone());
         }
 
-        (d.jump_destination.0, d.sub_entrypoint.0)
+        d.0
     }
 
-    fn find_jump_and_sub_destinations(code: &[u8]) -> CacheItem {
+    fn find_jump_destinations(code: &[u8]) -> Bits {
         let mut jump_dests = BitSet::with_capacity(code.len());
-        let mut sub_entrypoints = BitSet::with_capacity(code.len());
         let mut position = 0;
 
         while position < code.len() {
@@ -94,9 +123,6 @@ impl SharedCache {
                     instructions::JUMPDEST => {
                         jump_dests.insert(position);
                     }
-                    instructions::BEGINSUB => {
-                        sub_entrypoints.insert(position);
-                    }
                     _ => {
                         if let Some(push_bytes) = instruction.push_bytes() {
                             position += push_bytes;
@@ -108,10 +134,7 @@ impl SharedCache {
         }
 
         jump_dests.shrink_to_fit();
-        CacheItem {
-            jump_destination: Bits(Arc::new(jump_dests)),
-            sub_entrypoint: Bits(Arc::new(sub_entrypoints)),
-        }
+        Bits(Arc::new(jump_dests))
     }
 }
 
@@ -139,15 +162,10 @@ mod test {
         let code = hex!("7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff5b01600055");
 
         // when
-        let cache_item = SharedCache::find_jump_and_sub_destinations(&code);
+        let cache_item = SharedCache::find_jump_destinations(&code);
 
         // then
-        assert!(cache_item
-            .jump_destination
-            .0
-            .iter()
-            .eq(vec![66].into_iter()));
-        assert!(cache_item.sub_entrypoint.0.is_empty());
+        assert!(cache_item.0.iter().eq(vec![66].into_iter()));
     }
 
     #[test]
@@ -164,34 +182,33 @@ mod test {
         let code = hex!("600656605B565B6004");
 
         // when
-        let cache_item = SharedCache::find_jump_and_sub_destinations(&code);
+        let cache_item = SharedCache::find_jump_destinations(&code);
 
         // then
-        assert!(cache_item.jump_destination.0.iter().eq(vec![6].into_iter()));
-        assert!(cache_item.sub_entrypoint.0.is_empty());
+        assert!(cache_item.0.iter().eq(vec![6].into_iter()));
     }
 
     #[test]
-    fn test_find_sub_entrypoints() {
+    fn test_eof_container_cached() {
         // given
+        let cache = SharedCache::default();
+        let code_hash = Some(H256::from_low_u64_be(1));
 
-        // see https://eips.ethereum.org/EIPS/eip-2315 for disassembly
-        let code = hex!("6800000000000000000c5e005c60115e5d5c5d");
+        // PUSH1 0 PUSH1 0 RETURN
+        let code = hex!("ef00010100040200010005030000000000000260006000f3");
 
         // when
-        let cache_item = SharedCache::find_jump_and_sub_destinations(&code);
+        let container = cache.eof_container(&code_hash, &code);
 
         // then
-        assert!(cache_item.jump_destination.0.is_empty());
-        assert!(cache_item
-            .sub_entrypoint
-            .0
-            .iter()
-            .eq(vec![12, 17].into_iter()));
+        assert_eq!(container.unwrap().code, vec![19..24]);
+        // validation result is looked up by code hash
+        assert!(cache.eof_container(&code_hash, &code[..20]).is_some());
+        assert!(cache.eof_container(&None, &code[..20]).is_none());
     }
 
     #[test]
-    fn test_find_jump_and_sub_allowing_unknown_opcodes() {
+    fn test_find_jump_destinations_allowing_unknown_opcodes() {
         // precondition
         assert!(Instruction::from_u8(0xcc) == None);
 
@@ -199,14 +216,13 @@ mod test {
 
         // 0000 5B   JUMPDEST
         // 0001 CC   ???
-        // 0002 5C   BEGINSUB
-        let code = hex!("5BCC5C");
+        // 0002 5B   JUMPDEST
+        let code = hex!("5BCC5B");
 
         // when
-        let cache_item = SharedCache::find_jump_and_sub_destinations(&code);
+        let cache_item = SharedCache::find_jump_destinations(&code);
 
         // then
-        assert!(cache_item.jump_destination.0.iter().eq(vec![0].into_iter()));
-        assert!(cache_item.sub_entrypoint.0.iter().eq(vec![2].into_iter()));
+        assert!(cache_item.0.iter().eq(vec![0, 2].into_iter()));
     }
 }
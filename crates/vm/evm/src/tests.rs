@@ -14,7 +14,6 @@
 // You should have received a copy of the GNU General Public License
 // along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::interpreter::MAX_SUB_STACK_SIZE;
 use ethereum_types::{Address, H256, U256};
 use factory::Factory;
 use hex_literal::hex;
@@ -490,6 +489,313 @@ fn test_base_fee(factory: super::Factory) {
     );
 }
 
+evm_test! {test_push0: test_push0_int}
+fn test_push0(factory: super::Factory) {
+    let code = "60075f55".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_shanghai(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+
+    let gas_left = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+    };
+
+    assert_eq!(gas_left, U256::from(77_895));
+    assert_store(
+        &ext,
+        0,
+        "0000000000000000000000000000000000000000000000000000000000000007",
+    );
+}
+
+#[test]
+fn test_push0_before_shanghai() {
+    let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+    let code = "5f".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_london(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+
+    let err = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap_err()
+    };
+
+    assert_eq!(err, vm::Error::BadInstruction { instruction: 0x5f });
+}
+
+evm_test! {test_create_initcode_too_big: test_create_initcode_too_big_int}
+fn test_create_initcode_too_big(factory: super::Factory) {
+    // CREATE with 49153 bytes of initcode, one over the limit.
+    let code = "61c00160006000f0".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(1_000_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_shanghai(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+
+    let err = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap_err()
+    };
+
+    assert_eq!(err, vm::Error::OutOfGas);
+    assert_eq!(ext.calls.len(), 0);
+}
+
+evm_test! {test_transient_storage: test_transient_storage_int}
+fn test_transient_storage(factory: super::Factory) {
+    // TSTORE(1, 7), SSTORE(0, TLOAD(1))
+    let code = "600760015d60015c600055".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_cancun(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+
+    let gas_left = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+    };
+
+    assert_eq!(gas_left, U256::from(77_688));
+    assert_eq!(
+        ext.transient_store.get(&H256::from_low_u64_be(1)).unwrap(),
+        &H256::from_low_u64_be(7)
+    );
+    assert_store(
+        &ext,
+        0,
+        "0000000000000000000000000000000000000000000000000000000000000007",
+    );
+}
+
+evm_test! {test_transient_storage_without_eip2929: test_transient_storage_without_eip2929_int}
+fn test_transient_storage_without_eip2929(factory: super::Factory) {
+    // TSTORE(1, 7)
+    let code = "600760015d".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new();
+    ext.schedule.eip1153 = true;
+
+    let gas_left = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+    };
+
+    assert_eq!(gas_left, U256::from(99_894));
+}
+
+evm_test! {test_transient_storage_in_staticcall: test_transient_storage_in_staticcall_int}
+fn test_transient_storage_in_staticcall(factory: super::Factory) {
+    let code = "600760015d".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_cancun(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+    ext.is_static = true;
+
+    let err = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap_err()
+    };
+
+    assert_eq!(err, vm::Error::MutableCallInStaticContext);
+    assert_eq!(ext.transient_store.len(), 0);
+}
+
+evm_test! {test_mcopy: test_mcopy_int}
+fn test_mcopy(factory: super::Factory) {
+    // MSTORE(0, 7), MCOPY(0x20, 0, 0x20), SSTORE(0, MLOAD(0x20))
+    let code = "60076000526020600060205e602051600055".from_hex().unwrap();
+
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_cancun(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+
+    let gas_left = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+    };
+
+    assert_eq!(gas_left, U256::from(77_861));
+    assert_store(
+        &ext,
+        0,
+        "0000000000000000000000000000000000000000000000000000000000000007",
+    );
+}
+
+#[test]
+fn test_cancun_opcodes_before_cancun() {
+    let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+
+    for opcode in &[0x5cu8, 0x5d, 0x5e] {
+        let mut params = ActionParams::default();
+        params.gas = U256::from(100_000);
+        params.code = Some(Arc::new(vec![*opcode]));
+        let mut ext = FakeExt::new_shanghai(
+            Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+            Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+            &[],
+        );
+
+        let err = {
+            let vm = factory.create(params, ext.schedule(), ext.depth());
+            test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap_err()
+        };
+
+        assert_eq!(
+            err,
+            vm::Error::BadInstruction {
+                instruction: *opcode
+            }
+        );
+    }
+}
+
+fn eof_test(factory: &super::Factory, code: Vec<u8>) -> (vm::Result<U256>, FakeExt) {
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_cancun(
+        Address::from_str("0000000000000000000000000000000000000000").unwrap(),
+        Address::from_str("000000000000000000000000636F6E7472616374").unwrap(),
+        &[],
+    );
+    ext.schedule.eip3540 = true;
+
+    let result = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap())
+    };
+    (result, ext)
+}
+
+evm_test! {test_eof_functions: test_eof_functions_int}
+fn test_eof_functions(factory: super::Factory) {
+    // section 0: PUSH1 3, loop: CALLF 1, DUP1, RJUMPI loop, STOP
+    // section 1: SSTORE(0, SLOAD(0) + 1), return input - 1
+    let code = hex!("ef0001010008020002000a000e0300000000000002010100036003e3000180e1fff90060005460010160005560019003e4").to_vec();
+
+    let (result, ext) = eof_test(&factory, code);
+
+    assert!(result.is_ok());
+    assert_store(
+        &ext,
+        0,
+        "0000000000000000000000000000000000000000000000000000000000000003",
+    );
+}
+
+evm_test! {test_eof_jump_table: test_eof_jump_table_int}
+fn test_eof_jump_table(factory: super::Factory) {
+    // PUSH1 1, RJUMPV [+0, +6], SSTORE(0, 1), STOP, SSTORE(0, 2), STOP
+    let code =
+        hex!("ef0001010004020001001403000000000000026001e20100000006600160005500600260005500")
+            .to_vec();
+
+    let (result, ext) = eof_test(&factory, code);
+
+    assert!(result.is_ok());
+    assert_store(
+        &ext,
+        0,
+        "0000000000000000000000000000000000000000000000000000000000000002",
+    );
+}
+
+evm_test! {test_eof_invalid_container: test_eof_invalid_container_int}
+fn test_eof_invalid_container(factory: super::Factory) {
+    // PUSH1 0, JUMP is not allowed in EOF code
+    let code = hex!("ef000101000402000100030300000000000001600056").to_vec();
+
+    let (result, _) = eof_test(&factory, code.clone());
+    assert_eq!(result.unwrap_err(), vm::Error::InvalidCode);
+
+    // executed as legacy code without EOF rules
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_cancun(Address::zero(), Address::zero(), &[]);
+    let err = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap_err()
+    };
+    assert_eq!(err, vm::Error::BadInstruction { instruction: 0xef });
+}
+
+evm_test! {test_eof_inactive_instruction: test_eof_inactive_instruction_int}
+fn test_eof_inactive_instruction(factory: super::Factory) {
+    // PUSH0, STOP
+    let code = hex!("ef0001010004020001000203000000000000015f00").to_vec();
+
+    let (result, _) = eof_test(&factory, code.clone());
+    assert!(result.is_ok());
+
+    // the container is invalid before PUSH0 is activated
+    let mut params = ActionParams::default();
+    params.gas = U256::from(100_000);
+    params.code = Some(Arc::new(code));
+    let mut ext = FakeExt::new_cancun(Address::zero(), Address::zero(), &[]);
+    ext.schedule.eip3540 = true;
+    ext.schedule.eip3855 = false;
+    let err = {
+        let vm = factory.create(params, ext.schedule(), ext.depth());
+        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap_err()
+    };
+    assert_eq!(err, vm::Error::InvalidCode);
+}
+
+#[test]
+fn test_eof_opcodes_in_legacy_code() {
+    let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+
+    for opcode in 0xe0u8..0xe5 {
+        let (result, _) = eof_test(&factory, vec![0x60, 0x00, opcode, 0x00, 0x00]);
+        assert_eq!(
+            result.unwrap_err(),
+            vm::Error::BadInstruction {
+                instruction: opcode
+            }
+        );
+    }
+}
+
 evm_test! {test_gas_limit: test_gas_limit_int}
 fn test_gas_limit(factory: super::Factory) {
     let gas_limit = U256::from(0x1234);
@@ -1067,171 +1373,6 @@ fn test_jumps(factory: super::Factory) {
     assert_eq!(gas_left, U256::from(54_117));
 }
 
-evm_test! {test_subs_simple: test_subs_simple_int}
-fn test_subs_simple(factory: super::Factory) {
-    // as defined in https://eips.ethereum.org/EIPS/eip-2315
-    let code = hex!("60045e005c5d").to_vec();
-
-    let mut params = ActionParams::default();
-    params.gas = U256::from(18);
-    params.code = Some(Arc::new(code));
-    let mut ext = FakeExt::new_berlin(Address::zero(), Address::zero(), &[]);
-
-    let gas_left = {
-        let vm = factory.create(params, ext.schedule(), ext.depth());
-        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
-    };
-
-    assert_eq!(gas_left, U256::from(0));
-}
-
-evm_test! {test_subs_two_levels: test_subs_two_levels_int}
-fn test_subs_two_levels(factory: super::Factory) {
-    // as defined in https://eips.ethereum.org/EIPS/eip-2315
-    let code = hex!("6800000000000000000c5e005c60115e5d5c5d").to_vec();
-
-    let mut params = ActionParams::default();
-    params.gas = U256::from(36);
-    params.code = Some(Arc::new(code));
-    let mut ext = FakeExt::new_berlin(Address::zero(), Address::zero(), &[]);
-
-    let gas_left = {
-        let vm = factory.create(params, ext.schedule(), ext.depth());
-        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
-    };
-
-    assert_eq!(gas_left, U256::from(0));
-}
-
-evm_test! {test_subs_invalid_jump: test_subs_invalid_jump_int}
-fn test_subs_invalid_jump(factory: super::Factory) {
-    // as defined in https://eips.ethereum.org/EIPS/eip-2315
-    let code = hex!("6801000000000000000c5e005c60115e5d5c5d").to_vec();
-
-    let mut params = ActionParams::default();
-    params.gas = U256::from(24);
-    params.code = Some(Arc::new(code));
-    let mut ext = FakeExt::new_berlin(Address::zero(), Address::zero(), &[]);
-
-    let current = {
-        let vm = factory.create(params, ext.schedule(), ext.depth());
-        test_finalize(vm.exec(&mut ext).ok().unwrap())
-    };
-
-    let expected = Result::Err(vm::Error::BadJumpDestination { destination: 0xc });
-    assert_eq!(current, expected);
-}
-
-evm_test! {test_subs_shallow_return_stack: test_subs_shallow_return_stack_int}
-fn test_subs_shallow_return_stack(factory: super::Factory) {
-    // as defined in https://eips.ethereum.org/EIPS/eip-2315
-    let code = hex!("5d5858").to_vec();
-
-    let mut params = ActionParams::default();
-    params.gas = U256::from(24);
-    params.code = Some(Arc::new(code));
-    let mut ext = FakeExt::new_berlin(Address::zero(), Address::zero(), &[]);
-
-    let current = {
-        let vm = factory.create(params, ext.schedule(), ext.depth());
-        test_finalize(vm.exec(&mut ext).ok().unwrap())
-    };
-
-    let expected = Result::Err(vm::Error::SubStackUnderflow {
-        wanted: 1,
-        on_stack: 0,
-    });
-    assert_eq!(current, expected);
-}
-
-evm_test! {test_subs_substack_limit: test_subs_substack_limit_int}
-fn test_subs_substack_limit(factory: super::Factory) {
-    //    PUSH <recursion_limit>
-    //    JUMP a
-    // s: BEGINSUB
-    // a: JUMPDEST
-    //    DUP1
-    //    JUMPI c
-    //    STOP
-    // c: JUMPDEST
-    //    PUSH1 1
-    //    SWAP
-    //    SUB
-    //    JUMPSUB s
-
-    let mut code = hex!("6104006007565c5b80600d57005b6001900360065e").to_vec();
-    code[1..3].copy_from_slice(&(MAX_SUB_STACK_SIZE as u16).to_be_bytes()[..]);
-
-    let mut params = ActionParams::default();
-    params.gas = U256::from(1_000_000);
-    params.code = Some(Arc::new(code));
-    let mut ext = FakeExt::new_berlin(Address::zero(), Address::zero(), &[]);
-
-    let gas_left = {
-        let vm = factory.create(params, ext.schedule(), ext.depth());
-        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
-    };
-
-    assert_eq!(gas_left, U256::from(959_049));
-}
-
-evm_test! {test_subs_substack_out: test_subs_substack_out_int}
-fn test_subs_substack_out(factory: super::Factory) {
-    let mut code = hex!("6104006007565c5b80600d57005b6001900360065e").to_vec();
-    code[1..3].copy_from_slice(&((MAX_SUB_STACK_SIZE + 1) as u16).to_be_bytes()[..]);
-
-    let mut params = ActionParams::default();
-    params.gas = U256::from(1_000_000);
-    params.code = Some(Arc::new(code));
-    let mut ext = FakeExt::new_berlin(Address::zero(), Address::zero(), &[]);
-
-    let current = {
-        let vm = factory.create(params, ext.schedule(), ext.depth());
-        test_finalize(vm.exec(&mut ext).ok().unwrap())
-    };
-
-    let expected = Result::Err(vm::Error::OutOfSubStack {
-        wanted: 1,
-        limit: MAX_SUB_STACK_SIZE,
-    });
-    assert_eq!(current, expected);
-}
-
-evm_test! {test_subs_sub_at_end: test_subs_sub_at_end_int}
-fn test_subs_sub_at_end(factory: super::Factory) {
-    let code = hex!("6005565c5d5b60035e").to_vec();
-
-    let mut params = ActionParams::default();
-    params.gas = U256::from(30);
-    params.code = Some(Arc::new(code));
-    let mut ext = FakeExt::new_berlin(Address::zero(), Address::zero(), &[]);
-
-    let gas_left = {
-        let vm = factory.create(params, ext.schedule(), ext.depth());
-        test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
-    };
-
-    assert_eq!(gas_left, U256::from(0));
-}
-
-evm_test! {test_subs_walk_into_subroutine: test_subs_walk_into_subroutine_int}
-fn test_subs_walk_into_subroutine(factory: super::Factory) {
-    let code = hex!("5c5d00").to_vec();
-
-    let mut params = ActionParams::default();
-    params.gas = U256::from(100);
-    params.code = Some(Arc::new(code));
-    let mut ext = FakeExt::new_berlin(Address::zero(), Address::zero(), &[]);
-
-    let current = {
-        let vm = factory.create(params, ext.schedule(), ext.depth());
-        test_finalize(vm.exec(&mut ext).ok().unwrap())
-    };
-
-    let expected = Result::Err(vm::Error::InvalidSubEntry);
-    assert_eq!(current, expected);
-}
-
 evm_test! {test_calls: test_calls_int}
 fn test_calls(factory: super::Factory) {
     let code = "600054602d57600160005560006000600060006050610998610100f160006000600060006050610998610100f25b".from_hex().unwrap();
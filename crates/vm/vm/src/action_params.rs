@@ -15,7 +15,7 @@
 // along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Evm input params.
-use super::access_list::AccessList;
+use super::{access_list::AccessList, transient_storage::TransientStorage};
 use bytes::Bytes;
 use call_type::CallType;
 use ethereum_types::{Address, H256, U256};
@@ -92,6 +92,8 @@ pub struct ActionParams {
     pub params_type: ParamsType,
     /// Current access list
     pub access_list: AccessList,
+    /// Transient storage of the transaction
+    pub transient_storage: TransientStorage,
 }
 
 impl Default for ActionParams {
@@ -111,6 +113,7 @@ impl Default for ActionParams {
             call_type: CallType::None,
             params_type: ParamsType::Separate,
             access_list: AccessList::default(),
+            transient_storage: TransientStorage::default(),
         }
     }
 }
@@ -135,6 +138,7 @@ impl From<ethjson::vm::Transaction> for ActionParams {
             }, // TODO @debris is this correct?
             params_type: ParamsType::Separate,
             access_list: AccessList::default(),
+            transient_storage: TransientStorage::default(),
         }
     }
 }
@@ -31,6 +31,7 @@ mod error;
 mod ext;
 mod return_data;
 pub mod schedule;
+pub mod transient_storage;
 
 pub mod tests;
 
@@ -41,7 +42,8 @@ pub use env_info::{EnvInfo, LastHashes};
 pub use error::{Error, ExecTrapError, ExecTrapResult, Result, TrapError, TrapKind, TrapResult};
 pub use ext::{ContractCreateResult, CreateContractAddress, Ext, MessageCallResult};
 pub use return_data::{GasLeft, ReturnData};
-pub use schedule::{CleanDustMode, Schedule, WasmCosts};
+pub use schedule::{CleanDustMode, Schedule, ScheduleOverrides, WasmCosts};
+pub use transient_storage::TransientStorage;
 
 /// Virtual Machine interface
 pub trait Exec {
@@ -34,6 +34,10 @@ pub const EIP3529_MAX_REFUND_QUOTIENT: usize = 5;
 /// Reduced SSTORE refund as by EIP-3529
 pub const EIP3529_SSTORE_CLEARS_SCHEDULE: usize =
     EIP2929_SSTORE_RESET_GAS + EIP2930_ACCESS_LIST_STORAGE_KEY_COST;
+/// Gas per 32 byte word of initcode as by EIP-3860
+pub const EIP3860_INITCODE_WORD_GAS: usize = 2;
+/// Maximum size of initcode as by EIP-3860, used if the code size is not limited
+pub const EIP3860_MAX_INITCODE_SIZE: usize = 49152;
 
 /// Definition of the cost schedule and other parameterisations for the EVM.
 #[derive(Debug)]
@@ -70,6 +74,8 @@ pub struct Schedule {
     pub sstore_reset_gas: usize,
     /// Gas refund for `SSTORE` clearing (when `storage!=0`, `new==0`)
     pub sstore_refund_gas: usize,
+    /// Gas price for altering a dirty storage slot (EIP-1283), `sload_gas` if not set
+    pub sstore_dirty_gas: Option<usize>,
     /// Gas price for `JUMPDEST` opcode
     pub jumpdest_gas: usize,
     /// Gas price for `LOG*`
@@ -88,6 +94,14 @@ pub struct Schedule {
     pub cold_account_access_cost: usize,
     /// EIP-2929 WARM_STORAGE_READ_COST
     pub warm_storage_read_cost: usize,
+    /// EIP-2930 ACCESS_LIST_ADDRESS_COST
+    pub access_list_address_cost: usize,
+    /// EIP-2930 ACCESS_LIST_STORAGE_KEY_COST
+    pub access_list_storage_key_cost: usize,
+    /// Gas price for `TLOAD` opcode (EIP-1153)
+    pub tload_gas: usize,
+    /// Gas price for `TSTORE` opcode (EIP-1153)
+    pub tstore_gas: usize,
     /// Stipend for transfer for `CALL|CALLCODE` opcode when `value>0`
     pub call_stipend: usize,
     /// Additional gas required for value transfer (`CALL|CALLCODE`)
@@ -104,6 +118,8 @@ pub struct Schedule {
     pub create_data_gas: usize,
     /// Maximum code size when creating a contract.
     pub create_data_limit: usize,
+    /// Additional gas for each word of initcode (EIP-3860)
+    pub initcode_word_gas: usize,
     /// Transaction cost
     pub tx_gas: usize,
     /// `CREATE` transaction cost
@@ -145,8 +161,6 @@ pub struct Schedule {
     pub have_chain_id: bool,
     /// SELFBALANCE opcode enabled.
     pub have_selfbalance: bool,
-    /// BEGINSUB, JUMPSUB and RETURNSUB opcodes enabled.
-    pub have_subs: bool,
     /// Kill basic accounts below this balance if touched.
     pub kill_dust: CleanDustMode,
     /// Enable EIP-1283 rules
@@ -173,6 +187,25 @@ pub struct Schedule {
     pub max_refund_quotient: usize,
     // Enable EIP-3541 rule
     pub eip3541: bool,
+    /// Enable PUSH0 opcode (EIP-3855)
+    pub eip3855: bool,
+    /// Block difficulty is the randomness of the beacon chain, read by PREVRANDAO (EIP-4399)
+    pub eip4399: bool,
+    /// Blocks carry beacon chain withdrawals (EIP-4895)
+    pub eip4895: bool,
+    /// Limit and meter initcode (EIP-3860)
+    pub eip3860: bool,
+    /// Warm COINBASE address at the start of a transaction (EIP-3651)
+    pub eip3651: bool,
+    /// Enable TLOAD and TSTORE opcodes (EIP-1153)
+    pub eip1153: bool,
+    /// Enable MCOPY opcode (EIP-5656)
+    pub eip5656: bool,
+    /// SELFDESTRUCT only deletes contracts created in the same transaction (EIP-6780)
+    pub eip6780: bool,
+    /// Execute EOF v1 containers (EIP-3540, EIP-3670, EIP-4200, EIP-4750, EIP-5450),
+    /// which are never deployed by legacy creates
+    pub eip3540: bool,
 }
 
 /// Wasm cost table
@@ -229,6 +262,111 @@ impl Default for WasmCosts {
     }
 }
 
+macro_rules! schedule_overrides {
+    ($($field:ident,)*) => {
+        /// Values overriding the ones of a `Schedule`. Each field which is set replaces the
+        /// `Schedule` field of the same name.
+        ///
+        /// Every gas cost and limit can be overridden except for `quad_coeff_div`, which is fixed
+        /// by the interpreter. Feature flags, the EIP-1559 values and the dust cleanup mode are
+        /// set by fork transitions and spec params, and wasm costs by the wasm activation.
+        #[derive(Debug, Default, Clone, PartialEq)]
+        pub struct ScheduleOverrides {
+            /// Gas prices for instructions in all tiers
+            pub tier_step_gas: Option<[usize; 8]>,
+            /// Divisor limiting the gas available to sub calls and creates
+            pub sub_gas_cap_divisor: Option<usize>,
+            /// Gas price for altering a dirty storage slot
+            pub sstore_dirty_gas: Option<usize>,
+            $(pub $field: Option<usize>,)*
+        }
+
+        impl From<ethjson::spec::GasSchedule> for ScheduleOverrides {
+            fn from(g: ethjson::spec::GasSchedule) -> Self {
+                ScheduleOverrides {
+                    tier_step_gas: g.tier_step_gas.map(|tiers| {
+                        let mut tier_step_gas = [0; 8];
+                        for (gas, tier) in tier_step_gas.iter_mut().zip(tiers.iter()) {
+                            *gas = (*tier).into();
+                        }
+                        tier_step_gas
+                    }),
+                    sub_gas_cap_divisor: g.sub_gas_cap_divisor.map(Into::into),
+                    sstore_dirty_gas: g.sstore_dirty_gas.map(Into::into),
+                    $($field: g.$field.map(Into::into),)*
+                }
+            }
+        }
+
+        impl Schedule {
+            /// Replaces the values of the schedule which are set in `overrides`.
+            pub fn apply_overrides(&mut self, overrides: &ScheduleOverrides) {
+                if let Some(tier_step_gas) = overrides.tier_step_gas {
+                    self.tier_step_gas = tier_step_gas;
+                }
+                if let Some(divisor) = overrides.sub_gas_cap_divisor {
+                    self.sub_gas_cap_divisor = Some(divisor);
+                }
+                if let Some(gas) = overrides.sstore_dirty_gas {
+                    self.sstore_dirty_gas = Some(gas);
+                }
+                $(
+                    if let Some(value) = overrides.$field {
+                        self.$field = value;
+                    }
+                )*
+            }
+        }
+    };
+}
+
+schedule_overrides! {
+    stack_limit,
+    max_depth,
+    exp_gas,
+    exp_byte_gas,
+    sha3_gas,
+    sha3_word_gas,
+    sload_gas,
+    sstore_set_gas,
+    sstore_reset_gas,
+    sstore_refund_gas,
+    jumpdest_gas,
+    log_gas,
+    log_data_gas,
+    log_topic_gas,
+    create_gas,
+    call_gas,
+    cold_sload_cost,
+    cold_account_access_cost,
+    warm_storage_read_cost,
+    access_list_address_cost,
+    access_list_storage_key_cost,
+    tload_gas,
+    tstore_gas,
+    call_stipend,
+    call_value_transfer_gas,
+    call_new_account_gas,
+    suicide_refund_gas,
+    memory_gas,
+    create_data_gas,
+    create_data_limit,
+    initcode_word_gas,
+    tx_gas,
+    tx_create_gas,
+    tx_data_zero_gas,
+    tx_data_non_zero_gas,
+    copy_gas,
+    extcodesize_gas,
+    extcodecopy_base_gas,
+    balance_gas,
+    extcodehash_gas,
+    suicide_gas,
+    suicide_to_new_account_cost,
+    blockhash_gas,
+    max_refund_quotient,
+}
+
 /// Dust accounts cleanup mode.
 #[derive(Debug, PartialEq, Eq)]
 pub enum CleanDustMode {
@@ -267,7 +405,6 @@ impl Schedule {
             have_bitwise_shifting: false,
             have_chain_id: false,
             have_selfbalance: false,
-            have_subs: false,
             have_extcodehash: false,
             stack_limit: 1024,
             max_depth: 1024,
@@ -280,6 +417,7 @@ impl Schedule {
             sstore_set_gas: 20000,
             sstore_reset_gas: 5000,
             sstore_refund_gas: 15000,
+            sstore_dirty_gas: None,
             jumpdest_gas: 1,
             log_gas: 375,
             log_data_gas: 8,
@@ -289,6 +427,10 @@ impl Schedule {
             cold_account_access_cost: 0,
             cold_sload_cost: 0,
             warm_storage_read_cost: 0,
+            access_list_address_cost: EIP2930_ACCESS_LIST_ADDRESS_COST,
+            access_list_storage_key_cost: EIP2930_ACCESS_LIST_STORAGE_KEY_COST,
+            tload_gas: EIP2929_WARM_STORAGE_READ_COST,
+            tstore_gas: EIP2929_WARM_STORAGE_READ_COST,
             call_stipend: 2300,
             call_value_transfer_gas: 9000,
             call_new_account_gas: 25000,
@@ -297,6 +439,7 @@ impl Schedule {
             quad_coeff_div: 512,
             create_data_gas: 200,
             create_data_limit: max_code_size,
+            initcode_word_gas: EIP3860_INITCODE_WORD_GAS,
             tx_gas: 21000,
             tx_create_gas: 53000,
             tx_data_zero_gas: 4,
@@ -326,6 +469,15 @@ impl Schedule {
             eip3198: false,
             max_refund_quotient: MAX_REFUND_QUOTIENT,
             eip3541: false,
+            eip3855: false,
+            eip4399: false,
+            eip4895: false,
+            eip3860: false,
+            eip3651: false,
+            eip1153: false,
+            eip5656: false,
+            eip6780: false,
+            eip3540: false,
         }
     }
 
@@ -362,7 +514,6 @@ impl Schedule {
     /// Schedule for the Yolov3 testnet of the Ethereum main net.
     pub fn new_berlin() -> Schedule {
         let mut schedule = Self::new_istanbul();
-        schedule.have_subs = true; // EIP 2315
 
         schedule.eip1283 = true;
         schedule.eip2929 = true;
@@ -400,6 +551,30 @@ impl Schedule {
         schedule
     }
 
+    /// Schedule for the Shanghai fork of the Ethereum main net.
+    pub fn new_shanghai() -> Schedule {
+        let mut schedule = Self::new_london();
+
+        schedule.eip3855 = true;
+        schedule.eip4399 = true;
+        schedule.eip4895 = true;
+        schedule.eip3860 = true;
+        schedule.eip3651 = true;
+
+        schedule
+    }
+
+    /// Schedule for the Cancun fork of the Ethereum main net.
+    pub fn new_cancun() -> Schedule {
+        let mut schedule = Self::new_shanghai();
+
+        schedule.eip1153 = true;
+        schedule.eip5656 = true;
+        schedule.eip6780 = true;
+
+        schedule
+    }
+
     fn new(efcd: bool, hdc: bool, tcg: usize) -> Schedule {
         Schedule {
             exceptional_failed_code_deposit: efcd,
@@ -410,7 +585,6 @@ impl Schedule {
             have_bitwise_shifting: false,
             have_chain_id: false,
             have_selfbalance: false,
-            have_subs: false,
             have_extcodehash: false,
             stack_limit: 1024,
             max_depth: 1024,
@@ -423,6 +597,7 @@ impl Schedule {
             sstore_set_gas: 20000,
             sstore_reset_gas: 5000,
             sstore_refund_gas: 15000,
+            sstore_dirty_gas: None,
             jumpdest_gas: 1,
             log_gas: 375,
             log_data_gas: 8,
@@ -432,6 +607,10 @@ impl Schedule {
             cold_account_access_cost: 0,
             cold_sload_cost: 0,
             warm_storage_read_cost: 0,
+            access_list_address_cost: EIP2930_ACCESS_LIST_ADDRESS_COST,
+            access_list_storage_key_cost: EIP2930_ACCESS_LIST_STORAGE_KEY_COST,
+            tload_gas: EIP2929_WARM_STORAGE_READ_COST,
+            tstore_gas: EIP2929_WARM_STORAGE_READ_COST,
             call_stipend: 2300,
             call_value_transfer_gas: 9000,
             call_new_account_gas: 25000,
@@ -440,6 +619,7 @@ impl Schedule {
             quad_coeff_div: 512,
             create_data_gas: 200,
             create_data_limit: usize::max_value(),
+            initcode_word_gas: EIP3860_INITCODE_WORD_GAS,
             tx_gas: 21000,
             tx_create_gas: tcg,
             tx_data_zero_gas: 4,
@@ -469,6 +649,23 @@ impl Schedule {
             eip3198: false,
             max_refund_quotient: MAX_REFUND_QUOTIENT,
             eip3541: false,
+            eip3855: false,
+            eip4399: false,
+            eip4895: false,
+            eip3860: false,
+            eip3651: false,
+            eip1153: false,
+            eip5656: false,
+            eip6780: false,
+            eip3540: false,
+        }
+    }
+
+    /// Maximum size of initcode, twice the maximum code size as by EIP-3860.
+    pub fn max_initcode_size(&self) -> usize {
+        match self.create_data_limit {
+            usize::MAX => EIP3860_MAX_INITCODE_SIZE,
+            limit => limit.saturating_mul(2),
         }
     }
 
@@ -499,3 +696,57 @@ fn schedule_evm_assumptions() {
     assert_eq!(s1.quad_coeff_div, 512);
     assert_eq!(s2.quad_coeff_div, 512);
 }
+
+#[test]
+#[cfg(test)]
+fn schedule_overrides() {
+    use ethjson::{spec::GasSchedule, uint::Uint};
+
+    let overrides: ScheduleOverrides = GasSchedule {
+        tier_step_gas: Some([Uint(1.into()); 8]),
+        sub_gas_cap_divisor: Some(Uint(32.into())),
+        sstore_set_gas: Some(Uint(30000.into())),
+        log_data_gas: Some(Uint(16.into())),
+        sstore_dirty_gas: Some(Uint(200.into())),
+        access_list_storage_key_cost: Some(Uint(3800.into())),
+        tstore_gas: Some(Uint(2900.into())),
+        create_data_limit: Some(Uint(49152.into())),
+        initcode_word_gas: Some(Uint(4.into())),
+        ..Default::default()
+    }
+    .into();
+    let mut schedule = Schedule::new_cancun();
+    assert_eq!(schedule.sstore_dirty_gas, None);
+    schedule.apply_overrides(&overrides);
+
+    assert_eq!(schedule.tier_step_gas, [1; 8]);
+    assert_eq!(schedule.sub_gas_cap_divisor, Some(32));
+    assert_eq!(schedule.sstore_set_gas, 30000);
+    assert_eq!(schedule.log_data_gas, 16);
+    assert_eq!(schedule.sstore_dirty_gas, Some(200));
+    assert_eq!(schedule.access_list_storage_key_cost, 3800);
+    assert_eq!(schedule.tstore_gas, 2900);
+    assert_eq!(schedule.create_data_limit, 49152);
+    assert_eq!(schedule.max_initcode_size(), 98304);
+    assert_eq!(schedule.initcode_word_gas, 4);
+
+    let cancun = Schedule::new_cancun();
+    assert_eq!(schedule.log_gas, cancun.log_gas);
+    assert_eq!(
+        schedule.access_list_address_cost,
+        cancun.access_list_address_cost
+    );
+    assert_eq!(schedule.tload_gas, cancun.tload_gas);
+}
+
+#[test]
+#[cfg(test)]
+fn max_initcode_size() {
+    let mut schedule = Schedule::new_frontier();
+    assert_eq!(schedule.create_data_limit, usize::max_value());
+    assert_eq!(schedule.max_initcode_size(), EIP3860_MAX_INITCODE_SIZE);
+
+    schedule.create_data_limit = 32768;
+    assert_eq!(schedule.max_initcode_size(), 65536);
+    assert_eq!(Schedule::new_london().max_initcode_size(), 49152);
+}
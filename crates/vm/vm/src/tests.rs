@@ -28,6 +28,7 @@ use CallType;
 use ContractCreateResult;
 use CreateContractAddress;
 use EnvInfo;
+use Error;
 use Ext;
 use GasLeft;
 use MessageCallResult;
@@ -65,6 +66,7 @@ pub struct FakeCall {
 pub struct FakeExt {
     pub initial_store: HashMap<H256, H256>,
     pub store: HashMap<H256, H256>,
+    pub transient_store: HashMap<H256, H256>,
     pub suicides: HashSet<Address>,
     pub calls: HashSet<FakeCall>,
     pub sstore_clears: i128,
@@ -138,6 +140,20 @@ impl FakeExt {
         ext
     }
 
+    /// New fake externalities with Shanghai schedule rules
+    pub fn new_shanghai(from: Address, to: Address, builtins: &[Address]) -> Self {
+        let mut ext = FakeExt::new_london(from, to, builtins);
+        ext.schedule = Schedule::new_shanghai();
+        ext
+    }
+
+    /// New fake externalities with Cancun schedule rules
+    pub fn new_cancun(from: Address, to: Address, builtins: &[Address]) -> Self {
+        let mut ext = FakeExt::new_shanghai(from, to, builtins);
+        ext.schedule = Schedule::new_cancun();
+        ext
+    }
+
     /// Alter fake externalities to allow wasm
     pub fn with_wasm(mut self) -> Self {
         self.schedule.wasm = Some(Default::default());
@@ -184,6 +200,22 @@ impl Ext for FakeExt {
         Ok(())
     }
 
+    fn transient_storage_at(&self, key: &H256) -> Result<H256> {
+        Ok(self
+            .transient_store
+            .get(key)
+            .unwrap_or(&H256::default())
+            .clone())
+    }
+
+    fn set_transient_storage(&mut self, key: H256, value: H256) -> Result<()> {
+        if self.is_static {
+            return Err(Error::MutableCallInStaticContext);
+        }
+        self.transient_store.insert(key, value);
+        Ok(())
+    }
+
     fn exists(&self, address: &Address) -> Result<bool> {
         Ok(self.balances.contains_key(address))
     }
@@ -0,0 +1,203 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of OpenEthereum.
+
+// OpenEthereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// OpenEthereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with OpenEthereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction scoped storage (EIP-1153) and the set of contracts
+//! created within the transaction (EIP-6780).
+//!
+//! Like the access list, a single journal is shared by all call frames of
+//! a transaction. Every frame holds a handle with its own id, so the changes
+//! of a reverted frame (and its children) can be rolled back.
+
+use ethereum_types::{Address, H256};
+use std::{cell::RefCell, collections::HashMap, collections::HashSet, rc::Rc};
+
+#[derive(Debug)]
+enum Change {
+    /// Storage slot was written, holds the previous value.
+    Storage(Address, H256, Option<H256>),
+    /// Contract was created.
+    Created(Address),
+}
+
+#[derive(Debug, Default)]
+struct Journal {
+    last_id: usize,
+    storage: HashMap<(Address, H256), H256>,
+    created: HashSet<Address>,
+    changes: Vec<(usize, Change)>,
+}
+
+/// Transient storage of a transaction, discarded once the transaction ends.
+#[derive(Debug)]
+pub struct TransientStorage {
+    id: usize,
+    journal: Rc<RefCell<Journal>>,
+}
+
+impl Clone for TransientStorage {
+    fn clone(&self) -> Self {
+        let mut journal = self.journal.as_ref().borrow_mut();
+        let id = journal.last_id + 1;
+        journal.last_id = id;
+        Self {
+            id: id,
+            journal: self.journal.clone(),
+        }
+    }
+}
+
+impl Default for TransientStorage {
+    fn default() -> Self {
+        TransientStorage::new()
+    }
+}
+
+impl TransientStorage {
+    /// Creates empty transient storage.
+    pub fn new() -> Self {
+        Self {
+            id: 0,
+            journal: Rc::new(RefCell::new(Journal::default())),
+        }
+    }
+
+    /// Returns the value stored for a given key, zero if never set.
+    pub fn storage_at(&self, address: &Address, key: &H256) -> H256 {
+        let journal = self.journal.as_ref().borrow();
+        journal
+            .storage
+            .get(&(*address, *key))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Stores a value for a given key.
+    pub fn set_storage(&mut self, address: Address, key: H256, value: H256) {
+        let mut journal = self.journal.as_ref().borrow_mut();
+        let previous = if value.is_zero() {
+            journal.storage.remove(&(address, key))
+        } else {
+            journal.storage.insert((address, key), value)
+        };
+        journal
+            .changes
+            .push((self.id, Change::Storage(address, key, previous)));
+    }
+
+    /// Checks if a contract was created within the transaction.
+    pub fn is_created(&self, address: &Address) -> bool {
+        let journal = self.journal.as_ref().borrow();
+        journal.created.contains(address)
+    }
+
+    /// Marks a contract as created within the transaction.
+    pub fn insert_created(&mut self, address: Address) {
+        let mut journal = self.journal.as_ref().borrow_mut();
+        if journal.created.insert(address) {
+            journal.changes.push((self.id, Change::Created(address)));
+        }
+    }
+
+    /// Removes all changes made by this frame and the frames it spawned.
+    pub fn rollback(&self) {
+        let mut journal = self.journal.as_ref().borrow_mut();
+        // Frames spawned later always have a higher id, so their changes are at the end.
+        while journal
+            .changes
+            .last()
+            .map_or(false, |(id, _)| *id >= self.id)
+        {
+            let (_, change) = journal
+                .changes
+                .pop()
+                .expect("last change checked above; qed");
+            match change {
+                Change::Storage(address, key, Some(value)) => {
+                    journal.storage.insert((address, key), value);
+                }
+                Change::Storage(address, key, None) => {
+                    journal.storage.remove(&(address, key));
+                }
+                Change::Created(address) => {
+                    journal.created.remove(&address);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unset_storage_is_zero() {
+        let storage = TransientStorage::default();
+        assert_eq!(
+            storage.storage_at(&Address::from_low_u64_be(1), &H256::from_low_u64_be(2)),
+            H256::zero()
+        );
+    }
+
+    #[test]
+    fn cloned_storage_shares_values() {
+        let mut storage = TransientStorage::default();
+        storage.set_storage(
+            Address::from_low_u64_be(1),
+            H256::from_low_u64_be(2),
+            H256::from_low_u64_be(3),
+        );
+
+        let mut storage_call = storage.clone();
+        assert_eq!(
+            storage_call.storage_at(&Address::from_low_u64_be(1), &H256::from_low_u64_be(2)),
+            H256::from_low_u64_be(3)
+        );
+        storage_call.set_storage(
+            Address::from_low_u64_be(1),
+            H256::from_low_u64_be(4),
+            H256::from_low_u64_be(5),
+        );
+        storage_call.insert_created(Address::from_low_u64_be(6));
+        assert_eq!(
+            storage.storage_at(&Address::from_low_u64_be(1), &H256::from_low_u64_be(4)),
+            H256::from_low_u64_be(5)
+        );
+        assert!(storage.is_created(&Address::from_low_u64_be(6)));
+    }
+
+    #[test]
+    fn rollback_restores_parent_values() {
+        let address = Address::from_low_u64_be(1);
+        let key = H256::from_low_u64_be(2);
+
+        let mut storage = TransientStorage::default();
+        storage.set_storage(address, key, H256::from_low_u64_be(3));
+
+        let mut storage_call = storage.clone();
+        storage_call.set_storage(address, key, H256::from_low_u64_be(4));
+        storage_call.insert_created(Address::from_low_u64_be(5));
+
+        let mut storage_call_call = storage_call.clone();
+        storage_call_call.set_storage(address, key, H256::zero());
+        storage_call_call.rollback();
+        assert_eq!(storage.storage_at(&address, &key), H256::from_low_u64_be(4));
+
+        storage_call.rollback();
+        assert_eq!(storage.storage_at(&address, &key), H256::from_low_u64_be(3));
+        assert!(!storage.is_created(&Address::from_low_u64_be(5)));
+    }
+}